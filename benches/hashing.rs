@@ -0,0 +1,73 @@
+//! Throughput of hashing files and creating bags with SHA-2 algorithms
+//!
+//! Run it with the portable implementation, then with the assembly one, and compare:
+//!
+//! ```console
+//! $ cargo bench --bench hashing --features="sha2"
+//! $ cargo bench --bench hashing --features="sha2-asm"
+//! ```
+
+use async_bagit::{checksum::hash_file, BagIt, ChecksumAlgorithm};
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Size of the payload hashed, large enough for disk caches to not matter after the first round
+const PAYLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Best of several rounds is kept, to leave out noise from other processes
+const ROUNDS: usize = 5;
+
+/// Run `task` several times, printing the throughput of its best round
+async fn measure<F: Future>(name: &str, mut task: impl FnMut() -> F) {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        task().await;
+        best = best.min(start.elapsed());
+    }
+
+    let throughput = PAYLOAD_SIZE as f64 / (1024.0 * 1024.0) / best.as_secs_f64();
+    println!(
+        "{name:<24} {:>8.1} ms {throughput:>8.1} MiB/s",
+        best.as_secs_f64() * 1000.0
+    );
+}
+
+/// Create a bag with the payload, and write its manifest
+async fn create_bag(payload: &Path, bag_directory: &Path) {
+    let algorithm = ChecksumAlgorithm::sha256();
+    let mut bag = BagIt::new_empty(bag_directory, &algorithm);
+    bag.add_file::<sha2::Sha256>(payload).await.unwrap();
+    bag.finalize::<sha2::Sha256>().await.unwrap();
+    tokio::fs::remove_dir_all(bag_directory).await.unwrap();
+}
+
+#[tokio::main]
+async fn main() {
+    let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+    let payload = temp_directory.join("payload.bin");
+
+    // Contents that can't be compressed by the filesystem
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let contents = (0..PAYLOAD_SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect::<Vec<_>>();
+    tokio::fs::write(&payload, contents).await.unwrap();
+
+    let implementation = match cfg!(feature = "sha2-asm") {
+        true => "assembly",
+        false => "portable",
+    };
+    println!("SHA-2 implementation: {implementation}, payload of {PAYLOAD_SIZE} bytes");
+
+    measure("hash_file sha256", || hash_file::<sha2::Sha256>(&payload)).await;
+    measure("hash_file sha512", || hash_file::<sha2::Sha512>(&payload)).await;
+    let bag_directory = temp_directory.join("bag");
+    measure("create bag sha256", || create_bag(&payload, &bag_directory)).await;
+}
@@ -23,7 +23,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     source_directory.push("tests/sample-bag/data");
 
     // Algorithm to use for checksums
-    let algorithm = ChecksumAlgorithm::<Blake3>::new(Algorithm::Custom("blake3"));
+    let algorithm = ChecksumAlgorithm::<Blake3>::new(Algorithm::custom("blake3"));
 
     let mut bag = BagIt::new_empty(bag_directory, &algorithm);
 
@@ -56,19 +56,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Finding file `bagit.md` and showing its first 5 lines\n===============");
     let bagit_dot_md = bag_it
         .payload_items()
-        .find(|payload| {
-            payload
-                .relative_path()
-                .file_name()
-                .and_then(|file_name| file_name.to_str())
-                == Some("bagit.md")
-        })
+        .find(|payload| payload.file_name() == Some("bagit.md"))
         .ok_or(Box::<dyn std::error::Error>::from(
             "failed to find payload named `bagit.md` in bag",
         ))?;
 
     // Read the first 5 lines of file, and display them
-    let markdown_file = File::open(bagit_dot_md.absolute_path(&bag_it)).await?;
+    let markdown_file = bagit_dot_md.open().await?;
     let markdown_reader = BufReader::new(markdown_file);
     let mut lines = markdown_reader.lines();
     let mut display = String::new();
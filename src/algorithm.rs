@@ -1,11 +1,18 @@
-use digest::Digest;
+use crate::manifest::{algorithm_name, PREFIX_MANIFEST, PREFIX_TAG_MANIFEST};
+use digest::{Digest, DynDigest};
+use std::borrow::Cow;
 use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Create a hasher of an algorithm only known at runtime, behind a trait object
+pub(crate) type NewHasher = fn() -> Box<dyn DynDigest + Send>;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// List of common hashing algorithms
 ///
 /// The string representation of the algorithm is used in the filename of manifest files.
-
+///
 /// This list was taken from <https://www.iana.org/assignments/named-information/named-information.xhtml>, but it is not exhaustive, as new secure algorithms come, and old ones get broken.
 pub enum Algorithm {
     /// Secure Hash Algorithm 2 hash function with 32-bit words
@@ -16,11 +23,29 @@ pub enum Algorithm {
     Blake2b256,
     /// BLAKE2 hash function with 64-bit words
     Blake2b512,
-    /// Custom hash function
-    Custom(&'static str),
+    /// Custom hash function, see [`Algorithm::custom()`]
+    ///
+    /// Its name is either known at compile time or chosen at runtime, from a configuration file or the manifests of a bag.
+    Custom(Cow<'static, str>),
 }
 
 impl Algorithm {
+    /// Custom hash function named `name`, borrowed when it is a `&'static str` and owned when it is a [`String`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::Algorithm;
+    /// let blake3 = Algorithm::custom("blake3");
+    ///
+    /// // Name read from a configuration file
+    /// let name = String::from("blake3");
+    /// assert_eq!(Algorithm::custom(name), blake3);
+    /// ```
+    pub fn custom(name: impl Into<Cow<'static, str>>) -> Self {
+        Self::Custom(name.into())
+    }
+
     /// Returns name of the algorithm, used in the filenames of the manifests files with checksums
     pub fn name(&self) -> &str {
         match self {
@@ -33,12 +58,96 @@ impl Algorithm {
     }
 }
 
+impl Algorithm {
+    /// Kind of manifest and name of its algorithm, from the filename `manifest-<algorithm>.txt` or `tagmanifest-<algorithm>.txt`
+    ///
+    /// Filenames are compared ignoring ASCII case, the name of the algorithm is returned in lowercase.
+    /// Parse it with [`Algorithm::from_str()`] to get the algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, ManifestKind};
+    /// assert_eq!(
+    ///     Algorithm::name_from_manifest("/path/to/bag/tagmanifest-sha512.txt"),
+    ///     Some((ManifestKind::Tag, "sha512".to_string()))
+    /// );
+    /// assert_eq!(
+    ///     Algorithm::name_from_manifest("manifest-blake3.txt"),
+    ///     Some((ManifestKind::Payload, "blake3".to_string()))
+    /// );
+    /// assert_eq!(Algorithm::name_from_manifest("bag-info.txt"), None);
+    /// ```
+    pub fn name_from_manifest(path: impl AsRef<Path>) -> Option<(ManifestKind, String)> {
+        let path = path.as_ref();
+
+        algorithm_name(path, PREFIX_MANIFEST)
+            .map(|name| (ManifestKind::Payload, name))
+            .or_else(|| {
+                algorithm_name(path, PREFIX_TAG_MANIFEST).map(|name| (ManifestKind::Tag, name))
+            })
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = AlgorithmParseError;
+
+    /// Parse algorithm from its name, see [`Algorithm::name()`], ignoring ASCII case
+    ///
+    /// Names of algorithms without a variant give a [`Algorithm::Custom`] algorithm, in lowercase.
+    /// Names that are empty or have characters other than ASCII letters, digits, `-` and `_` are rejected,
+    /// since they would not make valid filenames of manifests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::Algorithm;
+    /// assert_eq!("sha512".parse(), Ok(Algorithm::Sha512));
+    /// assert_eq!("SHA256".parse(), Ok(Algorithm::Sha256));
+    /// assert_eq!("BLAKE3".parse(), Ok(Algorithm::custom("blake3")));
+    /// assert!("../md5".parse::<Algorithm>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty()
+            || !s
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(AlgorithmParseError(s.to_string()));
+        }
+
+        Ok([
+            Algorithm::Sha256,
+            Algorithm::Sha512,
+            Algorithm::Blake2b256,
+            Algorithm::Blake2b512,
+        ]
+        .into_iter()
+        .find(|algorithm| algorithm.name().eq_ignore_ascii_case(s))
+        .unwrap_or_else(|| Algorithm::custom(s.to_ascii_lowercase())))
+    }
+}
+
 impl Display for Algorithm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name())
     }
 }
 
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Algorithm could not be parsed from its name
+#[error("Invalid algorithm name `{0}`")]
+pub struct AlgorithmParseError(pub String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// What a manifest lists checksums of, see [`Algorithm::name_from_manifest()`]
+pub enum ManifestKind {
+    /// Manifest of payloads, `manifest-<algorithm>.txt`
+    Payload,
+    /// Tag manifest, `tagmanifest-<algorithm>.txt`
+    Tag,
+}
+
 #[derive(Debug, PartialEq)]
 /// Wrapper around the [`Algorithm`] enum that associates a specific hashing algorithm with a concrete type computing digests.
 ///
@@ -59,7 +168,7 @@ impl<ChecksumAlgo: Digest> ChecksumAlgorithm<ChecksumAlgo> {
     /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
     ///
     /// // BLAKE3, a bit less known algorithm
-    /// let algorithm = ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::Custom("blake3"));
+    /// let algorithm = ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::custom("blake3"));
     /// ```
     ///
     pub fn new(algorithm: Algorithm) -> Self {
@@ -79,3 +188,89 @@ impl<ChecksumAlgo: Digest> ChecksumAlgorithm<ChecksumAlgo> {
         &self.inner
     }
 }
+
+impl<ChecksumAlgo: Digest + DynDigest + Send + 'static> ChecksumAlgorithm<ChecksumAlgo> {
+    /// Create a new hasher behind a trait object, to compute checksums with several algorithms at once
+    ///
+    /// See [`hash_file_multi()`](crate::checksum::hash_file_multi).
+    pub fn hasher(&self) -> Box<dyn DynDigest + Send> {
+        Box::new(<ChecksumAlgo as Digest>::new())
+    }
+
+    /// Function creating hashers like [`Self::hasher()`], to keep along with the algorithm
+    #[cfg(any(feature = "read", feature = "generate"))]
+    pub(crate) fn new_hasher(&self) -> NewHasher {
+        || Box::new(<ChecksumAlgo as Digest>::new())
+    }
+}
+
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+impl ChecksumAlgorithm<sha2::Sha256> {
+    /// SHA-256 implementation of the [`sha2`](https://docs.rs/sha2) crate
+    ///
+    /// The fastest implementation available on the CPU (SHA extensions, AVX2, ...) is selected at runtime.
+    /// Enable the `sha2-asm` feature to use the assembly implementation instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, ChecksumAlgorithm};
+    /// let algorithm = ChecksumAlgorithm::sha256();
+    /// assert_eq!(algorithm.algorithm(), &Algorithm::Sha256);
+    /// ```
+    pub fn sha256() -> Self {
+        Self::new(Algorithm::Sha256)
+    }
+}
+
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+impl ChecksumAlgorithm<sha2::Sha512> {
+    /// SHA-512 implementation of the [`sha2`](https://docs.rs/sha2) crate
+    ///
+    /// See [`ChecksumAlgorithm::sha256()`] regarding hardware acceleration.
+    pub fn sha512() -> Self {
+        Self::new(Algorithm::Sha512)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Algorithm, AlgorithmParseError, ManifestKind};
+
+    #[test]
+    fn parse_name() {
+        for algorithm in [
+            Algorithm::Sha256,
+            Algorithm::Sha512,
+            Algorithm::Blake2b256,
+            Algorithm::Blake2b512,
+        ] {
+            assert_eq!(algorithm.name().parse(), Ok(algorithm));
+        }
+        assert_eq!("Md5".parse(), Ok(Algorithm::custom("md5")));
+        assert_eq!("sha3-256".parse(), Ok(Algorithm::custom("sha3-256")));
+        for name in ["", "md 5", "md5.txt", "data/md5"] {
+            assert_eq!(
+                name.parse::<Algorithm>(),
+                Err(AlgorithmParseError(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn name_from_manifest() {
+        assert_eq!(
+            Algorithm::name_from_manifest("Manifest-SHA256.TXT"),
+            Some((ManifestKind::Payload, "sha256".to_string()))
+        );
+        assert_eq!(
+            Algorithm::name_from_manifest("bag/tagmanifest-md5.txt"),
+            Some((ManifestKind::Tag, "md5".to_string()))
+        );
+        for name in ["manifest-.txt", "manifest-sha256.bak", "data/sha256.txt"] {
+            assert_eq!(Algorithm::name_from_manifest(name), None);
+        }
+    }
+}
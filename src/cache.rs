@@ -0,0 +1,423 @@
+use crate::{long_path, Algorithm, Checksum, Payload};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+#[cfg(feature = "read")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+
+/// Name of sidecar file in bag directory, it is not a tag file and is never listed in manifests
+pub(crate) const CACHE_FILE_NAME: &str = ".bagit-cache.txt";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// How to use the sidecar verification cache of a bag
+///
+/// See [`BagIt::validate_with_cache()`](crate::BagIt::validate_with_cache).
+pub enum CacheMode {
+    /// Skip payloads whose file did not change since their last verification
+    #[default]
+    SkipUnchanged,
+    /// Compute checksums of all payloads, regardless of the cache
+    FullRehash,
+}
+
+#[derive(Debug, PartialEq)]
+/// Checksum of a file along with its size and modification time when it was computed
+struct CacheEntry {
+    checksum: Checksum<'static>,
+    bytes: u64,
+    modified: Duration,
+}
+
+impl CacheEntry {
+    /// Parse "\<checksum\> \<bytes\> \<modification time in nanoseconds\> \<path\>", the end of a line of a cache file
+    fn parse(fields: &str) -> Option<(PathBuf, Self)> {
+        let mut parts = fields.splitn(4, ' ');
+        let checksum = Checksum::from_str(parts.next()?).ok()?;
+        let bytes = parts.next()?.parse().ok()?;
+        let modified = parts.next()?.parse::<u64>().ok()?;
+        let path = PathBuf::from(parts.next()?);
+
+        Some((
+            path,
+            Self {
+                checksum,
+                bytes,
+                modified: Duration::from_nanos(modified),
+            },
+        ))
+    }
+
+    /// Fields of entry for file at `path` as read by [`Self::parse()`], without a line break
+    ///
+    /// Paths that are not valid UTF-8 or span several lines can't be written back as they are, they are left out:
+    /// their file is hashed again next time.
+    fn fields(&self, path: &Path) -> Option<String> {
+        let path = path.to_str().filter(|path| !path.contains(['\n', '\r']))?;
+        Some(format!(
+            "{} {} {} {path}",
+            self.checksum,
+            self.bytes,
+            self.modified.as_nanos()
+        ))
+    }
+}
+
+/// Modification time of file since the Unix epoch, if the platform supports it
+fn modified_since_epoch(metadata: &std::fs::Metadata) -> Option<Duration> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+}
+
+#[derive(Debug, Default)]
+/// Checksum, size and modification time of payloads at their last verification
+pub(crate) struct SidecarCache(HashMap<PathBuf, CacheEntry>);
+
+impl SidecarCache {
+    /// Load cache from bag directory, a missing or unreadable cache is treated as empty
+    pub async fn load(bag_directory: &Path) -> Self {
+        let path = bag_directory.join(CACHE_FILE_NAME);
+        let Ok(contents) = fs::read_to_string(long_path::extended(&path)).await else {
+            return Self::default();
+        };
+
+        // Each line is: "<checksum> <bytes> <modification time in nanoseconds> <relative path>"
+        Self(contents.lines().filter_map(CacheEntry::parse).collect())
+    }
+
+    /// Payload file has the same checksum, size and modification time as when it was last verified
+    pub fn is_unchanged(&self, payload: &Payload, metadata: &std::fs::Metadata) -> bool {
+        let Some(entry) = self.0.get(payload.relative_path()) else {
+            return false;
+        };
+
+        entry.checksum == *payload.checksum()
+            && entry.bytes == metadata.len()
+            && modified_since_epoch(metadata) == Some(entry.modified)
+    }
+
+    /// Record payload as verified
+    pub fn insert(&mut self, payload: &Payload, metadata: &std::fs::Metadata) {
+        let Some(modified) = modified_since_epoch(metadata) else {
+            self.remove(payload);
+            return;
+        };
+
+        self.0.insert(
+            payload.relative_path().to_path_buf(),
+            CacheEntry {
+                checksum: payload.checksum().clone().into_owned(),
+                bytes: metadata.len(),
+                modified,
+            },
+        );
+    }
+
+    /// Forget previous verification of payload
+    pub fn remove(&mut self, payload: &Payload) {
+        self.0.remove(payload.relative_path());
+    }
+
+    pub async fn write(&self, bag_directory: &Path) -> Result<(), std::io::Error> {
+        let contents = self
+            .0
+            .iter()
+            .filter_map(|(relative_path, entry)| entry.fields(relative_path))
+            .map(|line| line + "\n")
+            .collect::<String>();
+
+        fs::write(
+            long_path::extended(&bag_directory.join(CACHE_FILE_NAME)),
+            contents,
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// File on disk as it is when its checksum is computed, identifying entries of a [`ChecksumCache`]
+///
+/// A cached checksum only applies to a file with the same canonical path, size and modification time.
+pub struct CacheKey {
+    path: PathBuf,
+    bytes: u64,
+    modified: Duration,
+}
+
+impl CacheKey {
+    /// Key of the file at `path`, from its metadata
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        let path = fs::canonicalize(long_path::extended(path.as_ref())).await?;
+        let metadata = fs::metadata(&path).await?;
+        let modified = modified_since_epoch(&metadata).ok_or(std::io::ErrorKind::Unsupported)?;
+
+        Ok(Self {
+            path,
+            bytes: metadata.len(),
+            modified,
+        })
+    }
+
+    /// Canonical path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Size of the file in bytes
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Modification time of the file, since the Unix epoch
+    pub fn modified(&self) -> Duration {
+        self.modified
+    }
+}
+
+/// Checksums of files computed earlier, consulted before hashing a file again
+///
+/// Methods take `&self`, as files are hashed concurrently: use interior mutability to record checksums.
+/// See [`FileChecksumCache`] for a cache kept in a file, [`ReadOptions::checksum_cache()`](crate::ReadOptions::checksum_cache)
+/// and [`hash_file_cached()`](crate::checksum::hash_file_cached) to use it.
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::{Algorithm, CacheKey, Checksum, ChecksumCache};
+/// # use std::collections::HashMap;
+/// # use std::sync::Mutex;
+/// // Cache for the lifetime of the process
+/// #[derive(Default)]
+/// struct MemoryCache(Mutex<HashMap<(CacheKey, Algorithm), Checksum<'static>>>);
+///
+/// impl ChecksumCache for MemoryCache {
+///     fn get(&self, key: &CacheKey, algorithm: &Algorithm) -> Option<Checksum<'static>> {
+///         let entries = self.0.lock().unwrap();
+///         entries.get(&(key.clone(), algorithm.clone())).cloned()
+///     }
+///
+///     fn insert(&self, key: CacheKey, algorithm: &Algorithm, checksum: Checksum<'static>) {
+///         let mut entries = self.0.lock().unwrap();
+///         entries.insert((key, algorithm.clone()), checksum);
+///     }
+/// }
+/// ```
+pub trait ChecksumCache: Send + Sync {
+    /// Checksum of the file computed with `algorithm`, if it was recorded for the same `key`
+    fn get(&self, key: &CacheKey, algorithm: &Algorithm) -> Option<Checksum<'static>>;
+
+    /// Record checksum of the file computed with `algorithm`
+    fn insert(&self, key: CacheKey, algorithm: &Algorithm, checksum: Checksum<'static>);
+}
+
+/// Cache kept in options, shared with streams validating manifests
+#[cfg(feature = "read")]
+#[derive(Clone)]
+pub(crate) struct SharedCache(pub(crate) Arc<dyn ChecksumCache>);
+
+#[cfg(feature = "read")]
+impl std::fmt::Debug for SharedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedCache")
+    }
+}
+
+#[derive(Debug)]
+/// [`ChecksumCache`] kept in a text file, to be reused across runs like nightly fixity checks
+///
+/// Only the latest checksum of every file is kept for each algorithm. Entries are kept in memory,
+/// they are written to the file with [`FileChecksumCache::save()`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, FileChecksumCache, ReadOptions};
+/// # use std::sync::Arc;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// let cache = Arc::new(FileChecksumCache::load("/var/cache/fixity.txt").await);
+///
+/// // Only files modified since the last run are hashed
+/// let options = ReadOptions::default().checksum_cache(cache.clone());
+/// let bag = BagIt::read_existing_with_options("/path/to/bag", &algorithm, options).await?;
+/// cache.save().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileChecksumCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<(String, PathBuf), CacheEntry>>,
+}
+
+impl FileChecksumCache {
+    /// Load cache from file at `path`, a missing or unreadable cache is treated as empty
+    pub async fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read_to_string(long_path::extended(&path)).await {
+            Ok(contents) => contents.lines().filter_map(Self::parse_line).collect(),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Each line is: "\<algorithm\> \<checksum\> \<bytes\> \<modification time in nanoseconds\> \<canonical path\>"
+    fn parse_line(line: &str) -> Option<((String, PathBuf), CacheEntry)> {
+        let (algorithm, fields) = line.split_once(' ')?;
+        let (path, entry) = CacheEntry::parse(fields)?;
+
+        Some(((algorithm.to_string(), path), entry))
+    }
+
+    /// Write entries to the file of the cache, replacing it
+    ///
+    /// Files whose path is not valid UTF-8 or spans several lines are left out.
+    pub async fn save(&self) -> Result<(), std::io::Error> {
+        let contents = self
+            .entries
+            .lock()
+            .expect("cache lock is never poisoned")
+            .iter()
+            .filter_map(|((algorithm, path), entry)| {
+                Some(format!("{algorithm} {}\n", entry.fields(path)?))
+            })
+            .collect::<String>();
+
+        fs::write(long_path::extended(&self.path), contents).await
+    }
+
+    /// Number of checksums in the cache
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("cache lock is never poisoned")
+            .len()
+    }
+
+    /// Whether the cache has no checksum
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ChecksumCache for FileChecksumCache {
+    fn get(&self, key: &CacheKey, algorithm: &Algorithm) -> Option<Checksum<'static>> {
+        let entries = self.entries.lock().expect("cache lock is never poisoned");
+        entries
+            .get(&(algorithm.name().to_string(), key.path.clone()))
+            .filter(|entry| entry.bytes == key.bytes && entry.modified == key.modified)
+            .map(|entry| entry.checksum.clone())
+    }
+
+    fn insert(&self, key: CacheKey, algorithm: &Algorithm, checksum: Checksum<'static>) {
+        self.entries
+            .lock()
+            .expect("cache lock is never poisoned")
+            .insert(
+                (algorithm.name().to_string(), key.path),
+                CacheEntry {
+                    checksum,
+                    bytes: key.bytes,
+                    modified: key.modified,
+                },
+            );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CacheEntry, CacheKey, ChecksumCache, FileChecksumCache};
+    use crate::{checksum::hash_file_cached, Algorithm, Checksum};
+    use sha2::Sha256;
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn cache_entry_fields() {
+        let entry = CacheEntry {
+            checksum: Checksum::from(vec![0, 255]),
+            bytes: 5,
+            modified: Duration::from_nanos(1_722_470_400_000_000_001),
+        };
+
+        let fields = entry.fields(Path::new("data/my file.txt")).unwrap();
+        assert_eq!(fields, "00ff 5 1722470400000000001 data/my file.txt");
+        assert_eq!(
+            CacheEntry::parse(&fields),
+            Some((Path::new("data/my file.txt").to_path_buf(), entry))
+        );
+
+        // Paths that can't be read back are left out
+        let entry = CacheEntry::parse("00ff 5 0 x").unwrap().1;
+        for path in ["data/two\nlines.txt", "data/carriage\rreturn.txt"] {
+            assert_eq!(entry.fields(Path::new(path)), None, "`{path:?}`");
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(std::ffi::OsStr::from_bytes(b"data/\xff.txt"));
+            assert_eq!(entry.fields(path), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn file_checksum_cache() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let file = temp_directory.join("hello.txt");
+        let cache_file = temp_directory.join("cache.txt");
+        tokio::fs::write(&file, "hello").await.unwrap();
+
+        // Missing cache is empty, checksums computed are recorded
+        let cache = FileChecksumCache::load(&cache_file).await;
+        assert!(cache.is_empty());
+        let hello = Checksum::digest::<Sha256>(b"hello".to_vec());
+        assert_eq!(
+            hash_file_cached::<Sha256>(&file, &Algorithm::Sha256, &cache).await,
+            Ok(hello.clone())
+        );
+        assert_eq!(cache.len(), 1);
+        let key = CacheKey::from_file(&file).await.unwrap();
+        assert_eq!(key.path(), file.canonicalize().unwrap());
+        assert_eq!(key.bytes(), 5);
+        assert_eq!(cache.get(&key, &Algorithm::Sha256), Some(hello.clone()));
+        assert_eq!(cache.get(&key, &Algorithm::Sha512), None);
+
+        // Cache is kept across runs
+        cache.save().await.unwrap();
+        let cache = FileChecksumCache::load(&cache_file).await;
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key, &Algorithm::Sha256), Some(hello.clone()));
+
+        // Contents changed without changing size nor modification time are not noticed
+        let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+        tokio::fs::write(&file, "world").await.unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(
+            hash_file_cached::<Sha256>(&file, &Algorithm::Sha256, &cache).await,
+            Ok(hello)
+        );
+
+        // File of another size is hashed again, replacing its entry
+        tokio::fs::write(&file, "hello world").await.unwrap();
+        assert_eq!(
+            hash_file_cached::<Sha256>(&file, &Algorithm::Sha256, &cache).await,
+            Ok(Checksum::digest::<Sha256>(b"hello world".to_vec()))
+        );
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -1,122 +1,624 @@
-pub(crate) use compute::compute_checksum_file;
-pub use compute::ChecksumComputeError;
+//! Checksums of payloads and tag files
+//!
+//! See [`Checksum`] to compute checksums of bytes in memory, and [`hash_file()`] for files on disk.
+//! When several algorithms are needed at once, [`hash_file_multi()`] reads the file only once.
+//! Checksums of files left unchanged can be kept in a [`ChecksumCache`](crate::ChecksumCache) with [`hash_file_cached()`].
+//! How files are read can be tuned for the storage they are on with [`HashingOptions::scope()`].
+
+#[cfg(feature = "generate")]
+pub(crate) use compute::buffer_size;
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) use compute::hash_file_with;
+#[cfg(feature = "zstd")]
+pub(crate) use compute::hash_reader;
+pub use compute::{
+    hash_file, hash_file_cached, hash_file_multi, hashing_options, ChecksumComputeError,
+    HashingOptions, DEFAULT_BUFFER_SIZE,
+};
+#[cfg(feature = "read")]
+pub(crate) use compute::{hash_file_multi_progress, hash_file_progress};
 use digest::Digest;
-use std::{borrow::Cow, fmt::Display};
+use std::{
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
 
 mod compute {
     use super::Checksum;
-    use digest::Digest;
-    use std::path::Path;
+    use crate::{Algorithm, CacheKey, ChecksumCache};
+    use digest::{Digest, DynDigest};
+    use futures::StreamExt;
+    use std::future::Future;
+    use std::path::{Path, PathBuf};
     use tokio::{
         fs::File,
-        io::{AsyncReadExt, BufReader},
+        io::{AsyncRead, AsyncReadExt},
         task::spawn_blocking,
     };
 
-    #[derive(thiserror::Error, Debug, PartialEq)]
+    /// Size of chunks read from disk and fed to the hasher, unless set otherwise with [`HashingOptions::buffer_size()`]
+    pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+    tokio::task_local! {
+        static OPTIONS: HashingOptions;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    /// How files are read when computing their checksums, see [`HashingOptions::scope()`]
+    ///
+    /// Large chunks suit network filesystems and spinning disks, reading ahead keeps fast storage busy while a chunk is hashed.
+    pub struct HashingOptions {
+        buffer_size: usize,
+        read_ahead: usize,
+    }
+
+    impl Default for HashingOptions {
+        fn default() -> Self {
+            Self {
+                buffer_size: DEFAULT_BUFFER_SIZE,
+                read_ahead: 0,
+            }
+        }
+    }
+
+    impl HashingOptions {
+        /// Size of chunks read from files, [`DEFAULT_BUFFER_SIZE`] by default and at least one byte
+        pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+            self.buffer_size = buffer_size.max(1);
+            self
+        }
+
+        /// Number of chunks read ahead of the one being hashed, none by default
+        pub fn read_ahead(mut self, chunks: usize) -> Self {
+            self.read_ahead = chunks;
+            self
+        }
+
+        /// Run `future` reading files with these options for all checksums it computes
+        ///
+        /// Options only apply to `future`, other bags and checksums computed concurrently keep their own.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// # use async_bagit::checksum::{hash_file, hashing_options, HashingOptions};
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        /// # let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        /// # file.push("tests/sample-bag/data/sources.csv");
+        /// // File on a network filesystem
+        /// let options = HashingOptions::default().buffer_size(4 * 1024 * 1024).read_ahead(2);
+        /// let checksum = options
+        ///     .scope(async {
+        ///         assert_eq!(hashing_options(), options);
+        ///         hash_file::<sha2::Sha256>(file).await
+        ///     })
+        ///     .await?;
+        /// assert_eq!(hashing_options(), HashingOptions::default());
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub async fn scope<F: Future>(self, future: F) -> F::Output {
+            OPTIONS.scope(self, future).await
+        }
+    }
+
+    /// Options files are read with when computing checksums, those of the enclosing [`HashingOptions::scope()`] or the default ones
+    pub fn hashing_options() -> HashingOptions {
+        OPTIONS.try_with(|options| *options).unwrap_or_default()
+    }
+
+    /// Size of chunks read from files, see [`HashingOptions::buffer_size()`]
+    #[cfg(feature = "generate")]
+    pub(crate) fn buffer_size() -> usize {
+        hashing_options().buffer_size
+    }
+
+    #[derive(thiserror::Error, Clone, Debug, PartialEq)]
     /// Possible errors when computing checksums for bagit payloads
     pub enum ChecksumComputeError {
         /// File was not found
-        #[error("File not found on disk")]
-        FileNotFound,
+        #[error("File `{}` not found on disk", .0.display())]
+        FileNotFound(PathBuf),
         /// Failed to open file
-        #[error("Failed to open file")]
-        OpenFile(std::io::ErrorKind),
+        #[error("Failed to open file `{}`: {1}", .0.display())]
+        OpenFile(PathBuf, std::io::ErrorKind),
         /// Failed to read file
-        #[error("Failed to read file")]
-        ReadFile(std::io::ErrorKind),
-        /// Failed to compute checksum
+        #[error("Failed to read file `{}`: {1}", .0.display())]
+        ReadFile(PathBuf, std::io::ErrorKind),
+        /// Failed to compute checksum, the blocking thread hashing the file stopped
         #[error("Failed to compute checksum of file")]
         ComputeChecksum,
     }
 
-    pub(crate) async fn compute_checksum_file<ChecksumAlgo: Digest>(
+    /// Compute checksum of a file on disk
+    ///
+    /// The file is read chunk by chunk, it is never fully loaded in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::checksum::hash_file;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # file.push("tests/sample-bag/data/sources.csv");
+    /// let checksum = hash_file::<sha2::Sha256>(file).await?;
+    /// assert_eq!(
+    ///     checksum.to_string(),
+    ///     "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hash_file<ChecksumAlgo: Digest + Send + 'static>(
+        path: impl AsRef<Path>,
+    ) -> Result<Checksum<'static>, ChecksumComputeError> {
+        let hasher = read_chunks(
+            path,
+            ChecksumAlgo::new(),
+            |hasher, chunk| hasher.update(chunk),
+            |_| (),
+        )
+        .await?;
+
+        Ok(hasher.finalize().as_slice().into())
+    }
+
+    /// Compute checksum of a file on disk like [`hash_file()`], unless `cache` has it for the file as it is now
+    ///
+    /// Checksums are looked up with the [`CacheKey`] of the file, those computed are recorded in `cache`.
+    /// The file is hashed without the cache when its key can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{checksum::hash_file_cached, Algorithm, FileChecksumCache};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let cache = FileChecksumCache::load("/var/cache/fixity.txt").await;
+    ///
+    /// // Second call does not read the file
+    /// let checksum =
+    ///     hash_file_cached::<sha2::Sha256>("/path/to/dataset.csv", &Algorithm::Sha256, &cache)
+    ///         .await?;
+    /// let checksum =
+    ///     hash_file_cached::<sha2::Sha256>("/path/to/dataset.csv", &Algorithm::Sha256, &cache)
+    ///         .await?;
+    /// cache.save().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hash_file_cached<ChecksumAlgo: Digest + Send + 'static>(
         path: impl AsRef<Path>,
+        algorithm: &Algorithm,
+        cache: &dyn ChecksumCache,
     ) -> Result<Checksum<'static>, ChecksumComputeError> {
-        if !path.as_ref().is_file() {
-            return Err(ChecksumComputeError::FileNotFound);
+        let Ok(key) = CacheKey::from_file(&path).await else {
+            return hash_file::<ChecksumAlgo>(path).await;
+        };
+        if let Some(checksum) = cache.get(&key, algorithm) {
+            return Ok(checksum);
         }
 
-        // Read file and verify checksum
-        let file = File::open(&path)
-            .await
-            .map_err(|e| ChecksumComputeError::OpenFile(e.kind()))?;
-        let mut buffer_reader = BufReader::new(file);
+        let checksum = hash_file::<ChecksumAlgo>(path).await?;
+        cache.insert(key, algorithm, checksum.clone());
+        Ok(checksum)
+    }
 
-        // TODO: read file chunks by chunks?
-        let mut buffer = Vec::new();
-        buffer_reader
-            .read_to_end(&mut buffer)
-            .await
-            .map_err(|e| ChecksumComputeError::ReadFile(e.kind()))?;
+    /// Compute checksum of a file on disk like [`hash_file()`], calling `hashed` with the size of every chunk once hashed
+    #[cfg(feature = "read")]
+    pub(crate) async fn hash_file_progress<ChecksumAlgo: Digest + Send + 'static>(
+        path: impl AsRef<Path>,
+        hashed: impl FnMut(u64),
+    ) -> Result<Checksum<'static>, ChecksumComputeError> {
+        let hasher = read_chunks(
+            path,
+            ChecksumAlgo::new(),
+            |hasher, chunk| hasher.update(chunk),
+            hashed,
+        )
+        .await?;
+
+        Ok(hasher.finalize().as_slice().into())
+    }
 
-        let checksum = spawn_blocking(move || Checksum::digest::<ChecksumAlgo>(buffer))
+    /// Compute checksums of a file on disk with several algorithms, reading it only once
+    ///
+    /// Every chunk read from disk is fed to all `hashers`, checksums are returned in the same order as the hashers.
+    /// Hashers can be created with [`ChecksumAlgorithm::hasher()`](crate::ChecksumAlgorithm::hasher).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{checksum::{hash_file, hash_file_multi}, Algorithm, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # file.push("tests/sample-bag/data/sources.csv");
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+    ///
+    /// let checksums = hash_file_multi(&file, vec![sha256.hasher(), sha512.hasher()]).await?;
+    /// assert_eq!(checksums[0], hash_file::<sha2::Sha256>(&file).await?);
+    /// assert_eq!(checksums[1], hash_file::<sha2::Sha512>(&file).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hash_file_multi(
+        path: impl AsRef<Path>,
+        hashers: Vec<Box<dyn DynDigest + Send>>,
+    ) -> Result<Vec<Checksum<'static>>, ChecksumComputeError> {
+        hash_file_multi_progress(path, hashers, |_| ()).await
+    }
+
+    /// Compute checksums of a file on disk like [`hash_file_multi()`], calling `hashed` with the size of every chunk once hashed
+    pub(crate) async fn hash_file_multi_progress(
+        path: impl AsRef<Path>,
+        hashers: Vec<Box<dyn DynDigest + Send>>,
+        hashed: impl FnMut(u64),
+    ) -> Result<Vec<Checksum<'static>>, ChecksumComputeError> {
+        let hashers = read_chunks(path, hashers, update_all, hashed).await?;
+
+        Ok(hashers
+            .into_iter()
+            .map(|hasher| hasher.finalize().as_ref().into())
+            .collect())
+    }
+
+    /// Compute checksum of a file on disk, along with checksums of `others` hashers, reading it only once
+    ///
+    /// `hashed` is called with the size of every chunk once hashed.
+    #[cfg(any(feature = "read", feature = "generate"))]
+    pub(crate) async fn hash_file_with<ChecksumAlgo: Digest + Send + 'static>(
+        path: impl AsRef<Path>,
+        others: Vec<Box<dyn DynDigest + Send>>,
+        hashed: impl FnMut(u64),
+    ) -> Result<(Checksum<'static>, Vec<Checksum<'static>>), ChecksumComputeError> {
+        let (hasher, others) = read_chunks(
+            path,
+            (ChecksumAlgo::new(), others),
+            |(hasher, others), chunk| {
+                Digest::update(hasher, chunk);
+                update_all(others, chunk);
+            },
+            hashed,
+        )
+        .await?;
+
+        Ok((
+            hasher.finalize().as_slice().into(),
+            others
+                .into_iter()
+                .map(|other| other.finalize().as_ref().into())
+                .collect(),
+        ))
+    }
+
+    /// Compute checksum of all bytes of `reader` reading file at `path`, along with their count
+    #[cfg(feature = "zstd")]
+    pub(crate) async fn hash_reader<ChecksumAlgo: Digest + Send + 'static>(
+        reader: impl AsyncRead + Unpin,
+        path: &Path,
+    ) -> Result<(Checksum<'static>, u64), ChecksumComputeError> {
+        let mut bytes = 0;
+        let hasher = read_chunks_from(
+            reader,
+            path,
+            ChecksumAlgo::new(),
+            |hasher, chunk| hasher.update(chunk),
+            |chunk| bytes += chunk,
+        )
+        .await?;
+
+        Ok((hasher.finalize().as_slice().into(), bytes))
+    }
+
+    /// Feed `chunk` to every hasher
+    fn update_all(hashers: &mut Vec<Box<dyn DynDigest + Send>>, chunk: &[u8]) {
+        for hasher in hashers.iter_mut() {
+            hasher.update(chunk);
+        }
+    }
+
+    /// Read file chunk by chunk, handing every chunk to `process_chunk` along with `state`, see [`read_chunks_from()`]
+    async fn read_chunks<State: Send + 'static>(
+        path: impl AsRef<Path>,
+        state: State,
+        process_chunk: fn(&mut State, &[u8]),
+        processed: impl FnMut(u64),
+    ) -> Result<State, ChecksumComputeError> {
+        let path = path.as_ref();
+        let extended_path = crate::long_path::extended(path);
+        if !extended_path.is_file() {
+            return Err(ChecksumComputeError::FileNotFound(path.to_path_buf()));
+        }
+
+        let file = File::open(&extended_path)
             .await
-            .map_err(|_| ChecksumComputeError::ComputeChecksum)?;
+            .map_err(|e| ChecksumComputeError::OpenFile(path.to_path_buf(), e.kind()))?;
 
-        Ok(checksum)
+        read_chunks_from(file, path, state, process_chunk, processed).await
+    }
+
+    /// Read `reader` of file at `path` chunk by chunk, handing every chunk to `process_chunk` along with `state`
+    ///
+    /// Chunks are processed on a blocking thread, keeping hashing off the threads of the runtime,
+    /// `processed` is called with the size of every chunk once processed.
+    /// Up to [`HashingOptions::read_ahead()`] chunks are read while previous ones are processed.
+    async fn read_chunks_from<State: Send + 'static>(
+        mut reader: impl AsyncRead + Unpin,
+        path: &Path,
+        mut state: State,
+        process_chunk: fn(&mut State, &[u8]),
+        mut processed: impl FnMut(u64),
+    ) -> Result<State, ChecksumComputeError> {
+        let options = hashing_options();
+        let read_error =
+            |e: std::io::Error| ChecksumComputeError::ReadFile(path.to_path_buf(), e.kind());
+
+        // Buffers of chunks go to the blocking thread, and come back once processed to be filled again
+        let (sender, receiver) = std::sync::mpsc::channel::<Vec<u8>>();
+        let (processed_sender, mut processed_receiver) = futures::channel::mpsc::unbounded();
+        let processing = spawn_blocking(move || {
+            while let Ok(chunk) = receiver.recv() {
+                process_chunk(&mut state, &chunk);
+                if processed_sender.unbounded_send(chunk).is_err() {
+                    break;
+                }
+            }
+            state
+        });
+
+        let mut in_flight = 0;
+        loop {
+            let mut chunk = if in_flight <= options.read_ahead {
+                Vec::with_capacity(options.buffer_size)
+            } else {
+                let mut chunk = processed_receiver
+                    .next()
+                    .await
+                    .ok_or(ChecksumComputeError::ComputeChecksum)?;
+                in_flight -= 1;
+                processed(chunk.len() as u64);
+                chunk.clear();
+                chunk
+            };
+
+            (&mut reader)
+                .take(options.buffer_size as u64)
+                .read_to_end(&mut chunk)
+                .await
+                .map_err(read_error)?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            sender
+                .send(chunk)
+                .map_err(|_| ChecksumComputeError::ComputeChecksum)?;
+            in_flight += 1;
+        }
+        drop(sender);
+
+        while let Some(chunk) = processed_receiver.next().await {
+            processed(chunk.len() as u64);
+        }
+
+        processing
+            .await
+            .map_err(|_| ChecksumComputeError::ComputeChecksum)
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Checksum could not be parsed from a string
+#[error("Checksum is not a valid hex string")]
+pub struct ChecksumParseError;
+
+/// Longest digest stored inline in a [`Checksum`], the size of SHA-256 digests
+///
+/// Longer digests are boxed, a checksum then takes as much memory as a digest of this size.
+const INLINE_DIGEST_SIZE: usize = 32;
+
+#[derive(Clone)]
+/// Storage of a checksum
+enum Bytes {
+    /// Digest of up to [`INLINE_DIGEST_SIZE`] bytes, unused bytes are zeroes
+    Inline {
+        length: u8,
+        digest: [u8; INLINE_DIGEST_SIZE],
+    },
+    /// Longer digest
+    Boxed(Box<[u8]>),
+    /// String that is not hex, kept as given to `From<&str>` or `From<String>`
+    Text(Box<str>),
+}
+
+#[derive(Clone)]
 /// Integrity checksum for a payload of a BagIt container.
 ///
 /// Every payload in a BagIt container must have a checksum, you can compute one with [`Checksum::digest()`].
-pub struct Checksum<'a>(Cow<'a, str>);
+///
+/// The digest is stored as raw bytes in a fixed-size array, without allocating for digests of up to 32 bytes like those
+/// of SHA-256, it is encoded as a lowercase hex string when displayed.
+/// Hex strings of manifests are accepted in any case, as allowed by RFC 8493.
+pub struct Checksum<'a> {
+    bytes: Bytes,
+    lifetime: PhantomData<&'a [u8]>,
+}
 
 impl Checksum<'_> {
-    /// Compute checksum for bytes, encoded as a lowercase hex string
+    /// Compute checksum for bytes
     ///
     /// # Examples
     ///
     /// ```
     /// # use async_bagit::Checksum;
     /// assert_eq!(
-    ///      Checksum::digest::<sha2::Sha256>("i love my bag, it is awesome".into()),
+    ///     Checksum::digest::<sha2::Sha256>("i love my bag, it is awesome".into()),
     ///     Checksum::from("9d5e40310ff9851f519fe3f84770e7c4ef9d840d26d040804db4a1fd0a9d4038")
     /// );
-    ///
+    /// ```
     pub fn digest<Algorithm: Digest>(bytes: Vec<u8>) -> Self {
-        Algorithm::digest(bytes).to_vec().into()
+        Algorithm::digest(bytes).as_slice().into()
+    }
+
+    /// Raw bytes of the digest
+    ///
+    /// Checksums created from a string that is not hex with `From<&str>` or `From<String>` give the bytes of the string.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.bytes {
+            Bytes::Inline { length, digest } => &digest[..*length as usize],
+            Bytes::Boxed(digest) => digest,
+            Bytes::Text(text) => text.as_bytes(),
+        }
+    }
+
+    /// Get an owned checksum
+    pub fn into_owned(self) -> Checksum<'static> {
+        Checksum {
+            bytes: self.bytes,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// Checksum kept as a string, because it is not hex
+    fn text(text: Box<str>) -> Self {
+        Self {
+            bytes: Bytes::Text(text),
+            lifetime: PhantomData,
+        }
+    }
+
+    /// What checksums are compared with: whether they are text, and their bytes
+    fn key(&self) -> (bool, &[u8]) {
+        (matches!(self.bytes, Bytes::Text(_)), self.as_bytes())
     }
 }
 
 impl From<&[u8]> for Checksum<'_> {
     fn from(value: &[u8]) -> Self {
-        Self(Cow::Owned(hex::encode(value)))
+        let bytes = match value.len() <= INLINE_DIGEST_SIZE {
+            true => {
+                let mut digest = [0; INLINE_DIGEST_SIZE];
+                digest[..value.len()].copy_from_slice(value);
+                Bytes::Inline {
+                    length: value.len() as u8,
+                    digest,
+                }
+            }
+            false => Bytes::Boxed(value.into()),
+        };
+
+        Self {
+            bytes,
+            lifetime: PhantomData,
+        }
     }
 }
 
 impl From<Vec<u8>> for Checksum<'_> {
     fn from(value: Vec<u8>) -> Self {
-        Self(Cow::Owned(hex::encode(value)))
+        match value.len() <= INLINE_DIGEST_SIZE {
+            true => value.as_slice().into(),
+            false => Self {
+                bytes: Bytes::Boxed(value.into_boxed_slice()),
+                lifetime: PhantomData,
+            },
+        }
     }
 }
 
-impl<'a> From<&'a str> for Checksum<'a> {
-    fn from(value: &'a str) -> Checksum<'a> {
-        Self(Cow::Borrowed(value))
+impl From<&str> for Checksum<'_> {
+    /// Parse checksum from its hex representation like [`Checksum::from_str()`]
+    ///
+    /// A string that is not hex is kept as it is, it never equals the checksum of a digest.
+    fn from(value: &str) -> Self {
+        Self::from_str(value).unwrap_or_else(|_| Self::text(value.into()))
     }
 }
 
 impl From<String> for Checksum<'_> {
+    /// Parse checksum from its hex representation like [`Checksum::from_str()`]
+    ///
+    /// A string that is not hex is kept as it is, it never equals the checksum of a digest.
     fn from(value: String) -> Self {
-        Self(Cow::Owned(value))
+        Self::from_str(&value).unwrap_or_else(|_| Self::text(value.into_boxed_str()))
+    }
+}
+
+impl FromStr for Checksum<'_> {
+    type Err = ChecksumParseError;
+
+    /// Parse checksum from its hex representation, in lowercase, uppercase or a mix of both
+    ///
+    /// Checksums are compared by their digest, whatever the case of the hex string they were parsed from.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > 2 * INLINE_DIGEST_SIZE {
+            return hex::decode(s)
+                .map(Self::from)
+                .map_err(|_| ChecksumParseError);
+        }
+
+        let mut digest = [0; INLINE_DIGEST_SIZE];
+        hex::decode_to_slice(s, &mut digest[..s.len() / 2]).map_err(|_| ChecksumParseError)?;
+        Ok(Self {
+            bytes: Bytes::Inline {
+                length: (s.len() / 2) as u8,
+                digest,
+            },
+            lifetime: PhantomData,
+        })
+    }
+}
+
+impl PartialEq for Checksum<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Checksum<'_> {}
+
+impl PartialOrd for Checksum<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Checksum<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Checksum<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
     }
 }
 
 impl Display for Checksum<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match &self.bytes {
+            Bytes::Text(text) => write!(f, "{text}"),
+            _ => write!(f, "{}", hex::encode(self.as_bytes())),
+        }
     }
 }
 
-impl AsRef<str> for Checksum<'_> {
-    fn as_ref(&self) -> &str {
-        match &self.0 {
-            Cow::Borrowed(borrowed) => borrowed,
-            Cow::Owned(owned) => owned.as_ref(),
-        }
+impl Debug for Checksum<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Checksum").field(&self.to_string()).finish()
+    }
+}
+
+impl AsRef<[u8]> for Checksum<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
     }
 }
 
@@ -138,6 +640,181 @@ mod test {
         assert_eq!(left, right);
     }
 
+    #[test]
+    fn display_hex() {
+        let checksum = Checksum::from(vec![0, 15, 16, 255]);
+        assert_eq!(checksum.to_string(), "000f10ff");
+        assert_eq!(Checksum::from_str("000f10ff"), Ok(checksum));
+    }
+
+    #[test]
+    fn parse_any_case() {
+        let lowercase = Checksum::from_str("000f10ff").unwrap();
+        for input in ["000F10FF", "000f10FF", "000F10ff"] {
+            assert_eq!(
+                Checksum::from_str(input),
+                Ok(lowercase.clone()),
+                "failing on input value `{input}`"
+            );
+        }
+        assert_eq!(
+            Checksum::from_str("000F10FF").unwrap().to_string(),
+            "000f10ff"
+        );
+    }
+
+    #[test]
+    fn from_text() {
+        assert_eq!(
+            Checksum::from(String::from("000F10FF")),
+            Checksum::from_str("000f10ff").unwrap()
+        );
+
+        // Strings that are not hex are kept, they are not digests
+        let text = Checksum::from("not hex");
+        assert_eq!(text.to_string(), "not hex");
+        assert_eq!(text, Checksum::from(String::from("not hex")));
+        assert_ne!(text, Checksum::from(b"not hex".as_ref()));
+    }
+
+    #[test]
+    fn long_digest() {
+        let bytes = (0..=100).collect::<Vec<u8>>();
+        let checksum = Checksum::from(bytes.clone());
+        assert_eq!(checksum.as_bytes(), bytes);
+        assert_eq!(Checksum::from_str(&checksum.to_string()), Ok(checksum));
+        assert_eq!(Checksum::from(vec![]).as_bytes(), b"");
+
+        // Digests are stored inline, with room for a box or a string when longer
+        assert!(std::mem::size_of::<Checksum>() <= INLINE_DIGEST_SIZE + 8);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for input in ["not hex at all", "abc", "0g"] {
+            assert_eq!(
+                Checksum::from_str(input),
+                Err(ChecksumParseError),
+                "failing on input value `{input}`"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn hash_file_sha256() {
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        assert_eq!(
+            hash_file::<sha2::Sha256>(source_directory.join("rfc8493.txt")).await,
+            Ok(Checksum::from_str(
+                "4964147d2e6e16442d4a6dbfbe68178a8f33c3e791c06d68a8b33f51ad821537"
+            )
+            .unwrap())
+        );
+
+        assert_eq!(
+            hash_file::<sha2::Sha256>(source_directory.join("missing.txt")).await,
+            Err(ChecksumComputeError::FileNotFound(
+                source_directory.join("missing.txt")
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn hash_file_multi_single_read() {
+        let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        file.push("tests/sample-bag/data/totebag.jpg");
+
+        let sha256 = crate::ChecksumAlgorithm::<sha2::Sha256>::new(crate::Algorithm::Sha256);
+        let md5 = crate::ChecksumAlgorithm::<md5::Md5>::new(crate::Algorithm::custom("md5"));
+
+        let checksums = hash_file_multi(&file, vec![md5.hasher(), sha256.hasher()])
+            .await
+            .unwrap();
+        assert_eq!(
+            checksums,
+            vec![
+                hash_file::<md5::Md5>(&file).await.unwrap(),
+                hash_file::<sha2::Sha256>(&file).await.unwrap()
+            ]
+        );
+
+        assert_eq!(hash_file_multi(&file, vec![]).await, Ok(vec![]));
+    }
+
+    #[tokio::test]
+    async fn hashing_options_same_checksum() {
+        let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        file.push("tests/sample-bag/data/totebag.jpg");
+        let expected = hash_file::<sha2::Sha256>(&file).await.unwrap();
+
+        for options in [
+            HashingOptions::default().buffer_size(0),
+            HashingOptions::default().buffer_size(1000),
+            HashingOptions::default().read_ahead(1),
+            HashingOptions::default().buffer_size(4096).read_ahead(4),
+        ] {
+            let checksum = options
+                .scope(async {
+                    assert_eq!(hashing_options(), options);
+                    hash_file::<sha2::Sha256>(&file).await
+                })
+                .await;
+            assert_eq!(
+                checksum,
+                Ok(expected.clone()),
+                "failing with options {options:?}"
+            );
+        }
+        assert_eq!(hashing_options(), HashingOptions::default());
+    }
+
+    /// SHA-256 recording threads it is fed bytes on
+    #[derive(Clone, Default)]
+    struct ThreadRecorder(sha2::Sha256);
+
+    static HASHING_THREADS: std::sync::Mutex<Vec<std::thread::ThreadId>> =
+        std::sync::Mutex::new(Vec::new());
+
+    impl digest::HashMarker for ThreadRecorder {}
+
+    impl digest::OutputSizeUser for ThreadRecorder {
+        type OutputSize = <sha2::Sha256 as digest::OutputSizeUser>::OutputSize;
+    }
+
+    impl digest::Update for ThreadRecorder {
+        fn update(&mut self, data: &[u8]) {
+            HASHING_THREADS
+                .lock()
+                .unwrap()
+                .push(std::thread::current().id());
+            digest::Update::update(&mut self.0, data);
+        }
+    }
+
+    impl digest::FixedOutput for ThreadRecorder {
+        fn finalize_into(self, out: &mut digest::Output<Self>) {
+            digest::FixedOutput::finalize_into(self.0, out);
+        }
+    }
+
+    #[tokio::test]
+    async fn hash_file_off_runtime() {
+        let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        file.push("tests/sample-bag/data/rfc8493.txt");
+
+        // Runtime of the test has a single thread, hashing does not happen on it
+        let checksum = HashingOptions::default()
+            .buffer_size(4096)
+            .scope(hash_file::<ThreadRecorder>(&file))
+            .await;
+        assert_eq!(checksum, hash_file::<sha2::Sha256>(&file).await);
+        let threads = HASHING_THREADS.lock().unwrap();
+        assert!(!threads.is_empty());
+        assert!(!threads.contains(&std::thread::current().id()));
+    }
+
     #[test]
     fn sha256() {
         assert_eq!(
@@ -0,0 +1,252 @@
+//! Framed codecs for manifests and tag files
+//!
+//! Manifests and tag files can be parsed from, or written to, any framed transport such as sockets or archives,
+//! with the same rules as files of a bag on disk. See [`ManifestCodec`] and [`TagFileCodec`].
+
+use crate::{
+    metadata::Metadata,
+    payload::{self, Payload},
+    BagVersion, Checksum,
+};
+use bytes::{Buf, BufMut, BytesMut};
+use std::{path::PathBuf, str::FromStr};
+use tokio_util::codec::{Decoder, Encoder};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Possible errors when decoding or encoding manifests and tag files
+pub enum CodecError {
+    /// Failed to read from or write to the transport
+    #[error("Failed to read or write: {0}")]
+    Io(std::io::ErrorKind),
+    /// Line is not encoded in UTF-8
+    #[error("Line is not valid UTF-8")]
+    Encoding,
+    /// Manifest line must be: "\<checksum\> \<relative path\>", with checksum encoded as hex
+    #[error("Invalid manifest line")]
+    InvalidManifestLine,
+    /// Tag line must be: "\<label\>: \<value\>"
+    #[error("Invalid tag")]
+    InvalidTag,
+}
+
+impl From<std::io::Error> for CodecError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value.kind())
+    }
+}
+
+/// Take next line of `source` without its line ending, if it is complete or if `eof` is reached
+fn next_line(source: &mut BytesMut, eof: bool) -> Result<Option<String>, CodecError> {
+    let line = match source.iter().position(|byte| *byte == b'\n') {
+        Some(position) => {
+            let line = source.split_to(position);
+            source.advance(1);
+            line
+        }
+        None if eof && !source.is_empty() => source.split(),
+        None => return Ok(None),
+    };
+
+    let mut line = String::from_utf8(line.to_vec()).map_err(|_| CodecError::Encoding)?;
+    if line.ends_with('\r') {
+        line.pop();
+    }
+
+    Ok(Some(line))
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Codec for lines of a manifest or tag manifest
+///
+/// Decodes checksums and paths of payloads relative to the bag directory, checksums are not verified.
+/// Payloads are encoded like in manifests written by [`BagIt::finalize()`](crate::BagIt::finalize).
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::codec::ManifestCodec;
+/// # use futures::StreamExt;
+/// # use tokio_util::codec::FramedRead;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let transport: &[u8] = b"0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/sources.csv\n";
+/// let mut manifest = FramedRead::new(transport, ManifestCodec);
+///
+/// let (checksum, path) = manifest.next().await.unwrap()?;
+/// assert_eq!(path, std::path::Path::new("data/sources.csv"));
+/// assert!(manifest.next().await.is_none());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ManifestCodec;
+
+impl ManifestCodec {
+    fn decode_line(
+        line: Option<String>,
+    ) -> Result<Option<(Checksum<'static>, PathBuf)>, CodecError> {
+        let Some(line) = line else {
+            return Ok(None);
+        };
+
+        let (checksum, relative_path) = payload::parse_manifest_line(&line, BagVersion::V1_0)
+            .map_err(|_| CodecError::InvalidManifestLine)?;
+
+        Ok(Some((checksum, relative_path.into_owned().into())))
+    }
+}
+
+impl Decoder for ManifestCodec {
+    type Item = (Checksum<'static>, PathBuf);
+    type Error = CodecError;
+
+    fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Self::decode_line(next_line(source, false)?)
+    }
+
+    fn decode_eof(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Self::decode_line(next_line(source, true)?)
+    }
+}
+
+impl Encoder<&Payload<'_>> for ManifestCodec {
+    type Error = CodecError;
+
+    fn encode(
+        &mut self,
+        payload: &Payload<'_>,
+        destination: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        destination.put(payload.to_string().as_bytes());
+        destination.put_u8(b'\n');
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Codec for tags of a tag file such as `bag-info.txt`
+///
+/// Tags are decoded as labels and values, after checking them like tags of `bag-info.txt` in a bag on disk.
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::codec::TagFileCodec;
+/// # use futures::{SinkExt, StreamExt};
+/// # use tokio_util::codec::{FramedRead, FramedWrite};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut transport = vec![];
+/// let mut writer = FramedWrite::new(&mut transport, TagFileCodec);
+/// writer.send(("Source-Organization", "Totebag Inc.")).await?;
+/// assert_eq!(transport, b"Source-Organization: Totebag Inc.\n");
+///
+/// let mut reader = FramedRead::new(transport.as_slice(), TagFileCodec);
+/// assert_eq!(
+///     reader.next().await.unwrap()?,
+///     ("Source-Organization".to_string(), "Totebag Inc.".to_string())
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TagFileCodec;
+
+impl TagFileCodec {
+    fn decode_line(line: Option<String>) -> Result<Option<(String, String)>, CodecError> {
+        let Some(line) = line else {
+            return Ok(None);
+        };
+
+        let tag = Metadata::from_str(&line).map_err(|_| CodecError::InvalidTag)?;
+
+        Ok(Some((tag.key().to_string(), tag.value())))
+    }
+}
+
+impl Decoder for TagFileCodec {
+    type Item = (String, String);
+    type Error = CodecError;
+
+    fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Self::decode_line(next_line(source, false)?)
+    }
+
+    fn decode_eof(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Self::decode_line(next_line(source, true)?)
+    }
+}
+
+impl<Label: Into<String>, Value: Into<String>> Encoder<(Label, Value)> for TagFileCodec {
+    type Error = CodecError;
+
+    fn encode(
+        &mut self,
+        (label, value): (Label, Value),
+        destination: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        let tag = Metadata::custom(label, value).map_err(|_| CodecError::InvalidTag)?;
+
+        destination.put(tag.to_string().as_bytes());
+        destination.put_u8(b'\n');
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn manifest_lines() {
+        let mut source = BytesMut::from(
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/sources.csv\r\n4964147d2e6e16442d4a6dbfbe68178a8f33c3e791c06d68a8b33f51ad821537 data/rfc",
+        );
+
+        let (checksum, path) = ManifestCodec.decode(&mut source).unwrap().unwrap();
+        assert_eq!(
+            checksum.to_string(),
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72"
+        );
+        assert_eq!(path, PathBuf::from("data/sources.csv"));
+
+        // Incomplete line, waiting for more bytes
+        assert_eq!(ManifestCodec.decode(&mut source), Ok(None));
+        source.put(&b"8493.txt"[..]);
+
+        // Last line without line ending
+        let (_, path) = ManifestCodec.decode_eof(&mut source).unwrap().unwrap();
+        assert_eq!(path, PathBuf::from("data/rfc8493.txt"));
+        assert_eq!(ManifestCodec.decode_eof(&mut source), Ok(None));
+
+        let mut source = BytesMut::from("not a checksum data/sources.csv\n");
+        assert_eq!(
+            ManifestCodec.decode(&mut source),
+            Err(CodecError::InvalidManifestLine)
+        );
+    }
+
+    #[test]
+    fn tag_lines() {
+        let mut destination = BytesMut::new();
+        TagFileCodec
+            .encode(("Contact-Name", "Philippe"), &mut destination)
+            .unwrap();
+        assert_eq!(
+            TagFileCodec.encode(("Contact:Name", "Philippe"), &mut destination),
+            Err(CodecError::InvalidTag)
+        );
+        assert_eq!(&destination[..], b"Contact-Name: Philippe\n");
+
+        assert_eq!(
+            TagFileCodec.decode(&mut destination),
+            Ok(Some(("Contact-Name".into(), "Philippe".into())))
+        );
+
+        let mut source = BytesMut::from("Tag-File-Character-Encoding: Shift_JIS\n");
+        assert_eq!(
+            TagFileCodec.decode(&mut source),
+            Err(CodecError::InvalidTag)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Completeness of bags on disk, checked without computing checksums
+
+use crate::encoding::{TagEncoding, TagFileReader};
+use crate::manifest::{algorithm_name, next_line, Manifest, PREFIX_MANIFEST};
+use crate::payload::{check_manifest_path, parse_manifest_line, PathResolution};
+use crate::{error::ReadError, walk, BagIt, BagVersion, ReadOptions};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::{fs, io::BufReader};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Reasons for a bag to be incomplete, see [`BagIt::check_completeness()`]
+pub enum CompletenessError {
+    /// Failed to read `bagit.txt` or manifests of the bag
+    #[error(transparent)]
+    Read(#[from] ReadError),
+    /// Payload directory `data` is missing
+    #[error("Missing payload directory `data`")]
+    MissingPayloadDirectory,
+    /// Failed to list files of payload directory
+    #[error("Listing payload files of `{}`: {1}", .0.display())]
+    ListPayloads(PathBuf, std::io::ErrorKind),
+    /// Bag must have at least one payload manifest
+    #[error("Missing payload manifest")]
+    MissingManifest,
+    /// File listed in a manifest or tag manifest is not in the bag
+    #[error("File `{}` is missing", .0.display())]
+    MissingFile(PathBuf),
+    /// File of payload directory is not listed in every payload manifest
+    #[error("Payload `{}` is not listed in every manifest", .0.display())]
+    UnlistedPayload(PathBuf),
+}
+
+/// Paths of files listed in a manifest or tag manifest, relative to the bag and resolved with `resolution`
+///
+/// Paths leading outside of the bag, or outside of where the manifest may point to, are rejected like when reading the bag.
+async fn listed_paths(
+    manifest: &Manifest,
+    encoding: TagEncoding,
+    version: BagVersion,
+    resolution: PathResolution,
+) -> Result<HashSet<PathBuf>, ReadError> {
+    let scope = manifest.scope();
+    let manifest = manifest.as_ref();
+    let file = fs::File::open(manifest)
+        .await
+        .map_err(|e| ReadError::OpenFile(manifest.to_path_buf(), e.kind()))?;
+    let mut file = BufReader::new(TagFileReader::new(file, encoding));
+    let mut line = String::new();
+
+    let mut paths = HashSet::new();
+    while let Some(line) = next_line(&mut file, &mut line)
+        .await
+        .map_err(|e| ReadError::ReadLine(manifest.to_path_buf(), e.kind()))?
+    {
+        let (_, relative_path) = parse_manifest_line(line, version)?;
+        check_manifest_path(&relative_path, scope)?;
+        paths.insert(PathBuf::from(resolution.apply(relative_path).as_ref()));
+    }
+
+    Ok(paths)
+}
+
+impl BagIt<'_, '_> {
+    /// Check that a bag on disk is complete, without computing checksums of its files
+    ///
+    /// A bag is complete when `bagit.txt` is valid, payload directory `data` and at least one payload manifest are present,
+    /// every file listed in manifests and tag manifests is in the bag, and every payload is listed in every payload manifest.
+    /// Payloads of `fetch.txt` must have been fetched.
+    ///
+    /// Only names of files are looked up, making it a quick sanity pass before a full validation with [`BagIt::read_existing()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::BagIt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// BagIt::check_completeness(bagit_directory).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_completeness(
+        bag_it_directory: impl AsRef<Path>,
+    ) -> Result<(), CompletenessError> {
+        Self::check_completeness_with_options(bag_it_directory, &ReadOptions::default()).await
+    }
+
+    /// Check that a bag on disk is complete like [`BagIt::check_completeness()`], resolving paths of manifests like when
+    /// reading the bag with `options`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{BagIt, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let options = ReadOptions::default().windows_separators(true);
+    /// BagIt::check_completeness_with_options(bagit_directory, &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_completeness_with_options(
+        bag_it_directory: impl AsRef<Path>,
+        options: &ReadOptions,
+    ) -> Result<(), CompletenessError> {
+        let bag_it_directory = bag_it_directory.as_ref();
+        let (version, encoding) = Self::read_declaration(bag_it_directory).await?;
+        options.check_encoding(encoding)?;
+
+        let payload_directory = bag_it_directory.join("data");
+        match fs::metadata(&payload_directory).await {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => return Err(CompletenessError::MissingPayloadDirectory),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CompletenessError::MissingPayloadDirectory)
+            }
+            Err(e) => return Err(CompletenessError::ListPayloads(payload_directory, e.kind())),
+        }
+        let payloads = walk::list_files(&payload_directory)
+            .await
+            .map_err(|e| CompletenessError::ListPayloads(payload_directory.clone(), e.kind()))?
+            .into_iter()
+            .map(|payload| Path::new("data").join(payload))
+            .collect::<Vec<_>>();
+
+        let mut manifests = Manifest::find_all(bag_it_directory).await?;
+        manifests.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+        let is_payload_manifest =
+            |manifest: &Manifest| algorithm_name(manifest.as_ref(), PREFIX_MANIFEST).is_some();
+        if !manifests
+            .iter()
+            .any(|(manifest, _)| is_payload_manifest(manifest))
+        {
+            return Err(CompletenessError::MissingManifest);
+        }
+
+        for (manifest, _) in manifests {
+            let listed =
+                listed_paths(&manifest, encoding, version, options.path_resolution()).await?;
+
+            if is_payload_manifest(&manifest) {
+                let mut missing = listed
+                    .iter()
+                    .filter(|path| payloads.binary_search(path).is_err())
+                    .collect::<Vec<_>>();
+                missing.sort();
+                if let Some(path) = missing.first() {
+                    return Err(CompletenessError::MissingFile(path.to_path_buf()));
+                }
+
+                if let Some(path) = payloads.iter().find(|path| !listed.contains(*path)) {
+                    return Err(CompletenessError::UnlistedPayload(path.clone()));
+                }
+            } else {
+                let mut listed = listed.into_iter().collect::<Vec<_>>();
+                listed.sort();
+                for path in listed {
+                    if !fs::metadata(bag_it_directory.join(&path))
+                        .await
+                        .is_ok_and(|metadata| metadata.is_file())
+                    {
+                        return Err(CompletenessError::MissingFile(path));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use super::CompletenessError;
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm};
+    use sha2::Sha256;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn completeness() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+        assert_eq!(BagIt::check_completeness(&temp_directory).await, Ok(()));
+
+        // Contents of files are not looked at
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        assert_eq!(BagIt::check_completeness(&temp_directory).await, Ok(()));
+
+        // Payload not in manifest
+        tokio::fs::create_dir(temp_directory.join("data/extra"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/extra/notes.txt"), "")
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::UnlistedPayload(
+                "data/extra/notes.txt".into()
+            ))
+        );
+        tokio::fs::remove_dir_all(temp_directory.join("data/extra"))
+            .await
+            .unwrap();
+
+        // Payload of manifest missing
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::MissingFile("data/totebag.jpg".into()))
+        );
+
+        // Bag without payload manifest
+        tokio::fs::rename(
+            temp_directory.join("manifest-sha256.txt"),
+            temp_directory.join("manifest-sha256.bak"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::MissingManifest)
+        );
+        tokio::fs::rename(
+            temp_directory.join("manifest-sha256.bak"),
+            temp_directory.join("manifest-sha256.txt"),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(temp_directory.join("data/totebag.jpg"), "")
+            .await
+            .unwrap();
+
+        // Tag file of tag manifest missing
+        tokio::fs::remove_file(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::MissingFile("bag-info.txt".into()))
+        );
+
+        tokio::fs::remove_dir_all(temp_directory.join("data"))
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::MissingPayloadDirectory)
+        );
+    }
+
+    #[tokio::test]
+    async fn completeness_manifest_paths() {
+        use crate::error::{PayloadError, ReadError};
+        use crate::ReadOptions;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        // Manifest written by a tool running on Windows
+        tokio::fs::create_dir_all(temp_directory.join("data/notes"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/notes/hello.txt"), "hello")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data\\notes\\hello.txt\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::check_completeness(&temp_directory).await,
+            Err(CompletenessError::MissingFile(
+                "data\\notes\\hello.txt".into()
+            ))
+        );
+        let options = ReadOptions::default().windows_separators(true);
+        assert_eq!(
+            BagIt::check_completeness_with_options(&temp_directory, &options).await,
+            Ok(())
+        );
+
+        // Tag manifest pointing outside of the bag
+        tokio::fs::write(temp_directory.join("tagmanifest-sha256.txt"), {
+            let outside = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+            format!("{outside}  ../outside.txt\n")
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::check_completeness_with_options(&temp_directory, &options).await,
+            Err(CompletenessError::Read(ReadError::ProcessManifestLine(
+                PayloadError::ParentDirectory
+            )))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Opt-in extension storing payloads compressed with Zstandard
+//!
+//! Compressed payloads are regular payloads for other BagIt tools: the manifest lists checksums of compressed files.
+//! Checksums and sizes of original files are recorded in tag file `compressed-payloads.txt`, one line per payload:
+//! "\<original checksum\> \<original size\> \<relative path of compressed payload\>".
+
+use crate::Checksum;
+#[cfg(any(feature = "read", feature = "generate"))]
+use crate::Payload;
+#[cfg(feature = "read")]
+use crate::{error::ReadError, payload::PayloadError};
+use async_compression::tokio::bufread::ZstdDecoder;
+#[cfg(any(feature = "read", feature = "generate"))]
+use std::path::Path;
+#[cfg(feature = "read")]
+use std::str::FromStr;
+use tokio::{
+    fs,
+    io::{AsyncRead, BufReader},
+};
+
+/// Name of tag file with checksums and sizes of original files of compressed payloads
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) const COMPRESSED_PAYLOADS_FILE_NAME: &str = "compressed-payloads.txt";
+
+/// Extension added to names of compressed payloads
+#[cfg(feature = "generate")]
+pub(crate) const COMPRESSED_EXTENSION: &str = "zst";
+
+#[derive(Clone, Debug, PartialEq)]
+/// Original file of a compressed payload
+pub(crate) struct Original {
+    pub checksum: Checksum<'static>,
+    pub bytes: u64,
+}
+
+/// Compress `source` file into `destination` file
+#[cfg(feature = "generate")]
+pub(crate) async fn compress_file(
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+) -> Result<(), std::io::Error> {
+    use crate::long_path;
+    use async_compression::tokio::write::ZstdEncoder;
+    use tokio::io::AsyncWriteExt;
+
+    let mut source = fs::File::open(long_path::extended(source.as_ref())).await?;
+    let mut encoder =
+        ZstdEncoder::new(fs::File::create(long_path::extended(destination.as_ref())).await?);
+
+    tokio::io::copy(&mut source, &mut encoder).await?;
+    encoder.shutdown().await
+}
+
+/// Decompress contents of compressed payload while it is read
+pub(crate) fn decompress(file: fs::File) -> impl AsyncRead + Send + Sync {
+    ZstdDecoder::new(BufReader::new(file))
+}
+
+/// Contents of tag file of original files of compressed payloads
+#[cfg(feature = "generate")]
+pub(crate) fn compressed_payloads_contents<'a>(
+    payloads: impl Iterator<Item = &'a Payload<'a>>,
+) -> String {
+    payloads
+        .filter_map(|payload| {
+            payload.original().map(|original| {
+                format!(
+                    "{} {} {}\n",
+                    original.checksum,
+                    original.bytes,
+                    payload.relative_path().display()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Read tag file of original files of compressed payloads if the bag has one, and attach them to `payloads`
+#[cfg(feature = "read")]
+pub(crate) async fn read_compressed_payloads(
+    bag_directory: &Path,
+    payloads: &mut [Payload<'_>],
+) -> Result<(), ReadError> {
+    let path = bag_directory.join(COMPRESSED_PAYLOADS_FILE_NAME);
+    let contents = match fs::read_to_string(&path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(ReadError::OpenFile(path, e.kind())),
+    };
+
+    for line in contents.lines() {
+        let mut parts = line.splitn(3, ' ');
+        let (Some(checksum), Some(bytes), Some(relative_path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(PayloadError::InvalidLine.into());
+        };
+
+        let original = Original {
+            checksum: Checksum::from_str(checksum).map_err(|_| PayloadError::InvalidLine)?,
+            bytes: bytes.parse().map_err(|_| PayloadError::InvalidLine)?,
+        };
+
+        // Original must be one of a payload of the bag
+        let payload = payloads
+            .iter_mut()
+            .find(|payload| payload.relative_path() == Path::new(relative_path))
+            .ok_or(PayloadError::InvalidLine)?;
+        payload.set_original(original);
+    }
+
+    Ok(())
+}
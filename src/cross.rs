@@ -0,0 +1,339 @@
+//! Validation of payloads against manifests of several algorithms, reading every file only once
+
+use crate::fetch::read_fetch_file;
+use crate::manifest::{ListedFiles, Manifest};
+use crate::{
+    error::{PayloadError, ReadError},
+    Algorithm, BagIt, ReadOptions, ValidationOutcome,
+};
+use futures::StreamExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq)]
+/// Payloads of a bag validated against the manifest of one algorithm
+///
+/// See [`BagIt::cross_validate()`].
+pub struct AlgorithmValidation {
+    algorithm: Algorithm,
+    checked: usize,
+    failed_payloads: Vec<(PathBuf, PayloadError)>,
+    unlisted_payloads: Vec<PathBuf>,
+}
+
+impl AlgorithmValidation {
+    /// Algorithm of the manifest
+    pub fn algorithm(&self) -> &Algorithm {
+        &self.algorithm
+    }
+
+    /// Number of payloads of the manifest that were hashed
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// Payloads of the manifest that failed validation, like [`PayloadError::ChecksumDiffers`]
+    pub fn failed_payloads(&self) -> &[(PathBuf, PayloadError)] {
+        &self.failed_payloads
+    }
+
+    /// Payloads listed in manifests of other algorithms, but not in this one
+    pub fn unlisted_payloads(&self) -> &[PathBuf] {
+        &self.unlisted_payloads
+    }
+
+    /// Whether every payload matched the manifest, and the manifest lists the same payloads as the others
+    pub fn outcome(&self) -> ValidationOutcome {
+        match self.failed_payloads.is_empty() && self.unlisted_payloads.is_empty() {
+            true => ValidationOutcome::Valid,
+            false => ValidationOutcome::Invalid,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+/// Results of validating payloads of a bag against manifests of several algorithms
+///
+/// See [`BagIt::cross_validate()`].
+pub struct CrossValidation {
+    algorithms: Vec<AlgorithmValidation>,
+}
+
+impl CrossValidation {
+    /// Results of every algorithm having a manifest in the bag, in order of registration with [`ReadOptions::algorithm()`]
+    pub fn algorithms(&self) -> &[AlgorithmValidation] {
+        &self.algorithms
+    }
+
+    /// Results of `algorithm`, if it has a manifest in the bag
+    pub fn algorithm(&self, algorithm: &Algorithm) -> Option<&AlgorithmValidation> {
+        self.algorithms
+            .iter()
+            .find(|validation| validation.algorithm == *algorithm)
+    }
+
+    /// Whether payloads matched manifests of every algorithm
+    pub fn is_valid(&self) -> bool {
+        self.algorithms
+            .iter()
+            .all(|validation| validation.outcome() == ValidationOutcome::Valid)
+    }
+}
+
+impl BagIt<'_, '_> {
+    /// Validate payloads of a bag on disk against manifests of every algorithm registered with [`ReadOptions::algorithm()`]
+    ///
+    /// Every payload is read once, its checksums are computed for all algorithms listing it at the same time,
+    /// with up to [`ReadOptions::concurrency()`] files hashed at once. Results are reported per algorithm,
+    /// along with payloads missing from some manifests but not from others.
+    ///
+    /// Registered algorithms without a manifest in the bag are ignored, the bag is rejected with
+    /// [`ReadError::NotRequestedAlgorithm`] when none of them has one. A manifest listing a path twice is rejected with
+    /// [`ReadError::DuplicateManifestEntry`].
+    /// Paths are resolved like when reading the bag with `options`, hashing of files is reported to [`ReadOptions::progress()`].
+    /// Only payloads are validated, see [`BagIt::read_existing()`] for tag files and Payload-Oxum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+    /// let options = ReadOptions::default().algorithm(&sha256).algorithm(&sha512);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// let results = BagIt::cross_validate(bagit_directory, &options).await?;
+    /// for validation in results.algorithms() {
+    ///     println!("{}: {:?}", validation.algorithm(), validation.outcome());
+    /// }
+    /// assert!(results.is_valid());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cross_validate(
+        bag_it_directory: impl AsRef<Path>,
+        options: &ReadOptions,
+    ) -> Result<CrossValidation, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+        let (version, encoding) =
+            Self::read_accepted_declaration(&bag_it_directory, options).await?;
+
+        let remote_paths = read_fetch_file(&bag_it_directory, encoding, version)
+            .await?
+            .iter()
+            .map(|item| item.relative_path().to_path_buf())
+            .collect();
+        let context =
+            options.read_context(bag_it_directory.clone(), encoding, version, remote_paths);
+
+        // Checksums listed in manifest of every registered algorithm the bag has
+        let mut listed = ListedFiles::default();
+        let mut algorithms = vec![];
+        for (algorithm, new_hasher) in options.registered_algorithms() {
+            let (Some(manifest), _) =
+                Manifest::find_manifests(&bag_it_directory, algorithm, &mut vec![]).await?
+            else {
+                continue;
+            };
+            let payloads = manifest
+                .read_payloads_unvalidated(&context, new_hasher().output_size())
+                .await?;
+
+            listed.add(algorithms.len(), new_hasher, &payloads);
+            algorithms.push(AlgorithmValidation {
+                algorithm: algorithm.clone(),
+                checked: payloads.len(),
+                failed_payloads: vec![],
+                unlisted_payloads: vec![],
+            });
+        }
+        if algorithms.is_empty() {
+            return Err(ReadError::NotRequestedAlgorithm);
+        }
+
+        // Hash every payload once, with algorithms of manifests listing it
+        let hashed = futures::stream::iter(listed.files)
+            .map(|file| {
+                let bag_it_directory = bag_it_directory.clone();
+                let progress = options.observer();
+                async move {
+                    let computed = file.hash_others(&bag_it_directory, progress).await;
+                    (file, computed)
+                }
+            })
+            .buffered(options.concurrency_limit())
+            .collect::<Vec<_>>()
+            .await;
+
+        for (file, computed) in hashed {
+            for (index, validation) in algorithms.iter_mut().enumerate() {
+                if !file.others.iter().any(|(listed, _, _)| *listed == index) {
+                    validation
+                        .unlisted_payloads
+                        .push(file.relative_path.clone());
+                }
+            }
+
+            // Checksums were computed in order of manifests listing the payload
+            for (position, (index, _, expected)) in file.others.iter().enumerate() {
+                let error = match &computed {
+                    Ok(computed) if computed[position] == *expected => continue,
+                    Ok(computed) => PayloadError::ChecksumDiffers {
+                        path: file.relative_path.clone(),
+                        expected: expected.clone(),
+                        actual: computed[position].clone(),
+                    },
+                    Err(e) => PayloadError::ComputeChecksum(e.clone()),
+                };
+                algorithms[*index]
+                    .failed_payloads
+                    .push((file.relative_path.clone(), error));
+            }
+        }
+
+        Ok(CrossValidation { algorithms })
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use crate::error::PayloadError;
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions, ValidationOutcome};
+    use md5::Md5;
+    use sha2::Sha256;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn cross_validate() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&md5).unwrap();
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // No registered algorithm has a manifest
+        assert_eq!(
+            BagIt::cross_validate(&temp_directory, &ReadOptions::default()).await,
+            Err(crate::error::ReadError::NotRequestedAlgorithm)
+        );
+
+        let options = ReadOptions::default().algorithm(&md5).algorithm(&sha256);
+        let results = BagIt::cross_validate(&temp_directory, &options)
+            .await
+            .unwrap();
+        assert!(results.is_valid());
+        assert_eq!(
+            results
+                .algorithms()
+                .iter()
+                .map(|validation| (validation.algorithm().clone(), validation.checked()))
+                .collect::<Vec<_>>(),
+            [(Algorithm::custom("md5"), 2), (Algorithm::Sha256, 2)]
+        );
+
+        // Manifests disagree: one payload is damaged according to MD5, and missing from SHA-256 manifest
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-md5.txt"))
+            .await
+            .unwrap();
+        let manifest = manifest.replacen(&manifest[..4], "0000", 1);
+        tokio::fs::write(temp_directory.join("manifest-md5.txt"), &manifest)
+            .await
+            .unwrap();
+        let damaged = PathBuf::from(manifest.lines().next().unwrap().split_once(' ').unwrap().1);
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        let manifest = manifest
+            .lines()
+            .filter(|line| !line.ends_with(damaged.to_str().unwrap()))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        tokio::fs::write(temp_directory.join("manifest-sha256.txt"), manifest)
+            .await
+            .unwrap();
+
+        let results = BagIt::cross_validate(&temp_directory, &options)
+            .await
+            .unwrap();
+        assert!(!results.is_valid());
+
+        let md5_results = results.algorithm(&Algorithm::custom("md5")).unwrap();
+        assert_eq!(md5_results.outcome(), ValidationOutcome::Invalid);
+        assert!(matches!(
+            md5_results.failed_payloads(),
+            [(path, PayloadError::ChecksumDiffers { expected, actual, .. })]
+                if *path == damaged && expected.to_string().starts_with("0000") && expected != actual
+        ));
+        assert!(md5_results.unlisted_payloads().is_empty());
+
+        let sha256_results = results.algorithm(&Algorithm::Sha256).unwrap();
+        assert_eq!(sha256_results.outcome(), ValidationOutcome::Invalid);
+        assert_eq!(sha256_results.checked(), 1);
+        assert!(sha256_results.failed_payloads().is_empty());
+        assert_eq!(sha256_results.unlisted_payloads(), [damaged]);
+    }
+
+    #[tokio::test]
+    async fn cross_validate_path_options() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        // Manifest of SHA-256 written by a tool running on Windows
+        tokio::fs::create_dir_all(temp_directory.join("data/notes"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/notes/hello.txt"), "hello")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data\\notes\\hello.txt\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            temp_directory.join("manifest-md5.txt"),
+            "5d41402abc4b2a76b9719d911017c592  data/notes/hello.txt\n",
+        )
+        .await
+        .unwrap();
+
+        let options = ReadOptions::default().algorithm(&sha256).algorithm(&md5);
+        assert!(BagIt::cross_validate(&temp_directory, &options)
+            .await
+            .is_err());
+
+        let results = BagIt::cross_validate(&temp_directory, &options.windows_separators(true))
+            .await
+            .unwrap();
+        assert!(results.is_valid());
+        assert!(results
+            .algorithms()
+            .iter()
+            .all(|validation| validation.checked() == 1));
+    }
+}
@@ -0,0 +1,576 @@
+//! Algorithms chosen at runtime, from a configuration file or from the manifests of a bag
+//!
+//! Most methods take the digest of the algorithm as a type parameter, only known at compile time. A [`DynChecksumAlgorithm`]
+//! erases it: monomorphized functions of the digest are kept behind a trait object, and an [`AlgorithmRegistry`] finds them
+//! by name.
+
+use crate::algorithm::NewHasher;
+#[cfg(feature = "generate")]
+use crate::error::GenerateError;
+#[cfg(feature = "read")]
+use crate::read::{read_with, ReadWith};
+use crate::{Algorithm, ChecksumAlgorithm};
+#[cfg(feature = "generate")]
+use crate::{BagIt, FinalizeOptions, FinalizePreview};
+use digest::{Digest, DynDigest};
+#[cfg(feature = "generate")]
+use futures::future::BoxFuture;
+use std::marker::PhantomData;
+#[cfg(feature = "generate")]
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "generate")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "generate")]
+use tokio::io::AsyncRead;
+
+/// Operations of [`BagIt`] monomorphized for a digest, object-safe so that the digest can be erased
+trait DigestOps: Send + Sync {
+    /// Function creating hashers of the digest
+    fn new_hasher(&self) -> NewHasher;
+
+    /// Function reading bags with the digest
+    #[cfg(feature = "read")]
+    fn read_with(&self) -> ReadWith;
+
+    /// See [`BagIt::add_file()`]
+    #[cfg(feature = "generate")]
+    fn add_file<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        file: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>>;
+
+    /// See [`BagIt::add_file_as()`]
+    #[cfg(feature = "generate")]
+    fn add_file_as<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        file: PathBuf,
+        destination: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>>;
+
+    /// See [`BagIt::add_directory()`]
+    #[cfg(feature = "generate")]
+    #[allow(clippy::type_complexity)]
+    fn add_directory<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        directory: PathBuf,
+    ) -> BoxFuture<'b, Result<Vec<(PathBuf, Result<(), GenerateError>)>, GenerateError>>;
+
+    /// See [`BagIt::add_from_reader()`]
+    #[cfg(feature = "generate")]
+    fn add_from_reader<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        reader: Box<dyn AsyncRead + Unpin + Send + 'b>,
+        destination: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>>;
+
+    /// See [`BagIt::finalize_with_options()`]
+    #[cfg(feature = "generate")]
+    fn finalize_with_options<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        options: FinalizeOptions,
+    ) -> BoxFuture<'b, Result<(), GenerateError>>;
+
+    /// See [`BagIt::preview_finalize_with_options()`]
+    #[cfg(feature = "generate")]
+    fn preview_finalize_with_options<'b>(
+        &self,
+        bag: &'b BagIt<'_, '_>,
+        options: FinalizeOptions,
+    ) -> BoxFuture<'b, Result<FinalizePreview, GenerateError>>;
+}
+
+/// Implementation of [`DigestOps`] for digest `ChecksumAlgo`
+struct Ops<ChecksumAlgo>(PhantomData<fn() -> ChecksumAlgo>);
+
+impl<ChecksumAlgo: Digest + DynDigest + Send + 'static> DigestOps for Ops<ChecksumAlgo> {
+    fn new_hasher(&self) -> NewHasher {
+        || Box::new(<ChecksumAlgo as Digest>::new())
+    }
+
+    #[cfg(feature = "read")]
+    fn read_with(&self) -> ReadWith {
+        read_with::<ChecksumAlgo>
+    }
+
+    #[cfg(feature = "generate")]
+    fn add_file<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        file: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>> {
+        Box::pin(bag.add_file::<ChecksumAlgo>(file))
+    }
+
+    #[cfg(feature = "generate")]
+    fn add_file_as<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        file: PathBuf,
+        destination: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>> {
+        Box::pin(bag.add_file_as::<ChecksumAlgo>(file, destination))
+    }
+
+    #[cfg(feature = "generate")]
+    fn add_directory<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        directory: PathBuf,
+    ) -> BoxFuture<'b, Result<Vec<(PathBuf, Result<(), GenerateError>)>, GenerateError>> {
+        Box::pin(bag.add_directory::<ChecksumAlgo>(directory))
+    }
+
+    #[cfg(feature = "generate")]
+    fn add_from_reader<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        reader: Box<dyn AsyncRead + Unpin + Send + 'b>,
+        destination: PathBuf,
+    ) -> BoxFuture<'b, Result<(), GenerateError>> {
+        Box::pin(bag.add_from_reader::<ChecksumAlgo>(reader, destination))
+    }
+
+    #[cfg(feature = "generate")]
+    fn finalize_with_options<'b>(
+        &self,
+        bag: &'b mut BagIt<'_, '_>,
+        options: FinalizeOptions,
+    ) -> BoxFuture<'b, Result<(), GenerateError>> {
+        Box::pin(bag.finalize_with_options::<ChecksumAlgo>(options))
+    }
+
+    #[cfg(feature = "generate")]
+    fn preview_finalize_with_options<'b>(
+        &self,
+        bag: &'b BagIt<'_, '_>,
+        options: FinalizeOptions,
+    ) -> BoxFuture<'b, Result<FinalizePreview, GenerateError>> {
+        Box::pin(bag.preview_finalize_with_options::<ChecksumAlgo>(options))
+    }
+}
+
+#[derive(Clone)]
+/// Algorithm along with its digest, chosen at runtime
+///
+/// Created from a [`ChecksumAlgorithm`], or found by name in an [`AlgorithmRegistry`]. Bags are read with
+/// [`BagIt::read_existing_dyn()`] and created with [`DynBagIt`], the digest is not a type parameter of their methods.
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::{Algorithm, ChecksumAlgorithm, DynChecksumAlgorithm};
+/// let algorithm = DynChecksumAlgorithm::new(&ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256));
+/// assert_eq!(algorithm.algorithm(), &Algorithm::Sha256);
+///
+/// let mut hasher = algorithm.hasher();
+/// hasher.update(b"hello");
+/// assert_eq!(hasher.finalize().len(), 32);
+/// ```
+pub struct DynChecksumAlgorithm {
+    algorithm: Algorithm,
+    ops: Arc<dyn DigestOps>,
+}
+
+impl DynChecksumAlgorithm {
+    /// Erase the digest of `checksum_algorithm`
+    pub fn new<ChecksumAlgo: Digest + DynDigest + Send + 'static>(
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        Self {
+            algorithm: checksum_algorithm.algorithm().clone(),
+            ops: Arc::new(Ops::<ChecksumAlgo>(PhantomData)),
+        }
+    }
+
+    /// Shortcut to get name of the Algorithm. See [`Algorithm::name()`]
+    pub fn name(&self) -> &str {
+        self.algorithm.name()
+    }
+
+    /// Get a reference on the [`Algorithm`] enum.
+    pub fn algorithm(&self) -> &Algorithm {
+        &self.algorithm
+    }
+
+    /// Create a hasher of the digest, see [`ChecksumAlgorithm::hasher()`]
+    pub fn hasher(&self) -> Box<dyn DynDigest + Send> {
+        self.ops.new_hasher()()
+    }
+
+    /// Function creating hashers like [`Self::hasher()`], to keep along with the algorithm
+    #[cfg(any(feature = "read", feature = "generate"))]
+    pub(crate) fn new_hasher(&self) -> NewHasher {
+        self.ops.new_hasher()
+    }
+
+    /// Function reading bags with the digest
+    #[cfg(feature = "read")]
+    pub(crate) fn read_with(&self) -> ReadWith {
+        self.ops.read_with()
+    }
+}
+
+impl<ChecksumAlgo: Digest + DynDigest + Send + 'static> From<&ChecksumAlgorithm<ChecksumAlgo>>
+    for DynChecksumAlgorithm
+{
+    fn from(checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>) -> Self {
+        Self::new(checksum_algorithm)
+    }
+}
+
+impl std::fmt::Debug for DynChecksumAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynChecksumAlgorithm")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Algorithms known by name, to pick one at runtime
+///
+/// The default registry has the algorithms of enabled features: SHA-512 and SHA-256 with `sha2`, CRC32C with `crc32c`
+/// and XXH3 with `xxhash`. Use [`Self::empty()`] to only have registered algorithms.
+/// A registry is given to [`ReadOptions::algorithms()`](crate::ReadOptions::algorithms) to read bags with any of its
+/// algorithms, see [`BagIt::read_existing_auto()`].
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::{Algorithm, AlgorithmRegistry, ChecksumAlgorithm};
+/// let registry = AlgorithmRegistry::default()
+///     .register(&ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::custom("blake3")));
+///
+/// let algorithm = registry.get("BLAKE3").unwrap();
+/// assert_eq!(algorithm.algorithm(), &Algorithm::custom("blake3"));
+/// assert!(registry.get("md5").is_none());
+/// ```
+pub struct AlgorithmRegistry {
+    algorithms: Vec<DynChecksumAlgorithm>,
+}
+
+impl Default for AlgorithmRegistry {
+    fn default() -> Self {
+        let registry = Self::empty();
+        #[cfg(feature = "sha2")]
+        let registry = registry
+            .register(&ChecksumAlgorithm::sha512())
+            .register(&ChecksumAlgorithm::sha256());
+        #[cfg(feature = "crc32c")]
+        let registry = registry.register(&ChecksumAlgorithm::crc32c());
+        #[cfg(feature = "xxhash")]
+        let registry = registry.register(&ChecksumAlgorithm::xxh3());
+        registry
+    }
+}
+
+impl AlgorithmRegistry {
+    /// Registry without any algorithm
+    pub fn empty() -> Self {
+        Self { algorithms: vec![] }
+    }
+
+    /// Register `checksum_algorithm`, replacing the algorithm of the same name if there is one
+    pub fn register<ChecksumAlgo: Digest + DynDigest + Send + 'static>(
+        mut self,
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        let algorithm = DynChecksumAlgorithm::new(checksum_algorithm);
+        match self
+            .algorithms
+            .iter_mut()
+            .find(|registered| registered.algorithm == algorithm.algorithm)
+        {
+            Some(registered) => *registered = algorithm,
+            None => self.algorithms.push(algorithm),
+        }
+        self
+    }
+
+    /// Algorithm named `name`, ignoring ASCII case, see [`Algorithm::name()`]
+    pub fn get(&self, name: &str) -> Option<&DynChecksumAlgorithm> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Registered algorithms, in order of registration
+    pub fn iter(&self) -> impl Iterator<Item = &DynChecksumAlgorithm> {
+        self.algorithms.iter()
+    }
+}
+
+#[cfg(feature = "generate")]
+#[derive(Debug)]
+/// Bag being created, with an algorithm chosen at runtime
+///
+/// Like [`TypedBagIt`](crate::TypedBagIt), methods adding payloads and finalizing the bag don't take the digest as a type
+/// parameter, it comes from the [`DynChecksumAlgorithm`] of the bag. Other methods of [`BagIt`] are available through [`Deref`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{AlgorithmRegistry, DynBagIt};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let name = "sha256";
+/// let registry = AlgorithmRegistry::default();
+/// let algorithm = registry.get(name).ok_or("unknown algorithm")?;
+///
+/// let mut bag = DynBagIt::new_empty("/path/to/bag", algorithm);
+/// bag.add_file("/path/to/dataset.csv").await?;
+/// bag.finalize().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DynBagIt<'a, 'algo> {
+    bag: BagIt<'a, 'algo>,
+    checksum_algorithm: &'algo DynChecksumAlgorithm,
+}
+
+#[cfg(feature = "generate")]
+impl<'a, 'algo> DynBagIt<'a, 'algo> {
+    /// Create an empty bag, like [`BagIt::new_empty()`]
+    pub fn new_empty(
+        directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo DynChecksumAlgorithm,
+    ) -> Self {
+        Self {
+            bag: BagIt::new_empty_with(directory, checksum_algorithm.algorithm()),
+            checksum_algorithm,
+        }
+    }
+
+    /// Bind `bag` to `checksum_algorithm`, like [`TypedBagIt::from_bag()`](crate::TypedBagIt::from_bag)
+    ///
+    /// Fails with [`GenerateError::AlgorithmMismatch`] when `checksum_algorithm` is not the algorithm of the bag.
+    pub fn from_bag(
+        bag: BagIt<'a, 'algo>,
+        checksum_algorithm: &'algo DynChecksumAlgorithm,
+    ) -> Result<Self, GenerateError> {
+        if bag.algorithm() != checksum_algorithm.algorithm() {
+            return Err(GenerateError::AlgorithmMismatch(
+                checksum_algorithm.algorithm().clone(),
+            ));
+        }
+
+        Ok(Self {
+            bag,
+            checksum_algorithm,
+        })
+    }
+
+    /// Bag, no longer bound to its algorithm
+    pub fn into_inner(self) -> BagIt<'a, 'algo> {
+        self.bag
+    }
+
+    /// See [`BagIt::add_algorithm()`]
+    pub fn add_algorithm(
+        &mut self,
+        checksum_algorithm: &'algo DynChecksumAlgorithm,
+    ) -> Result<(), GenerateError> {
+        self.bag.add_algorithm_with(
+            checksum_algorithm.algorithm(),
+            checksum_algorithm.new_hasher(),
+        )
+    }
+
+    /// See [`BagIt::add_file()`]
+    pub async fn add_file(&mut self, file: impl AsRef<Path>) -> Result<(), GenerateError> {
+        let ops = Arc::clone(&self.checksum_algorithm.ops);
+        ops.add_file(&mut self.bag, file.as_ref().to_path_buf())
+            .await
+    }
+
+    /// See [`BagIt::add_file_as()`]
+    pub async fn add_file_as(
+        &mut self,
+        file: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let ops = Arc::clone(&self.checksum_algorithm.ops);
+        ops.add_file_as(
+            &mut self.bag,
+            file.as_ref().to_path_buf(),
+            destination.as_ref().to_path_buf(),
+        )
+        .await
+    }
+
+    /// See [`BagIt::add_directory()`]
+    pub async fn add_directory(
+        &mut self,
+        directory: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, Result<(), GenerateError>)>, GenerateError> {
+        let ops = Arc::clone(&self.checksum_algorithm.ops);
+        ops.add_directory(&mut self.bag, directory.as_ref().to_path_buf())
+            .await
+    }
+
+    /// See [`BagIt::add_from_reader()`]
+    pub async fn add_from_reader(
+        &mut self,
+        reader: impl AsyncRead + Unpin + Send,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let ops = Arc::clone(&self.checksum_algorithm.ops);
+        ops.add_from_reader(
+            &mut self.bag,
+            Box::new(reader),
+            destination.as_ref().to_path_buf(),
+        )
+        .await
+    }
+
+    /// See [`BagIt::add_bytes()`]
+    pub async fn add_bytes(
+        &mut self,
+        bytes: impl AsRef<[u8]>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.add_from_reader(bytes.as_ref(), destination).await
+    }
+
+    /// See [`BagIt::finalize()`]
+    pub async fn finalize(&mut self) -> Result<(), GenerateError> {
+        self.finalize_with_options(FinalizeOptions::default()).await
+    }
+
+    /// See [`BagIt::finalize_with_options()`]
+    pub async fn finalize_with_options(
+        &mut self,
+        options: FinalizeOptions,
+    ) -> Result<(), GenerateError> {
+        let ops = Arc::clone(&self.checksum_algorithm.ops);
+        ops.finalize_with_options(&mut self.bag, options).await
+    }
+
+    /// See [`BagIt::preview_finalize_with_options()`]
+    pub async fn preview_finalize_with_options(
+        &self,
+        options: FinalizeOptions,
+    ) -> Result<FinalizePreview, GenerateError> {
+        self.checksum_algorithm
+            .ops
+            .preview_finalize_with_options(&self.bag, options)
+            .await
+    }
+}
+
+#[cfg(feature = "generate")]
+impl<'a, 'algo> Deref for DynBagIt<'a, 'algo> {
+    type Target = BagIt<'a, 'algo>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bag
+    }
+}
+
+#[cfg(feature = "generate")]
+impl DerefMut for DynBagIt<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bag
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AlgorithmRegistry;
+    use crate::{Algorithm, ChecksumAlgorithm};
+
+    #[test]
+    fn registry() {
+        let registry = AlgorithmRegistry::empty()
+            .register(&ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256))
+            .register(&ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom(
+                "md5",
+            )));
+        assert_eq!(registry.iter().count(), 2);
+        assert_eq!(
+            registry.get("SHA256").unwrap().algorithm(),
+            &Algorithm::Sha256
+        );
+        assert!(registry.get("sha512").is_none());
+
+        // Registering an algorithm of the same name replaces it, keeping its place
+        let registry =
+            registry.register(&ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha256));
+        assert_eq!(registry.iter().count(), 2);
+        assert_eq!(
+            registry.iter().next().unwrap().algorithm(),
+            &Algorithm::Sha256
+        );
+        assert_eq!(registry.get("sha256").unwrap().hasher().output_size(), 64);
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn default_registry() {
+        let registry = AlgorithmRegistry::default();
+        assert!(registry.get("sha256").is_some());
+        assert!(registry.get("sha512").is_some());
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn dyn_bag() {
+        use super::DynBagIt;
+        use crate::BagIt;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let registry = AlgorithmRegistry::empty()
+            .register(&ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256))
+            .register(&ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom(
+                "md5",
+            )));
+
+        // Algorithms picked by name
+        let mut bag = DynBagIt::new_empty(&temp_directory, registry.get("md5").unwrap());
+        bag.add_algorithm(registry.get("sha256").unwrap()).unwrap();
+        bag.add_bytes("hello", "data/hello.txt").await.unwrap();
+        bag.finalize().await.unwrap();
+        assert_eq!(bag.algorithm(), &Algorithm::custom("md5"));
+
+        assert_eq!(
+            tokio::fs::read_to_string(temp_directory.join("manifest-md5.txt"))
+                .await
+                .unwrap(),
+            "5d41402abc4b2a76b9719d911017c592 data/hello.txt\n"
+        );
+        assert!(temp_directory.join("manifest-sha256.txt").exists());
+
+        // Bag of another algorithm is rejected
+        let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+        let bag = BagIt::new_empty(&temp_directory, &sha512);
+        assert!(DynBagIt::from_bag(bag, registry.get("md5").unwrap()).is_err());
+
+        #[cfg(feature = "read")]
+        {
+            use crate::ReadOptions;
+
+            for name in ["md5", "sha256"] {
+                let algorithm = registry.get(name).unwrap();
+                let bag = BagIt::read_existing_dyn(&temp_directory, algorithm)
+                    .await
+                    .unwrap();
+                assert_eq!(bag.algorithm(), algorithm.algorithm());
+                assert_eq!(bag.payload_items().count(), 1);
+            }
+
+            // Algorithm found from the manifests of the bag
+            let options = ReadOptions::default().algorithms(&registry);
+            let bag = BagIt::read_existing_auto(&temp_directory, &options)
+                .await
+                .unwrap();
+            assert_eq!(bag.algorithm(), &Algorithm::Sha256);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Character encodings of tag files
+//!
+//! Bags following RFC 8493 have tag files encoded in UTF-8. Older bags may declare a legacy encoding in `bagit.txt`,
+//! their tag files are transcoded to UTF-8 while they are read, see [`ReadOptions::legacy_encodings()`](crate::ReadOptions::legacy_encodings).
+
+#[cfg(feature = "read")]
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+#[cfg(feature = "read")]
+use tokio::io::{AsyncRead, ReadBuf};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Character encoding of tag files, declared in `bagit.txt`
+pub enum TagEncoding {
+    /// Required by RFC 8493
+    #[default]
+    Utf8,
+    /// ISO-8859-1, found in bags made before version 1.0 of the spec
+    Latin1,
+}
+
+impl TagEncoding {
+    /// Encoding from its name in `bagit.txt`, if it is supported
+    ///
+    /// US-ASCII is read as ISO-8859-1, which is a superset of it.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_uppercase().as_str() {
+            "UTF-8" => Some(Self::Utf8),
+            "ISO-8859-1" | "ISO_8859-1" | "LATIN1" | "US-ASCII" => Some(Self::Latin1),
+            _ => None,
+        }
+    }
+
+    /// Name of encoding in `bagit.txt`
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Latin1 => "ISO-8859-1",
+        }
+    }
+}
+
+/// Byte order mark starting some UTF-8 files written on Windows, it is not part of their contents
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// Reader of a tag file, transcoding its contents to UTF-8 on the fly
+///
+/// A byte order mark at start of a UTF-8 file is left out, it is not part of the contents.
+#[cfg(feature = "read")]
+pub(crate) struct TagFileReader<R> {
+    inner: R,
+    encoding: TagEncoding,
+    /// Second byte of a transcoded character, when it did not fit in the last read
+    pending: Option<u8>,
+    /// Whether start of a UTF-8 file was checked for a byte order mark
+    start_checked: bool,
+    /// Bytes read at start of a UTF-8 file while looking for a byte order mark, left to hand over
+    start: Vec<u8>,
+}
+
+#[cfg(feature = "read")]
+impl<R: AsyncRead + Unpin> TagFileReader<R> {
+    pub fn new(inner: R, encoding: TagEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            pending: None,
+            start_checked: false,
+            start: vec![],
+        }
+    }
+
+    /// Read start of a UTF-8 file, leaving out the byte order mark it may begin with
+    ///
+    /// Only bytes that may still be part of the mark are read, a mark further in the file is kept.
+    fn poll_byte_order_mark(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let mut mark = [0; 3];
+        let mark = BYTE_ORDER_MARK.encode_utf8(&mut mark).as_bytes();
+
+        while self.start.len() < mark.len() && mark.starts_with(&self.start) {
+            let mut bytes = [0; 3];
+            let mut bytes = ReadBuf::new(&mut bytes[..mark.len() - self.start.len()]);
+            ready!(Pin::new(&mut self.inner).poll_read(cx, &mut bytes))?;
+            if bytes.filled().is_empty() {
+                break;
+            }
+            self.start.extend_from_slice(bytes.filled());
+        }
+
+        if self.start == mark {
+            self.start.clear();
+        }
+        self.start_checked = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "read")]
+impl<R: AsyncRead + Unpin> AsyncRead for TagFileReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.encoding == TagEncoding::Utf8 {
+            let this = &mut *self;
+            if !this.start_checked {
+                ready!(this.poll_byte_order_mark(cx))?;
+            }
+            if !this.start.is_empty() {
+                let length = this.start.len().min(buf.remaining());
+                buf.put_slice(&this.start[..length]);
+                this.start.drain(..length);
+                return Poll::Ready(Ok(()));
+            }
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        }
+
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        if let Some(byte) = self.pending.take() {
+            buf.put_slice(&[byte]);
+            return Poll::Ready(Ok(()));
+        }
+
+        // Every ISO-8859-1 byte takes at most two bytes in UTF-8
+        let mut raw = [0; 4096];
+        let length = (buf.remaining() / 2).clamp(1, raw.len());
+        let mut raw = ReadBuf::new(&mut raw[..length]);
+        ready!(Pin::new(&mut self.inner).poll_read(cx, &mut raw))?;
+
+        for byte in raw.filled() {
+            let mut character = [0; 2];
+            let character = char::from(*byte).encode_utf8(&mut character).as_bytes();
+            if buf.remaining() >= character.len() {
+                buf.put_slice(character);
+            } else {
+                buf.put_slice(&character[..1]);
+                self.pending = Some(character[1]);
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(all(test, feature = "read"))]
+mod test {
+    use super::{TagEncoding, TagFileReader};
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn transcode_latin1() {
+        let latin1: &[u8] = b"Source-Organization: Biblioth\xe8que nationale\n";
+
+        let mut transcoded = String::new();
+        TagFileReader::new(latin1, TagEncoding::Latin1)
+            .read_to_string(&mut transcoded)
+            .await
+            .unwrap();
+        assert_eq!(transcoded, "Source-Organization: Bibliothèque nationale\n");
+
+        // One byte at a time, characters are split across reads
+        let mut reader = TagFileReader::new(latin1, TagEncoding::Latin1);
+        let mut transcoded = vec![];
+        let mut byte = [0];
+        while reader.read(&mut byte).await.unwrap() == 1 {
+            transcoded.push(byte[0]);
+        }
+        assert_eq!(
+            String::from_utf8(transcoded).unwrap(),
+            "Source-Organization: Bibliothèque nationale\n"
+        );
+
+        assert_eq!(
+            TagEncoding::from_label("iso-8859-1"),
+            Some(TagEncoding::Latin1)
+        );
+        assert_eq!(TagEncoding::from_label("Shift_JIS"), None);
+    }
+
+    #[tokio::test]
+    async fn byte_order_mark() {
+        let utf8 = "\u{feff}first line\n\u{feff}second line\n";
+
+        let mut contents = String::new();
+        TagFileReader::new(utf8.as_bytes(), TagEncoding::Utf8)
+            .read_to_string(&mut contents)
+            .await
+            .unwrap();
+        assert_eq!(contents, "first line\n\u{feff}second line\n");
+
+        // Start of file looking like a byte order mark is kept
+        let mut contents = vec![];
+        TagFileReader::new(&b"\xef\xbbfirst"[..], TagEncoding::Utf8)
+            .read_to_end(&mut contents)
+            .await
+            .unwrap();
+        assert_eq!(contents, b"\xef\xbbfirst");
+
+        // One byte at a time
+        let mut reader = TagFileReader::new(utf8.as_bytes(), TagEncoding::Utf8);
+        let mut contents = vec![];
+        let mut byte = [0];
+        while reader.read(&mut byte).await.unwrap() == 1 {
+            contents.push(byte[0]);
+        }
+        assert_eq!(
+            String::from_utf8(contents).unwrap(),
+            "first line\n\u{feff}second line\n"
+        );
+    }
+}
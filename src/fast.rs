@@ -0,0 +1,242 @@
+//! Non-cryptographic checksums, much faster to compute than cryptographic ones
+//!
+//! They detect accidental corruption, like a file damaged while it was transferred, but not deliberate tampering:
+//! use them for transfer integrity of bags exchanged internally, not for preservation.
+//!
+//! [`Crc32c`] (feature `crc32c`) and [`Xxh3`] (feature `xxhash`) implement [`Digest`](digest::Digest), they are used like
+//! any other algorithm. Their checksums are written in big-endian order, like the canonical representations of CRC32C and XXH3.
+
+use crate::{Algorithm, ChecksumAlgorithm};
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+/// State of a non-cryptographic hash function, turned into a [`Digest`](digest::Digest) by `fast_digest!`
+trait FastHash: Clone + Default {
+    /// Feed `data` to the hash function
+    fn update(&mut self, data: &[u8]);
+
+    /// Write checksum of data fed so far in `output`, big-endian
+    fn write_checksum(&self, output: &mut [u8]);
+}
+
+/// Public hasher named `$name` around `$state`, implementing traits of [`digest`] for checksums of `$size` bytes
+macro_rules! fast_digest {
+    ($(#[$attribute:meta])* $name:ident($state:ty), $size:ty) => {
+        $(#[$attribute])*
+        #[derive(Clone, Default)]
+        pub struct $name($state);
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(stringify!($name))
+            }
+        }
+
+        impl HashMarker for $name {}
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $size;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                FastHash::update(&mut self.0, data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                self.0.write_checksum(out);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                self.0 = Default::default();
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.0.write_checksum(out);
+                Reset::reset(self);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "crc32c")]
+#[derive(Clone, Default)]
+struct Crc32cState(u32);
+
+#[cfg(feature = "crc32c")]
+impl FastHash for Crc32cState {
+    fn update(&mut self, data: &[u8]) {
+        self.0 = crc32c::crc32c_append(self.0, data);
+    }
+
+    fn write_checksum(&self, output: &mut [u8]) {
+        output.copy_from_slice(&self.0.to_be_bytes());
+    }
+}
+
+#[cfg(feature = "crc32c")]
+fast_digest!(
+    /// CRC32C (Castagnoli) checksums of 4 bytes, with the [`crc32c`](https://docs.rs/crc32c) crate
+    ///
+    /// Hardware instructions of the CPU are used when available (SSE 4.2, ARMv8 CRC).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{fast::Crc32c, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::crc32c();
+    ///
+    /// // Writes `manifest-crc32c.txt`
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<Crc32c>("/path/to/dataset.csv").await?;
+    /// bag.finalize::<Crc32c>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "crc32c")))]
+    Crc32c(Crc32cState),
+    digest::consts::U4
+);
+
+#[cfg(feature = "crc32c")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crc32c")))]
+impl ChecksumAlgorithm<Crc32c> {
+    /// CRC32C, with manifests named `manifest-crc32c.txt`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, ChecksumAlgorithm};
+    /// let algorithm = ChecksumAlgorithm::crc32c();
+    /// assert_eq!(algorithm.algorithm(), &Algorithm::custom("crc32c"));
+    /// ```
+    pub fn crc32c() -> Self {
+        Self::new(Algorithm::custom("crc32c"))
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl FastHash for xxhash_rust::xxh3::Xxh3Default {
+    fn update(&mut self, data: &[u8]) {
+        xxhash_rust::xxh3::Xxh3Default::update(self, data);
+    }
+
+    fn write_checksum(&self, output: &mut [u8]) {
+        output.copy_from_slice(&self.digest().to_be_bytes());
+    }
+}
+
+#[cfg(feature = "xxhash")]
+fast_digest!(
+    /// XXH3 checksums of 8 bytes, with the [`xxhash-rust`](https://docs.rs/xxhash-rust) crate
+    #[cfg_attr(docsrs, doc(cfg(feature = "xxhash")))]
+    Xxh3(xxhash_rust::xxh3::Xxh3Default),
+    digest::consts::U8
+);
+
+#[cfg(feature = "xxhash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xxhash")))]
+impl ChecksumAlgorithm<Xxh3> {
+    /// XXH3 with 64 bits checksums, with manifests named `manifest-xxh3.txt`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, ChecksumAlgorithm};
+    /// let algorithm = ChecksumAlgorithm::xxh3();
+    /// assert_eq!(algorithm.algorithm(), &Algorithm::custom("xxh3"));
+    /// ```
+    pub fn xxh3() -> Self {
+        Self::new(Algorithm::custom("xxh3"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Checksum;
+    use std::str::FromStr;
+
+    #[cfg(feature = "crc32c")]
+    #[test]
+    fn crc32c() {
+        use super::Crc32c;
+        use digest::Digest;
+
+        assert_eq!(
+            Checksum::digest::<Crc32c>(b"123456789".to_vec()),
+            Checksum::from_str("e3069283").unwrap()
+        );
+        assert_eq!(
+            Checksum::digest::<Crc32c>(vec![]),
+            Checksum::from_str("00000000").unwrap()
+        );
+
+        // Fed in several chunks, hasher is reusable once reset
+        let mut hasher = Crc32c::new();
+        hasher.update(b"1234");
+        hasher.update(b"56789");
+        assert_eq!(hasher.finalize_reset().as_slice(), [0xe3, 0x06, 0x92, 0x83]);
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finalize().as_slice(), [0xe3, 0x06, 0x92, 0x83]);
+    }
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn xxh3() {
+        use super::Xxh3;
+        use xxhash_rust::xxh3::xxh3_64;
+
+        assert_eq!(
+            Checksum::digest::<Xxh3>(vec![]),
+            Checksum::from_str("2d06800538d394c2").unwrap()
+        );
+        let bytes = vec![42; 10_000];
+        assert_eq!(
+            Checksum::digest::<Xxh3>(bytes.clone()),
+            Checksum::from(xxh3_64(&bytes).to_be_bytes().to_vec())
+        );
+    }
+
+    #[cfg(all(feature = "crc32c", feature = "xxhash", feature = "generate"))]
+    #[tokio::test]
+    async fn bag() {
+        use super::Crc32c;
+        use crate::{BagIt, ChecksumAlgorithm};
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let crc32c = ChecksumAlgorithm::crc32c();
+        let xxh3 = ChecksumAlgorithm::xxh3();
+
+        let mut bag = BagIt::new_empty(&temp_directory, &crc32c);
+        bag.add_algorithm(&xxh3).unwrap();
+        bag.add_bytes::<Crc32c>("123456789", "data/digits.txt")
+            .await
+            .unwrap();
+        bag.finalize::<Crc32c>().await.unwrap();
+
+        assert_eq!(
+            tokio::fs::read_to_string(temp_directory.join("manifest-crc32c.txt"))
+                .await
+                .unwrap(),
+            "e3069283 data/digits.txt\n"
+        );
+        assert!(temp_directory.join("manifest-xxh3.txt").exists());
+
+        #[cfg(feature = "read")]
+        {
+            let bag = BagIt::read_existing(&temp_directory, &crc32c)
+                .await
+                .unwrap();
+            assert_eq!(bag.payload_items().count(), 1);
+            assert!(BagIt::read_existing(&temp_directory, &xxh3).await.is_ok());
+        }
+    }
+}
@@ -0,0 +1,629 @@
+//! Payloads fetched from remote locations, listed in tag file `fetch.txt`
+//!
+//! Each line of `fetch.txt` is: "\<url\> \<length\> \<relative path of payload\>", with length in bytes or `-` when unknown.
+//! Payloads listed there are in the manifest like any other, but their files may not be in the bag yet.
+
+#[cfg(feature = "fetch")]
+use crate::manifest::Manifest;
+#[cfg(feature = "read")]
+use crate::{
+    encoding::TagEncoding,
+    error::ReadError,
+    payload::{decode_path, split_field},
+};
+use crate::{payload::manifest_path, BagVersion, Checksum};
+#[cfg(any(feature = "read", feature = "generate"))]
+use std::path::Component;
+use std::{fmt::Display, path::Path};
+
+/// Name of tag file listing payloads to fetch
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) const FETCH_FILE_NAME: &str = "fetch.txt";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Payload of a bag to fetch from a remote location, listed in `fetch.txt`
+///
+/// See [`BagIt::fetch_items()`](crate::BagIt::fetch_items).
+pub struct FetchItem {
+    url: String,
+    length: Option<u64>,
+    relative_path: Box<Path>,
+    /// Checksum of payload for the manifest, only known for items added to a new bag
+    checksum: Option<Checksum<'static>>,
+}
+
+impl Display for FetchItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_line(BagVersion::V1_0))
+    }
+}
+
+impl FetchItem {
+    /// Line of `fetch.txt` of a bag with `version`
+    pub(crate) fn to_line(&self, version: BagVersion) -> String {
+        let length = match self.length {
+            Some(length) => length.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {length} {}",
+            self.url,
+            manifest_path(&self.relative_path, version)
+        )
+    }
+
+    /// URL of payload
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Size of payload in bytes, if known
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Path of payload relative to the bag directory, once fetched
+    pub fn relative_path(&self) -> &Path {
+        &self.relative_path
+    }
+
+    /// Checksum of payload, when it was added with [`BagIt::add_fetch_item()`](crate::BagIt::add_fetch_item)
+    ///
+    /// Bags that are read only list checksums of payloads in their manifest, see [`BagIt::payload_items()`](crate::BagIt::payload_items).
+    pub fn checksum(&self) -> Option<&Checksum<'static>> {
+        self.checksum.as_ref()
+    }
+
+    /// Payload to fetch from `url`, written as-is in `fetch.txt`: URL must not contain whitespace
+    #[cfg(feature = "generate")]
+    pub(crate) fn new(
+        url: String,
+        length: Option<u64>,
+        relative_path: &Path,
+        checksum: Checksum<'static>,
+    ) -> Option<Self> {
+        let single_line = relative_path
+            .to_str()
+            .is_some_and(|path| !path.contains(['\r', '\n']));
+        if url.is_empty()
+            || url.contains(char::is_whitespace)
+            || !single_line
+            || !is_payload_path(relative_path)
+        {
+            return None;
+        }
+
+        Some(Self {
+            url,
+            length,
+            relative_path: relative_path.into(),
+            checksum: Some(checksum),
+        })
+    }
+
+    /// Parse a line of `fetch.txt` of a bag with `version`, payload must be inside the payload directory
+    #[cfg(feature = "read")]
+    fn from_line(line: &str, version: BagVersion) -> Option<Self> {
+        let (url, rest) = split_field(line)?;
+        let (length, relative_path) = split_field(rest)?;
+        let length = match length {
+            "-" => None,
+            length => Some(length.parse().ok()?),
+        };
+        let relative_path = match version.percent_encodes_paths() {
+            true => decode_path(relative_path),
+            false => relative_path.into(),
+        };
+        let relative_path = Path::new(relative_path.as_ref());
+
+        if !is_payload_path(relative_path) {
+            return None;
+        }
+
+        Some(Self {
+            url: url.to_string(),
+            length,
+            relative_path: relative_path.into(),
+            checksum: None,
+        })
+    }
+}
+
+/// Whether `path` is a relative path inside the payload directory
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) fn is_payload_path(path: &Path) -> bool {
+    path.starts_with("data")
+        && path.components().count() > 1
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Read `fetch.txt` of bag, if it has one
+#[cfg(feature = "read")]
+pub(crate) async fn read_fetch_file(
+    bag_directory: &Path,
+    encoding: TagEncoding,
+    version: BagVersion,
+) -> Result<Vec<FetchItem>, ReadError> {
+    use crate::{encoding::TagFileReader, manifest::next_line};
+    use tokio::{fs, io::BufReader};
+
+    let path = bag_directory.join(FETCH_FILE_NAME);
+    let file = match fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(ReadError::OpenFile(path, e.kind())),
+    };
+    let mut file = BufReader::new(TagFileReader::new(file, encoding));
+
+    let mut items = vec![];
+    let mut line = String::new();
+    while let Some(line) = next_line(&mut file, &mut line)
+        .await
+        .map_err(|e| ReadError::ReadLine(path.clone(), e.kind()))?
+    {
+        items.push(FetchItem::from_line(line, version).ok_or(ReadError::InvalidFetchLine)?);
+    }
+
+    Ok(items)
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Possible errors when fetching payloads of a bag, see [`BagIt::complete()`](crate::BagIt::complete)
+#[cfg(feature = "fetch")]
+pub enum FetchError {
+    /// Failed to read manifest of the bag
+    #[error("Failed to read manifest `{}`: {1}", .0.display())]
+    ReadManifest(std::path::PathBuf, std::io::ErrorKind),
+    /// Payload to fetch is not listed in the manifest, its checksum is unknown
+    #[error("Payload to fetch is missing from manifest")]
+    NotInManifest,
+    /// Failed to download payload, with the error of the HTTP client
+    #[error("Failed to download payload: {0}")]
+    Download(String),
+    /// Failed to write downloaded payload in the bag
+    #[error("Failed to write payload `{}`: {1}", .0.display())]
+    WritePayload(std::path::PathBuf, std::io::ErrorKind),
+    /// Checksum of downloaded payload differs from the one in manifest
+    #[error("Checksum of downloaded payload differs from manifest")]
+    ChecksumDiffers,
+    /// Downloaded payload is not of the length listed in `fetch.txt`, download stops once it is longer
+    #[error("Downloaded payload has {received} bytes instead of {expected}")]
+    LengthDiffers {
+        /// Length listed in `fetch.txt`
+        expected: u64,
+        /// Bytes received from the server
+        received: u64,
+    },
+    /// See [`ReadError`](crate::error::ReadError)
+    #[error("Failed to read bag: {0}")]
+    Read(#[from] crate::error::ReadError),
+    /// See [`PayloadError`](crate::error::PayloadError)
+    #[error(transparent)]
+    Payload(#[from] crate::error::PayloadError),
+    /// See [`GenerateError`](crate::error::GenerateError)
+    #[error("Failed to update bag: {0}")]
+    Update(#[from] crate::error::GenerateError),
+}
+
+#[cfg(feature = "fetch")]
+impl crate::BagIt<'_, '_> {
+    /// Download payloads listed in `fetch.txt` that are not in the bag yet
+    ///
+    /// Payloads are downloaded over HTTP one after the other, in the payload directory.
+    /// Each one is verified against the checksum of the manifest before being added to [`BagIt::payload_items()`](crate::BagIt::payload_items),
+    /// a payload whose checksum differs is discarded.
+    /// Payloads with a length in `fetch.txt` must have exactly that many bytes, a download going past it is stopped.
+    /// Once every payload is in the bag, `fetch.txt` is removed along with its line in the tag manifest.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::read_existing("/path/to/holey/bag", &algorithm).await?;
+    ///
+    /// if !bag.is_complete() {
+    ///     bag.complete::<sha2::Sha256>().await?;
+    /// }
+    /// assert_eq!(bag.fetch_items().count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "fetch")))]
+    pub async fn complete<ChecksumAlgo: digest::Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), FetchError> {
+        let pending = self.pending_fetch_items().cloned().collect::<Vec<_>>();
+        let checksums = self.manifest_checksums(&pending).await?;
+
+        let client = reqwest::Client::new();
+        for (item, checksum) in pending.iter().zip(checksums) {
+            download::<ChecksumAlgo>(&client, self.path(), item, &checksum).await?;
+
+            let payload =
+                crate::Payload::new(self.path.clone(), item.relative_path(), checksum).await?;
+            self.octet_count += payload.bytes();
+            self.items.push(payload);
+        }
+
+        // Nothing left to fetch
+        self.fetch_items.clear();
+        let fetch_file = self.path.join(FETCH_FILE_NAME);
+        match tokio::fs::remove_file(&fetch_file).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(FetchError::WritePayload(fetch_file, e.kind()))
+            }
+            _ => (),
+        }
+        self.remove_tagmanifest_entry(FETCH_FILE_NAME).await?;
+
+        Ok(())
+    }
+
+    /// Checksums of payloads to fetch, from the items or from the manifest of the bag
+    async fn manifest_checksums(
+        &self,
+        items: &[FetchItem],
+    ) -> Result<Vec<Checksum<'static>>, FetchError> {
+        if let Some(checksums) = items
+            .iter()
+            .map(|item| item.checksum().cloned())
+            .collect::<Option<Vec<_>>>()
+        {
+            return Ok(checksums);
+        }
+
+        // Manifest is read like when opening the bag, whatever the case of its name and the encoding of tag files
+        let (_, encoding) = Self::read_declaration(&self.path).await?;
+        let (Some(manifest), _) =
+            Manifest::find_manifests(&self.path, self.checksum_algorithm, &mut vec![]).await?
+        else {
+            return Err(FetchError::ReadManifest(
+                self.path.join(self.manifest_name()),
+                std::io::ErrorKind::NotFound,
+            ));
+        };
+        let manifest = manifest.read_checksums(encoding, self.version).await?;
+
+        items
+            .iter()
+            .map(|item| {
+                manifest
+                    .iter()
+                    .find(|(_, path)| path == item.relative_path())
+                    .map(|(checksum, _)| checksum.clone())
+                    .ok_or(FetchError::NotInManifest)
+            })
+            .collect()
+    }
+}
+
+/// Download payload of `item` in the bag, checking its checksum
+///
+/// The payload is written in a hidden file next to its destination first, and moved there once it is verified.
+#[cfg(feature = "fetch")]
+async fn download<ChecksumAlgo: digest::Digest + Send + 'static>(
+    client: &reqwest::Client,
+    bag_directory: &Path,
+    item: &FetchItem,
+    checksum: &Checksum<'_>,
+) -> Result<(), FetchError> {
+    use crate::long_path;
+    use tokio::{fs, io::AsyncWriteExt};
+
+    let destination = bag_directory.join(item.relative_path());
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(long_path::extended(parent))
+            .await
+            .map_err(|e| FetchError::WritePayload(parent.to_path_buf(), e.kind()))?;
+    }
+    let (partial, mut file) = create_partial(&destination).await?;
+
+    let downloaded = async {
+        let mut response = client
+            .get(item.url())
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| FetchError::Download(e.to_string()))?;
+
+        let mut hasher = ChecksumAlgo::new();
+        let mut received = 0;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| FetchError::Download(e.to_string()))?
+        {
+            // Stop as soon as the server sends more than listed, before filling the disk
+            received += chunk.len() as u64;
+            if let Some(expected) = item.length().filter(|expected| received > *expected) {
+                return Err(FetchError::LengthDiffers { expected, received });
+            }
+
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| FetchError::WritePayload(partial.clone(), e.kind()))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| FetchError::WritePayload(partial.clone(), e.kind()))?;
+
+        if let Some(expected) = item.length().filter(|expected| received != *expected) {
+            return Err(FetchError::LengthDiffers { expected, received });
+        }
+
+        if Checksum::from(hasher.finalize().to_vec()) != *checksum {
+            return Err(FetchError::ChecksumDiffers);
+        }
+
+        fs::rename(
+            long_path::extended(&partial),
+            long_path::extended(&destination),
+        )
+        .await
+        .map_err(|e| FetchError::WritePayload(partial.clone(), e.kind()))
+    }
+    .await;
+
+    // Do not leave incomplete or corrupted payloads behind
+    if downloaded.is_err() {
+        let _ = fs::remove_file(long_path::extended(&partial)).await;
+    }
+
+    downloaded
+}
+
+/// Create a file to download payload at `destination` in, with a random name that can't be the one of another payload
+///
+/// The file is in the same directory as its destination, to be moved there without copying it.
+#[cfg(feature = "fetch")]
+async fn create_partial(
+    destination: &Path,
+) -> Result<(std::path::PathBuf, tokio::fs::File), FetchError> {
+    use std::hash::{BuildHasher, Hasher};
+
+    let name = destination
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    loop {
+        let suffix = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        let partial = destination.with_file_name(format!(".{name}.{suffix:016x}.part"));
+
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(crate::long_path::extended(&partial))
+            .await
+        {
+            Ok(file) => return Ok((partial, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(FetchError::WritePayload(partial, e.kind())),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "read"))]
+mod test {
+    use super::FetchItem;
+    use crate::BagVersion;
+    use std::path::Path;
+
+    #[test]
+    fn parse_line() {
+        let item = FetchItem::from_line(
+            "https://example.com/totebag.jpg 10417 data/totes/my bag.jpg",
+            BagVersion::V1_0,
+        )
+        .unwrap();
+        assert_eq!(item.url(), "https://example.com/totebag.jpg");
+        assert_eq!(item.length(), Some(10417));
+        assert_eq!(item.relative_path(), Path::new("data/totes/my bag.jpg"));
+        assert_eq!(
+            item.to_string(),
+            "https://example.com/totebag.jpg 10417 data/totes/my bag.jpg"
+        );
+
+        let item = FetchItem::from_line(
+            "https://example.com/totebag.jpg - data/totebag.jpg",
+            BagVersion::V1_0,
+        )
+        .unwrap();
+        assert_eq!(item.length(), None);
+        assert_eq!(item.checksum(), None);
+
+        // Fields separated by several spaces or tabs, like manifests
+        let item = FetchItem::from_line(
+            "https://example.com/totebag.jpg  \t10417\t  data/totes/my bag.jpg",
+            BagVersion::V1_0,
+        )
+        .unwrap();
+        assert_eq!(item.length(), Some(10417));
+        assert_eq!(item.relative_path(), Path::new("data/totes/my bag.jpg"));
+
+        for line in [
+            "https://example.com/totebag.jpg 10417",
+            "https://example.com/totebag.jpg ten data/totebag.jpg",
+            "https://example.com/bagit.txt - bagit.txt",
+            "https://example.com/passwd - data/../../etc/passwd",
+            "https://example.com/passwd - /etc/passwd",
+        ] {
+            assert_eq!(
+                FetchItem::from_line(line, BagVersion::V1_0),
+                None,
+                "failing on line `{line}`"
+            );
+        }
+    }
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    async fn complete() {
+        use super::FetchError;
+        use crate::{Algorithm, BagIt, Checksum, ChecksumAlgorithm};
+        use md5::Digest;
+        use sha2::Sha256;
+        use std::str::FromStr;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        // Serve the same payload to every request
+        let totebag = tokio::fs::read(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/totebag.jpg", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![];
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let headers = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    totebag.len()
+                );
+                socket.write_all(headers.as_bytes()).await.unwrap();
+                socket.write_all(&totebag).await.unwrap();
+            }
+        });
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        let checksum =
+            Checksum::from_str("38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31")
+                .unwrap();
+        bag.add_fetch_item(&url, Some(10417), "data/bags/totebag.jpg", checksum)
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Checksums of bags that are read come from the manifest, even when its name differs by case
+        tokio::fs::rename(
+            temp_directory.join("manifest-sha256.txt"),
+            temp_directory.join("Manifest-SHA256.txt"),
+        )
+        .await
+        .unwrap();
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap()
+            .replace(" manifest-sha256.txt", " Manifest-SHA256.txt");
+        tokio::fs::write(temp_directory.join("tagmanifest-sha256.txt"), &tag_manifest)
+            .await
+            .unwrap();
+
+        // Entry of `fetch.txt` is removed from tag manifests of every algorithm
+        let mut md5_tag_manifest = String::new();
+        for line in tag_manifest.lines() {
+            let (_, path) = line.split_once(' ').unwrap();
+            let contents = tokio::fs::read(temp_directory.join(path)).await.unwrap();
+            let checksum = Checksum::from(md5::Md5::digest(contents).to_vec());
+            md5_tag_manifest.push_str(&format!("{checksum} {path}\n"));
+        }
+        tokio::fs::write(temp_directory.join("tagmanifest-md5.txt"), md5_tag_manifest)
+            .await
+            .unwrap();
+
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert!(!bag.is_complete());
+        assert_eq!(bag.complete::<Sha256>().await, Ok(()));
+        assert!(bag.is_complete());
+        assert_eq!(bag.payload_items().count(), 2);
+        assert!(!temp_directory.join("fetch.txt").exists());
+        for tag_manifest in ["tagmanifest-sha256.txt", "tagmanifest-md5.txt"] {
+            let contents = tokio::fs::read_to_string(temp_directory.join(tag_manifest))
+                .await
+                .unwrap();
+            assert!(!contents.contains("fetch.txt"), "{tag_manifest}");
+        }
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.fetch_items().count(), 0);
+        assert!(bag
+            .tag_manifest_items()
+            .all(|item| item.relative_path() != std::path::Path::new("fetch.txt")));
+
+        // Payload that is not the expected one is discarded
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(
+            source_directory.join("sources.csv"),
+            "data/sources.csv.part",
+        )
+        .await
+        .unwrap();
+        let checksum =
+            Checksum::from_str("0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72")
+                .unwrap();
+        bag.add_fetch_item(&url, None, "data/sources.csv", checksum)
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        assert_eq!(
+            bag.complete::<Sha256>().await,
+            Err(FetchError::ChecksumDiffers)
+        );
+        assert!(!bag.is_complete());
+        assert!(!temp_directory.join("data/sources.csv").exists());
+
+        // Payloads whose name looks like a partial download are left alone
+        let mut payload_directory = tokio::fs::read_dir(temp_directory.join("data"))
+            .await
+            .unwrap();
+        let mut payloads = vec![];
+        while let Some(entry) = payload_directory.next_entry().await.unwrap() {
+            payloads.push(entry.file_name());
+        }
+        assert_eq!(payloads, ["sources.csv.part"]);
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+
+        // Payloads longer or shorter than listed in `fetch.txt` are discarded
+        let checksum =
+            Checksum::from_str("38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31")
+                .unwrap();
+        for length in [100, 20_000] {
+            let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+            let temp_directory = temp_directory.to_path_buf();
+            let mut bag = BagIt::new_empty(&temp_directory, &algo);
+            bag.add_fetch_item(&url, Some(length), "data/totebag.jpg", checksum.clone())
+                .unwrap();
+            bag.finalize::<Sha256>().await.unwrap();
+
+            match bag.complete::<Sha256>().await {
+                Err(FetchError::LengthDiffers { expected, received }) => {
+                    assert_eq!(expected, length);
+                    match length < 10417 {
+                        true => assert!(received > length && received <= 10417),
+                        false => assert_eq!(received, 10417),
+                    }
+                }
+                result => panic!("unexpected result {result:?} with length {length}"),
+            }
+            assert!(!bag.is_complete());
+            let mut payload_directory = tokio::fs::read_dir(temp_directory.join("data"))
+                .await
+                .unwrap();
+            assert!(payload_directory.next_entry().await.unwrap().is_none());
+        }
+    }
+}
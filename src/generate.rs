@@ -1,12 +1,21 @@
+#[cfg(feature = "zstd")]
+use crate::compression;
 use crate::{
-    checksum::{compute_checksum_file, ChecksumComputeError},
+    algorithm::NewHasher,
+    cache::CACHE_FILE_NAME,
+    checksum::{buffer_size, hash_file_with, ChecksumComputeError},
+    encoding::{TagEncoding, BYTE_ORDER_MARK},
+    fetch::{is_payload_path, FetchItem, FETCH_FILE_NAME},
+    long_path,
+    manifest::{algorithm_name, PREFIX_MANIFEST, PREFIX_TAG_MANIFEST},
     metadata::{Metadata, MetadataFile},
-    payload::{Payload, PayloadError},
-    ChecksumAlgorithm,
+    payload::{self, manifest_path, parse_manifest_line, Payload, PayloadError, SymlinkPolicy},
+    validation::VALIDATION_LOG_FILE_NAME,
+    walk, Algorithm, AlgorithmRegistry, BagVersion, Checksum, ChecksumAlgorithm,
 };
-use digest::Digest;
-use std::path::Path;
-use tokio::fs;
+use digest::{Digest, DynDigest};
+use std::path::{Component, Path, PathBuf};
+use tokio::{fs, io::AsyncRead};
 
 #[derive(thiserror::Error, Debug, PartialEq)]
 /// Possible errors when creating bagit containers
@@ -18,23 +27,572 @@ pub enum GenerateError {
     #[error("File has no name! This should not be possible")]
     FileHasNoName,
     /// Failed to create directory on filesystem
-    #[error("Failed to create payload directory: {0}")]
-    OpenChecksumFile(std::io::ErrorKind),
+    #[error("Failed to create payload directory `{}`: {1}", .0.display())]
+    OpenChecksumFile(PathBuf, std::io::ErrorKind),
     /// Failed to read file and/or create file on filesystem
-    #[error("Failed to copy file to payload directory: {0}")]
-    CopyToPayloadFolder(std::io::ErrorKind),
+    #[error("Failed to copy file `{}` to payload directory: {1}", .0.display())]
+    CopyToPayloadFolder(PathBuf, std::io::ErrorKind),
     /// Failed to compute relative path of newly copied payload
     #[error("Failed to get relative path of file inside bag: {0}")]
     StripPrefixPath(#[from] std::path::StripPrefixError),
     /// Failed to finalize bag: usually IO
-    #[error("Failed to finalize bag: {0}")]
-    Finalize(std::io::ErrorKind),
+    #[error("Failed to finalize bag, with file `{}`: {1}", .0.display())]
+    Finalize(PathBuf, std::io::ErrorKind),
     /// Payload related error
     #[error(transparent)]
     Payload(#[from] PayloadError),
+    /// Bag was never validated, there is nothing to record
+    #[error("Bag was never validated")]
+    NotValidated,
+    /// Destination of tag files must be a relative path inside the bag, outside of payload directory
+    #[error("Invalid destination for tag files")]
+    TagDirectoryDestination,
+    /// Failed to list files to copy and/or copy them in the bag
+    #[error("Failed to copy file `{}` to tag directory: {1}", .0.display())]
+    CopyToTagDirectory(PathBuf, std::io::ErrorKind),
+    /// Failed to write sidecar tag file of payload
+    #[error("Failed to write sidecar `{}`: {1}", .0.display())]
+    WriteSidecar(PathBuf, std::io::ErrorKind),
+    /// Remote payload must have a URL without whitespace, and a relative path inside payload directory `data`
+    #[error("Invalid payload to fetch")]
+    FetchItem,
+    /// Algorithms must be added before payloads, see [`BagIt::add_algorithm()`](crate::BagIt::add_algorithm)
+    #[error("Algorithm added after payloads")]
+    AlgorithmAfterPayloads,
+    /// Destination of payload must be a relative path inside payload directory `data`, without `..`
+    #[error("Invalid destination for payload")]
+    PayloadDestination,
+    /// Only versions 0.97 and 1.0 can be written, see [`BagIt::set_version()`](crate::BagIt::set_version)
+    #[error("BagIt version {0} can not be written")]
+    UnsupportedVersion(BagVersion),
+    /// Tag is derived from the bag when finalizing it, it can't be added, see [`BagIt::add_tag()`](crate::BagIt::add_tag)
+    #[error("Tag `{0}` is derived from the bag")]
+    DerivedTag(String),
+    /// Path of payload contains CR or LF, which can only be written in manifests from version 1.0
+    #[error("Path of payload can not be written with BagIt version {0}")]
+    PathNotSupported(BagVersion),
+    /// Failed to remove original of a file moved to payload directory, see [`BagIt::add_file_move()`](crate::BagIt::add_file_move)
+    #[error("Failed to remove file `{}` after copying it to payload directory: {1}", .0.display())]
+    RemoveMovedFile(PathBuf, std::io::ErrorKind),
+    /// Payload is not part of the bag, see [`BagIt::remove_file()`](crate::BagIt::remove_file)
+    #[error("Payload `{}` is not in the bag", .0.display())]
+    UnknownPayload(PathBuf),
+    /// Failed to delete file of payload, or its sidecar, from the bag
+    #[error("Failed to remove file `{}` from the bag: {1}", .0.display())]
+    RemovePayload(PathBuf, std::io::ErrorKind),
+    /// Digest is not the one of the algorithm of the bag, see [`TypedBagIt::from_bag()`](crate::TypedBagIt::from_bag)
+    #[error("Bag does not use algorithm {0}")]
+    AlgorithmMismatch(Algorithm),
+    /// Bag already has a file where the payload would be added, see [`CollisionPolicy::Error`]
+    #[error("Payload `{}` already exists in the bag", .0.display())]
+    DestinationExists(PathBuf),
+    /// Failed to remove a file created by a bag being abandoned, see [`BagIt::abandon()`](crate::BagIt::abandon)
+    #[error("Failed to remove `{}` while abandoning the bag: {1}", .0.display())]
+    Abandon(PathBuf, std::io::ErrorKind),
+    /// Tag manifest can't be updated, its algorithm was not registered when reading the bag
+    /// with [`ReadOptions::algorithm()`](crate::ReadOptions::algorithm) and is not known by default
+    #[error("Tag manifest `{}` can't be updated, its algorithm is unknown", .0.display())]
+    UnknownTagManifestAlgorithm(PathBuf),
+    /// Failed to create directory gathering contents of the bag directory, see [`BagIt::bag_in_place()`](crate::BagIt::bag_in_place)
+    #[error("Failed to create staging directory `{}`: {1}", .0.display())]
+    CreateStagingDirectory(PathBuf, std::io::ErrorKind),
 }
 
-impl<'algo> super::BagIt<'_, 'algo> {
+/// Name of manifest of payloads for `algorithm`
+pub(crate) fn manifest_name(algorithm: &Algorithm) -> String {
+    format!("{PREFIX_MANIFEST}{algorithm}.txt")
+}
+
+/// Name of tag manifest for `algorithm`
+pub(crate) fn tagmanifest_name(algorithm: &Algorithm) -> String {
+    format!("{PREFIX_TAG_MANIFEST}{algorithm}.txt")
+}
+
+/// Tag manifest found in the bag directory, updated when a tag file changes
+pub(crate) struct TagManifestFile {
+    /// Name of file, as it is on disk
+    name: String,
+    /// Creates hashers of its algorithm, `None` for the algorithm of the bag
+    new_hasher: Option<NewHasher>,
+}
+
+/// Manifest of an algorithm other than the one of the bag, see [`BagIt::add_algorithm()`](crate::BagIt::add_algorithm)
+#[derive(Debug)]
+pub(crate) struct AdditionalManifest<'algo> {
+    algorithm: &'algo Algorithm,
+    new_hasher: NewHasher,
+    /// Checksums of payloads, in the same order as items of the bag
+    checksums: Vec<Checksum<'static>>,
+}
+
+/// How files added to the bag end up in payload directory `data`, see [`BagIt::set_add_mode()`](crate::BagIt::set_add_mode)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddMode {
+    /// File is copied, the original is left untouched
+    #[default]
+    Copy,
+    /// File is renamed into the bag, or copied then removed when it is on another device
+    Move,
+    /// File is hard linked into the bag: it takes no space, but changes to the original show up in the bag.
+    /// The original must be on the same filesystem as the bag
+    Hardlink,
+    /// File is cloned into the bag: it takes no space until the original or the payload is modified.
+    /// The filesystem must support reflinks, like Btrfs, XFS or APFS
+    #[cfg(feature = "reflink")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reflink")))]
+    Reflink,
+}
+
+/// What to do when a payload is added where the bag already has a file, see [`BagIt::set_collision_policy()`](crate::BagIt::set_collision_policy)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Adding the payload fails with [`GenerateError::DestinationExists`]
+    Error,
+    /// Existing file is replaced by the payload
+    #[default]
+    Overwrite,
+    /// Payload is added with a suffix before its extension, like `report-1.pdf` next to `report.pdf`
+    RenameWithSuffix,
+    /// Payload is not added, existing file is kept
+    Skip,
+}
+
+/// Bring `file` to `destination` according to `mode`, an existing file at `destination` is replaced
+async fn transfer_file(
+    file: &Path,
+    destination: &Path,
+    mode: AddMode,
+) -> Result<(), GenerateError> {
+    let copy_error =
+        |e: std::io::Error| GenerateError::CopyToPayloadFolder(file.to_path_buf(), e.kind());
+
+    // Renaming or linking a symbolic link would bring the link itself, its target is used instead
+    let is_symlink = fs::symlink_metadata(long_path::extended(file))
+        .await
+        .is_ok_and(|metadata| metadata.is_symlink());
+
+    match mode {
+        AddMode::Copy => {
+            fs::copy(long_path::extended(file), long_path::extended(destination))
+                .await
+                .map_err(copy_error)?;
+        }
+        AddMode::Move => {
+            let renamed = !is_symlink
+                && fs::rename(long_path::extended(file), long_path::extended(destination))
+                    .await
+                    .is_ok();
+
+            // Copy file, when it could not be renamed, usually because it is on another device
+            if !renamed {
+                fs::copy(long_path::extended(file), long_path::extended(destination))
+                    .await
+                    .map_err(copy_error)?;
+
+                if let Err(e) = fs::remove_file(long_path::extended(file)).await {
+                    let _ = fs::remove_file(long_path::extended(destination)).await;
+                    return Err(GenerateError::RemoveMovedFile(file.to_path_buf(), e.kind()));
+                }
+            }
+        }
+        AddMode::Hardlink => {
+            let source = match is_symlink {
+                true => fs::canonicalize(long_path::extended(file))
+                    .await
+                    .map_err(copy_error)?,
+                false => file.to_path_buf(),
+            };
+            let _ = fs::remove_file(long_path::extended(destination)).await;
+            fs::hard_link(
+                long_path::extended(&source),
+                long_path::extended(destination),
+            )
+            .await
+            .map_err(copy_error)?;
+        }
+        #[cfg(feature = "reflink")]
+        AddMode::Reflink => {
+            let source = long_path::extended(file).into_owned();
+            let target = long_path::extended(destination).into_owned();
+            let _ = fs::remove_file(&target).await;
+            tokio::task::spawn_blocking(move || reflink_copy::reflink(source, target))
+                .await
+                .map_err(|e| copy_error(e.into()))?
+                .map_err(copy_error)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Manifests as last written when finalizing the bag, to only append payloads added since when finalizing it again
+#[derive(Clone, Debug)]
+pub(crate) struct WrittenManifests {
+    /// Manifests list the first `items` payloads of the bag
+    items: usize,
+    /// Version the manifests were written for
+    version: BagVersion,
+    /// Payloads to fetch listed in the manifest
+    fetch_items: Vec<FetchItem>,
+}
+
+impl WrittenManifests {
+    fn of(bag: &crate::BagIt<'_, '_>) -> Self {
+        Self {
+            items: bag.items.len(),
+            version: bag.version,
+            fetch_items: bag.fetch_items.clone(),
+        }
+    }
+}
+
+/// Whether `path` is fit for a tag file: relative, inside the bag, away from payloads and files reserved by the spec
+fn is_tag_file_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        && !path.as_os_str().is_empty()
+        && !path.starts_with("data")
+        && !walk::is_reserved_tag_file(path)
+}
+
+/// Remove `files` created by a bag in `bag_directory`, then their parent directories left empty up to the bag directory
+///
+/// Blocking, as it is also done when a [`BagItGuard`](crate::BagItGuard) is dropped.
+pub(crate) fn remove_created_files(
+    bag_directory: &Path,
+    files: &[PathBuf],
+) -> Result<(), GenerateError> {
+    for file in files {
+        let path = bag_directory.join(file);
+        match std::fs::remove_file(long_path::extended(&path)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(GenerateError::Abandon(path, e.kind())),
+        }
+    }
+
+    // Deepest directories first, removing a directory that is not empty fails
+    let mut directories = files
+        .iter()
+        .flat_map(|file| file.ancestors().skip(1))
+        .map(|directory| bag_directory.join(directory))
+        .collect::<Vec<_>>();
+    directories.sort_by_key(|directory| std::cmp::Reverse(directory.components().count()));
+    directories.dedup();
+    for directory in directories {
+        let _ = std::fs::remove_dir(long_path::extended(&directory));
+    }
+
+    Ok(())
+}
+
+/// Contents of a file with `lines`, each one ending with a line break
+fn lines_contents(lines: impl Iterator<Item = impl ToString>) -> String {
+    lines.map(|line| line.to_string() + "\n").collect()
+}
+
+/// Human-readable `size`, in bytes or with the largest decimal unit keeping the value at least 1
+fn human_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["kB", "MB", "GB", "TB", "PB"];
+
+    if size < 1000 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1000.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next_unit;
+    }
+    format!("{value:.1} {unit}")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Optional tag file written when finalizing a bag, see [`FinalizeOptions::tag_files()`]
+pub enum TagFile {
+    /// `bag-info.txt`, with metadata tags of the bag and its Payload-Oxum
+    BagInfo,
+    /// Tag manifest, with checksums of all other tag files
+    TagManifest,
+    /// File provided by the caller
+    Custom {
+        /// Path relative to the bag directory, outside of payload directory `data`
+        path: PathBuf,
+        /// Contents of file
+        contents: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Reserved tag of `bag-info.txt` computed when finalizing a bag, see [`FinalizeOptions::generated_tags()`]
+pub enum GeneratedTag {
+    /// `Payload-Oxum`, from payloads of the bag and payloads to fetch
+    PayloadOxum,
+    /// `Bag-Size`, human-readable size of payloads of the bag, such as "10.4 kB"
+    BagSize,
+    /// `Bagging-Date`, date of the day in the time zone of the system
+    #[cfg(feature = "date")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "date")))]
+    BaggingDate,
+}
+
+#[derive(Clone, Debug)]
+/// Options when finalizing a bag
+///
+/// See [`BagIt::finalize_with_options()`](crate::BagIt::finalize_with_options).
+/// Manifests of other algorithms are written for algorithms added with [`BagIt::add_algorithm()`](crate::BagIt::add_algorithm),
+/// as checksums are computed when adding payloads.
+pub struct FinalizeOptions {
+    tag_files: Vec<TagFile>,
+    fold_width: Option<usize>,
+    generated_tags: Vec<GeneratedTag>,
+}
+
+impl Default for FinalizeOptions {
+    fn default() -> Self {
+        Self {
+            tag_files: vec![TagFile::BagInfo, TagFile::TagManifest],
+            fold_width: None,
+            generated_tags: vec![GeneratedTag::PayloadOxum],
+        }
+    }
+}
+
+impl FinalizeOptions {
+    /// Write `bag-info.txt`, enabled by default
+    ///
+    /// The file is optional: without it, the bag is minimal, with no metadata tags and no Payload-Oxum.
+    pub fn bag_info(mut self, bag_info: bool) -> Self {
+        self.tag_files
+            .retain(|tag_file| *tag_file != TagFile::BagInfo);
+        if bag_info {
+            self.tag_files.insert(0, TagFile::BagInfo);
+        }
+        self
+    }
+
+    /// Exact list of optional tag files to write, by default `bag-info.txt` and the tag manifest
+    ///
+    /// `bagit.txt` and the manifest are required, they are always written. Files are written in the order of the list,
+    /// except for the tag manifest which is written last, covering all other tag files.
+    /// Optional tag files left by a previous finalization of the bag are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{FinalizeOptions, TagFile};
+    /// // Custom tag file, without tag manifest
+    /// let options = FinalizeOptions::default().tag_files([
+    ///     TagFile::BagInfo,
+    ///     TagFile::Custom {
+    ///         path: "provenance.txt".into(),
+    ///         contents: b"Exported from the archive".to_vec(),
+    ///     },
+    /// ]);
+    /// ```
+    pub fn tag_files(mut self, tag_files: impl IntoIterator<Item = TagFile>) -> Self {
+        self.tag_files = tag_files.into_iter().collect();
+        self
+    }
+
+    /// Fold long values of `bag-info.txt` onto continuation lines, so that lines are at most `width` characters long
+    ///
+    /// Lines are only broken between words, disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::FinalizeOptions;
+    /// let options = FinalizeOptions::default().fold_values(79);
+    /// ```
+    pub fn fold_values(mut self, width: usize) -> Self {
+        self.fold_width = Some(width);
+        self
+    }
+
+    /// Exact list of reserved tags to compute and write in `bag-info.txt`, by default only `Payload-Oxum`
+    ///
+    /// Generated tags replace tags of the bag with the same label, and are written after them in the order of the list.
+    /// A `Payload-Oxum` of the bag is never written as it is, it would be stale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{FinalizeOptions, GeneratedTag};
+    /// // Human-readable size along with Payload-Oxum
+    /// let options =
+    ///     FinalizeOptions::default().generated_tags([GeneratedTag::BagSize, GeneratedTag::PayloadOxum]);
+    /// ```
+    pub fn generated_tags(
+        mut self,
+        generated_tags: impl IntoIterator<Item = GeneratedTag>,
+    ) -> Self {
+        self.generated_tags = generated_tags.into_iter().collect();
+        self
+    }
+
+    fn has(&self, tag_file: &TagFile) -> bool {
+        self.tag_files.contains(tag_file)
+    }
+
+    fn custom_files(&self) -> impl Iterator<Item = (&PathBuf, &Vec<u8>)> {
+        self.tag_files.iter().filter_map(|tag_file| match tag_file {
+            TagFile::Custom { path, contents } => Some((path, contents)),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Files a bag would write when finalizing, see [`BagIt::preview_finalize()`](crate::BagIt::preview_finalize)
+pub struct FinalizePreview {
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl FinalizePreview {
+    /// Files with their contents, in the order they would be written
+    ///
+    /// Paths are relative to the bag directory.
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &[u8])> {
+        self.files
+            .iter()
+            .map(|(path, contents)| (path.as_path(), contents.as_slice()))
+    }
+
+    /// Contents of file at `path` relative to the bag directory, if it would be written
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|(file, _)| file == path.as_ref())
+            .map(|(_, contents)| contents.as_slice())
+    }
+
+    /// Contents of file at `path` as text, if it would be written and is valid UTF-8
+    ///
+    /// Manifests and tag files generated by the bag are always valid UTF-8.
+    pub fn file_str(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.file(path)
+            .and_then(|contents| std::str::from_utf8(contents).ok())
+    }
+}
+
+#[derive(Debug)]
+/// Options when creating a bag, producing an empty bag ready for payloads
+///
+/// [`BagIt::new_empty()`](crate::BagIt::new_empty) creates a bag with default options.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{AddMode, Algorithm, BagItBuilder, BagVersion, ChecksumAlgorithm, Metadata};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+///
+/// let mut bag = BagItBuilder::new("/path/to/bag", &sha256)
+///     .version(BagVersion::V0_97)
+///     .algorithm(&sha512)
+///     .add_mode(AddMode::Hardlink)
+///     .tag(Metadata::Custom {
+///         key: "Source-Organization".into(),
+///         value: "Archive".into(),
+///     })
+///     .build()?;
+///
+/// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+/// bag.finalize::<sha2::Sha256>().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BagItBuilder<'a, 'algo> {
+    bag: crate::BagIt<'a, 'algo>,
+    version: BagVersion,
+    tags: Vec<Metadata>,
+}
+
+impl<'a, 'algo> BagItBuilder<'a, 'algo> {
+    /// Start a bag residing at `directory`, with checksums of `checksum_algorithm` in its manifest and tag manifest
+    pub fn new<ChecksumAlgo: Digest>(
+        directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        Self {
+            bag: crate::BagIt::new_empty(directory, checksum_algorithm),
+            version: BagVersion::V1_0,
+            tags: vec![],
+        }
+    }
+
+    /// Version of the bag, 1.0 by default, see [`BagIt::set_version()`](crate::BagIt::set_version)
+    pub fn version(mut self, version: BagVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Compute checksums with another algorithm as well, see [`BagIt::add_algorithm()`](crate::BagIt::add_algorithm)
+    pub fn algorithm<ChecksumAlgo: Digest + DynDigest + Send + 'static>(
+        mut self,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        self.bag.register_algorithm(
+            checksum_algorithm.algorithm(),
+            checksum_algorithm.new_hasher(),
+        );
+        self
+    }
+
+    /// What to do with symbolic links, see [`BagIt::set_symlink_policy()`](crate::BagIt::set_symlink_policy)
+    pub fn symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.bag.set_symlink_policy(symlinks);
+        self
+    }
+
+    /// How files are brought into the bag, see [`BagIt::set_add_mode()`](crate::BagIt::set_add_mode)
+    pub fn add_mode(mut self, mode: AddMode) -> Self {
+        self.bag.set_add_mode(mode);
+        self
+    }
+
+    /// What to do when a payload is added where the bag already has a file,
+    /// see [`BagIt::set_collision_policy()`](crate::BagIt::set_collision_policy)
+    pub fn collisions(mut self, collisions: CollisionPolicy) -> Self {
+        self.bag.set_collision_policy(collisions);
+        self
+    }
+
+    /// Tag written in `bag-info.txt`, see [`BagIt::add_tag()`](crate::BagIt::add_tag)
+    pub fn tag(mut self, tag: Metadata) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Create the bag, failing if the version can't be written or if a tag is derived from the bag
+    pub fn build(mut self) -> Result<crate::BagIt<'a, 'algo>, GenerateError> {
+        self.bag.set_version(self.version)?;
+        for tag in self.tags {
+            self.bag.add_tag(tag)?;
+        }
+
+        Ok(self.bag)
+    }
+}
+
+/// Remove file left by a previous finalization, if any
+async fn remove_stale_file(path: impl AsRef<Path>) -> Result<(), GenerateError> {
+    match fs::remove_file(long_path::extended(path.as_ref())).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(GenerateError::Finalize(
+            path.as_ref().to_path_buf(),
+            e.kind(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl<'a, 'algo> super::BagIt<'a, 'algo> {
     /// Create an empty bag
     ///
     /// # Arguments
@@ -45,243 +603,4202 @@ impl<'algo> super::BagIt<'_, 'algo> {
         directory: impl AsRef<Path>,
         checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
     ) -> Self {
+        Self::new_empty_with(directory, checksum_algorithm.algorithm())
+    }
+
+    /// [`Self::new_empty()`] with an algorithm whose digest is chosen at runtime
+    pub(crate) fn new_empty_with(directory: impl AsRef<Path>, algorithm: &'algo Algorithm) -> Self {
         Self {
-            path: directory.as_ref().to_path_buf(),
-            checksum_algorithm: checksum_algorithm.algorithm(),
+            path: directory.as_ref().into(),
+            version: BagVersion::V1_0,
+            checksum_algorithm: algorithm,
             items: vec![],
+            octet_count: 0,
             tags: vec![],
+            tag_files: vec![],
+            tag_items: vec![],
+            fetch_items: vec![],
+            validation: None,
+            #[cfg(feature = "read")]
+            warnings: vec![],
+            additional_manifests: vec![],
+            known_algorithms: vec![],
+            symlinks: SymlinkPolicy::Follow,
+            add_mode: AddMode::Copy,
+            collisions: CollisionPolicy::Overwrite,
+            skipped_symlinks: vec![],
+            stale_manifests: vec![],
+            written_manifests: None,
+            created_files: vec![],
+        }
+    }
+
+    /// Open an existing bag to add or remove payloads, then finalize it again with up to date manifests, Payload-Oxum and tag manifest
+    ///
+    /// The bag is read and validated like with [`Self::read_existing()`]. Its tags and tag files are kept.
+    /// When finalizing, lines of added payloads are appended to the manifest, see [`Self::finalize()`].
+    /// Manifests of algorithms other than `checksum_algorithm` are removed when finalizing, since new payloads are not hashed with them.
+    /// Its own manifest and tag manifest, when found with names that differ by case, are renamed to the names they are written with.
+    /// Bags of versions that can't be written, see [`Self::set_version()`], are written as version 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::open_for_update("/path/to/bag", &algorithm).await?;
+    ///
+    /// bag.remove_file("data/report-draft.pdf").await?;
+    /// bag.add_file::<sha2::Sha256>("/path/to/report.pdf").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "read")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+    pub async fn open_for_update<ChecksumAlgo: Digest + Send + 'static>(
+        directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<Self, crate::error::ReadError> {
+        use crate::error::ReadError;
+
+        let mut bag = Self::read_existing(directory, checksum_algorithm).await?;
+        bag.written_manifests = Some(WrittenManifests::of(&bag));
+        if bag.set_version(bag.version).is_err() {
+            bag.version = BagVersion::V1_0;
+        }
+
+        // Manifests and tag manifests of other algorithms, removed when finalizing
+        let own_manifests = [bag.manifest_name(), bag.tagmanifest_name()];
+        let mut entries = fs::read_dir(long_path::extended(&bag.path))
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag.path.to_path_buf(), e.kind()))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag.path.to_path_buf(), e.kind()))?
+        {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+
+            let lowercase = file_name.to_ascii_lowercase();
+            let is_manifest = (lowercase.starts_with(PREFIX_MANIFEST)
+                || lowercase.starts_with(PREFIX_TAG_MANIFEST))
+                && lowercase.ends_with(".txt");
+            if is_manifest
+                && !own_manifests
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(file_name))
+            {
+                bag.stale_manifests.push(file_name.into());
+            }
+        }
+
+        Ok(bag)
+    }
+
+    /// Compute checksums with another algorithm as well, written in its own manifest and tag manifest when finalizing the bag
+    ///
+    /// Files are read only once, checksums of all algorithms are computed at the same time.
+    /// Algorithms must be added before payloads, and can't be combined with payloads to fetch, see [`Self::add_fetch_item()`].
+    /// Adding the algorithm of the bag, or the same algorithm twice, does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+    ///
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &sha256);
+    /// bag.add_algorithm(&sha512)?;
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// // Writes `manifest-sha256.txt` and `manifest-sha512.txt`, along with their tag manifests
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_algorithm<ChecksumAlgo: Digest + DynDigest + Send + 'static>(
+        &mut self,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<(), GenerateError> {
+        self.add_algorithm_with(
+            checksum_algorithm.algorithm(),
+            checksum_algorithm.new_hasher(),
+        )
+    }
+
+    /// [`Self::add_algorithm()`] with hashers created by `new_hasher`, for an algorithm chosen at runtime
+    pub(crate) fn add_algorithm_with(
+        &mut self,
+        algorithm: &'algo Algorithm,
+        new_hasher: NewHasher,
+    ) -> Result<(), GenerateError> {
+        if !self.items.is_empty() || !self.fetch_items.is_empty() {
+            return Err(GenerateError::AlgorithmAfterPayloads);
+        }
+
+        self.register_algorithm(algorithm, new_hasher);
+        Ok(())
+    }
+
+    /// Add manifest of `algorithm` to the bag, unless it already has one
+    fn register_algorithm(&mut self, algorithm: &'algo Algorithm, new_hasher: NewHasher) {
+        if algorithm == self.checksum_algorithm
+            || self
+                .additional_manifests
+                .iter()
+                .any(|manifest| manifest.algorithm == algorithm)
+        {
+            return;
+        }
+
+        self.additional_manifests.push(AdditionalManifest {
+            algorithm,
+            new_hasher,
+            checksums: vec![],
+        });
+    }
+
+    /// Choose version of the BagIt specification declared in `bagit.txt` when finalizing the bag, 1.0 by default
+    ///
+    /// Some repositories still only accept bags with version 0.97: paths in their manifests and `fetch.txt` are written
+    /// as-is instead of being percent-encoded, finalizing fails with [`GenerateError::PathNotSupported`]
+    /// if a path contains CR or LF.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, BagVersion, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.set_version(BagVersion::V0_97)?;
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// // `bagit.txt` declares `BagIt-Version: 0.97`
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_version(&mut self, version: BagVersion) -> Result<(), GenerateError> {
+        if version != BagVersion::V0_97 && version != BagVersion::V1_0 {
+            return Err(GenerateError::UnsupportedVersion(version));
+        }
+
+        self.version = version;
+        Ok(())
+    }
+
+    /// What to do when a file to add is a symbolic link, it is followed and its target is copied by default
+    ///
+    /// With [`SymlinkPolicy::Reject`], adding it fails with [`PayloadError::SymlinkRejected`].
+    /// With [`SymlinkPolicy::SkipWithWarning`], it is not added and is listed by [`Self::skipped_symlinks()`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, SymlinkPolicy};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.set_symlink_policy(SymlinkPolicy::SkipWithWarning);
+    ///
+    /// // `latest.csv` is a symbolic link to `dataset.csv`
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    /// bag.add_file::<sha2::Sha256>("/path/to/latest.csv").await?;
+    /// assert_eq!(bag.skipped_symlinks(), [std::path::Path::new("/path/to/latest.csv")]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_symlink_policy(&mut self, symlinks: SymlinkPolicy) {
+        self.symlinks = symlinks;
+    }
+
+    /// How files are brought into payload directory `data` when they are added, they are copied by default
+    ///
+    /// Applies to [`Self::add_file()`], [`Self::add_file_relative()`], [`Self::add_file_as()`] and [`Self::add_directory()`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{AddMode, Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/staging/bag", &algorithm);
+    /// bag.set_add_mode(AddMode::Hardlink);
+    ///
+    /// // `/staging/dataset.csv` stays where it is, without taking more space
+    /// bag.add_file::<sha2::Sha256>("/staging/dataset.csv").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_add_mode(&mut self, mode: AddMode) {
+        self.add_mode = mode;
+    }
+
+    /// What to do when a payload is added where the bag already has a file, on disk or in its list of items
+    ///
+    /// Existing files are overwritten by default. Applies to every method adding payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, CollisionPolicy};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.set_collision_policy(CollisionPolicy::RenameWithSuffix);
+    ///
+    /// // Ends up in `/path/to/bag/data/notes.txt` and `/path/to/bag/data/notes-1.txt`
+    /// bag.add_file::<sha2::Sha256>("/path/to/monday/notes.txt").await?;
+    /// bag.add_file::<sha2::Sha256>("/path/to/tuesday/notes.txt").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_collision_policy(&mut self, collisions: CollisionPolicy) {
+        self.collisions = collisions;
+    }
+
+    /// Whether the bag already has a file at `relative_path`, on disk or in its list of items
+    async fn is_taken(&self, relative_path: &Path) -> bool {
+        self.items
+            .iter()
+            .any(|payload| payload.relative_path() == relative_path)
+            || fs::symlink_metadata(long_path::extended(&self.path.join(relative_path)))
+                .await
+                .is_ok()
+    }
+
+    /// Where a payload to add at `relative_path` ends up according to the collision policy, `None` when it is not added
+    async fn resolve_collision(
+        &mut self,
+        relative_path: PathBuf,
+    ) -> Result<Option<PathBuf>, GenerateError> {
+        if !self.is_taken(&relative_path).await {
+            return Ok(Some(relative_path));
+        }
+
+        match self.collisions {
+            CollisionPolicy::Error => Err(GenerateError::DestinationExists(relative_path)),
+            CollisionPolicy::Overwrite => Ok(Some(relative_path)),
+            CollisionPolicy::RenameWithSuffix => {
+                let stem = relative_path.file_stem().unwrap_or_default();
+                let mut suffix = 1;
+                loop {
+                    let mut file_name = stem.to_os_string();
+                    file_name.push(format!("-{suffix}"));
+                    if let Some(extension) = relative_path.extension() {
+                        file_name.push(".");
+                        file_name.push(extension);
+                    }
+
+                    let candidate = relative_path.with_file_name(file_name);
+                    if !self.is_taken(&candidate).await {
+                        return Ok(Some(candidate));
+                    }
+                    suffix += 1;
+                }
+            }
+            CollisionPolicy::Skip => Ok(None),
+        }
+    }
+
+    /// Files that were not added because they are symbolic links, see [`SymlinkPolicy::SkipWithWarning`]
+    pub fn skipped_symlinks(&self) -> &[PathBuf] {
+        &self.skipped_symlinks
+    }
+
+    /// Whether `file` to add must be left out because it is a symbolic link, according to the policy of the bag
+    async fn skip_symlink(&mut self, file: &Path) -> Result<bool, GenerateError> {
+        let is_symlink = fs::symlink_metadata(long_path::extended(file))
+            .await
+            .is_ok_and(|metadata| metadata.is_symlink());
+
+        match self.symlinks {
+            _ if !is_symlink => Ok(false),
+            SymlinkPolicy::Follow => Ok(false),
+            SymlinkPolicy::Reject => Err(PayloadError::SymlinkRejected(file.to_path_buf()).into()),
+            SymlinkPolicy::SkipWithWarning => {
+                self.skipped_symlinks.push(file.to_path_buf());
+                Ok(true)
+            }
+        }
+    }
+
+    /// Line of manifest or tag manifest for file at `path`, written for the version of the bag
+    fn manifest_line(&self, checksum: &Checksum<'_>, path: &Path) -> String {
+        format!("{checksum} {}", manifest_path(path, self.version))
+    }
+
+    /// Compute checksum of `file` with the algorithm of the bag, along with checksums of additional algorithms
+    async fn hash_payload<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+        file: impl AsRef<Path>,
+    ) -> Result<(Checksum<'static>, Vec<Checksum<'static>>), ChecksumComputeError> {
+        let others = self
+            .additional_manifests
+            .iter()
+            .map(|manifest| (manifest.new_hasher)())
+            .collect();
+
+        hash_file_with::<ChecksumAlgo>(file, others, |_| ()).await
+    }
+
+    /// Compute checksum of `bytes` with the algorithm of the bag, along with checksums of additional algorithms
+    fn hash_bytes<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+        bytes: &[u8],
+    ) -> (Checksum<'static>, Vec<Checksum<'static>>) {
+        let others = self
+            .additional_manifests
+            .iter()
+            .map(|manifest| {
+                let mut hasher = (manifest.new_hasher)();
+                hasher.update(bytes);
+                hasher.finalize().as_ref().into()
+            })
+            .collect();
+
+        (ChecksumAlgo::digest(bytes).as_slice().into(), others)
+    }
+
+    /// Add `payload` to list of items in bag, with its checksums of additional algorithms, replacing one at the same path
+    fn push_payload(&mut self, payload: Payload<'a>, other_checksums: Vec<Checksum<'static>>) {
+        // Payload overwritten at the same path is listed only once
+        if let Some(index) = self
+            .items
+            .iter()
+            .position(|item| item.relative_path() == payload.relative_path())
+        {
+            self.forget_payload(index);
+        }
+
+        self.octet_count += payload.bytes();
+        self.items.push(payload);
+        for (manifest, checksum) in self.additional_manifests.iter_mut().zip(other_checksums) {
+            manifest.checksums.push(checksum);
         }
     }
 
-    /// Compute checksum of specified `file`, copy it to bag directory, add to list of items inside the bag.
-    ///
-    /// # Arguments
-    ///
-    /// * `file` - File to add to the bag, it will be copied in the path returned by [`Self::path()`]`/data`.
-    pub async fn add_file<ChecksumAlgo: Digest>(
-        &mut self,
-        file: impl AsRef<Path>,
-    ) -> Result<(), GenerateError> {
-        let file_checksum = compute_checksum_file::<ChecksumAlgo>(&file).await?;
+    /// Compute checksum of specified `file`, copy it to bag directory, add to list of items inside the bag.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - File to add to the bag, it will be copied in the path returned by [`Self::path()`]`/data`.
+    pub async fn add_file<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let file_name = file
+            .as_ref()
+            .file_name()
+            .ok_or(GenerateError::FileHasNoName)?;
+
+        self.copy_payload::<ChecksumAlgo>(file.as_ref(), Path::new(file_name), self.add_mode)
+            .await
+            .map(|_| ())
+    }
+
+    /// Add `file` to the bag like [`Self::add_file()`], moving it instead of copying it, regardless of [`Self::set_add_mode()`]
+    ///
+    /// The file is renamed into payload directory `data`, which is nearly free for large files on the same filesystem.
+    /// When it is on another device, it is copied then removed. A symbolic link is removed once its target is copied.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // `/path/to/scan.tiff` is now `/path/to/bag/data/scan.tiff`
+    /// bag.add_file_move::<sha2::Sha256>("/path/to/scan.tiff").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_file_move<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let file_name = file
+            .as_ref()
+            .file_name()
+            .ok_or(GenerateError::FileHasNoName)?;
+
+        self.copy_payload::<ChecksumAlgo>(file.as_ref(), Path::new(file_name), AddMode::Move)
+            .await
+            .map(|_| ())
+    }
+
+    /// Add `file` to the bag like [`Self::add_file()`], keeping its path relative to `base_directory` inside payload directory `data`
+    ///
+    /// Files with the same name in different directories do not overwrite each other, the hierarchy of directories is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // Ends up in `/path/to/bag/data/images/2024/photo.jpg`
+    /// bag.add_file_relative::<sha2::Sha256>("/path/to/photos", "/path/to/photos/images/2024/photo.jpg")
+    ///     .await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_file_relative<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        base_directory: impl AsRef<Path>,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let relative_path = file.as_ref().strip_prefix(base_directory)?;
+
+        self.copy_payload::<ChecksumAlgo>(file.as_ref(), relative_path, self.add_mode)
+            .await
+            .map(|_| ())
+    }
+
+    /// Add `file` to the bag like [`Self::add_file()`], at `destination` chosen regardless of where the file comes from
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - File to add to the bag
+    /// * `destination` - Path of payload relative to [`Self::path()`], inside payload directory `data`. Directories are created as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // Ends up in `/path/to/bag/data/metadata/record.xml`
+    /// bag.add_file_as::<sha2::Sha256>("/tmp/export-1337.xml", "data/metadata/record.xml")
+    ///     .await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_file_as<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let relative_path = destination
+            .as_ref()
+            .strip_prefix("data")
+            .map_err(|_| GenerateError::PayloadDestination)?;
+
+        self.copy_payload::<ChecksumAlgo>(file.as_ref(), relative_path, self.add_mode)
+            .await
+            .map(|_| ())
+    }
+
+    /// Add every file of `directory` and its subdirectories to the bag, keeping their paths relative to `directory` inside payload directory `data`
+    ///
+    /// Files are added one by one like with [`Self::add_file_relative()`], in order of their paths. A file that can't be added
+    /// does not stop the others: results are returned for every file, along with its path. Symbolic links to files are added
+    /// according to [`Self::set_symlink_policy()`], symbolic links to directories are not followed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // `/path/to/photos/2024/beach.jpg` ends up in `/path/to/bag/data/2024/beach.jpg`
+    /// for (file, result) in bag.add_directory::<sha2::Sha256>("/path/to/photos").await? {
+    ///     if let Err(e) = result {
+    ///         eprintln!("Failed to add {}: {e}", file.display());
+    ///     }
+    /// }
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_directory<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        directory: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, Result<(), GenerateError>)>, GenerateError> {
+        let directory = directory.as_ref();
+        let files = walk::list_files(directory)
+            .await
+            .map_err(|e| GenerateError::CopyToPayloadFolder(directory.to_path_buf(), e.kind()))?;
+
+        let mut results = Vec::with_capacity(files.len());
+        for relative_path in files {
+            let file = directory.join(&relative_path);
+            let result = self
+                .copy_payload::<ChecksumAlgo>(&file, &relative_path, self.add_mode)
+                .await
+                .map(|_| ());
+            results.push((file, result));
+        }
+
+        Ok(results)
+    }
+
+    /// Turn the existing contents of the bag directory into payloads, without copying them
+    ///
+    /// Everything inside [`Self::path()`] is moved into a new payload directory `data`, including an existing directory named `data`
+    /// which ends up at `data/data`. Files are then hashed and added to the list of items, ready for [`Self::finalize()`].
+    /// Symbolic links are moved as they are, their targets are hashed.
+    ///
+    /// Algorithms and version of the bag must be set beforehand, like when adding files.
+    /// When an entry can't be moved, those moved before it are put back, leaving the directory as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// // `/path/to/collection/report.pdf` is now `/path/to/collection/data/report.pdf`
+    /// let mut bag = BagIt::new_empty("/path/to/collection", &algorithm);
+    /// bag.bag_in_place::<sha2::Sha256>().await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bag_in_place<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), GenerateError> {
+        let move_error = |path: &Path, e: std::io::Error| {
+            GenerateError::CopyToPayloadFolder(path.to_path_buf(), e.kind())
+        };
+
+        // Contents are gathered in a directory of their own, which becomes the payload directory once everything is inside
+        let mut staging = self.path.join(".bagit-in-place");
+        let mut attempt = 0;
+        while fs::symlink_metadata(long_path::extended(&staging))
+            .await
+            .is_ok()
+        {
+            attempt += 1;
+            staging = self.path.join(format!(".bagit-in-place-{attempt}"));
+        }
+        fs::create_dir(long_path::extended(&staging))
+            .await
+            .map_err(|e| GenerateError::CreateStagingDirectory(staging.clone(), e.kind()))?;
+
+        // Entries already moved are put back where they were when one can't be moved
+        let mut moved = vec![];
+        let payload_directory = self.path.join("data");
+        let gathered = async {
+            let mut entries = fs::read_dir(long_path::extended(&self.path))
+                .await
+                .map_err(|e| move_error(&self.path, e))?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| move_error(&self.path, e))?
+            {
+                let path = entry.path();
+                if path == staging {
+                    continue;
+                }
+                fs::rename(
+                    long_path::extended(&path),
+                    long_path::extended(&staging.join(entry.file_name())),
+                )
+                .await
+                .map_err(|e| move_error(&path, e))?;
+                moved.push(entry.file_name());
+            }
+
+            fs::rename(
+                long_path::extended(&staging),
+                long_path::extended(&payload_directory),
+            )
+            .await
+            .map_err(|e| move_error(&staging, e))
+        }
+        .await;
+        if let Err(e) = gathered {
+            for name in moved {
+                let _ = fs::rename(
+                    long_path::extended(&staging.join(&name)),
+                    long_path::extended(&self.path.join(&name)),
+                )
+                .await;
+            }
+            let _ = fs::remove_dir(long_path::extended(&staging)).await;
+            return Err(e);
+        }
+
+        // Add to list of items in bag
+        let files = walk::list_files(&payload_directory)
+            .await
+            .map_err(|e| move_error(&payload_directory, e))?;
+        for file in files {
+            let (file_checksum, other_checksums) = self
+                .hash_payload::<ChecksumAlgo>(payload_directory.join(&file))
+                .await?;
+            let payload = Payload::new(
+                self.path.clone(),
+                Path::new("data").join(file),
+                file_checksum,
+            )
+            .await?;
+            self.push_payload(payload, other_checksums);
+        }
+
+        Ok(())
+    }
+
+    /// Stream bytes of `reader` into the bag at `destination`, add it to list of items inside the bag
+    ///
+    /// Payloads can come from anywhere, like network sockets or object stores. Checksums are computed while the file
+    /// is written, bytes are read only once. If reading or writing fails, the partially written file is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Contents of payload
+    /// * `destination` - Path of payload relative to [`Self::path()`], inside payload directory `data`. Directories are created as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// let socket = tokio::net::TcpStream::connect("127.0.0.1:8080").await?;
+    /// bag.add_from_reader::<sha2::Sha256>(socket, "data/received.bin").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_from_reader<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        reader: impl AsyncRead + Unpin,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let relative_path = destination
+            .as_ref()
+            .strip_prefix("data")
+            .map_err(|_| GenerateError::PayloadDestination)?;
+        let relative_path = Path::new("data").join(relative_path);
+        if !is_payload_path(&relative_path) {
+            return Err(GenerateError::PayloadDestination);
+        }
+        let Some(relative_path) = self.resolve_collision(relative_path).await? else {
+            return Ok(());
+        };
+
+        // Create payload directory, and parents of file, if they do not exist yet
+        let target = self.path.join(&relative_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(long_path::extended(parent))
+                .await
+                .map_err(|e| GenerateError::OpenChecksumFile(parent.to_path_buf(), e.kind()))?;
+        }
+
+        self.track_created(&relative_path).await;
+
+        // Hash every chunk with all algorithms of the bag while writing it
+        let mut hasher = ChecksumAlgo::new();
+        let mut others = self
+            .additional_manifests
+            .iter()
+            .map(|manifest| (manifest.new_hasher)())
+            .collect::<Vec<_>>();
+        let write = async {
+            let mut file = fs::File::create(long_path::extended(&target)).await?;
+            let mut reader = BufReader::with_capacity(buffer_size(), reader);
+            loop {
+                let chunk = reader.fill_buf().await?;
+                if chunk.is_empty() {
+                    break;
+                }
+
+                Digest::update(&mut hasher, chunk);
+                for other in others.iter_mut() {
+                    other.update(chunk);
+                }
+                file.write_all(chunk).await?;
+
+                let length = chunk.len();
+                reader.consume(length);
+            }
+            file.flush().await
+        };
+        if let Err(e) = write.await {
+            let _ = fs::remove_file(long_path::extended(&target)).await;
+            return Err(GenerateError::CopyToPayloadFolder(target, e.kind()));
+        }
+
+        // Add to list of items in bag
+        let other_checksums = others
+            .into_iter()
+            .map(|other| other.finalize().as_ref().into())
+            .collect();
+        let payload = Payload::new(
+            self.path.clone(),
+            relative_path,
+            hasher.finalize().as_slice().into(),
+        )
+        .await?;
+        self.push_payload(payload, other_checksums);
+
+        Ok(())
+    }
+
+    /// Write `bytes` in the bag at `destination`, add it to list of items inside the bag
+    ///
+    /// Useful for small generated files, like receipts or JSON documents, without going through a temporary file.
+    /// See [`Self::add_from_reader()`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Contents of payload
+    /// * `destination` - Path of payload relative to [`Self::path()`], inside payload directory `data`. Directories are created as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// bag.add_bytes::<sha2::Sha256>(br#"{"status":"received"}"#, "data/receipt.json")
+    ///     .await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_bytes<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        bytes: impl AsRef<[u8]>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.add_from_reader::<ChecksumAlgo>(bytes.as_ref(), destination)
+            .await
+    }
+
+    /// Bring `file` in the bag at `relative_path` inside payload directory according to `mode`, add it to list of items
+    ///
+    /// Returns where the payload was added, if it was.
+    async fn copy_payload<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: &Path,
+        relative_path: &Path,
+        mode: AddMode,
+    ) -> Result<Option<PathBuf>, GenerateError> {
+        let relative_path = Path::new("data").join(relative_path);
+        if !is_payload_path(&relative_path) {
+            return Err(GenerateError::PayloadDestination);
+        }
+        if self.skip_symlink(file).await? {
+            return Ok(None);
+        }
+        let Some(relative_path) = self.resolve_collision(relative_path).await? else {
+            return Ok(None);
+        };
+
+        let (file_checksum, other_checksums) = self.hash_payload::<ChecksumAlgo>(file).await?;
+
+        // Create payload directory, and parents of file, if they do not exist yet
+        let destination = self.path.join(&relative_path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(long_path::extended(parent))
+                .await
+                .map_err(|e| GenerateError::OpenChecksumFile(parent.to_path_buf(), e.kind()))?;
+        }
+
+        // Files moved into the bag are kept if it is abandoned, so that they are not lost
+        if mode != AddMode::Move {
+            self.track_created(&relative_path).await;
+        }
+        transfer_file(file, &destination, mode).await?;
+
+        // Add to list of items in bag
+        let payload = Payload::new(self.path.clone(), &relative_path, file_checksum).await?;
+        self.push_payload(payload, other_checksums);
+
+        Ok(Some(relative_path))
+    }
+
+    /// Compress `file` with Zstandard into the bag, add it to list of items inside the bag
+    ///
+    /// The payload is stored as `data/<file name>.zst`, and is listed with the checksum of the compressed file in the manifest:
+    /// the bag stays valid for tools not aware of compression. Checksum and size of the original file are recorded in tag file
+    /// `compressed-payloads.txt` when finalizing the bag.
+    ///
+    /// [`Payload::open()`] decompresses the payload while it is read, and [`Payload::verify()`] checks the original file as well.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // Ends up in `/path/to/bag/data/server.log.zst`
+    /// bag.add_file_compressed::<sha2::Sha256>("/path/to/server.log").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub async fn add_file_compressed<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        if self.skip_symlink(file.as_ref()).await? {
+            return Ok(());
+        }
+
+        // Construct path of compressed file inside payload directory
+        let mut file_name = file
+            .as_ref()
+            .file_name()
+            .ok_or(GenerateError::FileHasNoName)?
+            .to_os_string();
+        file_name.push(".");
+        file_name.push(compression::COMPRESSED_EXTENSION);
+        let Some(relative_path) = self
+            .resolve_collision(Path::new("data").join(file_name))
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let original = compression::Original {
+            checksum: crate::checksum::hash_file::<ChecksumAlgo>(&file).await?,
+            bytes: fs::metadata(long_path::extended(file.as_ref()))
+                .await
+                .map_err(|e| {
+                    GenerateError::CopyToPayloadFolder(file.as_ref().to_path_buf(), e.kind())
+                })?
+                .len(),
+        };
+
+        // Create payload directory if it does not exist yet
+        let payload_directory = self.path.join("data/");
+        fs::create_dir_all(long_path::extended(&payload_directory))
+            .await
+            .map_err(|e| GenerateError::OpenChecksumFile(payload_directory.clone(), e.kind()))?;
+
+        self.track_created(&relative_path).await;
+        compression::compress_file(&file, self.path.join(&relative_path))
+            .await
+            .map_err(|e| {
+                GenerateError::CopyToPayloadFolder(file.as_ref().to_path_buf(), e.kind())
+            })?;
+        let (file_checksum, other_checksums) = self
+            .hash_payload::<ChecksumAlgo>(self.path.join(&relative_path))
+            .await?;
+
+        // Add to list of items in bag
+        let mut payload = Payload::new(self.path.clone(), relative_path, file_checksum).await?;
+        payload.set_original(original);
+        self.push_payload(payload, other_checksums);
+
+        Ok(())
+    }
+
+    /// Add `file` to the bag like [`Self::add_file()`], along with a sidecar tag file describing it
+    ///
+    /// The sidecar is written at [`Payload::sidecar_path()`], and will be listed in the tag manifest when finalizing the bag.
+    /// Read it back with [`Payload::sidecar()`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    ///
+    /// // Sidecar ends up in `/path/to/bag/metadata/scan.tiff.json`
+    /// bag.add_file_with_sidecar::<sha2::Sha256>("/path/to/scan.tiff", r#"{"dpi": 600}"#)
+    ///     .await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_file_with_sidecar<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        file: impl AsRef<Path>,
+        sidecar: impl AsRef<[u8]>,
+    ) -> Result<(), GenerateError> {
+        let file_name = file
+            .as_ref()
+            .file_name()
+            .ok_or(GenerateError::FileHasNoName)?;
+
+        // No sidecar for a payload that was not added, like a skipped symbolic link
+        let Some(relative_path) = self
+            .copy_payload::<ChecksumAlgo>(file.as_ref(), Path::new(file_name), self.add_mode)
+            .await?
+        else {
+            return Ok(());
+        };
+        let sidecar_path = payload::sidecar_path(&relative_path);
+
+        // Create parent directories of sidecar
+        let target = self.path.join(&sidecar_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(long_path::extended(parent))
+                .await
+                .map_err(|e| GenerateError::WriteSidecar(parent.to_path_buf(), e.kind()))?;
+        }
+
+        self.track_created(&sidecar_path).await;
+        fs::write(long_path::extended(&target), sidecar)
+            .await
+            .map_err(|e| GenerateError::WriteSidecar(target.clone(), e.kind()))?;
+
+        self.add_tag_file_path(sidecar_path);
+
+        Ok(())
+    }
+
+    /// Take a payload back out of the bag before it is finalized
+    ///
+    /// Its file is deleted from payload directory `data`, along with its sidecar if it has one, and it will not be listed in manifests.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - Path of payload relative to [`Self::path()`], like [`Payload::relative_path()`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/draft.docx").await?;
+    ///
+    /// // Wrong file
+    /// bag.remove_file("data/draft.docx").await?;
+    ///
+    /// bag.add_file::<sha2::Sha256>("/path/to/final.docx").await?;
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_file(
+        &mut self,
+        relative_path: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let relative_path = relative_path.as_ref();
+        let index = self
+            .items
+            .iter()
+            .position(|payload| payload.relative_path() == relative_path)
+            .ok_or_else(|| GenerateError::UnknownPayload(relative_path.to_path_buf()))?;
+
+        // Payload already gone from disk is fine, there is nothing left to delete
+        let remove = |path: PathBuf| async move {
+            match fs::remove_file(long_path::extended(&path)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(GenerateError::RemovePayload(path, e.kind()))
+                }
+                _ => Ok(()),
+            }
+        };
+        remove(self.path.join(relative_path)).await?;
+
+        let sidecar_path = payload::sidecar_path(relative_path);
+        if let Some(position) = self
+            .tag_files
+            .iter()
+            .position(|tag_file| **tag_file == sidecar_path)
+        {
+            remove(self.path.join(&sidecar_path)).await?;
+            self.tag_files.remove(position);
+        }
+
+        self.forget_payload(index);
+
+        Ok(())
+    }
+
+    /// Remove payload at `index` from list of items in bag, manifests have to be written from scratch
+    fn forget_payload(&mut self, index: usize) {
+        let payload = self.items.remove(index);
+        self.octet_count -= payload.bytes();
+        self.written_manifests = None;
+        for manifest in self.additional_manifests.iter_mut() {
+            manifest.checksums.remove(index);
+        }
+    }
+
+    /// Reference a payload stored at a remote location, it will be listed in `fetch.txt` when finalizing the bag
+    ///
+    /// Nothing is downloaded: the payload is listed in the manifest with `checksum`, but its file is not in the bag.
+    /// Bags with several algorithms can't have payloads to fetch, see [`Self::add_algorithm()`].
+    /// It counts in the Payload-Oxum of `bag-info.txt` with `length`: when a length is unknown, there is no Payload-Oxum.
+    /// Adding a payload again at the same path replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Location of payload, without whitespace
+    /// * `length` - Size of payload in bytes, if known
+    /// * `relative_path` - Path of payload relative to [`Self::path()`] once fetched, inside payload directory `data`
+    /// * `checksum` - Checksum of payload, computed with the algorithm of the bag
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, Checksum, ChecksumAlgorithm};
+    /// # use std::str::FromStr;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/readme.txt").await?;
+    ///
+    /// // Dataset too large to copy, it stays on the server
+    /// let checksum =
+    ///     Checksum::from_str("0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72")?;
+    /// bag.add_fetch_item(
+    ///     "https://example.com/dataset.tar",
+    ///     Some(1_099_511_627_776),
+    ///     "data/dataset.tar",
+    ///     checksum,
+    /// )?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// assert!(!bag.is_complete());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_fetch_item(
+        &mut self,
+        url: impl Into<String>,
+        length: Option<u64>,
+        relative_path: impl AsRef<Path>,
+        checksum: Checksum<'static>,
+    ) -> Result<(), GenerateError> {
+        if !self.additional_manifests.is_empty() {
+            return Err(GenerateError::FetchItem);
+        }
+        let item = FetchItem::new(url.into(), length, relative_path.as_ref(), checksum)
+            .ok_or(GenerateError::FetchItem)?;
+
+        self.fetch_items
+            .retain(|fetch_item| fetch_item.relative_path() != item.relative_path());
+        self.fetch_items.push(item);
+
+        Ok(())
+    }
+
+    /// Copy all files of `source` directory in the bag, as tag files under `destination`
+    ///
+    /// This is meant to bundle documentation or metadata that are not payloads, like `docs/` or `metadata/`.
+    /// Subdirectories are copied as well. Files will be listed in the tag manifest when finalizing the bag.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Directory to copy
+    /// * `destination` - Path relative to [`Self::path()`] where files will be copied, it can't be inside payload directory `data`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// // Ends up in `/path/to/bag/docs`
+    /// bag.add_tag_directory("/path/to/documentation", "docs").await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_tag_directory(
+        &mut self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let destination = destination.as_ref();
+
+        if !is_tag_file_path(destination) {
+            return Err(GenerateError::TagDirectoryDestination);
+        }
+
+        let files = walk::list_files(source.as_ref()).await.map_err(|e| {
+            GenerateError::CopyToTagDirectory(source.as_ref().to_path_buf(), e.kind())
+        })?;
+
+        for file in files {
+            let relative_path = destination.join(&file);
+
+            // Create parent directories of file
+            let target = self.path.join(&relative_path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(long_path::extended(parent))
+                    .await
+                    .map_err(|e| {
+                        GenerateError::CopyToTagDirectory(parent.to_path_buf(), e.kind())
+                    })?;
+            }
+
+            let source_file = source.as_ref().join(&file);
+            self.track_created(&relative_path).await;
+            fs::copy(
+                long_path::extended(&source_file),
+                long_path::extended(&target),
+            )
+            .await
+            .map_err(|e| GenerateError::CopyToTagDirectory(source_file, e.kind()))?;
+
+            self.add_tag_file_path(relative_path);
+        }
+
+        Ok(())
+    }
+
+    /// Copy a file in the bag as a tag file, outside of payload directory `data`
+    ///
+    /// This is meant for tag files that are not payloads, like `DPN-info.txt` or metadata in XML.
+    /// The file will be listed in the tag manifest when finalizing the bag. Adding a file again at the same destination replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - File to copy
+    /// * `destination` - Path relative to [`Self::path()`] where the file will be copied, it can't be inside payload directory `data`
+    ///   nor be a file reserved by the spec like `bag-info.txt`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// // Ends up in `/path/to/bag/dpn-tags/DPN-info.txt`
+    /// bag.add_tag_file("/path/to/DPN-info.txt", "dpn-tags/DPN-info.txt")
+    ///     .await?;
+    ///
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_tag_file(
+        &mut self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        let destination = destination.as_ref();
+
+        if !is_tag_file_path(destination) {
+            return Err(GenerateError::TagDirectoryDestination);
+        }
+
+        // Create parent directories of file
+        let target = self.path.join(destination);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(long_path::extended(parent))
+                .await
+                .map_err(|e| GenerateError::CopyToTagDirectory(parent.to_path_buf(), e.kind()))?;
+        }
+
+        self.track_created(destination).await;
+        fs::copy(
+            long_path::extended(source.as_ref()),
+            long_path::extended(&target),
+        )
+        .await
+        .map_err(|e| GenerateError::CopyToTagDirectory(source.as_ref().to_path_buf(), e.kind()))?;
+
+        self.add_tag_file_path(destination.to_path_buf());
+
+        Ok(())
+    }
+
+    /// Remember `relative_path` as created by the bag unless a file is already there, see [`Self::abandon()`]
+    async fn track_created(&mut self, relative_path: &Path) {
+        if self.created_files.iter().any(|file| file == relative_path) {
+            return;
+        }
+        let exists = fs::try_exists(long_path::extended(&self.path.join(relative_path)))
+            .await
+            .unwrap_or(true);
+        if !exists {
+            self.created_files.push(relative_path.to_path_buf());
+        }
+    }
+
+    /// Keep track of a tag file for the tag manifest, files can be written again: list them only once
+    fn add_tag_file_path(&mut self, relative_path: PathBuf) {
+        // Checksum in tag manifest is stale
+        self.tag_items
+            .retain(|item| item.relative_path() != relative_path);
+
+        if !self
+            .tag_files
+            .iter()
+            .any(|tag_file| **tag_file == relative_path)
+        {
+            self.tag_files.push(relative_path.into_boxed_path());
+        }
+    }
+
+    /// Add a tag to `bag-info.txt`
+    ///
+    /// Tags of `bagit.txt` and `Payload-Oxum` are written from the bag itself, adding them fails with [`GenerateError::DerivedTag`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, Metadata};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_tag(Metadata::source_organization("Totebag Inc.")?)?;
+    /// bag.add_tag(Metadata::contact_email("archives@totebag.example")?)?;
+    /// bag.add_tag(Metadata::custom("Project-Code", "TB-42")?)?;
+    ///
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_tag(&mut self, tag: Metadata) -> Result<(), GenerateError> {
+        if matches!(
+            tag,
+            Metadata::BagitVersion { .. }
+                | Metadata::Encoding(_)
+                | Metadata::PayloadOctetStreamSummary { .. }
+        ) {
+            return Err(GenerateError::DerivedTag(tag.key().to_string()));
+        }
+
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    #[cfg(feature = "date")]
+    /// Add ISO formatted date representing date when bag was created
+    ///
+    /// A date added before is replaced, the bag has a single `Bagging-Date`.
+    pub fn add_bagging_date(&mut self, date: jiff::civil::Date) {
+        let tag = Metadata::BaggingDate(date);
+        match self
+            .tags
+            .iter_mut()
+            .find(|tag| matches!(tag, Metadata::BaggingDate(_)))
+        {
+            Some(existing) => *existing = tag,
+            None => self.tags.push(tag),
+        }
+    }
+
+    #[cfg(feature = "date")]
+    /// Add today's date as date when bag was created, in the time zone of the system
+    ///
+    /// Like [`Self::add_bagging_date()`], a date added before is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_bagging_date_now();
+    ///
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    /// bag.finalize::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_bagging_date_now(&mut self) {
+        self.add_bagging_date(jiff::Zoned::now().date());
+    }
+
+    /// Procedure to make a bagit container ready for distribution
+    ///
+    /// - Write manifest file with payloads and their checksums
+    /// - Bagit file declaration
+    /// - Information file about bag
+    /// - Manifest with checksums of files that are not data payload
+    ///
+    /// A bag can be finalized again after adding payloads: their lines are appended to manifests, and checksums of tag files
+    /// the bag did not write again are kept. Removing payloads, changing the version or payloads to fetch writes everything from scratch.
+    pub async fn finalize<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), GenerateError> {
+        self.finalize_with_options::<ChecksumAlgo>(FinalizeOptions::default())
+            .await
+    }
+
+    /// Make a bagit container ready for distribution like [`Self::finalize()`], with non-default options
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, FinalizeOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/measurements.bin").await?;
+    ///
+    /// // Minimal bag, without `bag-info.txt`
+    /// bag.finalize_with_options::<sha2::Sha256>(FinalizeOptions::default().bag_info(false))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn finalize_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        options: FinalizeOptions,
+    ) -> Result<(), GenerateError> {
+        self.check_manifest_paths()?;
+        self.rename_manifests_case().await?;
+
+        // Tag files written for the first time are removed as well if the bag is abandoned
+        for file in self.finalize_files(&options) {
+            self.track_created(&file).await;
+        }
+
+        // Payload directory is required, even when the bag has no payloads
+        let payload_directory = self.path.join("data");
+        fs::create_dir_all(long_path::extended(&payload_directory))
+            .await
+            .map_err(|e| GenerateError::Finalize(payload_directory, e.kind()))?;
+
+        // Payloads added since the bag was last finalized are appended, manifests are written from scratch otherwise
+        let appended_from = self
+            .written_manifests
+            .as_ref()
+            .filter(|written| {
+                written.version == self.version
+                    && written.fetch_items == self.fetch_items
+                    && written.items <= self.items.len()
+            })
+            .map(|written| written.items);
+        let appended = match appended_from {
+            Some(start) => self.append_manifests(start).await?,
+            None => false,
+        };
+        if !appended {
+            self.write_file(self.manifest_name(), self.manifest_contents())
+                .await?;
+            for manifest in self.additional_manifests.iter() {
+                self.write_file(
+                    manifest_name(manifest.algorithm),
+                    self.additional_manifest_contents(manifest),
+                )
+                .await?;
+            }
+        }
+        let manifests_unchanged = appended && appended_from == Some(self.items.len());
+
+        // Write `fetch.txt`, if needed
+        let fetch_file = self.path.join(FETCH_FILE_NAME);
+        if self.fetch_items.is_empty() {
+            remove_stale_file(fetch_file).await?;
+        } else {
+            self.write_file(FETCH_FILE_NAME.into(), self.fetch_contents())
+                .await?;
+        }
+
+        // Write `bagit.txt`
+        self.write_file("bagit.txt".into(), self.bagit_file().render(None))
+            .await?;
+
+        // Write `bag-info.txt`, with tags derived from payloads replacing stale ones
+        let bag_info = self.path.join("bag-info.txt");
+        if options.has(&TagFile::BagInfo) {
+            self.write_file(
+                "bag-info.txt".into(),
+                self.bag_info_file(&options).render(options.fold_width),
+            )
+            .await?;
+        } else {
+            remove_stale_file(bag_info).await?;
+        }
+
+        // Write files provided by caller
+        for (path, contents) in options.custom_files() {
+            if !is_tag_file_path(path) {
+                return Err(GenerateError::TagDirectoryDestination);
+            }
+
+            let target = self.path.join(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(long_path::extended(parent))
+                    .await
+                    .map_err(|e| GenerateError::Finalize(parent.to_path_buf(), e.kind()))?;
+            }
+            fs::write(long_path::extended(&target), contents)
+                .await
+                .map_err(|e| GenerateError::Finalize(target, e.kind()))?;
+        }
+
+        // Write `compressed-payloads.txt`, if needed
+        #[cfg(feature = "zstd")]
+        if self.has_compressed_payloads() {
+            self.write_file(
+                compression::COMPRESSED_PAYLOADS_FILE_NAME.into(),
+                compression::compressed_payloads_contents(self.payload_items()),
+            )
+            .await?;
+        }
+
+        if options.has(&TagFile::TagManifest) {
+            // Checksums of tag files the bag did not write again are kept, unless they were added again
+            let mut unchanged = self
+                .tag_files
+                .iter()
+                .map(|tag_file| tag_file.to_path_buf())
+                .filter(|tag_file| !options.custom_files().any(|(path, _)| path == tag_file))
+                .collect::<Vec<_>>();
+            if manifests_unchanged {
+                unchanged.push(self.manifest_name().into());
+            }
+
+            self.tag_items = self
+                .write_tagmanifest_file::<ChecksumAlgo>(&options, &unchanged)
+                .await?;
+        } else {
+            remove_stale_file(self.path.join(self.tagmanifest_name())).await?;
+            for manifest in self.additional_manifests.iter() {
+                remove_stale_file(self.path.join(tagmanifest_name(manifest.algorithm))).await?;
+            }
+            self.tag_items.clear();
+        }
+
+        self.remove_stale_manifests(&options).await?;
+        self.written_manifests = Some(WrittenManifests::of(self));
+        self.created_files.clear();
+
+        Ok(())
+    }
+
+    /// Give up on the bag: remove files it created in its directory since it was last finalized, then directories left empty
+    ///
+    /// A bag that was never finalized is removed, a bag finalized before is left as it was then. Files that were in the bag
+    /// directory before are kept, even when the bag wrote over them, like payloads moved into the bag with [`AddMode::Move`]
+    /// so that they are not lost: their previous contents are not restored. See [`BagItGuard`](crate::BagItGuard) to abandon a bag that is dropped before being finalized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// if let Err(e) = bag.add_file::<sha2::Sha256>("/path/to/missing.csv").await {
+    ///     eprintln!("failed to create bag: {e}");
+    ///     bag.abandon().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn abandon(self) -> Result<(), GenerateError> {
+        let directory = self.path.to_path_buf();
+        let files = self.created_files;
+        let removal = {
+            let directory = directory.clone();
+            tokio::task::spawn_blocking(move || remove_created_files(&directory, &files))
+        };
+        removal
+            .await
+            .map_err(|_| GenerateError::Abandon(directory, std::io::ErrorKind::Other))?
+    }
+
+    /// Files created by the bag in its directory, see [`Self::abandon()`]
+    pub(crate) fn created_files(&self) -> &[PathBuf] {
+        &self.created_files
+    }
+
+    /// Contents of files [`Self::finalize()`] would write, without writing anything
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::new_empty("/path/to/bag", &algorithm);
+    /// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+    ///
+    /// let preview = bag.preview_finalize::<sha2::Sha256>().await?;
+    /// for (path, contents) in preview.files() {
+    ///     println!("{}:\n{}", path.display(), String::from_utf8_lossy(contents));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn preview_finalize<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+    ) -> Result<FinalizePreview, GenerateError> {
+        self.preview_finalize_with_options::<ChecksumAlgo>(FinalizeOptions::default())
+            .await
+    }
+
+    /// Contents of files [`Self::finalize_with_options()`] would write with `options`, without writing anything
+    ///
+    /// Manifests are rendered in full. Tag manifests have checksums of the rendered files, and of other tag files as they are
+    /// in the bag directory. When the bag was finalized before with payloads to fetch, lines of payloads added since then are
+    /// appended after theirs in the manifest, instead of before them here.
+    pub async fn preview_finalize_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+        options: FinalizeOptions,
+    ) -> Result<FinalizePreview, GenerateError> {
+        self.check_manifest_paths()?;
+
+        let mut files: Vec<(PathBuf, Vec<u8>)> =
+            vec![(self.manifest_name().into(), self.manifest_contents().into())];
+        for manifest in self.additional_manifests.iter() {
+            files.push((
+                manifest_name(manifest.algorithm).into(),
+                self.additional_manifest_contents(manifest).into(),
+            ));
+        }
+        if !self.fetch_items.is_empty() {
+            files.push((FETCH_FILE_NAME.into(), self.fetch_contents().into()));
+        }
+        files.push(("bagit.txt".into(), self.bagit_file().render(None).into()));
+        if options.has(&TagFile::BagInfo) {
+            let bag_info = self.bag_info_file(&options).render(options.fold_width);
+            files.push(("bag-info.txt".into(), bag_info.into()));
+        }
+        for (path, contents) in options.custom_files() {
+            if !is_tag_file_path(path) {
+                return Err(GenerateError::TagDirectoryDestination);
+            }
+            files.push((path.clone(), contents.clone()));
+        }
+        #[cfg(feature = "zstd")]
+        if self.has_compressed_payloads() {
+            files.push((
+                compression::COMPRESSED_PAYLOADS_FILE_NAME.into(),
+                compression::compressed_payloads_contents(self.payload_items()).into(),
+            ));
+        }
+
+        if options.has(&TagFile::TagManifest) {
+            // Rendered files are hashed in memory, other ones from the bag directory
+            let tag_files = self.tagmanifest_files(&options).await?;
+            let rendered = &files;
+            let checksums = futures::future::join_all(tag_files.iter().map(|file| async move {
+                match rendered.iter().find(|(path, _)| path == file) {
+                    Some((_, contents)) => Ok(self.hash_bytes::<ChecksumAlgo>(contents)),
+                    None => {
+                        self.hash_payload::<ChecksumAlgo>(self.path.join(file))
+                            .await
+                    }
+                }
+            }))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+            let mut lines = Vec::with_capacity(tag_files.len());
+            let mut other_lines = vec![vec![]; self.additional_manifests.len()];
+            for (path, (checksum, other_checksums)) in tag_files.iter().zip(checksums) {
+                lines.push(self.manifest_line(&checksum, path));
+                for (lines, checksum) in other_lines.iter_mut().zip(other_checksums) {
+                    lines.push(self.manifest_line(&checksum, path));
+                }
+            }
+
+            files.push((
+                self.tagmanifest_name().into(),
+                lines_contents(lines.into_iter()).into(),
+            ));
+            for (manifest, lines) in self.additional_manifests.iter().zip(other_lines) {
+                files.push((
+                    tagmanifest_name(manifest.algorithm).into(),
+                    lines_contents(lines.into_iter()).into(),
+                ));
+            }
+        }
+
+        Ok(FinalizePreview { files })
+    }
+
+    /// Append lines of payloads from index `start` to manifests, returns whether they were all found to append to
+    async fn append_manifests(&self, start: usize) -> Result<bool, GenerateError> {
+        let mut names = vec![self.manifest_name()];
+        names.extend(
+            self.additional_manifests
+                .iter()
+                .map(|manifest| manifest_name(manifest.algorithm)),
+        );
+        for name in names {
+            if !fs::try_exists(long_path::extended(&self.path.join(name)))
+                .await
+                .unwrap_or(false)
+            {
+                return Ok(false);
+            }
+        }
+
+        let added = &self.items[start..];
+        if added.is_empty() {
+            return Ok(true);
+        }
+
+        self.append_manifest_file(
+            self.manifest_name(),
+            added
+                .iter()
+                .map(|payload| self.manifest_line(payload.checksum(), payload.relative_path())),
+        )
+        .await?;
+        for manifest in self.additional_manifests.iter() {
+            let lines = added
+                .iter()
+                .zip(manifest.checksums[start..].iter())
+                .map(|(payload, checksum)| self.manifest_line(checksum, payload.relative_path()));
+            self.append_manifest_file(manifest_name(manifest.algorithm), lines)
+                .await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Give manifests and tag manifests whose names only differ by case, found that way when reading the bag, the names they are written with
+    ///
+    /// They would otherwise be left next to the ones written or removed when finalizing, on case-sensitive filesystems.
+    async fn rename_manifests_case(&self) -> Result<(), GenerateError> {
+        let mut names = vec![self.manifest_name(), self.tagmanifest_name()];
+        for manifest in self.additional_manifests.iter() {
+            names.push(manifest_name(manifest.algorithm));
+            names.push(tagmanifest_name(manifest.algorithm));
+        }
+
+        let mut entries = match fs::read_dir(long_path::extended(&self.path)).await {
+            Ok(entries) => entries,
+            // Bag directory is created when finalizing
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(GenerateError::Finalize(self.path.to_path_buf(), e.kind())),
+        };
+        let mut file_names = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| GenerateError::Finalize(self.path.to_path_buf(), e.kind()))?
+        {
+            if let Ok(file_name) = entry.file_name().into_string() {
+                file_names.push(file_name);
+            }
+        }
+
+        for name in names {
+            if file_names.contains(&name) {
+                continue;
+            }
+            let Some(found) = file_names
+                .iter()
+                .find(|file_name| file_name.eq_ignore_ascii_case(&name))
+            else {
+                continue;
+            };
+
+            let found = self.path.join(found);
+            fs::rename(
+                long_path::extended(&found),
+                long_path::extended(&self.path.join(name)),
+            )
+            .await
+            .map_err(|e| GenerateError::Finalize(found, e.kind()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove manifests and tag manifests of other algorithms of a bag opened for update, see [`Self::open_for_update()`]
+    ///
+    /// Their checksums can't be kept up to date, and would make the bag invalid.
+    async fn remove_stale_manifests(&self, options: &FinalizeOptions) -> Result<(), GenerateError> {
+        let mut written = vec![self.manifest_name()];
+        written.extend(
+            self.additional_manifests
+                .iter()
+                .map(|manifest| manifest_name(manifest.algorithm)),
+        );
+        if options.has(&TagFile::TagManifest) {
+            written.push(self.tagmanifest_name());
+            written.extend(
+                self.additional_manifests
+                    .iter()
+                    .map(|manifest| tagmanifest_name(manifest.algorithm)),
+            );
+        }
+
+        // Names of manifests are matched ignoring case, like when they are read
+        for stale_manifest in self.stale_manifests.iter() {
+            let is_written = stale_manifest
+                .to_str()
+                .is_some_and(|stale| written.iter().any(|name| name.eq_ignore_ascii_case(stale)));
+            if !is_written {
+                remove_stale_file(self.path.join(stale_manifest)).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    fn has_compressed_payloads(&self) -> bool {
+        self.payload_items().any(|payload| payload.is_compressed())
+    }
+
+    /// Files written when finalizing with `options`, relative to the bag directory
+    fn finalize_files(&self, options: &FinalizeOptions) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = vec![self.manifest_name().into()];
+        files.extend(
+            self.additional_manifests
+                .iter()
+                .map(|manifest| manifest_name(manifest.algorithm).into()),
+        );
+        if !self.fetch_items.is_empty() {
+            files.push(FETCH_FILE_NAME.into());
+        }
+        files.push("bagit.txt".into());
+        if options.has(&TagFile::BagInfo) {
+            files.push("bag-info.txt".into());
+        }
+        files.extend(options.custom_files().map(|(path, _)| path.clone()));
+        #[cfg(feature = "zstd")]
+        if self.has_compressed_payloads() {
+            files.push(compression::COMPRESSED_PAYLOADS_FILE_NAME.into());
+        }
+        if options.has(&TagFile::TagManifest) {
+            files.push(self.tagmanifest_name().into());
+            files.extend(
+                self.additional_manifests
+                    .iter()
+                    .map(|manifest| tagmanifest_name(manifest.algorithm).into()),
+            );
+        }
+        files
+    }
+
+    /// Fail when a path of payload can't be written on a line of manifest for the version of the bag
+    fn check_manifest_paths(&self) -> Result<(), GenerateError> {
+        // Lines of manifests can't be split before version 1.0
+        if !self.version.percent_encodes_paths() {
+            let has_line_break = |path: &Path| path.to_string_lossy().contains(['\r', '\n']);
+            if self
+                .payload_items()
+                .map(|payload| payload.relative_path())
+                .chain(self.fetch_items.iter().map(|item| item.relative_path()))
+                .any(has_line_break)
+            {
+                return Err(GenerateError::PathNotSupported(self.version));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Contents of manifest, payloads to fetch are listed along with those in the bag
+    fn manifest_contents(&self) -> String {
+        let remote_payloads = self.pending_fetch_items().filter_map(|item| {
+            let checksum = item.checksum()?;
+            Some(self.manifest_line(checksum, item.relative_path()))
+        });
+        lines_contents(
+            self.payload_items()
+                .map(|payload| self.manifest_line(payload.checksum(), payload.relative_path()))
+                .chain(remote_payloads),
+        )
+    }
+
+    /// Contents of manifest of an additional algorithm
+    fn additional_manifest_contents(&self, manifest: &AdditionalManifest) -> String {
+        lines_contents(
+            self.payload_items()
+                .zip(manifest.checksums.iter())
+                .map(|(payload, checksum)| self.manifest_line(checksum, payload.relative_path())),
+        )
+    }
+
+    /// Contents of `fetch.txt`
+    fn fetch_contents(&self) -> String {
+        lines_contents(
+            self.fetch_items
+                .iter()
+                .map(|item| item.to_line(self.version)),
+        )
+    }
+
+    /// Tags of `bagit.txt`
+    fn bagit_file(&self) -> MetadataFile {
+        let mut bagit_file = MetadataFile::default();
+        bagit_file.add(Metadata::BagitVersion {
+            major: self.version.major(),
+            minor: self.version.minor(),
+        });
+        bagit_file.add(Metadata::Encoding(TagEncoding::Utf8));
+        bagit_file
+    }
+
+    /// Tags of `bag-info.txt`, with tags generated from payloads replacing stale ones
+    fn bag_info_file(&self, options: &FinalizeOptions) -> MetadataFile {
+        // Payloads to fetch count as well, Oxum is left out when one of them has no length
+        let remote_octet_count = self
+            .pending_fetch_items()
+            .map(|item| item.length())
+            .sum::<Option<u64>>();
+        let generated = options
+            .generated_tags
+            .iter()
+            .filter_map(|generated_tag| match generated_tag {
+                GeneratedTag::PayloadOxum => remote_octet_count.map(|remote_octet_count| {
+                    Metadata::PayloadOctetStreamSummary {
+                        stream_count: self.items.len() + self.pending_fetch_items().count(),
+                        octet_count: self.octet_count + remote_octet_count,
+                    }
+                }),
+                GeneratedTag::BagSize => Some(Metadata::BagSize(human_size(self.octet_count))),
+                #[cfg(feature = "date")]
+                GeneratedTag::BaggingDate => Some(Metadata::BaggingDate(jiff::Zoned::now().date())),
+            })
+            .collect::<Vec<_>>();
+
+        self.tags
+            .iter()
+            .filter(|tag| {
+                !matches!(tag, Metadata::PayloadOctetStreamSummary { .. })
+                    && !generated
+                        .iter()
+                        .any(|generated| generated.key() == tag.key())
+            })
+            .cloned()
+            .chain(generated.iter().cloned())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Write `contents` to file `filename` at the root of the bag
+    async fn write_file(&self, filename: String, contents: String) -> Result<(), GenerateError> {
+        let path = self.path.join(filename);
+        fs::write(long_path::extended(&path), contents)
+            .await
+            .map_err(|e| GenerateError::Finalize(path, e.kind()))
+    }
+
+    /// Add lines of `payloads` at the end of an existing manifest file
+    async fn append_manifest_file(
+        &self,
+        filename: String,
+        payloads: impl Iterator<Item = impl ToString>,
+    ) -> Result<(), GenerateError> {
+        use std::io::SeekFrom;
+        use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+        let manifest_path = self.path.join(filename);
+        let append = async {
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(long_path::extended(&manifest_path))
+                .await?;
+
+            // Last line may not end with a line break
+            let mut separator = "";
+            if file.metadata().await?.len() > 0 {
+                let mut last = [0; 1];
+                file.seek(SeekFrom::End(-1)).await?;
+                file.read_exact(&mut last).await?;
+                if last != *b"\n" {
+                    separator = "\n";
+                }
+            }
+
+            let contents = lines_contents(payloads);
+            file.write_all(format!("{separator}{contents}").as_bytes())
+                .await?;
+            file.flush().await
+        };
+
+        append
+            .await
+            .map_err(|e| GenerateError::Finalize(manifest_path.clone(), e.kind()))
+    }
+
+    /// Files listed in tag manifest
+    ///
+    /// Every file outside of the payload directory is listed, the ones the bag knows about first.
+    async fn tagmanifest_files(
+        &self,
+        options: &FinalizeOptions,
+    ) -> Result<Vec<PathBuf>, GenerateError> {
+        let mut items: Vec<PathBuf> = vec!["bagit.txt".into()];
+        if options.has(&TagFile::BagInfo) {
+            items.push("bag-info.txt".into());
+        }
+        items.push(self.manifest_name().into());
+        items.extend(
+            self.additional_manifests
+                .iter()
+                .map(|manifest| manifest_name(manifest.algorithm).into()),
+        );
+        if !self.fetch_items.is_empty() {
+            items.push(FETCH_FILE_NAME.into());
+        }
+        items.extend(options.custom_files().map(|(path, _)| path.clone()));
+
+        // Keep history of validations, if bag has one
+        if fs::try_exists(long_path::extended(
+            &self.path.join(VALIDATION_LOG_FILE_NAME),
+        ))
+        .await
+        .unwrap_or(false)
+        {
+            items.push(VALIDATION_LOG_FILE_NAME.into());
+        }
+
+        // Original files of compressed payloads
+        #[cfg(feature = "zstd")]
+        if self.has_compressed_payloads() {
+            items.push(compression::COMPRESSED_PAYLOADS_FILE_NAME.into());
+        }
+
+        // Supplementary tag files, unless they were declared as custom tag files as well
+        for tag_file in self.tag_files.iter() {
+            if !items.iter().any(|item| **item == **tag_file) {
+                items.push(tag_file.to_path_buf());
+            }
+        }
+
+        // Any other file outside of the payload directory, like manifests of other algorithms or files copied by hand,
+        // except tag manifests, the checksum cache and manifests about to be removed
+        let bag_files = match walk::list_bag_files(&self.path).await {
+            Ok(bag_files) => bag_files,
+            // Bag directory is created when finalizing
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(GenerateError::Finalize(self.path.to_path_buf(), e.kind())),
+        };
+        for file in bag_files {
+            let is_excluded = file.to_str().is_some_and(|name| {
+                name.to_ascii_lowercase().starts_with(PREFIX_TAG_MANIFEST)
+                    || name == CACHE_FILE_NAME
+            }) || self.stale_manifests.contains(&file);
+            if !is_excluded && !items.contains(&file) {
+                items.push(file);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Write tag manifest, returning its items
+    ///
+    /// Checksums of `unchanged` files are taken from the current tag manifest when it lists them,
+    /// and when the bag has a single algorithm.
+    async fn write_tagmanifest_file<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+        options: &FinalizeOptions,
+        unchanged: &[PathBuf],
+    ) -> Result<Vec<Payload<'a>>, GenerateError> {
+        let items = self.tagmanifest_files(options).await?;
+
+        // Compute their checksums, with every algorithm at once
+        let previous = |file: &PathBuf| {
+            self.tag_items
+                .iter()
+                .find(|item| item.relative_path() == file)
+                .filter(|_| self.additional_manifests.is_empty() && unchanged.contains(file))
+        };
+        let checksums_items = futures::future::join_all(items.iter().map(|file| async move {
+            match previous(file) {
+                Some(item) => Ok((item.checksum().clone().into_owned(), vec![])),
+                None => {
+                    self.hash_payload::<ChecksumAlgo>(self.path().join(file))
+                        .await
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+        // Create payloads
+        let mut payloads = Vec::with_capacity(items.len());
+        let mut other_lines = vec![vec![]; self.additional_manifests.len()];
+        for (path, (checksum, other_checksums)) in items.iter().zip(checksums_items) {
+            payloads.push(Payload::new(self.path.clone(), path, checksum).await?);
+            for (lines, checksum) in other_lines.iter_mut().zip(other_checksums) {
+                lines.push(self.manifest_line(&checksum, path));
+            }
+        }
+
+        // Write like manifest file
+        self.write_file(
+            self.tagmanifest_name(),
+            lines_contents(
+                payloads
+                    .iter()
+                    .map(|payload| self.manifest_line(payload.checksum(), payload.relative_path())),
+            ),
+        )
+        .await?;
+        for (manifest, lines) in self.additional_manifests.iter().zip(other_lines) {
+            self.write_file(
+                tagmanifest_name(manifest.algorithm),
+                lines_contents(lines.into_iter()),
+            )
+            .await?;
+        }
+
+        Ok(payloads)
+    }
+
+    /// Write `bag-info.txt` with tags of the bag as they are, and update its checksum in tag manifests
+    #[cfg(feature = "read")]
+    pub(crate) async fn rewrite_bag_info<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), GenerateError> {
+        // Nothing is written unless every tag manifest can be updated
+        let tag_manifests = self.tag_manifest_files().await?;
+
+        let bag_info = MetadataFile::from(self.tags.clone()).render(None);
+        self.write_file("bag-info.txt".into(), bag_info).await?;
+
+        self.update_tagmanifest_entry::<ChecksumAlgo>("bag-info.txt", &tag_manifests)
+            .await
+    }
+
+    /// Names of tag manifests in bag directory, as they are on disk, along with the name of their algorithm in lowercase
+    async fn tag_manifest_names(&self) -> Result<Vec<(String, String)>, GenerateError> {
+        let list_error =
+            |e: std::io::Error| GenerateError::Finalize(self.path.to_path_buf(), e.kind());
+        let mut entries = fs::read_dir(long_path::extended(&self.path))
+            .await
+            .map_err(list_error)?;
+
+        let mut tag_manifests = vec![];
+        while let Some(entry) = entries.next_entry().await.map_err(list_error)? {
+            let path = entry.path();
+            let (Some(algorithm), Some(name)) = (
+                algorithm_name(&path, PREFIX_TAG_MANIFEST),
+                entry.file_name().to_str().map(str::to_string),
+            ) else {
+                continue;
+            };
+
+            if fs::metadata(long_path::extended(&path))
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+            {
+                tag_manifests.push((name, algorithm));
+            }
+        }
+        tag_manifests.sort();
+
+        Ok(tag_manifests)
+    }
+
+    /// Tag manifests in bag directory, with hashers of their algorithms
+    ///
+    /// Hashers of other algorithms than the one of the bag are the ones of additional manifests, then algorithms registered
+    /// when reading the bag, then those known by default, see [`AlgorithmRegistry`].
+    /// A tag manifest of another algorithm is rejected with [`GenerateError::UnknownTagManifestAlgorithm`].
+    pub(crate) async fn tag_manifest_files(&self) -> Result<Vec<TagManifestFile>, GenerateError> {
+        let builtin = AlgorithmRegistry::default();
+        let same_name =
+            |algorithm: &Algorithm, name: &str| algorithm.name().eq_ignore_ascii_case(name);
+
+        let mut tag_manifests = vec![];
+        for (name, algorithm) in self.tag_manifest_names().await? {
+            let new_hasher = match same_name(self.checksum_algorithm, &algorithm) {
+                true => None,
+                false => Some(
+                    self.additional_manifests
+                        .iter()
+                        .find(|manifest| same_name(manifest.algorithm, &algorithm))
+                        .map(|manifest| manifest.new_hasher)
+                        .or_else(|| {
+                            self.known_algorithms
+                                .iter()
+                                .find(|(known, _)| same_name(known, &algorithm))
+                                .map(|(_, new_hasher)| *new_hasher)
+                        })
+                        .or_else(|| builtin.get(&algorithm).map(|known| known.new_hasher()))
+                        .ok_or_else(|| {
+                            GenerateError::UnknownTagManifestAlgorithm(self.path.join(&name))
+                        })?,
+                ),
+            };
+            tag_manifests.push(TagManifestFile { name, new_hasher });
+        }
+
+        Ok(tag_manifests)
+    }
+
+    /// Compute checksum of `tag_file` again, and replace or add its line in every tag manifest of `tag_manifests`
+    ///
+    /// The file is read once, each tag manifest gets the checksum of its own algorithm. Other lines of tag manifests are kept as they are.
+    pub(crate) async fn update_tagmanifest_entry<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        tag_file: &str,
+        tag_manifests: &[TagManifestFile],
+    ) -> Result<(), GenerateError> {
+        if tag_manifests.is_empty() {
+            return Ok(());
+        }
+
+        let others = tag_manifests
+            .iter()
+            .filter_map(|tag_manifest| tag_manifest.new_hasher)
+            .map(|new_hasher| new_hasher())
+            .collect();
+        let (checksum, other_checksums) =
+            hash_file_with::<ChecksumAlgo>(self.path.join(tag_file), others, |_| ()).await?;
+
+        let mut other_checksums = other_checksums.into_iter();
+        for tag_manifest in tag_manifests {
+            let checksum = match tag_manifest.new_hasher {
+                None => checksum.clone(),
+                Some(_) => other_checksums
+                    .next()
+                    .expect("one checksum is computed for every hasher"),
+            };
+            self.rewrite_tagmanifest(&tag_manifest.name, tag_file, Some(&checksum))
+                .await?;
+        }
+
+        let entry = Payload::new(self.path.clone(), tag_file, checksum).await?;
+        self.tag_items
+            .retain(|item| item.relative_path() != Path::new(tag_file));
+        self.tag_items.push(entry);
+
+        Ok(())
+    }
+
+    /// Remove line of `tag_file` from every tag manifest, once the file was removed from the bag
+    ///
+    /// Other lines of tag manifests are kept as they are.
+    #[cfg(feature = "fetch")]
+    pub(crate) async fn remove_tagmanifest_entry(
+        &mut self,
+        tag_file: &str,
+    ) -> Result<(), GenerateError> {
+        for (name, _) in self.tag_manifest_names().await? {
+            self.rewrite_tagmanifest(&name, tag_file, None).await?;
+        }
+
+        self.tag_items
+            .retain(|item| item.relative_path() != Path::new(tag_file));
+
+        Ok(())
+    }
+
+    /// Write tag manifest `name` again without the line of `tag_file`, followed by a new line with `checksum` if any
+    async fn rewrite_tagmanifest(
+        &self,
+        name: &str,
+        tag_file: &str,
+        checksum: Option<&Checksum<'_>>,
+    ) -> Result<(), GenerateError> {
+        let path = self.path.join(name);
+        let contents = fs::read_to_string(long_path::extended(&path))
+            .await
+            .map_err(|e| GenerateError::Finalize(path, e.kind()))?;
+        let contents = contents.strip_prefix(BYTE_ORDER_MARK).unwrap_or(&contents);
+
+        // Lines that can't be parsed are not about `tag_file`, they are left for validation to report
+        let lines = contents
+            .lines()
+            .filter(|line| {
+                !parse_manifest_line(line, self.version)
+                    .is_ok_and(|(_, listed)| Path::new(listed.as_ref()) == Path::new(tag_file))
+            })
+            .map(str::to_string)
+            .chain(checksum.map(|checksum| self.manifest_line(checksum, Path::new(tag_file))));
+
+        self.write_file(name.to_string(), lines_contents(lines))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "read")]
+    use super::TagFile;
+    use super::{AddMode, BagItBuilder, CollisionPolicy, FinalizeOptions, GeneratedTag};
+    use crate::error::GenerateError;
+    #[cfg(feature = "read")]
+    use crate::Metadata;
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm};
+    #[cfg(all(feature = "date", feature = "read"))]
+    use jiff::civil::Date;
+    use sha2::Sha256;
+
+    #[tokio::test]
+    async fn bag_sha256() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        // Add files to the bag
+        let temp_payload_destination = temp_directory.join("data");
+        for file in [
+            "bagit.md",
+            "paper_bag.jpg",
+            "rfc8493.txt",
+            "sources.csv",
+            "totebag.jpg",
+        ] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+            assert!(temp_payload_destination.join(file).is_file());
+        }
+
+        // Manifest file
+        let manifest_name = format!("manifest-{}.txt", algo.algorithm());
+        let manifest_file = temp_directory.join(manifest_name);
+        assert!(!manifest_file.is_file());
+
+        // Bagit file
+        let bagit_file = temp_directory.join("bagit.txt");
+        assert!(!bagit_file.is_file());
+
+        // Bag info file
+        let bag_info_file = temp_directory.join("bag-info.txt");
+        assert!(!bag_info_file.is_file());
+
+        // Tag manifest file
+        let tag_manifest_name = format!("tagmanifest-{}.txt", algo.algorithm());
+        let tag_manifest_file = temp_directory.join(tag_manifest_name);
+        assert!(!tag_manifest_file.is_file());
+
+        // Finalize bag
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Make sure files have been created
+        assert!(manifest_file.is_file());
+        assert!(bagit_file.is_file());
+        assert!(bag_info_file.is_file());
+        assert!(tag_manifest_file.is_file());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    #[cfg(feature = "date")]
+    async fn bag_with_date() {
+        use crate::metadata::Metadata;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        // Add files to the bag
+        let temp_payload_destination = temp_directory.join("data");
+        for file in ["paper_bag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+            assert!(temp_payload_destination.join(file).is_file());
+        }
+
+        // Date added again replaces the previous one
+        bag.add_bagging_date_now();
+        assert_eq!(bag.tags, [Metadata::BaggingDate(jiff::Zoned::now().date())]);
+        bag.add_bagging_date(Date::new(2024, 8, 1).unwrap());
+        assert_eq!(
+            bag.tags,
+            [Metadata::BaggingDate(Date::new(2024, 8, 1).unwrap())]
+        );
+
+        // Date of the day replaces a date added before as well
+        bag.add_bagging_date(Date::new(2020, 1, 1).unwrap());
+        bag.add_bagging_date_now();
+        assert_eq!(bag.tags, [Metadata::BaggingDate(jiff::Zoned::now().date())]);
+        bag.add_bagging_date(Date::new(2024, 8, 1).unwrap());
+
+        // Finalize bag
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Read bag, make sure date is present
+        let read_bag = BagIt::read_existing::<Sha256>(temp_directory, &algo)
+            .await
+            .unwrap();
+        assert_eq!(
+            read_bag.tags,
+            vec![
+                Metadata::BaggingDate(Date::new(2024, 8, 1).unwrap()),
+                Metadata::PayloadOctetStreamSummary {
+                    octet_count: 19895,
+                    stream_count: 1
+                }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn finalize_twice() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Derived tags are not stored in the bag
+        assert!(bag.tags.is_empty());
+
+        // Oxum is written once
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert_eq!(bag_info, "Payload-Oxum: 369.1\n");
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn tag_directory() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let bag_directory = temp_directory.join("bag");
+
+        // Documentation to bundle, with a subdirectory
+        let documentation = temp_directory.join("documentation");
+        tokio::fs::create_dir_all(documentation.join("schemas"))
+            .await
+            .unwrap();
+        tokio::fs::write(documentation.join("readme.txt"), "About this bag")
+            .await
+            .unwrap();
+        tokio::fs::write(documentation.join("schemas/sources.json"), "{}")
+            .await
+            .unwrap();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+
+        for destination in ["data/docs", "../docs", "/docs", ""] {
+            assert_eq!(
+                bag.add_tag_directory(&documentation, destination).await,
+                Err(GenerateError::TagDirectoryDestination),
+                "failing on destination `{destination}`"
+            );
+        }
+        bag.add_tag_directory(&documentation, "docs").await.unwrap();
+        bag.add_tag_directory(&documentation, "docs").await.unwrap();
+        assert!(bag_directory.join("docs/schemas/sources.json").is_file());
+
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Tag files are in tag manifest, not in manifest
+        let tag_manifest = tokio::fs::read_to_string(bag_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        let tag_files = tag_manifest
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tag_files,
+            [
+                "bagit.txt",
+                "bag-info.txt",
+                "manifest-sha256.txt",
+                "docs/readme.txt",
+                "docs/schemas/sources.json"
+            ]
+        );
+
+        // Tag files are validated when reading
+        let read_bag = BagIt::read_existing(&bag_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 1);
+        tokio::fs::write(bag_directory.join("docs/readme.txt"), "Changed")
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing(&bag_directory, &algo).await.is_err());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn payload_sidecar() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_with_sidecar::<Sha256>(source_directory.join("sources.csv"), r#"{"rows": 7}"#)
+            .await
+            .unwrap();
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.contains(" metadata/sources.csv.json"));
+
+        // Sidecars are found from payloads
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let sidecars = futures::future::join_all(
+            bag.payload_items()
+                .map(|payload| async { payload.sidecar().await.unwrap() }),
+        )
+        .await;
+        assert_eq!(sidecars, [Some(br#"{"rows": 7}"#.to_vec()), None]);
+
+        // Sidecars are validated with tag manifest
+        tokio::fs::write(temp_directory.join("metadata/sources.csv.json"), "{}")
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_err());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    #[cfg(feature = "zstd")]
+    async fn compressed_payload() {
+        use crate::error::PayloadError;
+        use tokio::io::AsyncReadExt;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+        let original = tokio::fs::read(source_directory.join("rfc8493.txt"))
+            .await
+            .unwrap();
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_compressed::<Sha256>(source_directory.join("rfc8493.txt"))
+            .await
+            .unwrap();
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Compressed file is a regular payload, taking less space
+        let compressed = temp_directory.join("data/rfc8493.txt.zst");
+        assert!(compressed.is_file());
+        assert!(tokio::fs::metadata(&compressed).await.unwrap().len() < original.len() as u64);
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.contains(" compressed-payloads.txt"));
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut payloads = bag.payload_items();
+        let payload = payloads.next().unwrap();
+        assert!(payload.is_compressed());
+        assert_eq!(payload.original_bytes(), Some(48783));
+        assert!(!payloads.next().unwrap().is_compressed());
+
+        // Decompressed transparently
+        let mut contents = vec![];
+        payload
+            .open()
+            .await
+            .unwrap()
+            .read_to_end(&mut contents)
+            .await
+            .unwrap();
+        assert_eq!(contents, original);
+        assert_eq!(payload.verify::<Sha256>().await, Ok(()));
+
+        // Original file recorded with another size
+        let compressed_payloads = temp_directory.join("compressed-payloads.txt");
+        let contents = tokio::fs::read_to_string(&compressed_payloads)
+            .await
+            .unwrap();
+        tokio::fs::write(&compressed_payloads, contents.replace(" 48783 ", " 48784 "))
+            .await
+            .unwrap();
+        let bag = BagIt::read_unvalidated(&temp_directory, &algo)
+            .await
+            .unwrap();
+        let payload = bag.payload_items().next().unwrap();
+        assert_eq!(payload.original_bytes(), Some(48784));
+        assert_eq!(
+            payload.verify::<Sha256>().await,
+            Err(PayloadError::SizeDiffers {
+                path: "data/rfc8493.txt.zst".into(),
+                expected: 48784,
+                actual: 48783,
+            })
+        );
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn minimal_bag() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert!(temp_directory.join("bag-info.txt").is_file());
+
+        // Finalizing again without bag info removes it
+        assert_eq!(
+            bag.finalize_with_options::<Sha256>(FinalizeOptions::default().bag_info(false))
+                .await,
+            Ok(())
+        );
+        assert!(!temp_directory.join("bag-info.txt").exists());
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(!tag_manifest.contains("bag-info.txt"));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 1);
+        assert!(read_bag.tags.is_empty());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn declared_tag_files() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+
+        let custom = TagFile::Custom {
+            path: "provenance/origin.txt".into(),
+            contents: b"Field recordings".to_vec(),
+        };
+        assert_eq!(
+            bag.finalize_with_options::<Sha256>(
+                FinalizeOptions::default().tag_files([TagFile::TagManifest, custom.clone()])
+            )
+            .await,
+            Ok(())
+        );
+        assert!(!temp_directory.join("bag-info.txt").exists());
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.contains(" provenance/origin.txt"));
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+
+        // Without tag manifest
+        assert_eq!(
+            bag.finalize_with_options::<Sha256>(
+                FinalizeOptions::default().tag_files([TagFile::BagInfo, custom])
+            )
+            .await,
+            Ok(())
+        );
+        assert!(temp_directory.join("bag-info.txt").is_file());
+        assert!(!temp_directory.join("tagmanifest-sha256.txt").exists());
+
+        // Reserved files can't be replaced
+        for path in ["bagit.txt", "manifest-sha256.txt", "data/extra.txt"] {
+            let options = FinalizeOptions::default().tag_files([TagFile::Custom {
+                path: path.into(),
+                contents: vec![],
+            }]);
+            assert_eq!(
+                bag.finalize_with_options::<Sha256>(options).await,
+                Err(GenerateError::TagDirectoryDestination),
+                "failing on path `{path}`"
+            );
+        }
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn fetch_items() {
+        use crate::Checksum;
+        use std::str::FromStr;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let checksum =
+            Checksum::from_str("38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31")
+                .unwrap();
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        for (url, path) in [
+            ("https://example.com/totebag.jpg", "bagit.txt"),
+            ("https://example.com/totebag.jpg", "data/../totebag.jpg"),
+            ("https://example.com/tote bag.jpg", "data/totebag.jpg"),
+            ("", "data/totebag.jpg"),
+        ] {
+            assert_eq!(
+                bag.add_fetch_item(url, None, path, checksum.clone()),
+                Err(GenerateError::FetchItem),
+                "failing on url `{url}` and path `{path}`"
+            );
+        }
+        bag.add_fetch_item(
+            "https://example.com/totebag.jpg",
+            Some(10417),
+            "data/totebag.jpg",
+            checksum,
+        )
+        .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert!(!bag.is_complete());
+
+        let fetch_file = tokio::fs::read_to_string(temp_directory.join("fetch.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            fetch_file,
+            "https://example.com/totebag.jpg 10417 data/totebag.jpg\n"
+        );
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.contains("fetch.txt"));
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert!(bag_info.contains("Payload-Oxum: 10786.2"));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 1);
+        assert_eq!(
+            read_bag
+                .pending_fetch_items()
+                .map(|item| item.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new("data/totebag.jpg")]
+        );
+
+        // Once fetched, payload is verified with checksum of manifest
+        tokio::fs::copy(
+            source_directory.join("totebag.jpg"),
+            temp_directory.join("data/totebag.jpg"),
+        )
+        .await
+        .unwrap();
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 2);
+        assert!(read_bag.is_complete());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn several_algorithms() {
+        use crate::ReadOptions;
+        use sha2::Sha512;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let sha512 = ChecksumAlgorithm::<Sha512>::new(Algorithm::Sha512);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&sha512).unwrap();
+        bag.add_algorithm(&sha256).unwrap();
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            bag.add_algorithm(&sha512),
+            Err(GenerateError::AlgorithmAfterPayloads)
+        );
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        for name in [
+            "manifest-sha256.txt",
+            "manifest-sha512.txt",
+            "tagmanifest-sha256.txt",
+            "tagmanifest-sha512.txt",
+        ] {
+            assert!(temp_directory.join(name).is_file(), "missing {name}");
+        }
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha512.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.contains(" manifest-sha256.txt"));
+        assert!(tag_manifest.contains(" manifest-sha512.txt"));
+
+        // Every manifest is satisfied
+        let read_bag = BagIt::read_existing(&temp_directory, &sha512)
+            .await
+            .unwrap();
+        assert_eq!(read_bag.payload_items().count(), 2);
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .algorithm(&sha512)
+            .strict(true);
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn nested_payloads() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Files with the same name in different directories
+        for (directory, contents) in [("images/2023", "first"), ("images/2024", "second")] {
+            tokio::fs::create_dir_all(source_directory.join(directory))
+                .await
+                .unwrap();
+            tokio::fs::write(source_directory.join(directory).join("notes.txt"), contents)
+                .await
+                .unwrap();
+        }
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for directory in ["images/2023", "images/2024"] {
+            bag.add_file_relative::<Sha256>(
+                &source_directory,
+                source_directory.join(directory).join("notes.txt"),
+            )
+            .await
+            .unwrap();
+        }
+        assert!(matches!(
+            bag.add_file_relative::<Sha256>(source_directory.join("images"), &temp_directory)
+                .await,
+            Err(GenerateError::StripPrefixPath(_))
+        ));
+        assert_eq!(
+            bag.add_file_relative::<Sha256>(
+                &source_directory,
+                source_directory.join("images/../images/2024/notes.txt")
+            )
+            .await,
+            Err(GenerateError::PayloadDestination)
+        );
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut paths = read_bag
+            .payload_items()
+            .map(|payload| payload.relative_path().to_path_buf())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                std::path::Path::new("data/images/2023/notes.txt"),
+                std::path::Path::new("data/images/2024/notes.txt")
+            ]
+        );
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn payload_destination() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(
+            source_directory.join("sources.csv"),
+            "data/metadata/record.csv",
+        )
+        .await
+        .unwrap();
+        for destination in [
+            "metadata/record.csv",
+            "data",
+            "data/../bagit.txt",
+            "/data/record.csv",
+        ] {
+            assert_eq!(
+                bag.add_file_as::<Sha256>(source_directory.join("sources.csv"), destination)
+                    .await,
+                Err(GenerateError::PayloadDestination),
+                "failing on destination `{destination}`"
+            );
+        }
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/metadata/record.csv\n"
+        );
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinks() {
+        use crate::{error::PayloadError, SymlinkPolicy};
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let bag_directory = temp_directory.join("bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+        let symlink = temp_directory.join("latest.csv");
+        tokio::fs::symlink(source_directory.join("sources.csv"), &symlink)
+            .await
+            .unwrap();
+
+        // Followed by default
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        bag.add_file::<Sha256>(&symlink).await.unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert!(
+            !tokio::fs::symlink_metadata(bag_directory.join("data/latest.csv"))
+                .await
+                .unwrap()
+                .is_symlink()
+        );
+
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        bag.set_symlink_policy(SymlinkPolicy::Reject);
+        assert_eq!(
+            bag.add_file::<Sha256>(&symlink).await,
+            Err(GenerateError::Payload(PayloadError::SymlinkRejected(
+                symlink.clone()
+            )))
+        );
+
+        bag.set_symlink_policy(SymlinkPolicy::SkipWithWarning);
+        bag.add_file::<Sha256>(&symlink).await.unwrap();
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert_eq!(bag.skipped_symlinks(), [symlink]);
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn add_directory() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        for (file, contents) in [
+            ("images/2023/notes.txt", "first"),
+            ("images/2024/notes.txt", "second"),
+            ("readme.md", "third"),
+        ] {
+            let file = source_directory.join(file);
+            tokio::fs::create_dir_all(file.parent().unwrap())
+                .await
+                .unwrap();
+            tokio::fs::write(file, contents).await.unwrap();
+        }
+        #[cfg(unix)]
+        tokio::fs::symlink(
+            source_directory.join("readme.md"),
+            source_directory.join("latest.md"),
+        )
+        .await
+        .unwrap();
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.set_symlink_policy(crate::SymlinkPolicy::Reject);
+        assert!(matches!(
+            bag.add_directory::<Sha256>(source_directory.join("missing"))
+                .await,
+            Err(GenerateError::CopyToPayloadFolder(
+                _,
+                std::io::ErrorKind::NotFound
+            ))
+        ));
+        let results = bag
+            .add_directory::<Sha256>(&source_directory)
+            .await
+            .unwrap();
+        assert_eq!(
+            results.iter().filter(|(_, result)| result.is_ok()).count(),
+            3
+        );
+
+        // Symbolic link is refused, without stopping other files
+        #[cfg(unix)]
+        assert_eq!(
+            results
+                .iter()
+                .filter(|(_, result)| result.is_err())
+                .map(|(file, _)| file.clone())
+                .collect::<Vec<_>>(),
+            [source_directory.join("latest.md")]
+        );
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut paths = read_bag
+            .payload_items()
+            .map(|payload| payload.relative_path().to_path_buf())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                std::path::Path::new("data/images/2023/notes.txt"),
+                std::path::Path::new("data/images/2024/notes.txt"),
+                std::path::Path::new("data/readme.md")
+            ]
+        );
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn add_from_reader() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_from_reader::<Sha256>(&b"hello"[..], "data/greetings/hello.txt")
+            .await
+            .unwrap();
+        assert_eq!(
+            bag.add_from_reader::<Sha256>(&b"hello"[..], "metadata/hello.txt")
+                .await,
+            Err(GenerateError::PayloadDestination)
+        );
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824 data/greetings/hello.txt\n"
+        );
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-md5.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "5d41402abc4b2a76b9719d911017c592 data/greetings/hello.txt\n"
+        );
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().next().unwrap().bytes(), 5);
+    }
+
+    #[tokio::test]
+    async fn add_file_move() {
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let source = source_directory.join("hello.txt");
+        tokio::fs::write(&source, "hello").await.unwrap();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_move::<Sha256>(&source).await.unwrap();
+
+        assert!(!source.exists());
+        assert_eq!(
+            tokio::fs::read(temp_directory.join("data/hello.txt"))
+                .await
+                .unwrap(),
+            b"hello"
+        );
+        let payload = bag.payload_items().next().unwrap();
+        assert_eq!(payload.bytes(), 5);
+        assert_eq!(
+            payload.checksum().to_string(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        // Missing file is not added
+        assert_eq!(
+            bag.add_file_move::<Sha256>(&source).await,
+            Err(GenerateError::ComputeChecksum(
+                crate::error::ChecksumComputeError::FileNotFound(source)
+            ))
+        );
+        assert_eq!(bag.payload_items().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn abandon() {
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let bag_directory = temp_directory.to_path_buf().join("bag");
+
+        let source = source_directory.join("moved.txt");
+        tokio::fs::write(&source, "moved").await.unwrap();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        bag.add_file_move::<Sha256>(&source).await.unwrap();
+        bag.add_tag_file(&bag_directory.join("data/hello.txt"), "metadata/hello.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.abandon().await, Ok(()));
+
+        // Moved payload is not lost
+        assert!(!bag_directory.join("data/hello.txt").exists());
+        assert!(!bag_directory.join("metadata").exists());
+        assert_eq!(
+            tokio::fs::read(bag_directory.join("data/moved.txt"))
+                .await
+                .unwrap(),
+            b"moved"
+        );
+    }
+
+    #[tokio::test]
+    async fn add_mode_hardlink() {
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+        let temp_directory = source_directory.join("bag");
+
+        let source = source_directory.join("hello.txt");
+        tokio::fs::write(&source, "hello").await.unwrap();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.set_add_mode(AddMode::Hardlink);
+        bag.add_file::<Sha256>(&source).await.unwrap();
+        assert_eq!(bag.payload_items().next().unwrap().bytes(), 5);
+
+        // Original is kept, and shares its contents with the payload
+        tokio::fs::write(&source, "world").await.unwrap();
+        assert_eq!(
+            tokio::fs::read(temp_directory.join("data/hello.txt"))
+                .await
+                .unwrap(),
+            b"world"
+        );
+
+        // Adding it again replaces the link
+        bag.add_file_as::<Sha256>(&source, "data/hello.txt")
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn bag_in_place() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        tokio::fs::write(temp_directory.join("a.txt"), "a")
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(temp_directory.join("data"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/b.txt"), "bb")
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(temp_directory.join("sub/dir"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("sub/dir/c.txt"), "ccc")
+            .await
+            .unwrap();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.bag_in_place::<Sha256>().await.unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut payloads = read_bag
+            .payload_items()
+            .map(|payload| (payload.relative_path().to_path_buf(), payload.bytes()))
+            .collect::<Vec<_>>();
+        payloads.sort();
+        assert_eq!(
+            payloads,
+            [
+                (std::path::PathBuf::from("data/a.txt"), 1),
+                (std::path::PathBuf::from("data/data/b.txt"), 2),
+                (std::path::PathBuf::from("data/sub/dir/c.txt"), 3),
+            ]
+        );
+        assert!(!temp_directory.join(".bagit-in-place").exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn bag_in_place_rollback() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+
+        // Bag directory whose path leaves room for a long name in it, but not in the staging directory
+        let mut bag_directory = temp_directory.to_path_buf();
+        let target = 3830 - bag_directory.as_os_str().len();
+        for _ in 0..target / 200 {
+            bag_directory.push("d".repeat(199));
+        }
+        let missing = 3830 - bag_directory.as_os_str().len() - 1;
+        bag_directory.push("d".repeat(missing));
+        tokio::fs::create_dir_all(&bag_directory).await.unwrap();
+
+        let long_name = "n".repeat(255);
+        tokio::fs::write(bag_directory.join(&long_name), "long")
+            .await
+            .unwrap();
+        let mut names = vec![long_name.clone()];
+        for index in 0..10 {
+            let name = format!("{index}.txt");
+            tokio::fs::write(bag_directory.join(&name), "a")
+                .await
+                .unwrap();
+            names.push(name);
+        }
+        names.sort();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        assert!(matches!(
+            bag.bag_in_place::<Sha256>().await,
+            Err(GenerateError::CopyToPayloadFolder(path, _)) if path == bag_directory.join(&long_name)
+        ));
+        assert_eq!(bag.payload_items().count(), 0);
+
+        // Directory is left as it was
+        let mut entries = tokio::fs::read_dir(&bag_directory).await.unwrap();
+        let mut left = vec![];
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            left.push(entry.file_name().into_string().unwrap());
+        }
+        left.sort();
+        assert_eq!(left, names);
+    }
+
+    #[tokio::test]
+    async fn remove_file() {
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source_directory = source_directory.to_path_buf();
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_algorithm(&md5).unwrap();
+
+        tokio::fs::write(source_directory.join("wrong.txt"), "wrong")
+            .await
+            .unwrap();
+        bag.add_file_with_sidecar::<Sha256>(source_directory.join("wrong.txt"), "{}")
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+
+        assert_eq!(bag.remove_file("data/wrong.txt").await, Ok(()));
+        assert_eq!(
+            bag.remove_file("data/wrong.txt").await,
+            Err(GenerateError::UnknownPayload("data/wrong.txt".into()))
+        );
+        assert!(!temp_directory.join("data/wrong.txt").exists());
+        assert!(source_directory.join("wrong.txt").exists());
+
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-md5.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "5d41402abc4b2a76b9719d911017c592 data/hello.txt\n"
+        );
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert!(bag_info.contains("Payload-Oxum: 5.1"));
+        let tagmanifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(!tagmanifest.contains("wrong.txt"));
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn open_for_update() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_bytes::<Sha256>("old", "data/old.txt")
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("kept", "data/kept.txt")
+            .await
+            .unwrap();
+        bag.add_tag(Metadata::Custom {
+            key: "Project".into(),
+            value: "Example".into(),
+        })
+        .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let mut bag = BagIt::open_for_update(&temp_directory, &algo)
+            .await
+            .unwrap();
+        bag.remove_file("data/old.txt").await.unwrap();
+        bag.add_bytes::<Sha256>("new!", "data/new.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Manifests of md5 are stale, they are gone
+        assert!(!temp_directory.join("manifest-md5.txt").exists());
+        assert!(!temp_directory.join("tagmanifest-md5.txt").exists());
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut payloads = read_bag
+            .payload_items()
+            .map(|payload| payload.relative_path().to_path_buf())
+            .collect::<Vec<_>>();
+        payloads.sort();
+        assert_eq!(
+            payloads,
+            [
+                std::path::PathBuf::from("data/kept.txt"),
+                std::path::PathBuf::from("data/new.txt")
+            ]
+        );
+        assert!(read_bag.tags().any(|tag| *tag
+            == Metadata::Custom {
+                key: "Project".into(),
+                value: "Example".into(),
+            }));
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert!(bag_info.contains("Payload-Oxum: 8.2"));
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn open_for_update_manifest_name_case() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>("kept", "data/kept.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Bag coming from a case-insensitive filesystem
+        tokio::fs::rename(
+            temp_directory.join("manifest-sha256.txt"),
+            temp_directory.join("Manifest-SHA256.txt"),
+        )
+        .await
+        .unwrap();
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap()
+            .replace(" manifest-sha256.txt", " Manifest-SHA256.txt");
+        tokio::fs::write(temp_directory.join("TagManifest-SHA256.txt"), tag_manifest)
+            .await
+            .unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+
+        let mut bag = BagIt::open_for_update(&temp_directory, &algo)
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("new!", "data/new.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        // Manifests are written under their own names, without leaving the others behind
+        let mut entries = tokio::fs::read_dir(&temp_directory).await.unwrap();
+        let mut manifests = vec![];
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            let file_name = entry.file_name().into_string().unwrap();
+            if file_name.to_ascii_lowercase().contains("manifest") {
+                manifests.push(file_name);
+            }
+        }
+        manifests.sort();
+        assert_eq!(manifests, ["manifest-sha256.txt", "tagmanifest-sha256.txt"]);
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(manifest.lines().count(), 2);
+        assert_eq!(BagIt::check_completeness(&temp_directory).await, Ok(()));
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 2);
+        assert!(read_bag.warnings().is_empty());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn finalize_again() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let manifest_path = temp_directory.join("manifest-sha256.txt");
+        let source_directory = async_tempfile::TempDir::new().await.unwrap();
+        let source = source_directory.join("sidecar.json");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>("a", "data/a.txt").await.unwrap();
+        tokio::fs::write(&source, r#"{"a": 1}"#).await.unwrap();
+        bag.add_tag_file(&source, "sidecar.json").await.unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        let manifest = tokio::fs::read_to_string(&manifest_path).await.unwrap();
+
+        // Lines of new payloads are appended
+        bag.add_bytes::<Sha256>("b", "data/b.txt").await.unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert_eq!(
+            tokio::fs::read_to_string(&manifest_path).await.unwrap(),
+            format!("{manifest}3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d data/b.txt\n")
+        );
+
+        // Tag file added again is hashed again
+        tokio::fs::write(&source, r#"{"a": 2}"#).await.unwrap();
+        bag.add_tag_file(&source, "sidecar.json").await.unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+
+        // Removed payload is gone from manifest
+        bag.remove_file("data/a.txt").await.unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        assert_eq!(
+            tokio::fs::read_to_string(&manifest_path).await.unwrap(),
+            "3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d data/b.txt\n"
+        );
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn builder() {
+        use crate::BagVersion;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+        let mut bag = BagItBuilder::new(&temp_directory, &algo)
+            .version(BagVersion::V0_97)
+            .algorithm(&md5)
+            .tag(crate::Metadata::Custom {
+                key: "Project".into(),
+                value: "Example".into(),
+            })
+            .build()
+            .unwrap();
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let bagit = tokio::fs::read_to_string(temp_directory.join("bagit.txt"))
+            .await
+            .unwrap();
+        assert!(bagit.contains("BagIt-Version: 0.97"));
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert!(bag_info.contains("Project: Example"));
+        assert!(temp_directory.join("manifest-md5.txt").exists());
+
+        assert_eq!(
+            BagItBuilder::new(&temp_directory, &algo)
+                .version(BagVersion::new(0, 96))
+                .build()
+                .err(),
+            Some(GenerateError::UnsupportedVersion(BagVersion::new(0, 96)))
+        );
+        assert_eq!(
+            BagItBuilder::new(&temp_directory, &algo)
+                .tag(crate::Metadata::PayloadOctetStreamSummary {
+                    stream_count: 1,
+                    octet_count: 1,
+                })
+                .build()
+                .err(),
+            Some(GenerateError::DerivedTag("Payload-Oxum".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn collision_policy() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>("first", "data/notes.txt")
+            .await
+            .unwrap();
+
+        // Overwritten by default, listed once
+        bag.add_bytes::<Sha256>("second", "data/notes.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert_eq!(bag.payload_items().next().unwrap().bytes(), 6);
+
+        bag.set_collision_policy(CollisionPolicy::Error);
+        assert_eq!(
+            bag.add_bytes::<Sha256>("third", "data/notes.txt").await,
+            Err(GenerateError::DestinationExists("data/notes.txt".into()))
+        );
+
+        bag.set_collision_policy(CollisionPolicy::Skip);
+        bag.add_bytes::<Sha256>("third", "data/notes.txt")
+            .await
+            .unwrap();
+        assert_eq!(
+            tokio::fs::read(temp_directory.join("data/notes.txt"))
+                .await
+                .unwrap(),
+            b"second"
+        );
+
+        bag.set_collision_policy(CollisionPolicy::RenameWithSuffix);
+        bag.add_bytes::<Sha256>("third", "data/notes.txt")
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("fourth", "data/notes.txt")
+            .await
+            .unwrap();
+        assert_eq!(
+            bag.payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [
+                std::path::Path::new("data/notes.txt"),
+                std::path::Path::new("data/notes-1.txt"),
+                std::path::Path::new("data/notes-2.txt"),
+            ]
+        );
+
+        // File on disk not added by the bag is an existing file as well
+        tokio::fs::write(temp_directory.join("data/README"), "leftover")
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("readme", "data/README")
+            .await
+            .unwrap();
+        assert!(temp_directory.join("data/README-1").exists());
+    }
+
+    #[tokio::test]
+    async fn add_bytes() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>(b"hello".to_vec(), "data/hello.txt")
+            .await
+            .unwrap();
+        assert_eq!(
+            bag.add_bytes::<Sha256>("hello", "../hello.txt").await,
+            Err(GenerateError::PayloadDestination)
+        );
+
+        assert_eq!(
+            tokio::fs::read(temp_directory.join("data/hello.txt"))
+                .await
+                .unwrap(),
+            b"hello"
+        );
+        let payload = bag.payload_items().next().unwrap();
+        assert_eq!(payload.bytes(), 5);
+        assert_eq!(
+            payload.checksum().to_string(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn percent_encoded_paths() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(
+            source_directory.join("sources.csv"),
+            "data/100%\r\ncotton.csv",
+        )
+        .await
+        .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/100%25%0D%0Acotton.csv\n"
+        );
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag
+                .payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new("data/100%\r\ncotton.csv")]
+        );
+
+        // Hex digits of other writers may be lowercase
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/100%25%0d%0acotton.csv",
+        )
+        .await
+        .unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn paths_with_spaces() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(source_directory.join("totebag.jpg"), "data/my photo.jpg")
+            .await
+            .unwrap();
+        bag.add_file_as::<Sha256>(
+            source_directory.join("sources.csv"),
+            "data/old sources/ sources .csv",
+        )
+        .await
+        .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31 data/my photo.jpg\n\
+            0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/old sources/ sources .csv\n"
+        );
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let mut paths = read_bag
+            .payload_items()
+            .map(|payload| payload.relative_path())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                std::path::Path::new("data/my photo.jpg"),
+                std::path::Path::new("data/old sources/ sources .csv")
+            ]
+        );
+
+        // Checksum and path may be separated by several spaces or tabs
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31 \t data/my photo.jpg\n\
+            0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72\tdata/old sources/ sources .csv",
+        )
+        .await
+        .unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.payload_items().count(), 2);
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn version_0_97() {
+        use crate::BagVersion;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        assert_eq!(
+            bag.set_version(BagVersion::new(0, 96)),
+            Err(GenerateError::UnsupportedVersion(BagVersion::new(0, 96)))
+        );
+        bag.set_version(BagVersion::V0_97).unwrap();
+        bag.add_file_as::<Sha256>(source_directory.join("sources.csv"), "data/100%.csv")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        let bagit = tokio::fs::read_to_string(temp_directory.join("bagit.txt"))
+            .await
+            .unwrap();
+        assert!(bagit.starts_with("BagIt-Version: 0.97\n"));
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest,
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72 data/100%.csv\n"
+        );
 
-        // Create payload directory if it does not exist yet
-        let mut destination = self.path.join("data/");
-        fs::create_dir_all(&destination)
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.version(), BagVersion::V0_97);
+        assert_eq!(
+            read_bag.payload_items().next().unwrap().relative_path(),
+            std::path::Path::new("data/100%.csv")
+        );
+
+        // Line breaks in paths can't be written before version 1.0
+        bag.add_file_as::<Sha256>(source_directory.join("sources.csv"), "data/100%\n.csv")
             .await
-            .map_err(|e| GenerateError::OpenChecksumFile(e.kind()))?;
+            .unwrap();
+        assert_eq!(
+            bag.finalize::<Sha256>().await,
+            Err(GenerateError::PathNotSupported(BagVersion::V0_97))
+        );
+    }
 
-        // Construct path of file inside payload directory
-        let file_name = file
-            .as_ref()
-            .file_name()
-            .ok_or(GenerateError::FileHasNoName)?;
-        destination.push(file_name);
+    #[tokio::test]
+    async fn generated_tags() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let bag_info_path = temp_directory.join("bag-info.txt");
 
-        // Copy file
-        fs::copy(file, &destination)
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_tag(crate::Metadata::BagSize("1 TB".into()))
+            .unwrap();
+        bag.add_bytes::<Sha256>(vec![0; 10_417], "data/zeros.bin")
             .await
-            .map_err(|e| GenerateError::CopyToPayloadFolder(e.kind()))?;
+            .unwrap();
 
-        let relative_path = destination.strip_prefix(self.path())?.to_path_buf();
+        // Size of the bag is replaced
+        let options = FinalizeOptions::default()
+            .generated_tags([GeneratedTag::BagSize, GeneratedTag::PayloadOxum]);
+        assert_eq!(bag.finalize_with_options::<Sha256>(options).await, Ok(()));
+        assert_eq!(
+            tokio::fs::read_to_string(&bag_info_path).await.unwrap(),
+            "Bag-Size: 10.4 kB\nPayload-Oxum: 10417.1\n"
+        );
 
-        // Add to list of items in bag
-        self.items
-            .push(Payload::new(self.path(), relative_path, file_checksum)?);
+        // Without any generated tag
+        let options = FinalizeOptions::default().generated_tags([]);
+        assert_eq!(bag.finalize_with_options::<Sha256>(options).await, Ok(()));
+        assert_eq!(
+            tokio::fs::read_to_string(&bag_info_path).await.unwrap(),
+            "Bag-Size: 1 TB\n"
+        );
 
-        Ok(())
+        #[cfg(feature = "date")]
+        {
+            bag.add_bagging_date(jiff::civil::Date::new(2024, 8, 1).unwrap());
+            let options = FinalizeOptions::default().generated_tags([GeneratedTag::BaggingDate]);
+            assert_eq!(bag.finalize_with_options::<Sha256>(options).await, Ok(()));
+            assert_eq!(
+                tokio::fs::read_to_string(&bag_info_path).await.unwrap(),
+                format!(
+                    "Bag-Size: 1 TB\nBagging-Date: {}\n",
+                    jiff::Zoned::now().date()
+                )
+            );
+        }
     }
 
-    #[cfg(feature = "date")]
-    /// Add ISO formatted date representing date when bag was created
-    pub fn add_bagging_date(&mut self, date: jiff::civil::Date) {
-        self.tags.push(Metadata::BaggingDate(date));
-    }
+    #[tokio::test]
+    async fn preview_finalize() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
 
-    /// Procedure to make a bagit container ready for distribution
-    ///
-    /// - Write manifest file with payloads and their checksums
-    /// - Bagit file declaration
-    /// - Information file about bag
-    /// - Manifest with checksums of files that are not data payload
-    pub async fn finalize<ChecksumAlgo: Digest>(&mut self) -> Result<(), GenerateError> {
-        self.write_manifest_file(self.manifest_name(), self.payload_items())
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
             .await
-            .map_err(|e| GenerateError::Finalize(e.kind()))?;
+            .unwrap();
 
-        // Write `bagit.txt`
-        let mut bagit_file = MetadataFile::default();
-        bagit_file.add(Metadata::BagitVersion { major: 1, minor: 0 });
-        bagit_file.add(Metadata::Encoding);
-        bagit_file
-            .write(self.path.join("bagit.txt"))
+        let options = FinalizeOptions::default()
+            .generated_tags([GeneratedTag::BagSize, GeneratedTag::PayloadOxum]);
+        let preview = bag
+            .preview_finalize_with_options::<Sha256>(options.clone())
             .await
-            .map_err(|e| GenerateError::Finalize(e.kind()))?;
+            .unwrap();
+        assert!(!temp_directory.join("manifest-sha256.txt").exists());
+        assert_eq!(
+            preview.file_str("bag-info.txt"),
+            Some("Bag-Size: 5 B\nPayload-Oxum: 5.1\n")
+        );
+        assert_eq!(
+            preview.files().map(|(path, _)| path).collect::<Vec<_>>(),
+            [
+                "manifest-sha256.txt",
+                "manifest-md5.txt",
+                "bagit.txt",
+                "bag-info.txt",
+                "tagmanifest-sha256.txt",
+                "tagmanifest-md5.txt"
+            ]
+            .map(std::path::Path::new)
+        );
 
-        // Write `bag-info.txt`
-        self.tags.push(Metadata::PayloadOctetStreamSummary {
-            stream_count: self.payload_items().count(),
-            octet_count: self.payload_items().map(|payload| payload.bytes()).sum(),
-        });
-        MetadataFile::from(self.tags.clone())
-            .write(self.path.join("bag-info.txt"))
+        // Files written are the ones of the preview
+        assert_eq!(bag.finalize_with_options::<Sha256>(options).await, Ok(()));
+        for (path, contents) in preview.files() {
+            assert_eq!(
+                tokio::fs::read(temp_directory.join(path)).await.unwrap(),
+                contents,
+                "contents of `{}`",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn human_size() {
+        assert_eq!(super::human_size(0), "0 B");
+        assert_eq!(super::human_size(999), "999 B");
+        assert_eq!(super::human_size(1000), "1.0 kB");
+        assert_eq!(super::human_size(19_895), "19.9 kB");
+        assert_eq!(super::human_size(260_400_000_000), "260.4 GB");
+    }
+
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn reserved_tags() {
+        use crate::metadata::Metadata;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_tag(Metadata::source_organization("Totebag Inc.").unwrap())
+            .unwrap();
+        bag.add_tag(Metadata::bag_count(1, Some(2)).unwrap())
+            .unwrap();
+        assert_eq!(
+            bag.add_tag(Metadata::PayloadOctetStreamSummary {
+                octet_count: 0,
+                stream_count: 0
+            }),
+            Err(GenerateError::DerivedTag("Payload-Oxum".into()))
+        );
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
             .await
-            .map_err(|e| GenerateError::Finalize(e.kind()))?;
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
 
-        self.write_tagmanifest_file::<ChecksumAlgo>().await?;
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            bag_info,
+            "Source-Organization: Totebag Inc.\nBag-Count: 1 of 2\nPayload-Oxum: 369.1\n"
+        );
 
-        Ok(())
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag.tags().cloned().collect::<Vec<_>>(),
+            [
+                Metadata::SourceOrganization("Totebag Inc.".into()),
+                Metadata::BagCount {
+                    number: 1,
+                    total: Some(2)
+                },
+                Metadata::PayloadOctetStreamSummary {
+                    octet_count: 369,
+                    stream_count: 1
+                }
+            ]
+        );
     }
 
-    async fn write_manifest_file(
-        &self,
-        filename: String,
-        payloads: impl Iterator<Item = impl ToString>,
-    ) -> Result<(), std::io::Error> {
-        let manifest_path = self.path.join(filename);
+    #[cfg(feature = "read")]
+    #[tokio::test]
+    async fn folded_values() {
+        use crate::metadata::Metadata;
 
-        let contents = payloads
-            .map(|payload| payload.to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
 
-        fs::write(manifest_path, contents).await
-    }
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-    async fn write_tagmanifest_file<ChecksumAlgo: Digest>(&self) -> Result<(), GenerateError> {
-        // Files for tag manifest
-        let items = [
-            "bagit.txt".into(),
-            "bag-info.txt".into(),
-            self.manifest_name(),
-        ];
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
 
-        // Compute their checksums
-        let checksums_items = futures::future::join_all(
-            items
-                .iter()
-                .map(|file| compute_checksum_file::<ChecksumAlgo>(self.path().join(file))),
+        let description = Metadata::external_description(
+            "Photographs of tote bags and paper bags, taken for the 2024 collection of the museum",
         )
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>, _>>()?;
+        .unwrap();
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_tag(description.clone()).unwrap();
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize_with_options::<Sha256>(FinalizeOptions::default().fold_values(40))
+            .await
+            .unwrap();
 
-        // Create payloads
-        let payloads = items
-            .iter()
-            .zip(checksums_items)
-            .filter_map(|(path, checksum)| Payload::new(self.path(), path, checksum).ok());
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        assert!(bag_info.lines().all(|line| line.len() <= 40));
+        assert_eq!(bag_info.lines().count(), 4);
 
-        // Write like manifest file
-        self.write_manifest_file(self.tagmanifest_name(), payloads)
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(read_bag.tags().next(), Some(&description));
+
+        // Folded by another tool, with tabs
+        tokio::fs::write(
+            temp_directory.join("bag-info.txt"),
+            "Contact-Name: Philippe\nExternal-Description: Photographs\n\tof tote bags\nPayload-Oxum: 369.1",
+        )
+        .await
+        .unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
             .await
-            .map_err(|e| GenerateError::Finalize(e.kind()))
+            .unwrap();
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag.tags().nth(1),
+            Some(&Metadata::ExternalDescription(
+                "Photographs of tote bags".into()
+            ))
+        );
     }
-}
-
-#[cfg(test)]
-mod test {
-    use crate::{Algorithm, BagIt, ChecksumAlgorithm};
-    #[cfg(feature = "date")]
-    use jiff::civil::Date;
-    use sha2::Sha256;
 
     #[tokio::test]
-    async fn bag_sha256() {
+    async fn tag_file() {
         let temp_directory = async_tempfile::TempDir::new().await.unwrap();
         let temp_directory = temp_directory.to_path_buf();
+        let bag_directory = temp_directory.join("bag");
+
+        let dpn_info = temp_directory.join("DPN-info.txt");
+        tokio::fs::write(&dpn_info, "DPN-Object-ID: 1234")
+            .await
+            .unwrap();
 
         let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
 
         let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         source_directory.push("tests/sample-bag/data");
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
 
-        // Add files to the bag
-        let temp_payload_destination = temp_directory.join("data");
-        for file in [
-            "bagit.md",
-            "paper_bag.jpg",
-            "rfc8493.txt",
-            "sources.csv",
-            "totebag.jpg",
-        ] {
-            bag.add_file::<Sha256>(source_directory.join(file))
-                .await
-                .unwrap();
-            assert!(temp_payload_destination.join(file).is_file());
+        for destination in ["data/DPN-info.txt", "../DPN-info.txt", "bag-info.txt", ""] {
+            assert_eq!(
+                bag.add_tag_file(&dpn_info, destination).await,
+                Err(GenerateError::TagDirectoryDestination),
+                "failing on destination `{destination}`"
+            );
         }
+        bag.add_tag_file(&dpn_info, "DPN-info.txt").await.unwrap();
+        bag.add_tag_file(source_directory.join("bagit.md"), "metadata/bagit.md")
+            .await
+            .unwrap();
+        bag.add_tag_file(&dpn_info, "DPN-info.txt").await.unwrap();
+        assert_eq!(
+            bag.tag_files().collect::<Vec<_>>(),
+            [
+                std::path::Path::new("DPN-info.txt"),
+                std::path::Path::new("metadata/bagit.md")
+            ]
+        );
+        assert_eq!(
+            bag.add_tag_file(temp_directory.join("missing.xml"), "missing.xml")
+                .await,
+            Err(GenerateError::CopyToTagDirectory(
+                temp_directory.join("missing.xml"),
+                std::io::ErrorKind::NotFound
+            ))
+        );
 
-        // Manifest file
-        let manifest_name = format!("manifest-{}.txt", algo.algorithm());
-        let manifest_file = temp_directory.join(manifest_name);
-        assert!(!manifest_file.is_file());
-
-        // Bagit file
-        let bagit_file = temp_directory.join("bagit.txt");
-        assert!(!bagit_file.is_file());
-
-        // Bag info file
-        let bag_info_file = temp_directory.join("bag-info.txt");
-        assert!(!bag_info_file.is_file());
-
-        // Tag manifest file
-        let tag_manifest_name = format!("tagmanifest-{}.txt", algo.algorithm());
-        let tag_manifest_file = temp_directory.join(tag_manifest_name);
-        assert!(!tag_manifest_file.is_file());
-
-        // Finalize bag
         assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
 
-        // Make sure files have been created
-        assert!(manifest_file.is_file());
-        assert!(bagit_file.is_file());
-        assert!(bag_info_file.is_file());
-        assert!(tag_manifest_file.is_file());
+        let tag_manifest = tokio::fs::read_to_string(bag_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        let tag_files = tag_manifest
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tag_files,
+            [
+                "bagit.txt",
+                "bag-info.txt",
+                "manifest-sha256.txt",
+                "DPN-info.txt",
+                "metadata/bagit.md"
+            ]
+        );
+
+        // Tag files are found again when reading
+        #[cfg(feature = "read")]
+        {
+            let read_bag = BagIt::read_existing(&bag_directory, &algo).await.unwrap();
+            assert!(read_bag.tag_files().eq(bag.tag_files()));
+            assert!(read_bag.warnings().is_empty());
+        }
     }
 
     #[tokio::test]
-    #[cfg(feature = "date")]
-    async fn bag_with_date() {
-        use crate::metadata::Metadata;
-
+    async fn tagmanifest_covers_bag_files() {
         let temp_directory = async_tempfile::TempDir::new().await.unwrap();
         let temp_directory = temp_directory.to_path_buf();
 
         let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
-
         let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
 
-        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        source_directory.push("tests/sample-bag/data");
-
-        // Add files to the bag
-        let temp_payload_destination = temp_directory.join("data");
-        for file in ["paper_bag.jpg"] {
-            bag.add_file::<Sha256>(source_directory.join(file))
+        // Files copied in the bag without telling it
+        tokio::fs::create_dir_all(temp_directory.join("metadata"))
+            .await
+            .unwrap();
+        for file in [
+            "metadata/marc.xml",
+            "DPN-info.txt",
+            "manifest-md5.txt",
+            ".bagit-cache.txt",
+        ] {
+            tokio::fs::write(temp_directory.join(file), "")
                 .await
                 .unwrap();
-            assert!(temp_payload_destination.join(file).is_file());
         }
-
-        bag.add_bagging_date(Date::new(2024, 8, 1).unwrap());
-
-        // Finalize bag
         assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
 
-        // Read bag, make sure date is present
-        let read_bag = BagIt::read_existing::<Sha256>(temp_directory, &algo)
+        let tag_manifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
             .await
             .unwrap();
+        let tag_files = tag_manifest
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect::<Vec<_>>();
         assert_eq!(
-            read_bag.tags,
-            vec![
-                Metadata::BaggingDate(Date::new(2024, 8, 1).unwrap()),
-                Metadata::PayloadOctetStreamSummary {
-                    octet_count: 19895,
-                    stream_count: 1
-                }
+            tag_files,
+            [
+                "bagit.txt",
+                "bag-info.txt",
+                "manifest-sha256.txt",
+                "DPN-info.txt",
+                "manifest-md5.txt",
+                "metadata/marc.xml"
             ]
         );
     }
+
+    #[tokio::test]
+    async fn empty_bag() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let bag_directory = temp_directory.join("empty");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Bag directory does not exist yet, there are no payloads to create it
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+
+        assert!(bag_directory.join("data").is_dir());
+        assert_eq!(
+            tokio::fs::read_to_string(bag_directory.join("manifest-sha256.txt"))
+                .await
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(bag_directory.join("bag-info.txt"))
+                .await
+                .unwrap(),
+            "Payload-Oxum: 0.0\n"
+        );
+
+        #[cfg(feature = "read")]
+        {
+            let read_bag = BagIt::read_existing(&bag_directory, &algo).await.unwrap();
+            assert_eq!(read_bag.payload_items().count(), 0);
+            assert!(read_bag.tag_manifest_items().eq(bag.tag_manifest_items()));
+        }
+    }
 }
@@ -0,0 +1,252 @@
+//! Groups of bags, when a logical bag is split in several ones with `Bag-Group-Identifier` and `Bag-Count`
+
+use crate::{error::ReadError, BagIt, ChecksumAlgorithm, Metadata, Payload};
+use digest::Digest;
+use std::path::Path;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+/// Possible errors when opening a group of bags, see [`BagGroup::read_existing()`]
+pub enum GroupError {
+    /// Failed to read one of the bags
+    #[error(transparent)]
+    Read(#[from] ReadError),
+    /// A group must have at least one bag
+    #[error("Group has no bags")]
+    Empty,
+    /// Every bag must have a `Bag-Group-Identifier` tag
+    #[error("Bag has no group identifier")]
+    MissingIdentifier,
+    /// Bags of a group must have the same `Bag-Group-Identifier`
+    #[error("Bags have different group identifiers")]
+    IdentifierDiffers,
+    /// Every bag must have a `Bag-Count` tag
+    #[error("Bag has no count")]
+    MissingCount,
+    /// Bags of a group must agree on the number of bags in the group
+    #[error("Bags have different counts of bags in the group")]
+    TotalDiffers,
+    /// Two bags have the same number in the group
+    #[error("Bag number {0} is found twice in the group")]
+    DuplicateNumber(u32),
+    /// Bag with this number is missing from the group, or is past the number of bags in the group
+    #[error("Bag number {0} is missing or out of the group")]
+    MissingNumber(u32),
+}
+
+#[derive(Debug)]
+/// Bags belonging to the same group, opened together
+///
+/// Bags are validated one by one like with [`BagIt::read_existing()`], then their tags are checked:
+/// - they all have the same `Bag-Group-Identifier`
+/// - their `Bag-Count` give each of them a distinct number, and agree on the number of bags in the group when it is known
+/// - no bag is missing from the group, when its number of bags is known
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{Algorithm, BagGroup, ChecksumAlgorithm};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// let group = BagGroup::read_existing(["/path/to/bag-1", "/path/to/bag-2"], &algorithm).await?;
+///
+/// // Payloads of all bags, in order of their bags in the group
+/// for payload in group.payload_items() {
+///     println!("{}", payload.relative_path().display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct BagGroup<'a, 'algo> {
+    identifier: String,
+    total: Option<u32>,
+    /// Sorted by their number in the group
+    bags: Vec<BagIt<'a, 'algo>>,
+}
+
+impl<'a, 'algo> BagGroup<'a, 'algo> {
+    /// Read and validate bags of `bag_directories`, making sure they belong to the same group
+    pub async fn read_existing<ChecksumAlgo: Digest + Send + 'static>(
+        bag_directories: impl IntoIterator<Item = impl AsRef<Path>>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<Self, GroupError> {
+        let bags = futures::future::try_join_all(
+            bag_directories
+                .into_iter()
+                .map(|directory| BagIt::read_existing(directory, checksum_algorithm)),
+        )
+        .await?;
+
+        Self::from_bags(bags)
+    }
+
+    /// Check tags of `bags` and sort them by their number
+    fn from_bags(bags: Vec<BagIt<'a, 'algo>>) -> Result<Self, GroupError> {
+        let mut members = Vec::with_capacity(bags.len());
+        for bag in bags {
+            let identifier = bag
+                .tags()
+                .find_map(|tag| match tag {
+                    Metadata::BagGroupIdentifier(identifier) => Some(identifier.clone()),
+                    _ => None,
+                })
+                .ok_or(GroupError::MissingIdentifier)?;
+            let (number, total) = bag
+                .tags()
+                .find_map(|tag| match tag {
+                    Metadata::BagCount { number, total } => Some((*number, *total)),
+                    _ => None,
+                })
+                .ok_or(GroupError::MissingCount)?;
+
+            members.push((identifier, number, total, bag));
+        }
+
+        let Some((identifier, _, _, _)) = members.first() else {
+            return Err(GroupError::Empty);
+        };
+        let identifier = identifier.clone();
+        if members.iter().any(|(other, ..)| *other != identifier) {
+            return Err(GroupError::IdentifierDiffers);
+        }
+
+        // Number of bags in the group is known as soon as one of them tells it
+        let mut total = None;
+        for (_, _, bag_total, _) in members.iter() {
+            match (total, bag_total) {
+                (Some(total), Some(bag_total)) if total != *bag_total => {
+                    return Err(GroupError::TotalDiffers)
+                }
+                (None, Some(_)) => total = *bag_total,
+                _ => {}
+            }
+        }
+
+        members.sort_by_key(|(_, number, _, _)| *number);
+        for pair in members.windows(2) {
+            if pair[0].1 == pair[1].1 {
+                return Err(GroupError::DuplicateNumber(pair[0].1));
+            }
+        }
+        if let Some(total) = total {
+            if let Some((_, number, _, _)) = members.iter().find(|(_, number, ..)| *number > total)
+            {
+                return Err(GroupError::MissingNumber(*number));
+            }
+            if let Some(missing) =
+                (1..=total).find(|number| !members.iter().any(|(_, other, ..)| other == number))
+            {
+                return Err(GroupError::MissingNumber(missing));
+            }
+        }
+
+        Ok(Self {
+            identifier,
+            total,
+            bags: members.into_iter().map(|(.., bag)| bag).collect(),
+        })
+    }
+
+    /// Value of `Bag-Group-Identifier` shared by the bags
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Number of bags in the group, if bags declare it
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+
+    /// Bags of the group, sorted by their number in the group
+    pub fn bags(&self) -> &[BagIt<'a, 'algo>] {
+        &self.bags
+    }
+
+    /// Iterator over payloads of all bags, as if they were in a single bag
+    ///
+    /// Payloads of the first bag of the group come first, paths of payloads are relative to their own bag.
+    pub fn payload_items(&self) -> impl Iterator<Item = &Payload<'_>> {
+        self.bags.iter().flat_map(|bag| bag.payload_items())
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use super::{BagGroup, GroupError};
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm, Metadata};
+    use sha2::Sha256;
+    use std::path::{Path, PathBuf};
+
+    async fn bag_of_group(
+        directory: &Path,
+        file: &str,
+        identifier: &str,
+        number: u32,
+        total: Option<u32>,
+    ) {
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut source_directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(directory, &algo);
+        bag.add_tag(Metadata::bag_group_identifier(identifier).unwrap())
+            .unwrap();
+        bag.add_tag(Metadata::bag_count(number, total).unwrap())
+            .unwrap();
+        bag.add_file::<Sha256>(source_directory.join(file))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn group() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let first = temp_directory.join("first");
+        let second = temp_directory.join("second");
+        let other = temp_directory.join("other");
+        bag_of_group(&first, "sources.csv", "totebag-2024", 1, Some(2)).await;
+        bag_of_group(&second, "totebag.jpg", "totebag-2024", 2, None).await;
+        bag_of_group(&other, "bagit.md", "papers-2024", 2, Some(2)).await;
+
+        // Order of directories does not matter
+        let group = BagGroup::read_existing([&second, &first], &algo)
+            .await
+            .unwrap();
+        assert_eq!(group.identifier(), "totebag-2024");
+        assert_eq!(group.total(), Some(2));
+        assert_eq!(
+            group
+                .payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [Path::new("data/sources.csv"), Path::new("data/totebag.jpg")]
+        );
+
+        assert_eq!(
+            BagGroup::read_existing([&first], &algo).await.unwrap_err(),
+            GroupError::MissingNumber(2)
+        );
+        assert_eq!(
+            BagGroup::read_existing([&first, &other], &algo)
+                .await
+                .unwrap_err(),
+            GroupError::IdentifierDiffers
+        );
+        assert_eq!(
+            BagGroup::read_existing([&second, &second], &algo)
+                .await
+                .unwrap_err(),
+            GroupError::DuplicateNumber(2)
+        );
+        assert_eq!(
+            BagGroup::read_existing(Vec::<PathBuf>::new(), &algo)
+                .await
+                .unwrap_err(),
+            GroupError::Empty
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Bag removed from disk when it is dropped before being finalized
+
+use crate::{generate::remove_created_files, BagIt};
+use std::ops::{Deref, DerefMut};
+
+#[derive(Debug)]
+/// Bag being created, abandoned when dropped before being finalized
+///
+/// When adding payloads fails halfway, files already copied would be left in the bag directory. Once wrapped in a guard, a bag
+/// that goes out of scope without being finalized is abandoned like with [`BagIt::abandon()`]: files it created since it was
+/// last finalized are removed.
+/// Files that were in the bag directory before are kept as they are on drop: contents written over them, like with
+/// [`CollisionPolicy::Overwrite`](crate::CollisionPolicy::Overwrite), are not undone.
+/// Removal happens on drop, where it is blocking and its errors are ignored. Methods of [`BagIt`] are available through [`Deref`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{Algorithm, BagIt, BagItGuard, ChecksumAlgorithm};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// let mut bag = BagItGuard::new(BagIt::new_empty("/path/to/bag", &algorithm));
+///
+/// // Bag is removed if one of the files can't be added
+/// bag.add_file::<sha2::Sha256>("/path/to/dataset.csv").await?;
+/// bag.add_file::<sha2::Sha256>("/path/to/schema.json").await?;
+/// bag.finalize::<sha2::Sha256>().await?;
+///
+/// let bag = bag.into_inner();
+/// # Ok(())
+/// # }
+/// ```
+pub struct BagItGuard<'a, 'algo> {
+    bag: Option<BagIt<'a, 'algo>>,
+}
+
+impl<'a, 'algo> BagItGuard<'a, 'algo> {
+    /// Abandon `bag` if it is dropped before being finalized
+    pub fn new(bag: BagIt<'a, 'algo>) -> Self {
+        Self { bag: Some(bag) }
+    }
+
+    /// Bag, kept on disk whether it was finalized or not
+    pub fn into_inner(mut self) -> BagIt<'a, 'algo> {
+        self.bag
+            .take()
+            .expect("bag is only taken when consuming guard")
+    }
+}
+
+impl<'a, 'algo> Deref for BagItGuard<'a, 'algo> {
+    type Target = BagIt<'a, 'algo>;
+
+    fn deref(&self) -> &Self::Target {
+        self.bag
+            .as_ref()
+            .expect("bag is only taken when consuming guard")
+    }
+}
+
+impl DerefMut for BagItGuard<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.bag
+            .as_mut()
+            .expect("bag is only taken when consuming guard")
+    }
+}
+
+impl Drop for BagItGuard<'_, '_> {
+    fn drop(&mut self) {
+        // Bag finalized has no files left to remove
+        if let Some(bag) = self.bag.take() {
+            let _ = remove_created_files(bag.path(), bag.created_files());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BagItGuard;
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm};
+    use sha2::Sha256;
+
+    #[tokio::test]
+    async fn abandoned_when_dropped() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let bag_directory = temp_directory.join("bag");
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Bag not finalized is removed
+        {
+            let mut bag = BagItGuard::new(BagIt::new_empty(&bag_directory, &algo));
+            bag.add_bytes::<Sha256>("hello", "data/greetings/hello.txt")
+                .await
+                .unwrap();
+            assert!(bag
+                .add_file::<Sha256>(temp_directory.join("missing.txt"))
+                .await
+                .is_err());
+        }
+        assert!(!bag_directory.exists());
+
+        // Files that were there before are kept, with what the bag wrote over them
+        tokio::fs::create_dir_all(bag_directory.join("data"))
+            .await
+            .unwrap();
+        tokio::fs::write(bag_directory.join("data/README"), "before")
+            .await
+            .unwrap();
+        {
+            let mut bag = BagItGuard::new(BagIt::new_empty(&bag_directory, &algo));
+            bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+                .await
+                .unwrap();
+            bag.add_bytes::<Sha256>("world", "data/README")
+                .await
+                .unwrap();
+        }
+        assert!(!bag_directory.join("data/hello.txt").exists());
+        assert_eq!(
+            tokio::fs::read_to_string(bag_directory.join("data/README"))
+                .await
+                .unwrap(),
+            "world"
+        );
+        tokio::fs::remove_dir_all(&bag_directory).await.unwrap();
+
+        // Finalized bag is kept
+        let bag_directory = temp_directory.join("finalized");
+        {
+            let mut bag = BagItGuard::new(BagIt::new_empty(&bag_directory, &algo));
+            bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+                .await
+                .unwrap();
+            assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+        }
+        assert!(bag_directory.join("data/hello.txt").exists());
+        assert!(bag_directory.join("manifest-sha256.txt").exists());
+
+        // Payload added after finalizing is removed, the bag is left as it was finalized
+        {
+            let mut bag = BagItGuard::new(BagIt::new_empty(&bag_directory, &algo));
+            bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+                .await
+                .unwrap();
+            assert_eq!(bag.finalize::<Sha256>().await, Ok(()));
+            bag.add_bytes::<Sha256>("world", "data/world.txt")
+                .await
+                .unwrap();
+        }
+        assert!(bag_directory.join("data/hello.txt").exists());
+        assert!(!bag_directory.join("data/world.txt").exists());
+    }
+}
@@ -0,0 +1,475 @@
+use crate::encoding::TagFileReader;
+use crate::manifest::{algorithm_name, next_line, Manifest, PREFIX_MANIFEST, PREFIX_TAG_MANIFEST};
+use crate::metadata::Metadata;
+use crate::payload::{check_manifest_path, parse_manifest_line, ManifestScope};
+use crate::{
+    error::{PayloadError, ReadError},
+    walk, Algorithm, BagIt, BagVersion, ReadWarning,
+};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tokio::{fs, io::BufReader};
+
+/// Tag of `bag-info.txt` with the identifier of the BagIt profile the bag conforms to
+const KEY_PROFILE_IDENTIFIER: &str = "BagIt-Profile-Identifier";
+
+#[derive(Clone, Debug, PartialEq)]
+/// Summary of a bag on disk, gathered without computing checksums
+///
+/// See [`BagIt::health_check()`].
+pub struct BagHealth {
+    version: BagVersion,
+    algorithms: Vec<String>,
+    payload_count: usize,
+    payload_bytes: u64,
+    oxum_matches: Option<bool>,
+    has_tag_manifest: bool,
+    profile_identifier: Option<String>,
+    warnings: Vec<ReadWarning>,
+}
+
+impl BagHealth {
+    /// Version declared in `bagit.txt`
+    pub fn version(&self) -> BagVersion {
+        self.version
+    }
+
+    /// Names of algorithms having a payload manifest in the bag, in lowercase and sorted
+    pub fn algorithms(&self) -> &[String] {
+        &self.algorithms
+    }
+
+    /// Number of payloads listed in the manifest of the first algorithm
+    pub fn payload_count(&self) -> usize {
+        self.payload_count
+    }
+
+    /// Total size in bytes of payloads listed in the manifest of the first algorithm and present on disk
+    pub fn payload_bytes(&self) -> u64 {
+        self.payload_bytes
+    }
+
+    /// Whether Payload-Oxum of `bag-info.txt` matches payloads, if the bag has one
+    pub fn oxum_matches(&self) -> Option<bool> {
+        self.oxum_matches
+    }
+
+    /// Whether the bag has at least one tag manifest
+    pub fn has_tag_manifest(&self) -> bool {
+        self.has_tag_manifest
+    }
+
+    /// Value of `BagIt-Profile-Identifier` tag of `bag-info.txt`, if present
+    pub fn profile_identifier(&self) -> Option<&str> {
+        self.profile_identifier.as_deref()
+    }
+
+    /// Unusual things found in the bag
+    pub fn warnings(&self) -> &[ReadWarning] {
+        &self.warnings
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Algorithms having manifests in a bag on disk
+///
+/// See [`BagIt::available_algorithms()`].
+pub struct AvailableAlgorithms {
+    payloads: BTreeSet<String>,
+    tags: BTreeSet<String>,
+}
+
+impl AvailableAlgorithms {
+    /// Names of algorithms having a payload manifest, in lowercase and sorted
+    pub fn payload_manifests(&self) -> impl Iterator<Item = &str> {
+        self.payloads.iter().map(String::as_str)
+    }
+
+    /// Names of algorithms having a tag manifest, in lowercase and sorted
+    pub fn tag_manifests(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(String::as_str)
+    }
+
+    /// Whether the bag has a payload manifest of `algorithm`
+    pub fn has_payload_manifest(&self, algorithm: &Algorithm) -> bool {
+        self.payloads
+            .contains(&algorithm.name().to_ascii_lowercase())
+    }
+
+    /// Whether the bag has a tag manifest of `algorithm`
+    pub fn has_tag_manifest(&self, algorithm: &Algorithm) -> bool {
+        self.tags.contains(&algorithm.name().to_ascii_lowercase())
+    }
+}
+
+impl BagIt<'_, '_> {
+    /// List algorithms of manifests and tag manifests of a bag on disk, without reading any of them
+    ///
+    /// Names of manifests are compared ignoring ASCII case.
+    /// Useful to pick the type computing digests before calling [`BagIt::read_existing()`],
+    /// see also [`BagIt::read_existing_auto()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let algorithms = BagIt::available_algorithms(bagit_directory).await?;
+    ///
+    /// assert!(algorithms.has_payload_manifest(&Algorithm::Sha512));
+    /// assert_eq!(
+    ///     algorithms.payload_manifests().collect::<Vec<_>>(),
+    ///     ["sha256", "sha512"]
+    /// );
+    /// assert_eq!(algorithms.tag_manifests().count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn available_algorithms(
+        bag_it_directory: impl AsRef<Path>,
+    ) -> Result<AvailableAlgorithms, ReadError> {
+        let mut algorithms = AvailableAlgorithms::default();
+        for (manifest, algorithm) in Manifest::find_all(bag_it_directory.as_ref()).await? {
+            match manifest.scope() {
+                ManifestScope::Payloads => algorithms.payloads.insert(algorithm),
+                ManifestScope::Tags => algorithms.tags.insert(algorithm),
+            };
+        }
+
+        Ok(algorithms)
+    }
+
+    /// Summarize a bag on disk, without verifying checksums of its files
+    ///
+    /// Only `bagit.txt`, `bag-info.txt` and manifests are read, payloads are only looked up on disk for their size.
+    /// This is cheap enough to be run regularly over large bag stores, for dashboards and monitoring.
+    /// Use [`BagIt::read_existing()`] for a full validation.
+    ///
+    /// Lines of the manifest that can't be parsed, or whose path leads outside of payload directory `data`, are rejected
+    /// with [`ReadError::ProcessManifestLine`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{BagIt, BagVersion};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let health = BagIt::health_check(bagit_directory).await?;
+    ///
+    /// assert_eq!(health.version(), BagVersion::V1_0);
+    /// assert_eq!(health.algorithms(), ["sha256", "sha512"]);
+    /// assert_eq!(health.payload_count(), 5);
+    /// assert_eq!(health.oxum_matches(), Some(true));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn health_check(bag_it_directory: impl AsRef<Path>) -> Result<BagHealth, ReadError> {
+        let bag_it_directory = bag_it_directory.as_ref();
+        let (version, encoding) = Self::read_declaration(bag_it_directory).await?;
+        let bag_info = Self::read_bag_info(bag_it_directory, encoding).await?;
+
+        // Look for all manifests in directory
+        let mut dir = fs::read_dir(bag_it_directory)
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind()))?;
+        let mut manifests = vec![];
+        let mut has_tag_manifest = false;
+        let mut warnings = vec![];
+        while let Some(entry) = dir
+            .next_entry()
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind()))?
+        {
+            let path = entry.path();
+            let file_name = entry.file_name();
+
+            if let Some(algorithm) = algorithm_name(&path, PREFIX_MANIFEST) {
+                if file_name.to_str() != Some(&format!("{PREFIX_MANIFEST}{algorithm}.txt")) {
+                    warnings.push(ReadWarning::ManifestNameCase(path.clone()));
+                }
+                manifests.push((algorithm, path));
+            } else if let Some(algorithm) = algorithm_name(&path, PREFIX_TAG_MANIFEST) {
+                if file_name.to_str() != Some(&format!("{PREFIX_TAG_MANIFEST}{algorithm}.txt")) {
+                    warnings.push(ReadWarning::ManifestNameCase(path));
+                }
+                has_tag_manifest = true;
+            }
+        }
+        manifests.sort();
+
+        // Count payloads of first manifest, without hashing them
+        let mut payload_count = 0;
+        let mut payload_bytes = 0;
+        if let Some((_, manifest)) = manifests.first() {
+            let file = fs::File::open(manifest)
+                .await
+                .map_err(|e| ReadError::OpenFile(manifest.clone(), e.kind()))?;
+            let mut file = BufReader::new(TagFileReader::new(file, encoding));
+            let mut line = String::new();
+
+            while let Some(line) = next_line(&mut file, &mut line)
+                .await
+                .map_err(|e| ReadError::ReadLine(manifest.clone(), e.kind()))?
+            {
+                let (_, relative_path) =
+                    parse_manifest_line(line, version).map_err(ReadError::ProcessManifestLine)?;
+                check_manifest_path(&relative_path, ManifestScope::Payloads)
+                    .map_err(ReadError::ProcessManifestLine)?;
+
+                payload_count += 1;
+                if let Ok(metadata) =
+                    fs::metadata(bag_it_directory.join(relative_path.as_ref())).await
+                {
+                    payload_bytes += metadata.len();
+                }
+            }
+        }
+
+        let oxum_matches = bag_info.as_ref().and_then(|bag_info| {
+            bag_info
+                .tags()
+                .any(|tag| matches!(tag, Metadata::PayloadOctetStreamSummary { .. }))
+                .then(|| Self::check_oxum(Some(bag_info), payload_count, payload_bytes).is_ok())
+        });
+
+        let profile_identifier = bag_info.and_then(|bag_info| {
+            bag_info.tags().find_map(|tag| match tag {
+                Metadata::Custom { key, value } if key == KEY_PROFILE_IDENTIFIER => {
+                    Some(value.clone())
+                }
+                _ => None,
+            })
+        });
+
+        Ok(BagHealth {
+            version,
+            algorithms: manifests
+                .into_iter()
+                .map(|(algorithm, _)| algorithm)
+                .collect(),
+            payload_count,
+            payload_bytes,
+            oxum_matches,
+            has_tag_manifest,
+            profile_identifier,
+            warnings,
+        })
+    }
+
+    /// Quick validation of a bag on disk: compare files of payload directory `data` with Payload-Oxum of `bag-info.txt`
+    ///
+    /// Only the number of files and their total size are compared, nothing is hashed and manifests are not read:
+    /// it is cheap enough for monitoring jobs to detect truncated or missing payloads. Payloads of `fetch.txt` not fetched yet
+    /// are missing from `data`, their bag does not match.
+    ///
+    /// A mismatch is reported with [`ReadError::BagInfoOxum`], and a bag without Payload-Oxum with [`ReadError::MissingOxum`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::BagIt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// BagIt::validate_oxum(bagit_directory).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_oxum(bag_it_directory: impl AsRef<Path>) -> Result<(), ReadError> {
+        let bag_it_directory = bag_it_directory.as_ref();
+        let (_, encoding) = Self::read_declaration(bag_it_directory).await?;
+        Self::check_payload_directory(bag_it_directory).await?;
+        let bag_info = Self::read_bag_info(bag_it_directory, encoding)
+            .await?
+            .filter(|bag_info| {
+                bag_info
+                    .tags()
+                    .any(|tag| matches!(tag, Metadata::PayloadOctetStreamSummary { .. }))
+            })
+            .ok_or(ReadError::MissingOxum)?;
+
+        let payload_directory = bag_it_directory.join("data");
+        let files = walk::list_files(&payload_directory)
+            .await
+            .map_err(|e| ReadError::ListPayloadFiles(payload_directory.clone(), e.kind()))?;
+        let mut payload_bytes = 0;
+        for file in &files {
+            let path = payload_directory.join(file);
+            payload_bytes += fs::metadata(&path)
+                .await
+                .map_err(|e| PayloadError::FileSize(path, e.kind()))?
+                .len();
+        }
+
+        Self::check_oxum(Some(&bag_info), files.len(), payload_bytes)
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use crate::{
+        error::{PayloadError, ReadError},
+        Algorithm, BagIt, BagVersion, ChecksumAlgorithm, ReadWarning,
+    };
+    use sha2::Sha256;
+
+    #[tokio::test]
+    async fn health_check() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Payload was changed, health check does not notice it
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bag-info.txt"),
+            "BagIt-Profile-Identifier: https://example.org/profile.json\nPayload-Oxum: 10421.2",
+        )
+        .await
+        .unwrap();
+        tokio::fs::rename(
+            temp_directory.join("tagmanifest-sha256.txt"),
+            temp_directory.join("TAGMANIFEST-SHA256.txt"),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(temp_directory.join("manifest-md5.txt"), "")
+            .await
+            .unwrap();
+
+        let health = BagIt::health_check(&temp_directory).await.unwrap();
+        assert_eq!(health.version(), BagVersion::V1_0);
+        assert_eq!(health.algorithms(), ["md5", "sha256"]);
+        assert_eq!(health.payload_count(), 0);
+        assert_eq!(health.oxum_matches(), Some(false));
+        assert!(health.has_tag_manifest());
+        assert_eq!(
+            health.profile_identifier(),
+            Some("https://example.org/profile.json")
+        );
+        assert_eq!(
+            health.warnings(),
+            [ReadWarning::ManifestNameCase(
+                temp_directory.join("TAGMANIFEST-SHA256.txt")
+            )]
+        );
+
+        let algorithms = BagIt::available_algorithms(&temp_directory).await.unwrap();
+        assert_eq!(
+            algorithms.payload_manifests().collect::<Vec<_>>(),
+            ["md5", "sha256"]
+        );
+        assert_eq!(algorithms.tag_manifests().collect::<Vec<_>>(), ["sha256"]);
+        assert!(algorithms.has_tag_manifest(&Algorithm::Sha256));
+        assert!(!algorithms.has_tag_manifest(&Algorithm::custom("md5")));
+
+        // Only sizes are compared with Oxum
+        tokio::fs::remove_file(temp_directory.join("manifest-md5.txt"))
+            .await
+            .unwrap();
+        let health = BagIt::health_check(&temp_directory).await.unwrap();
+        assert_eq!(health.payload_count(), 2);
+        assert_eq!(health.payload_bytes(), 10421);
+        assert_eq!(health.oxum_matches(), Some(true));
+
+        // Manifest lines are checked before looking at files on disk
+        let checksum = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            format!("{checksum}  data/../../outside.txt\n"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::health_check(&temp_directory).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ParentDirectory
+            ))
+        );
+        tokio::fs::write(temp_directory.join("manifest-sha256.txt"), "not a line\n")
+            .await
+            .unwrap();
+        assert!(matches!(
+            BagIt::health_check(&temp_directory).await,
+            Err(ReadError::ProcessManifestLine(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn validate_oxum() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+        assert_eq!(BagIt::validate_oxum(&temp_directory).await, Ok(()));
+
+        // Payload was changed without changing its size, it goes unnoticed
+        let contents = tokio::fs::read(temp_directory.join("data/sources.csv"))
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("data/sources.csv"),
+            vec![b'0'; contents.len()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(BagIt::validate_oxum(&temp_directory).await, Ok(()));
+
+        // Truncated payload
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::validate_oxum(&temp_directory).await,
+            Err(ReadError::BagInfoOxum("octet_count"))
+        );
+
+        // Missing payload
+        tokio::fs::remove_file(temp_directory.join("data/sources.csv"))
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::validate_oxum(&temp_directory).await,
+            Err(ReadError::BagInfoOxum("stream_count"))
+        );
+
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "")
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::validate_oxum(&temp_directory).await,
+            Err(ReadError::MissingOxum)
+        );
+    }
+}
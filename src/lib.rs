@@ -1,4 +1,3 @@
-#![feature(iter_next_chunk)]
 #![warn(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))] // https://stackoverflow.com/a/61417700/4809297
 
@@ -16,6 +15,20 @@ Here are some resources to get started with BagIt containers:
 
 For the integrity part of BagIt, any type implementing the `Digest` trait from the [`digest`](https://docs.rs/digest) crate can be used to compute hashes.
 
+# Cargo features
+
+- `read` (enabled by default): read and validate existing bags, see [`BagIt::read_existing()`]
+- `generate` (enabled by default): create bags, see [`BagIt::new_empty()`]
+- `date` (enabled by default): parse and write `Bagging-Date` tags with the [`jiff`](https://docs.rs/jiff) crate, or stamp the date of the day with `BagIt::add_bagging_date_now()`
+- `sha2`: ready to use SHA-256 and SHA-512 algorithms, see [`ChecksumAlgorithm::sha256()`]
+- `sha2-asm`: assembly implementation of SHA-2 algorithms, requires a C compiler, compare it with benchmark `hashing` of the repository
+- `codec`: [`tokio_util`](https://docs.rs/tokio-util) codecs for manifests and tag files, see [`codec`]
+- `fetch`: download payloads listed in `fetch.txt` over HTTP with [`reqwest`](https://docs.rs/reqwest), see [`BagIt::complete()`]
+- `zstd`: store payloads compressed with Zstandard, see [`BagIt::add_file_compressed()`]
+- `crc32c`: non-cryptographic CRC32C checksums for transfer integrity, see [`fast`]
+- `xxhash`: non-cryptographic XXH3 checksums for transfer integrity, see [`fast`]
+- `reflink`: clone files into bags on filesystems supporting reflinks with [`reflink-copy`](https://docs.rs/reflink-copy), see [`AddMode`]
+
 ## Load existing bag
 
 ```no_run
@@ -25,7 +38,7 @@ use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
 # async fn main() -> Result<(), Box<dyn std::error::Error>> {
 // Specify the algorithm to use for checksums
 type AlgorithmToUse = blake3::Hasher;
-let algorithm = ChecksumAlgorithm::<AlgorithmToUse>::new(Algorithm::Custom("blake3"));
+let algorithm = ChecksumAlgorithm::<AlgorithmToUse>::new(Algorithm::custom("blake3"));
 
 // Where is the bag on the filesystem?
 let bag_directory = "/somewhere/where/the/bag/will/be/placed";
@@ -50,7 +63,7 @@ use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
 # async fn main() -> Result<(), Box<dyn std::error::Error>> {
 // Specify the algorithm to use for checksums
 type AlgorithmToUse = blake3::Hasher;
-let algorithm = ChecksumAlgorithm::<AlgorithmToUse>::new(Algorithm::Custom("blake3"));
+let algorithm = ChecksumAlgorithm::<AlgorithmToUse>::new(Algorithm::custom("blake3"));
 
 // Where the payloads and bag metadata will be placed
 let bag_directory = "/somewhere/where/the/bag/will/be/placed";
@@ -84,60 +97,366 @@ bag.finalize::<AlgorithmToUse>().await.unwrap();
 */
 
 mod algorithm;
-mod checksum;
+mod cache;
+pub mod checksum;
+#[cfg(feature = "codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+pub mod codec;
+#[cfg(feature = "read")]
+mod completeness;
+#[cfg(feature = "zstd")]
+mod compression;
+#[cfg(feature = "read")]
+mod cross;
+mod dynamic;
+mod encoding;
+#[cfg(any(feature = "crc32c", feature = "xxhash"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "crc32c", feature = "xxhash"))))]
+pub mod fast;
+mod fetch;
+#[cfg(feature = "generate")]
 mod generate;
+#[cfg(feature = "read")]
+mod group;
+#[cfg(feature = "generate")]
+mod guard;
+#[cfg(feature = "read")]
+mod health;
+mod long_path;
 mod manifest;
 mod metadata;
 mod payload;
+#[cfg(feature = "read")]
+mod progress;
+#[cfg(feature = "read")]
 mod read;
+#[cfg(feature = "read")]
+mod report;
+#[cfg(feature = "generate")]
+mod typed;
+mod validation;
+mod version;
+#[cfg(any(feature = "read", feature = "generate"))]
+mod walk;
 
 /// Possible errors when manipulating BagIt containers
 pub mod error {
-    pub use crate::checksum::ChecksumComputeError;
+    pub use crate::algorithm::AlgorithmParseError;
+    pub use crate::checksum::{ChecksumComputeError, ChecksumParseError};
+    #[cfg(feature = "codec")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+    pub use crate::codec::CodecError;
+    #[cfg(feature = "read")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+    pub use crate::completeness::CompletenessError;
+    #[cfg(feature = "fetch")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fetch")))]
+    pub use crate::fetch::FetchError;
+    #[cfg(feature = "generate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
     pub use crate::generate::GenerateError;
+    #[cfg(feature = "read")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+    pub use crate::group::GroupError;
+    pub use crate::metadata::MetadataError;
     pub use crate::payload::PayloadError;
+    #[cfg(feature = "read")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
     pub use crate::read::ReadError;
+
+    #[derive(thiserror::Error, Debug, PartialEq)]
+    /// Any error of this crate
+    ///
+    /// Every error converts into it, for applications that do not need to tell them apart.
+    /// The wrapped error is kept as is: `Display` and `source()` are forwarded to it, match on the variant to get the wrapped error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{error::BagItError, Algorithm, BagIt, ChecksumAlgorithm};
+    /// async fn rebag(source: &str, destination: &str) -> Result<(), BagItError> {
+    ///     let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    ///
+    ///     let source = BagIt::read_existing(source, &algorithm).await?;
+    ///     let mut destination = BagIt::new_empty(destination, &algorithm);
+    ///     for payload in source.payload_items() {
+    ///         payload.verify::<sha2::Sha256>().await?;
+    ///         destination.add_file::<sha2::Sha256>(payload.absolute_path()).await?;
+    ///     }
+    ///     destination.finalize::<sha2::Sha256>().await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub enum BagItError {
+        /// See [`ReadError`]
+        #[cfg(feature = "read")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+        #[error(transparent)]
+        Read(#[from] ReadError),
+        /// See [`GenerateError`]
+        #[cfg(feature = "generate")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+        #[error(transparent)]
+        Generate(#[from] GenerateError),
+        /// See [`PayloadError`]
+        #[error(transparent)]
+        Payload(#[from] PayloadError),
+        /// See [`ChecksumComputeError`]
+        #[error(transparent)]
+        ChecksumCompute(#[from] ChecksumComputeError),
+        /// See [`ChecksumParseError`]
+        #[error(transparent)]
+        ChecksumParse(#[from] ChecksumParseError),
+        /// See [`AlgorithmParseError`]
+        #[error(transparent)]
+        AlgorithmParse(#[from] AlgorithmParseError),
+        /// See [`CodecError`]
+        #[cfg(feature = "codec")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+        #[error(transparent)]
+        Codec(#[from] CodecError),
+        /// See [`FetchError`]
+        #[cfg(feature = "fetch")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "fetch")))]
+        #[error(transparent)]
+        Fetch(#[from] FetchError),
+        /// See [`MetadataError`]
+        #[error(transparent)]
+        Metadata(#[from] MetadataError),
+        /// See [`GroupError`]
+        #[cfg(feature = "read")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+        #[error(transparent)]
+        Group(#[from] GroupError),
+        /// See [`CompletenessError`]
+        #[cfg(feature = "read")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+        #[error(transparent)]
+        Completeness(#[from] CompletenessError),
+    }
 }
 
-pub use algorithm::{Algorithm, ChecksumAlgorithm};
+pub use algorithm::{Algorithm, ChecksumAlgorithm, ManifestKind};
+pub use cache::{CacheKey, CacheMode, ChecksumCache, FileChecksumCache};
 pub use checksum::Checksum;
-use metadata::Metadata;
-pub use payload::Payload;
-
-#[derive(Debug, PartialEq)]
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use cross::{AlgorithmValidation, CrossValidation};
+#[cfg(feature = "generate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+pub use dynamic::DynBagIt;
+pub use dynamic::{AlgorithmRegistry, DynChecksumAlgorithm};
+pub use encoding::TagEncoding;
+pub use fetch::FetchItem;
+use futures::StreamExt;
+#[cfg(feature = "generate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+pub use generate::{
+    AddMode, BagItBuilder, CollisionPolicy, FinalizeOptions, FinalizePreview, GeneratedTag, TagFile,
+};
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use group::BagGroup;
+#[cfg(feature = "generate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+pub use guard::BagItGuard;
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use health::{AvailableAlgorithms, BagHealth};
+pub use metadata::Metadata;
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use payload::PathNormalization;
+pub use payload::{Payload, PayloadReader, PayloadStatus, SymlinkPolicy};
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use progress::ReadProgress;
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use read::{ReadOptions, ReadWarning};
+#[cfg(feature = "read")]
+#[cfg_attr(docsrs, doc(cfg(feature = "read")))]
+pub use report::ValidationReport;
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+pub use sha2;
+#[cfg(feature = "generate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+pub use typed::TypedBagIt;
+pub use validation::{ValidationOutcome, ValidationStatus};
+pub use version::BagVersion;
+
+#[derive(Debug)]
 /// BagIt container: A set of opaque files contained within the structure defined by RFC 8493 <https://datatracker.ietf.org/doc/html/rfc8493>
 ///
-/// This struct represents valid and complete bags opened with [`BagIt::read_existing()`],
+/// This struct represents valid bags opened with [`BagIt::read_existing()`], whose payloads may still have to be fetched,
 /// or incomplete bags in the process of adding files.
 ///
-/// See [`BagIt::new_empty()`] and [`BagIt::add_file()`].
+/// See [`BagIt::new_empty()`], [`BagItBuilder`] and [`BagIt::add_file()`], or [`TypedBagIt`] to choose the digest only once.
 pub struct BagIt<'a, 'algo> {
-    /// Location of the bag
-    path: std::path::PathBuf,
+    /// Location of the bag, shared with its payloads
+    path: std::sync::Arc<std::path::Path>,
+
+    /// Version of the spec declared in `bagit.txt`
+    version: BagVersion,
 
     /// What's in my bag
     items: Vec<Payload<'a>>,
 
+    /// Total size of items in bytes, kept up to date as items are added
+    octet_count: u64,
+
     /// Which algorithm to use for checksums of the items
     checksum_algorithm: &'algo Algorithm,
 
     /// Metadata tags
     tags: Vec<Metadata>,
+
+    /// Supplementary tag files, relative to the bag directory
+    tag_files: Vec<Box<std::path::Path>>,
+
+    /// Tag files listed in the tag manifest, with their checksums
+    tag_items: Vec<Payload<'a>>,
+
+    /// Payloads to fetch from remote locations, listed in `fetch.txt`
+    fetch_items: Vec<FetchItem>,
+
+    /// Outcome of the last validation, if any
+    validation: Option<ValidationStatus>,
+
+    /// Unusual things found when reading the bag
+    #[cfg(feature = "read")]
+    warnings: Vec<ReadWarning>,
+
+    /// Manifests of other algorithms written when finalizing the bag
+    #[cfg(feature = "generate")]
+    additional_manifests: Vec<generate::AdditionalManifest<'algo>>,
+
+    /// Algorithms registered when reading the bag, to update tag manifests of other algorithms
+    #[cfg(feature = "generate")]
+    known_algorithms: Vec<(Algorithm, algorithm::NewHasher)>,
+
+    /// What to do with symbolic links when adding files
+    #[cfg(feature = "generate")]
+    symlinks: SymlinkPolicy,
+
+    /// How files are brought into payload directory when adding them
+    #[cfg(feature = "generate")]
+    add_mode: AddMode,
+
+    /// What to do when adding a payload where the bag already has a file
+    #[cfg(feature = "generate")]
+    collisions: CollisionPolicy,
+
+    /// Symbolic links that were not added, see [`SymlinkPolicy::SkipWithWarning`]
+    #[cfg(feature = "generate")]
+    skipped_symlinks: Vec<std::path::PathBuf>,
+
+    /// Manifests of other algorithms of a bag opened for update, removed when finalizing
+    #[cfg(feature = "generate")]
+    stale_manifests: Vec<std::path::PathBuf>,
+
+    /// Manifests as last written, to append payloads added since when finalizing again
+    #[cfg(feature = "generate")]
+    written_manifests: Option<generate::WrittenManifests>,
+
+    /// Files created by the bag in its directory, removed when abandoning it
+    #[cfg(feature = "generate")]
+    created_files: Vec<std::path::PathBuf>,
+}
+
+/// Bags are compared by their contents, regardless of when and how they were validated
+impl PartialEq for BagIt<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            path,
+            version,
+            items,
+            octet_count,
+            checksum_algorithm,
+            tags,
+            tag_files,
+            tag_items: _,
+            fetch_items,
+            validation: _,
+            #[cfg(feature = "read")]
+                warnings: _,
+            #[cfg(feature = "generate")]
+                additional_manifests: _,
+            #[cfg(feature = "generate")]
+                known_algorithms: _,
+            #[cfg(feature = "generate")]
+                symlinks: _,
+            #[cfg(feature = "generate")]
+                add_mode: _,
+            #[cfg(feature = "generate")]
+                collisions: _,
+            #[cfg(feature = "generate")]
+                skipped_symlinks: _,
+            #[cfg(feature = "generate")]
+                stale_manifests: _,
+            #[cfg(feature = "generate")]
+                written_manifests: _,
+            #[cfg(feature = "generate")]
+                created_files: _,
+        } = self;
+
+        *path == other.path
+            && *version == other.version
+            && *items == other.items
+            && *octet_count == other.octet_count
+            && *checksum_algorithm == other.checksum_algorithm
+            && *tags == other.tags
+            && *tag_files == other.tag_files
+            && *fetch_items == other.fetch_items
+    }
 }
 
 impl<'a, 'algo> BagIt<'a, 'algo> {
-    #[cfg(test)]
+    #[cfg(all(test, feature = "read"))]
     pub(crate) fn from_existing_items(
         directory: impl AsRef<std::path::Path>,
         items: Vec<Payload<'a>>,
         checksum_algorithm: &'algo Algorithm,
         tags: Vec<Metadata>,
     ) -> Result<Self, error::ReadError> {
+        let path: std::sync::Arc<std::path::Path> = directory.as_ref().into();
+        let items = items
+            .into_iter()
+            .map(|payload| payload.with_bag_directory(path.clone()))
+            .collect::<Vec<_>>();
+
         Ok(Self {
-            path: directory.as_ref().to_path_buf(),
+            path,
+            version: BagVersion::V1_0,
+            octet_count: items.iter().map(|payload| payload.bytes()).sum(),
             items,
             checksum_algorithm,
             tags,
+            tag_files: vec![],
+            tag_items: vec![],
+            fetch_items: vec![],
+            validation: None,
+            warnings: vec![],
+            #[cfg(feature = "generate")]
+            additional_manifests: vec![],
+            #[cfg(feature = "generate")]
+            known_algorithms: vec![],
+            #[cfg(feature = "generate")]
+            symlinks: SymlinkPolicy::Follow,
+            #[cfg(feature = "generate")]
+            add_mode: AddMode::Copy,
+            #[cfg(feature = "generate")]
+            collisions: CollisionPolicy::Overwrite,
+            #[cfg(feature = "generate")]
+            skipped_symlinks: vec![],
+            #[cfg(feature = "generate")]
+            stale_manifests: vec![],
+            #[cfg(feature = "generate")]
+            written_manifests: None,
+            #[cfg(feature = "generate")]
+            created_files: vec![],
         })
     }
 
@@ -146,6 +465,44 @@ impl<'a, 'algo> BagIt<'a, 'algo> {
         &self.path
     }
 
+    /// Algorithm of checksums of the bag, the one of its manifest and tag manifest
+    pub fn algorithm(&self) -> &Algorithm {
+        self.checksum_algorithm
+    }
+
+    /// Version of the BagIt specification declared by the bag
+    ///
+    /// New bags are created with version 1.0, see [`BagIt::set_version()`] to choose another one.
+    pub fn version(&self) -> BagVersion {
+        self.version
+    }
+
+    /// Tags of `bag-info.txt`
+    ///
+    /// Tags derived from payloads, such as `Payload-Oxum`, are only up to date once the bag is read or finalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, Metadata};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let bag = BagIt::read_existing(bagit_directory, &algorithm).await?;
+    ///
+    /// let oxum = bag
+    ///     .tags()
+    ///     .find(|tag| matches!(tag, Metadata::PayloadOctetStreamSummary { .. }));
+    /// assert_eq!(oxum.unwrap().value(), "85766.5");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tags(&self) -> impl Iterator<Item = &Metadata> {
+        self.tags.iter()
+    }
+
     /// Iterator over payloads inside the bag
     ///
     /// # Examples
@@ -163,25 +520,26 @@ impl<'a, 'algo> BagIt<'a, 'algo> {
     /// // Get the absolute paths of all payloads in this bag
     /// let absolute_paths: Vec<std::path::PathBuf> = bag
     ///     .payload_items()
-    ///     .map(|payload| payload.absolute_path(&bag))
+    ///     .map(|payload| payload.absolute_path())
     ///     .collect();
     ///
     /// // Find a payload by its filename
     /// let my_totebag = bag
     ///     .payload_items()
-    ///     .find(|payload| {
-    ///         payload
-    ///             .relative_path()
-    ///             .file_name()
-    ///             .and_then(|file_name| file_name.to_str())
-    ///             == Some("totebag.jpg")
-    ///     });
+    ///     .find(|payload| payload.file_name() == Some("totebag.jpg"));
     /// assert!(my_totebag.is_some());
     ///
+    /// // Get payloads in the payload directory, including its subdirectories
+    /// let number_payloads_in_data = bag
+    ///     .payload_items()
+    ///     .filter(|payload| payload.is_under("data"))
+    ///     .count();
+    /// # assert_eq!(number_payloads_in_data, 5);
+    ///
     /// // Get unique number of file extensions in the bag
     /// let number_file_extensions = bag
     ///     .payload_items()
-    ///     .filter_map(|item| item.relative_path().extension())
+    ///     .filter_map(|item| item.extension())
     ///     .collect::<std::collections::HashSet<_>>()
     ///     .len();
     /// # assert_eq!(number_file_extensions, 4);
@@ -189,24 +547,187 @@ impl<'a, 'algo> BagIt<'a, 'algo> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn payload_items(&self) -> impl Iterator<Item = &Payload> {
+    pub fn payload_items(&self) -> impl Iterator<Item = &Payload<'_>> {
         self.items.iter()
     }
 
+    /// Iterator over tag files listed in the tag manifest, with their checksums
+    ///
+    /// Checksums were verified when the bag was read, or computed when it was finalized.
+    /// Empty if the bag has no tag manifest.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let bag = BagIt::read_existing("/path/to/bag", &algorithm).await?;
+    ///
+    /// // Record fixity of tag files along with payloads
+    /// for item in bag.tag_manifest_items().chain(bag.payload_items()) {
+    ///     println!("{} {}", item.relative_path().display(), item.checksum());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tag_manifest_items(&self) -> impl Iterator<Item = &Payload<'_>> {
+        self.tag_items.iter()
+    }
+
+    /// Iterator over supplementary tag files of the bag, relative to [`BagIt::path()`]
+    ///
+    /// These are files outside of payload directory `data` and not reserved by the spec, like `metadata/marc.xml`
+    /// or `DPN-info.txt`: found in the bag when it was read, or added with [`BagIt::add_tag_file()`],
+    /// [`BagIt::add_tag_directory()`] and sidecars of payloads.
+    /// They are listed in the tag manifest when finalizing the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let bag = BagIt::read_existing("/path/to/bag", &algorithm).await?;
+    ///
+    /// for tag_file in bag.tag_files() {
+    ///     println!("{}", tag_file.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tag_files(&self) -> impl Iterator<Item = &std::path::Path> {
+        self.tag_files.iter().map(|tag_file| &**tag_file)
+    }
+
+    /// Iterator over payloads to fetch from remote locations, listed in `fetch.txt`
+    ///
+    /// Empty if the bag has no `fetch.txt`. Payloads already fetched are also in [`BagIt::payload_items()`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let bag = BagIt::read_existing("/path/to/bag", &algorithm).await?;
+    ///
+    /// if !bag.is_complete() {
+    ///     for item in bag.pending_fetch_items() {
+    ///         println!("{} -> {}", item.url(), item.relative_path().display());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_items(&self) -> impl Iterator<Item = &FetchItem> {
+        self.fetch_items.iter()
+    }
+
+    /// Iterator over payloads of `fetch.txt` whose files are not in the bag yet
+    pub fn pending_fetch_items(&self) -> impl Iterator<Item = &FetchItem> {
+        self.fetch_items.iter().filter(|item| {
+            !self
+                .items
+                .iter()
+                .any(|payload| payload.relative_path() == item.relative_path())
+        })
+    }
+
+    /// Whether all payloads are in the bag, with nothing left to fetch, see [`BagIt::fetch_items()`]
+    pub fn is_complete(&self) -> bool {
+        self.pending_fetch_items().next().is_none()
+    }
+
+    /// Consume the bag, and get its payloads
+    ///
+    /// Payloads are owned and keep the directory of the bag: they can be handed over to other parts of an application,
+    /// see [`Payload::absolute_path()`] and [`Payload::open()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, Payload};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let bag = BagIt::read_existing(bagit_directory, &algorithm).await?;
+    ///
+    /// // Inventory outliving the bag
+    /// let inventory: Vec<Payload<'static>> = bag.into_payloads().collect();
+    /// assert!(inventory.iter().all(|payload| payload.absolute_path().is_file()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_payloads(self) -> impl Iterator<Item = Payload<'static>> + 'a {
+        self.items.into_iter().map(Payload::into_owned)
+    }
+
+    /// Stream over payloads inside the bag, with metadata of their files currently on disk
+    ///
+    /// File metadata is fetched asynchronously, one payload at a time, when the stream is polled.
+    /// A missing file is reported by [`PayloadStatus::exists()`], other failures to get metadata of a file are yielded as errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// use futures::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let bag = BagIt::read_existing(bagit_directory, &algorithm).await.unwrap();
+    ///
+    /// // Find payloads whose file changed size since the bag was opened
+    /// let changed = bag
+    ///     .payload_stream()
+    ///     .filter(|status| {
+    ///         std::future::ready(match status {
+    ///             Ok(status) => status.bytes() != Some(status.payload().bytes()),
+    ///             Err(_) => true,
+    ///         })
+    ///     })
+    ///     .count()
+    ///     .await;
+    /// assert_eq!(changed, 0);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn payload_stream(
+        &self,
+    ) -> impl futures::Stream<Item = Result<PayloadStatus<'_>, error::PayloadError>> {
+        futures::stream::iter(self.items.iter()).then(PayloadStatus::gather)
+    }
+
+    #[cfg(feature = "generate")]
     fn manifest_name(&self) -> String {
-        format!("manifest-{}.txt", self.checksum_algorithm)
+        generate::manifest_name(self.checksum_algorithm)
     }
 
+    #[cfg(feature = "generate")]
     fn tagmanifest_name(&self) -> String {
-        format!("tagmanifest-{}.txt", self.checksum_algorithm)
+        generate::tagmanifest_name(self.checksum_algorithm)
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "generate"))]
 mod test {
-    use crate::{metadata::Metadata, Algorithm, BagIt, ChecksumAlgorithm, Payload};
+    use crate::{error::PayloadError, Algorithm, BagIt, Checksum, ChecksumAlgorithm};
+    #[cfg(feature = "read")]
+    use crate::{metadata::Metadata, Payload};
+    use futures::StreamExt;
     use sha2::Sha256;
 
+    #[cfg(feature = "read")]
     #[tokio::test]
     async fn generate_and_read_basic_bag_sha256() {
         let temp_directory = async_tempfile::TempDir::new().await.unwrap();
@@ -282,4 +803,123 @@ mod test {
             assert_eq!(bag, expected);
         }
     }
+
+    #[tokio::test]
+    async fn payload_stream_live_metadata() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["paper_bag.jpg", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+
+        // Remove one payload behind the bag's back
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+
+        let statuses = bag
+            .payload_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(statuses.len(), 2);
+
+        let paper_bag = &statuses[0];
+        assert_eq!(
+            paper_bag.payload().relative_path().to_str(),
+            Some("data/paper_bag.jpg")
+        );
+        assert!(paper_bag.exists());
+        assert_eq!(paper_bag.bytes(), Some(19895));
+        assert!(paper_bag.modified().is_some());
+
+        let totebag = &statuses[1];
+        assert_eq!(
+            totebag.payload().relative_path().to_str(),
+            Some("data/totebag.jpg")
+        );
+        assert!(!totebag.exists());
+        assert_eq!(totebag.bytes(), None);
+        assert_eq!(totebag.modified(), None);
+    }
+
+    #[tokio::test]
+    async fn payload_stream_metadata_error() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_file.push("tests/sample-bag/data/totebag.jpg");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(&source_file, "data/bags/totebag.jpg")
+            .await
+            .unwrap();
+
+        // Parent directory of payload is replaced by a file: this is not a missing file
+        tokio::fs::remove_dir_all(temp_directory.join("data/bags"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/bags"), b"not a directory")
+            .await
+            .unwrap();
+
+        let statuses = bag.payload_stream().collect::<Vec<_>>().await;
+        assert_eq!(statuses.len(), 1);
+        assert!(matches!(
+            &statuses[0],
+            Err(PayloadError::FileSize(path, kind))
+                if path == &temp_directory.join("data/bags/totebag.jpg")
+                    && *kind != std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[tokio::test]
+    async fn payload_verify_without_bag() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+
+        let payload = bag.payload_items().next().unwrap();
+        assert_eq!(
+            payload.absolute_path(),
+            temp_directory.join("data/sources.csv")
+        );
+        assert_eq!(payload.verify::<Sha256>().await, Ok(()));
+
+        // Tamper with file
+        tokio::fs::write(payload.absolute_path(), "not,the,same\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            payload.verify::<Sha256>().await,
+            Err(PayloadError::ChecksumDiffers {
+                path: "data/sources.csv".into(),
+                expected: payload.checksum().clone().into_owned(),
+                actual: Checksum::digest::<Sha256>(b"not,the,same\n".to_vec()),
+            })
+        );
+    }
 }
@@ -0,0 +1,70 @@
+//! Paths longer than 260 characters on Windows
+//!
+//! Windows APIs limit paths to 260 characters, unless they are given in their extended-length form prefixed with `\\?\`.
+//! Deep trees of payloads reach this limit quickly, paths are converted right before calling the filesystem.
+//! Nothing is changed on other platforms.
+
+use std::{borrow::Cow, path::Path};
+
+/// Extended-length form of `path`, made absolute
+#[cfg(windows)]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    // Extended-length paths are not normalized by Windows, this has to be done beforehand
+    let Ok(absolute) = std::path::absolute(path) else {
+        return Cow::Borrowed(path);
+    };
+
+    let Some(Component::Prefix(prefix)) = absolute.components().next() else {
+        return Cow::Owned(absolute);
+    };
+    let mut extended = match prefix.kind() {
+        // `C:\dir` becomes `\\?\C:\dir`
+        Prefix::Disk(_) => OsString::from(r"\\?\"),
+        // `\\server\share\dir` becomes `\\?\UNC\server\share\dir`
+        Prefix::UNC(..) => {
+            let mut extended = OsString::from(r"\\?\UNC");
+            extended.push(&absolute.as_os_str().to_string_lossy()[1..]);
+            return Cow::Owned(extended.into());
+        }
+        // Already in extended-length form, or a device
+        _ => return Cow::Owned(absolute),
+    };
+    extended.push(absolute.as_os_str());
+
+    Cow::Owned(extended.into())
+}
+
+/// Extended-length form of `path`, made absolute
+#[cfg(not(windows))]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+#[cfg(all(test, windows))]
+mod test {
+    use super::extended;
+    use std::path::Path;
+
+    #[test]
+    fn extended_length() {
+        assert_eq!(
+            extended(Path::new(r"C:\bags\my-bag\data")),
+            Path::new(r"\\?\C:\bags\my-bag\data")
+        );
+        assert_eq!(
+            extended(Path::new(r"C:\bags\my-bag/data\..\bagit.txt")),
+            Path::new(r"\\?\C:\bags\my-bag\bagit.txt")
+        );
+        assert_eq!(
+            extended(Path::new(r"\\server\share\my-bag")),
+            Path::new(r"\\?\UNC\server\share\my-bag")
+        );
+        assert_eq!(
+            extended(Path::new(r"\\?\C:\bags")),
+            Path::new(r"\\?\C:\bags")
+        );
+    }
+}
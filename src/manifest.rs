@@ -1,100 +1,1009 @@
-use crate::ChecksumAlgorithm;
-use crate::{error::ReadError, Payload};
+#[cfg(feature = "read")]
+use crate::{
+    algorithm::NewHasher,
+    cache::SharedCache,
+    checksum::{
+        hash_file, hash_file_multi_progress, hash_file_progress, hash_file_with,
+        ChecksumComputeError,
+    },
+    encoding::{TagEncoding, TagFileReader},
+    error::ReadError,
+    payload::{parse_manifest_line, ManifestScope, PathResolution, PayloadError, SymlinkPolicy},
+    progress::Observer,
+    Algorithm, BagVersion, CacheKey, Checksum, Payload, ReadWarning,
+};
+#[cfg(feature = "read")]
 use digest::Digest;
-use std::path::{Path, PathBuf};
-use tokio::fs;
-use tokio::io::{AsyncBufReadExt, BufReader};
+#[cfg(feature = "read")]
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::path::Path;
+#[cfg(feature = "read")]
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, RandomState},
+    path::PathBuf,
+    sync::Arc,
+};
+#[cfg(feature = "read")]
+use tokio::{
+    fs,
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader},
+};
 
 #[derive(Debug)]
+#[cfg(feature = "read")]
 pub(crate) struct Manifest(PathBuf);
 
+#[cfg(feature = "read")]
+impl From<Manifest> for PathBuf {
+    fn from(value: Manifest) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "read")]
 impl AsRef<Path> for Manifest {
     fn as_ref(&self) -> &Path {
         &self.0
     }
 }
 
+pub(crate) const PREFIX_MANIFEST: &str = "manifest-";
+pub(crate) const PREFIX_TAG_MANIFEST: &str = "tagmanifest-";
+
+/// How the name of a file matched the one of a manifest
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg(feature = "read")]
+enum NameMatch {
+    Exact,
+    IgnoringCase,
+}
+
+/// Name of algorithm of a manifest or tag manifest, from the name of its file
+///
+/// Names are compared ignoring ASCII case, the algorithm is returned in lowercase.
+pub(crate) fn algorithm_name(path: &Path, manifest_prefix: &str) -> Option<String> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+
+    name.strip_prefix(manifest_prefix)?
+        .strip_suffix(".txt")
+        .filter(|algorithm| !algorithm.is_empty())
+        .map(str::to_string)
+}
+
+/// Settings of a read shared by every manifest of the bag, built once from [`ReadOptions`](crate::ReadOptions)
+#[derive(Clone)]
+#[cfg(feature = "read")]
+pub(crate) struct ReadContext {
+    pub bag_it_directory: Arc<Path>,
+    pub encoding: TagEncoding,
+    pub version: BagVersion,
+    pub resolution: PathResolution,
+    /// Payloads listed in `fetch.txt`, skipped when their file is not in the bag yet
+    pub remote_paths: Arc<[PathBuf]>,
+    /// Observer of files being hashed
+    pub progress: Option<Observer>,
+    /// Where checksums of the requested algorithm are looked up before hashing files
+    pub cache: Option<(SharedCache, Algorithm)>,
+    /// Maximum number of files hashed at the same time
+    pub concurrency: usize,
+}
+
+#[cfg(feature = "read")]
+impl ReadContext {
+    /// Whether payload of manifest `line` is listed in `fetch.txt`, and its file is not in the bag yet
+    async fn is_unfetched(&self, line: &str) -> bool {
+        is_unfetched(
+            line,
+            self.version,
+            self.resolution,
+            &self.bag_it_directory,
+            &self.remote_paths,
+        )
+        .await
+    }
+}
+
+/// Paths read so far from a manifest, to reject one listed twice in the same pass that validates payloads
+///
+/// Only a hash of each path is kept: when a hash comes up again, lines read before are read once more
+/// to tell a path listed twice from two paths with the same hash.
+#[cfg(feature = "read")]
+pub(crate) struct SeenPaths<S = RandomState> {
+    manifest: PathBuf,
+    hasher: S,
+    hashes: HashSet<u64>,
+    lines: usize,
+}
+
+#[cfg(feature = "read")]
+impl SeenPaths {
+    pub fn new(manifest: &Path) -> Self {
+        Self::with_hasher(manifest, RandomState::new())
+    }
+}
+
+#[cfg(feature = "read")]
+impl<S: BuildHasher> SeenPaths<S> {
+    fn with_hasher(manifest: &Path, hasher: S) -> Self {
+        Self {
+            manifest: manifest.to_path_buf(),
+            hasher,
+            hashes: HashSet::new(),
+            lines: 0,
+        }
+    }
+
+    /// Record path of manifest `line`, read right after the lines recorded before
+    ///
+    /// Paths are compared once resolved with `context`, even with different checksums.
+    /// Lines that can't be parsed are left for validation to report.
+    pub async fn check(&mut self, line: &str, context: &ReadContext) -> Result<(), ReadError> {
+        let position = self.lines;
+        self.lines += 1;
+
+        let Ok((_, relative_path)) = parse_manifest_line(line, context.version) else {
+            return Ok(());
+        };
+        let relative_path = context.resolution.apply(relative_path);
+        if self.hashes.insert(self.hasher.hash_one(&*relative_path)) {
+            return Ok(());
+        }
+
+        // Same hash as a path read before, most likely the same path
+        if self
+            .listed_before(position, &relative_path, context)
+            .await?
+        {
+            return Err(ReadError::DuplicateManifestEntry(PathBuf::from(
+                relative_path.as_ref(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether `relative_path` is listed in the first `lines` lines of manifest
+    async fn listed_before(
+        &self,
+        lines: usize,
+        relative_path: &str,
+        context: &ReadContext,
+    ) -> Result<bool, ReadError> {
+        let checksum_file = fs::File::open(&self.manifest)
+            .await
+            .map_err(|e| ReadError::OpenFile(self.manifest.clone(), e.kind()))?;
+        let mut checksum_file = BufReader::new(TagFileReader::new(checksum_file, context.encoding));
+
+        let mut line = String::new();
+        for _ in 0..lines {
+            let Some(line) = next_line(&mut checksum_file, &mut line)
+                .await
+                .map_err(|e| ReadError::ReadLine(self.manifest.clone(), e.kind()))?
+            else {
+                break;
+            };
+            if let Ok((_, listed)) = parse_manifest_line(line, context.version) {
+                if context.resolution.apply(listed) == relative_path {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(feature = "read")]
 impl Manifest {
-    pub async fn find_manifest<ChecksumAlgo: Digest>(
-        files_in_directory: &[impl AsRef<Path>],
-        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
-    ) -> Result<Option<Self>, ReadError> {
-        Self::find(files_in_directory, checksum_algorithm, "manifest-").await
+    /// Where paths listed in the manifest must lead to, from the name of its file
+    pub fn scope(&self) -> ManifestScope {
+        match algorithm_name(&self.0, PREFIX_MANIFEST) {
+            Some(_) => ManifestScope::Payloads,
+            None => ManifestScope::Tags,
+        }
     }
 
-    pub async fn find_tag_manifest<ChecksumAlgo: Digest>(
-        files_in_directory: &[impl AsRef<Path>],
-        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
-    ) -> Result<Option<Self>, ReadError> {
-        Self::find(files_in_directory, checksum_algorithm, "tagmanifest-").await
+    /// Walk entries of the bag directory, looking for the manifest and tag manifest of requested checksum algorithm
+    ///
+    /// Names are compared ignoring ASCII case, for bags coming from case-insensitive filesystems:
+    /// a warning is pushed to `warnings` when a manifest is found that way.
+    /// Entries are processed as they are listed, the walk stops as soon as both manifests are found with their exact names.
+    pub async fn find_manifests(
+        bag_it_directory: impl AsRef<Path>,
+        algorithm: &Algorithm,
+        warnings: &mut Vec<ReadWarning>,
+    ) -> Result<(Option<Self>, Option<Self>), ReadError> {
+        let bag_it_directory = bag_it_directory.as_ref();
+        let mut dir = fs::read_dir(bag_it_directory)
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind()))?;
+
+        let mut manifest: Option<(Self, NameMatch)> = None;
+        let mut tag_manifest: Option<(Self, NameMatch)> = None;
+        let found_exact =
+            |found: &Option<(Self, NameMatch)>| matches!(found, Some((_, NameMatch::Exact)));
+
+        while !found_exact(&manifest) || !found_exact(&tag_manifest) {
+            let Some(entry) = dir.next_entry().await.map_err(|e| {
+                ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind())
+            })?
+            else {
+                break;
+            };
+            let path = entry.path();
+
+            // A file with exact name is preferred over one whose name only differs by case
+            if !found_exact(&manifest) {
+                if let Some(name_match) = Self::matches(&path, algorithm, PREFIX_MANIFEST).await {
+                    if manifest.is_none() || name_match == NameMatch::Exact {
+                        manifest = Some((Manifest(path), name_match));
+                        continue;
+                    }
+                }
+            }
+            if !found_exact(&tag_manifest) {
+                if let Some(name_match) = Self::matches(&path, algorithm, PREFIX_TAG_MANIFEST).await
+                {
+                    if tag_manifest.is_none() || name_match == NameMatch::Exact {
+                        tag_manifest = Some((Manifest(path), name_match));
+                    }
+                }
+            }
+        }
+
+        let mut keep = |found: Option<(Self, NameMatch)>| {
+            found.map(|(manifest, name_match)| {
+                if name_match == NameMatch::IgnoringCase {
+                    warnings.push(ReadWarning::ManifestNameCase(manifest.0.clone()));
+                }
+                manifest
+            })
+        };
+        let manifest = keep(manifest);
+        let tag_manifest = keep(tag_manifest);
+
+        Ok((manifest, tag_manifest))
     }
 
-    async fn find<ChecksumAlgo: Digest>(
-        files_in_directory: &[impl AsRef<Path>],
-        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    /// List all manifests and tag manifests of the bag directory, along with the name of their algorithm in lowercase
+    pub async fn find_all(bag_it_directory: &Path) -> Result<Vec<(Self, String)>, ReadError> {
+        let mut dir = fs::read_dir(bag_it_directory)
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind()))?;
+
+        let mut manifests = vec![];
+        while let Some(entry) = dir
+            .next_entry()
+            .await
+            .map_err(|e| ReadError::ListChecksumFiles(bag_it_directory.to_path_buf(), e.kind()))?
+        {
+            let path = entry.path();
+            let Some(algorithm) = algorithm_name(&path, PREFIX_MANIFEST)
+                .or_else(|| algorithm_name(&path, PREFIX_TAG_MANIFEST))
+            else {
+                continue;
+            };
+
+            if fs::metadata(&path)
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+            {
+                manifests.push((Manifest(path), algorithm));
+            }
+        }
+
+        Ok(manifests)
+    }
+
+    async fn matches(
+        path: &Path,
+        algorithm: &Algorithm,
         manifest_prefix: &str,
-    ) -> Result<Option<Self>, ReadError> {
-        // Get all potential manifests
-        let manifests = files_in_directory
-            .iter()
-            .filter(|potential_manifest| {
-                let path = potential_manifest.as_ref();
-
-                // Item is a regular file
-                path.is_file()
-                    // And
-                    &&
-                    // Filename starts with requested prefix
-                    path
-                        .file_stem()
-                        .and_then(|filename| filename.to_str())
-                        .map(|filename| filename.starts_with(manifest_prefix))
-                        .is_some_and(|does_filename_match| does_filename_match)
-                    // And
-                    &&
-                    // File has ".txt" extension
-                    path.extension().and_then(|ext| ext.to_str()) == Some("txt")
-            })
-            .collect::<Vec<_>>();
+    ) -> Option<NameMatch> {
+        let expected = format!("{manifest_prefix}{}.txt", algorithm.name());
+        let name = path.file_name().and_then(|filename| filename.to_str())?;
 
-        // Find manifest with algorithm name
-        Ok(manifests
-            .into_iter()
-            .find(|path| {
-                path.as_ref()
-                    .file_stem()
-                    .and_then(|file| file.to_str())
-                    .and_then(|name| name.strip_prefix(manifest_prefix))
-                    == Some(checksum_algorithm.name())
+        // Filename is requested prefix followed by algorithm name, with ".txt" extension
+        let name_match = if name == expected {
+            NameMatch::Exact
+        } else if name.eq_ignore_ascii_case(&expected) {
+            NameMatch::IgnoringCase
+        } else {
+            return None;
+        };
+
+        // Item is a regular file, only ask the filesystem when the name is promising
+        fs::metadata(path)
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+            .then_some(name_match)
+    }
+
+    /// Validate all payloads of manifest, hashing up to `concurrency` files at the same time
+    ///
+    /// Payloads are returned in order of the manifest, a path listed twice is rejected with [`ReadError::DuplicateManifestEntry`].
+    /// Files are hashed on blocking threads, several of them use as many CPUs.
+    /// Unless files are hashed one at a time, every line is read in a string of its own to be validated concurrently.
+    pub async fn get_validate_payloads<ChecksumAlgo: Digest + Send + 'static>(
+        self,
+        context: &ReadContext,
+    ) -> Result<Vec<Payload<'static>>, ReadError> {
+        if context.concurrency <= 1 {
+            return self
+                .stream_validate_payloads::<ChecksumAlgo>(context.clone())
+                .await?
+                .try_collect()
+                .await;
+        }
+
+        let scope = self.scope();
+        let path: Arc<Path> = self.0.into();
+        let checksum_file = fs::File::open(&path)
+            .await
+            .map_err(|e| ReadError::OpenFile(path.to_path_buf(), e.kind()))?;
+        let checksum_file = BufReader::new(TagFileReader::new(checksum_file, context.encoding));
+
+        let seen_paths = SeenPaths::new(&path);
+
+        // Lines are read one after the other, duplicates are caught before their file is hashed
+        let lines = futures::stream::try_unfold(
+            (checksum_file, seen_paths, context.clone()),
+            |(mut checksum_file, mut seen_paths, context)| {
+                let path = path.clone();
+                async move {
+                    let Some(line) = next_line_owned(&mut checksum_file)
+                        .await
+                        .map_err(|e| ReadError::ReadLine(path.to_path_buf(), e.kind()))?
+                    else {
+                        return Ok(None);
+                    };
+                    seen_paths.check(&line, &context).await?;
+
+                    Ok(Some((line, (checksum_file, seen_paths, context))))
+                }
+            },
+        );
+
+        lines
+            .try_filter_map(|line| {
+                let context = context.clone();
+                async move {
+                    let unfetched = context.is_unfetched(&line).await;
+                    Ok((!unfetched).then_some(line))
+                }
+            })
+            .map_ok(|line| {
+                let context = context.clone();
+                async move {
+                    let payload = Payload::from_manifest_with(
+                        &line,
+                        context.bag_it_directory.clone(),
+                        context.version,
+                        context.resolution,
+                        scope,
+                        <ChecksumAlgo as Digest>::output_size(),
+                        |path| {
+                            hash_file_cached::<ChecksumAlgo>(
+                                path,
+                                context.progress.clone(),
+                                context.cache.clone(),
+                            )
+                        },
+                    )
+                    .await;
+                    skip_symlink(payload, context.resolution.symlinks)
+                }
             })
-            .map(|path| path.as_ref().to_path_buf())
-            .map(Manifest))
+            .try_buffered(context.concurrency)
+            .try_filter_map(|payload| async move { Ok(payload) })
+            .try_collect()
+            .await
     }
 
-    pub async fn get_validate_payloads<ChecksumAlgo: Digest>(
+    /// Stream payloads of manifest, validating them one line at a time
+    ///
+    /// Lines are read in a buffer reused from one line to the next, payloads borrow from it while being parsed.
+    /// A path listed twice is rejected with [`ReadError::DuplicateManifestEntry`] when read again, before hashing its file.
+    /// Payloads in `remote_paths` are skipped when their file is missing, they have not been fetched yet.
+    /// Hashing of files is reported to `progress`, if any, checksums are looked up in `cache` first.
+    pub async fn stream_validate_payloads<ChecksumAlgo: Digest + Send + 'static>(
         self,
-        bag_it_directory: impl AsRef<Path>,
+        context: ReadContext,
+    ) -> Result<impl Stream<Item = Result<Payload<'static>, ReadError>>, ReadError> {
+        let scope = self.scope();
+        let path = self.0;
+        let checksum_file = fs::File::open(&path)
+            .await
+            .map_err(|e| ReadError::OpenFile(path.clone(), e.kind()))?;
+        let checksum_file = BufReader::new(TagFileReader::new(checksum_file, context.encoding));
+
+        let seen_paths = SeenPaths::new(&path);
+
+        Ok(futures::stream::try_unfold(
+            (checksum_file, path, String::new(), seen_paths, context),
+            move |(mut checksum_file, path, mut line, mut seen_paths, context)| async move {
+                loop {
+                    let Some(line_contents) = next_line(&mut checksum_file, &mut line)
+                        .await
+                        .map_err(|e| ReadError::ReadLine(path.clone(), e.kind()))?
+                    else {
+                        return Ok(None);
+                    };
+                    seen_paths.check(line_contents, &context).await?;
+
+                    if context.is_unfetched(line_contents).await {
+                        continue;
+                    }
+
+                    let manifest_item = Payload::from_manifest_with(
+                        line_contents,
+                        context.bag_it_directory.clone(),
+                        context.version,
+                        context.resolution,
+                        scope,
+                        <ChecksumAlgo as Digest>::output_size(),
+                        |path| {
+                            hash_file_cached::<ChecksumAlgo>(
+                                path,
+                                context.progress.clone(),
+                                context.cache.clone(),
+                            )
+                        },
+                    )
+                    .await;
+                    let Some(manifest_item) =
+                        skip_symlink(manifest_item, context.resolution.symlinks)?
+                    else {
+                        continue;
+                    };
+
+                    return Ok(Some((
+                        manifest_item,
+                        (checksum_file, path, line, seen_paths, context),
+                    )));
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(feature = "read")]
+impl Manifest {
+    /// Payloads of manifest with their checksums as listed, without hashing their files
+    ///
+    /// Paths are resolved with `resolution`, payloads going through a symbolic link it skips are left out,
+    /// a path listed twice is rejected with [`ReadError::DuplicateManifestEntry`].
+    /// Payloads in `remote_paths` are skipped when their file is missing, they have not been fetched yet.
+    /// Checksums must be digests of `digest_size` bytes, the size of those of the algorithm of the manifest.
+    pub async fn read_payloads_unvalidated(
+        self,
+        context: &ReadContext,
+        digest_size: usize,
     ) -> Result<Vec<Payload<'static>>, ReadError> {
-        let checksum_file = fs::File::open(self)
+        let scope = self.scope();
+        let path = self.0;
+        let checksum_file = fs::File::open(&path)
             .await
-            .map_err(|e| ReadError::OpenFile(e.kind()))?;
-        let checksum_file = BufReader::new(checksum_file);
-        let mut checksum_lines = checksum_file.lines();
+            .map_err(|e| ReadError::OpenFile(path.clone(), e.kind()))?;
+        let mut checksum_file = BufReader::new(TagFileReader::new(checksum_file, context.encoding));
 
-        let mut items = Vec::new();
+        let mut seen_paths = SeenPaths::new(&path);
+        let mut payloads = vec![];
+        let mut line = String::new();
+        while let Some(line) = next_line(&mut checksum_file, &mut line)
+            .await
+            .map_err(|e| ReadError::ReadLine(path.clone(), e.kind()))?
+        {
+            seen_paths.check(line, context).await?;
+            if context.is_unfetched(line).await {
+                continue;
+            }
 
-        while let Some(line) = checksum_lines
-            .next_line()
+            let payload = Payload::from_manifest_unvalidated(
+                line,
+                context.bag_it_directory.clone(),
+                context.version,
+                context.resolution,
+                scope,
+                digest_size,
+            )
+            .await;
+            if let Some(payload) = skip_symlink(payload, context.resolution.symlinks)? {
+                payloads.push(payload);
+            }
+        }
+
+        Ok(payloads)
+    }
+
+    /// Checksums listed in manifest along with the paths of their payloads, without checking them against files
+    #[cfg(feature = "fetch")]
+    pub async fn read_checksums(
+        &self,
+        encoding: TagEncoding,
+        version: BagVersion,
+    ) -> Result<Vec<(Checksum<'static>, PathBuf)>, ReadError> {
+        let checksum_file = fs::File::open(&self.0)
             .await
-            .map_err(|e| ReadError::ReadLine(e.kind()))?
+            .map_err(|e| ReadError::OpenFile(self.0.clone(), e.kind()))?;
+        let mut checksum_file = BufReader::new(TagFileReader::new(checksum_file, encoding));
+
+        let mut checksums = vec![];
+        let mut line = String::new();
+        while let Some(line) = next_line(&mut checksum_file, &mut line)
+            .await
+            .map_err(|e| ReadError::ReadLine(self.0.clone(), e.kind()))?
         {
-            let manifest_item = Payload::from_manifest::<ChecksumAlgo>(&line, &bag_it_directory)
-                .await
-                .map_err(ReadError::ProcessManifestLine)?;
+            let (checksum, relative_path) =
+                parse_manifest_line(line, version).map_err(ReadError::ProcessManifestLine)?;
+            checksums.push((checksum.into_owned(), PathBuf::from(relative_path.as_ref())));
+        }
+
+        Ok(checksums)
+    }
+
+    /// Validate payloads of `manifest` and `tag_manifest` of the requested algorithm along with manifests of algorithms
+    /// registered at runtime, see [`ReadOptions::algorithm()`](crate::ReadOptions::algorithm)
+    ///
+    /// Every file is read once, its checksums are computed for all manifests listing it at the same time,
+    /// with up to `concurrency` files hashed at once. Tag files are validated first.
+    /// Like the manifest of the requested algorithm, each manifest of other algorithms must list every file
+    /// of the payload directory but those under `skipped_symlinks`, and no manifest may list a path twice.
+    /// Payloads of `manifest` and `tag_manifest` are returned in order of their manifests.
+    pub async fn validate_payloads_with<ChecksumAlgo: Digest + Send + 'static>(
+        manifest: Self,
+        tag_manifest: Option<Self>,
+        others: Vec<(Self, NewHasher)>,
+        context: &ReadContext,
+        skipped_symlinks: &[PathBuf],
+    ) -> Result<(Vec<Payload<'static>>, Vec<Payload<'static>>), ReadError> {
+        let (other_tag_manifests, other_manifests): (Vec<_>, Vec<_>) = others
+            .into_iter()
+            .partition(|(manifest, _)| manifest.scope() == ManifestScope::Tags);
+
+        let mut listed = ListedFiles::default();
+        let own_digest_size = <ChecksumAlgo as Digest>::output_size();
+        let tag_items = match tag_manifest {
+            Some(tag_manifest) => {
+                let tag_items = tag_manifest
+                    .read_payloads_unvalidated(context, own_digest_size)
+                    .await?;
+                listed.add_own(&tag_items);
+                tag_items
+            }
+            None => vec![],
+        };
+        for (index, (other_manifest, new_hasher)) in other_tag_manifests.into_iter().enumerate() {
+            let tag_items = other_manifest
+                .read_payloads_unvalidated(context, new_hasher().output_size())
+                .await?;
+            listed.add(index, new_hasher, &tag_items);
+        }
+
+        let payloads = manifest
+            .read_payloads_unvalidated(context, own_digest_size)
+            .await?;
+        listed.add_own(&payloads);
+        for (index, (other_manifest, new_hasher)) in other_manifests.into_iter().enumerate() {
+            let other_payloads = other_manifest
+                .read_payloads_unvalidated(context, new_hasher().output_size())
+                .await?;
+            listed.add(index, new_hasher, &other_payloads);
+
+            let mut unlisted = crate::BagIt::unlisted_payloads(
+                &context.bag_it_directory,
+                other_payloads.iter().map(|payload| payload.relative_path()),
+            )
+            .await?;
+            unlisted.retain(|file| {
+                !skipped_symlinks
+                    .iter()
+                    .any(|symlink| file.starts_with(symlink))
+            });
+            if !unlisted.is_empty() {
+                return Err(ReadError::UnlistedPayloads(unlisted));
+            }
+        }
+
+        // Futures are created up front, their closure would otherwise need to be generic over lifetimes of checksums
+        let validations = listed
+            .files
+            .into_iter()
+            .map(|file| {
+                validate_listed::<ChecksumAlgo>(
+                    context.bag_it_directory.clone(),
+                    file,
+                    context.progress.clone(),
+                    context.cache.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        futures::stream::iter(validations)
+            .buffered(context.concurrency)
+            .try_collect::<()>()
+            .await?;
+
+        Ok((payloads, tag_items))
+    }
+}
+
+/// Checksums listed for files of the bag by several manifests, to read every file once for all of them
+#[cfg(feature = "read")]
+#[derive(Default)]
+pub(crate) struct ListedFiles {
+    /// Files in order of first appearance
+    pub(crate) files: Vec<ListedFile>,
+    positions: HashMap<PathBuf, usize>,
+}
+
+/// Checksums listed for a file of the bag, relative to the bag directory
+#[cfg(feature = "read")]
+pub(crate) struct ListedFile {
+    pub(crate) relative_path: PathBuf,
+    /// Checksum listed in the manifest of the requested algorithm, if it lists the file
+    pub(crate) own: Option<Checksum<'static>>,
+    /// Checksums listed in manifests of other algorithms, with the index of their manifest and the hasher of its algorithm
+    pub(crate) others: Vec<(usize, NewHasher, Checksum<'static>)>,
+}
+
+#[cfg(feature = "read")]
+impl ListedFiles {
+    /// Checksums of `payloads` listed in the manifest of the requested algorithm
+    pub(crate) fn add_own(&mut self, payloads: &[Payload<'_>]) {
+        for payload in payloads {
+            self.file(payload.relative_path()).own = Some(payload.checksum().clone().into_owned());
+        }
+    }
+
+    /// Checksums of `payloads` listed in manifest at `index`, whose algorithm hashes with `new_hasher`
+    pub(crate) fn add(&mut self, index: usize, new_hasher: NewHasher, payloads: &[Payload<'_>]) {
+        for payload in payloads {
+            self.file(payload.relative_path()).others.push((
+                index,
+                new_hasher,
+                payload.checksum().clone().into_owned(),
+            ));
+        }
+    }
+
+    fn file(&mut self, relative_path: &Path) -> &mut ListedFile {
+        let position = *self
+            .positions
+            .entry(relative_path.to_path_buf())
+            .or_insert_with(|| {
+                self.files.push(ListedFile {
+                    relative_path: relative_path.to_path_buf(),
+                    own: None,
+                    others: vec![],
+                });
+                self.files.len() - 1
+            });
+        &mut self.files[position]
+    }
+}
+
+#[cfg(feature = "read")]
+impl ListedFile {
+    /// Compute checksums of file with algorithms of manifests of other algorithms listing it, reading it once
+    ///
+    /// Checksums are returned in order of `others`, hashing of the file is reported to `progress`, if any.
+    pub(crate) async fn hash_others(
+        &self,
+        bag_it_directory: &Path,
+        progress: Option<Observer>,
+    ) -> Result<Vec<Checksum<'static>>, ChecksumComputeError> {
+        let path = bag_it_directory.join(&self.relative_path);
+        let hashers = self
+            .others
+            .iter()
+            .map(|(_, new_hasher, _)| new_hasher())
+            .collect();
+        let Some(Observer(progress)) = progress else {
+            return hash_file_multi_progress(path, hashers, |_| ()).await;
+        };
+
+        progress.file_started(&path);
+        let checksums =
+            hash_file_multi_progress(&path, hashers, |bytes| progress.bytes_hashed(bytes)).await;
+        progress.file_completed(&path);
+        checksums
+    }
+
+    /// Compute checksums of file with the requested algorithm, unless `cache` has it, and with algorithms of manifests
+    /// of other algorithms listing it, reading it once
+    async fn hash_all<ChecksumAlgo: Digest + Send + 'static>(
+        &self,
+        bag_it_directory: &Path,
+        progress: Option<Observer>,
+        cache: Option<(SharedCache, Algorithm)>,
+    ) -> Result<(Option<Checksum<'static>>, Vec<Checksum<'static>>), ChecksumComputeError> {
+        let path = bag_it_directory.join(&self.relative_path);
+        if self.own.is_none() {
+            return Ok((None, self.hash_others(bag_it_directory, progress).await?));
+        }
+
+        // Checksum of the requested algorithm is looked up in the cache, the file is only hashed with the others then
+        let key = match &cache {
+            Some(_) => CacheKey::from_file(&path).await.ok(),
+            None => None,
+        };
+        if let (Some((SharedCache(cache), algorithm)), Some(key)) = (&cache, &key) {
+            if let Some(checksum) = cache.get(key, algorithm) {
+                let others = match self.others.is_empty() {
+                    true => vec![],
+                    false => self.hash_others(bag_it_directory, progress).await?,
+                };
+                return Ok((Some(checksum), others));
+            }
+        }
+
+        let hashers = self
+            .others
+            .iter()
+            .map(|(_, new_hasher, _)| new_hasher())
+            .collect();
+        let (checksum, others) = match progress {
+            Some(Observer(progress)) => {
+                progress.file_started(&path);
+                let checksums = hash_file_with::<ChecksumAlgo>(&path, hashers, |bytes| {
+                    progress.bytes_hashed(bytes)
+                })
+                .await;
+                progress.file_completed(&path);
+                checksums?
+            }
+            None => hash_file_with::<ChecksumAlgo>(&path, hashers, |_| ()).await?,
+        };
+        if let (Some((SharedCache(cache), algorithm)), Some(key)) = (cache, key) {
+            cache.insert(key, &algorithm, checksum.clone());
+        }
+        Ok((Some(checksum), others))
+    }
+}
+
+/// Compute checksums of `file` once for every manifest listing it, comparing them with the listed ones
+#[cfg(feature = "read")]
+async fn validate_listed<ChecksumAlgo: Digest + Send + 'static>(
+    bag_it_directory: Arc<Path>,
+    file: ListedFile,
+    progress: Option<Observer>,
+    cache: Option<(SharedCache, Algorithm)>,
+) -> Result<(), ReadError> {
+    let (own, others) = file
+        .hash_all::<ChecksumAlgo>(&bag_it_directory, progress, cache)
+        .await
+        .map_err(|e| ReadError::ProcessManifestLine(e.into()))?;
+
+    let listed = file
+        .own
+        .into_iter()
+        .chain(file.others.into_iter().map(|(_, _, checksum)| checksum));
+    for (expected, actual) in listed.zip(own.into_iter().chain(others)) {
+        if actual != expected {
+            return Err(ReadError::ProcessManifestLine(
+                PayloadError::ChecksumDiffers {
+                    path: file.relative_path,
+                    expected,
+                    actual,
+                },
+            ));
+        }
+    }
+    Ok(())
+}
 
-            items.push(manifest_item);
+/// Payload of a manifest line, or nothing when it goes through a symbolic link that `symlinks` skips
+#[cfg(feature = "read")]
+fn skip_symlink(
+    payload: Result<Payload<'static>, PayloadError>,
+    symlinks: SymlinkPolicy,
+) -> Result<Option<Payload<'static>>, ReadError> {
+    match payload {
+        Err(PayloadError::SymlinkRejected(_)) if symlinks == SymlinkPolicy::SkipWithWarning => {
+            Ok(None)
         }
+        payload => payload.map(Some).map_err(ReadError::ProcessManifestLine),
+    }
+}
+
+/// Compute checksum of file at `path` like [`hash_file_observed()`], unless `cache` has it
+///
+/// Files found in the cache are not hashed, they are not reported to `progress`.
+#[cfg(feature = "read")]
+async fn hash_file_cached<ChecksumAlgo: Digest + Send + 'static>(
+    path: PathBuf,
+    progress: Option<Observer>,
+    cache: Option<(SharedCache, Algorithm)>,
+) -> Result<Checksum<'static>, ChecksumComputeError> {
+    let Some((SharedCache(cache), algorithm)) = cache else {
+        return hash_file_observed::<ChecksumAlgo>(path, progress).await;
+    };
+    let Ok(key) = CacheKey::from_file(&path).await else {
+        return hash_file_observed::<ChecksumAlgo>(path, progress).await;
+    };
+    if let Some(checksum) = cache.get(&key, &algorithm) {
+        return Ok(checksum);
+    }
+
+    let checksum = hash_file_observed::<ChecksumAlgo>(path, progress).await?;
+    cache.insert(key, &algorithm, checksum.clone());
+    Ok(checksum)
+}
+
+/// Compute checksum of file at `path`, reporting it to `progress` if any
+#[cfg(feature = "read")]
+async fn hash_file_observed<ChecksumAlgo: Digest + Send + 'static>(
+    path: PathBuf,
+    progress: Option<Observer>,
+) -> Result<Checksum<'static>, ChecksumComputeError> {
+    let Some(Observer(progress)) = progress else {
+        return hash_file::<ChecksumAlgo>(path).await;
+    };
+
+    progress.file_started(&path);
+    let checksum =
+        hash_file_progress::<ChecksumAlgo>(&path, |bytes| progress.bytes_hashed(bytes)).await;
+    progress.file_completed(&path);
+    checksum
+}
+
+/// Whether payload of manifest `line` is listed in `fetch.txt`, and its file is not in the bag yet
+#[cfg(feature = "read")]
+pub(crate) async fn is_unfetched(
+    line: &str,
+    version: BagVersion,
+    resolution: PathResolution,
+    bag_it_directory: &Path,
+    remote_paths: &[PathBuf],
+) -> bool {
+    let Ok((_, relative_path)) = parse_manifest_line(line, version) else {
+        return false;
+    };
+    let relative_path = resolution.apply(relative_path);
+
+    remote_paths
+        .iter()
+        .any(|path| path == Path::new(relative_path.as_ref()))
+        && !fs::try_exists(bag_it_directory.join(relative_path.as_ref()))
+            .await
+            .unwrap_or(false)
+}
+
+/// Read next line of `reader` in `buffer`, without its line ending
+///
+/// The buffer is cleared first, its allocation is reused. Returns `None` at end of file.
+/// A byte order mark at start of file is left out by [`TagFileReader`], one starting any other line is kept.
+#[cfg(feature = "read")]
+pub(crate) async fn next_line<'b>(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    buffer: &'b mut String,
+) -> Result<Option<&'b str>, std::io::Error> {
+    buffer.clear();
+    if reader.read_line(buffer).await? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(trim_line_ending(buffer)))
+}
+
+/// Read next line of `reader` like [`next_line()`], in a string of its own handed over to the caller
+#[cfg(feature = "read")]
+pub(crate) async fn next_line_owned(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Option<String>, std::io::Error> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    line.truncate(trim_line_ending(&line).len());
+    Ok(Some(line))
+}
+
+/// Line without its line ending, `\n` or `\r\n`
+#[cfg(feature = "read")]
+fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(all(test, feature = "read"))]
+mod test {
+    use super::{next_line, next_line_owned, ReadContext, SeenPaths};
+    use crate::{
+        encoding::{TagEncoding, TagFileReader},
+        error::ReadError,
+        BagVersion,
+    };
+    use std::{
+        hash::{BuildHasherDefault, Hasher},
+        path::PathBuf,
+        sync::Arc,
+    };
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn next_line_endings() {
+        let mut reader = "first line\r\nsecond\n\nlast".as_bytes();
+        let mut buffer = String::new();
+
+        let mut lines = vec![];
+        while let Some(line) = next_line(&mut reader, &mut buffer).await.unwrap() {
+            lines.push(line.to_string());
+        }
+
+        assert_eq!(lines, ["first line", "second", "", "last"]);
+
+        // Only a byte order mark at start of file is left out
+        let contents = "\u{feff}first line\n\u{feff}second\n";
+        let mut reader = BufReader::new(TagFileReader::new(contents.as_bytes(), TagEncoding::Utf8));
+        let mut lines = vec![];
+        while let Some(line) = next_line(&mut reader, &mut buffer).await.unwrap() {
+            lines.push(line.to_string());
+        }
+
+        assert_eq!(lines, ["first line", "\u{feff}second"]);
+    }
+
+    #[tokio::test]
+    async fn next_line_owned_endings() {
+        let contents = "\u{feff}first line\r\nsecond\n\nlast";
+        let mut reader = BufReader::new(TagFileReader::new(contents.as_bytes(), TagEncoding::Utf8));
+
+        let mut lines = vec![];
+        while let Some(line) = next_line_owned(&mut reader).await.unwrap() {
+            lines.push(line);
+        }
+
+        assert_eq!(lines, ["first line", "second", "", "last"]);
+    }
+
+    /// Hasher giving the same hash for every path
+    #[derive(Default)]
+    struct Colliding;
+
+    impl Hasher for Colliding {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    #[tokio::test]
+    async fn seen_paths_same_hash() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let manifest = temp_directory.join("manifest-sha256.txt");
+        let checksum = "0".repeat(64);
+        let lines = [
+            format!("{checksum}  data/first.txt"),
+            format!("{checksum}  data/second.txt"),
+            format!("{}  data/first.txt", "f".repeat(64)),
+        ];
+        tokio::fs::write(&manifest, lines.join("\n")).await.unwrap();
+
+        let context = ReadContext {
+            bag_it_directory: Arc::from(temp_directory.as_path()),
+            encoding: Default::default(),
+            version: BagVersion::new(1, 0),
+            resolution: Default::default(),
+            remote_paths: Arc::from([]),
+            progress: None,
+            cache: None,
+            concurrency: 1,
+        };
+        let mut seen_paths =
+            SeenPaths::with_hasher(&manifest, BuildHasherDefault::<Colliding>::default());
 
-        Ok(items)
+        // Paths with the same hash are not mistaken for a duplicate
+        seen_paths.check(&lines[0], &context).await.unwrap();
+        seen_paths.check(&lines[1], &context).await.unwrap();
+        assert_eq!(
+            seen_paths.check(&lines[2], &context).await,
+            Err(ReadError::DuplicateManifestEntry(PathBuf::from(
+                "data/first.txt"
+            )))
+        );
     }
 }
@@ -1,9 +1,14 @@
+#[cfg(any(feature = "read", feature = "generate"))]
 mod file;
 
 #[cfg(feature = "date")]
 use jiff::civil::Date;
 
-pub use file::{MetadataFile, MetadataFileError};
+use crate::encoding::TagEncoding;
+#[cfg(any(feature = "read", feature = "generate"))]
+pub use file::MetadataFile;
+#[cfg(feature = "read")]
+pub use file::MetadataFileError;
 use std::{fmt::Display, str::FromStr};
 
 pub const KEY_VERSION: &str = "BagIt-Version";
@@ -11,52 +16,151 @@ pub const KEY_ENCODING: &str = "Tag-File-Character-Encoding";
 #[cfg(feature = "date")]
 pub const KEY_DATE: &str = "Bagging-Date";
 pub const KEY_OXUM: &str = "Payload-Oxum";
+pub const KEY_SOURCE_ORGANIZATION: &str = "Source-Organization";
+pub const KEY_ORGANIZATION_ADDRESS: &str = "Organization-Address";
+pub const KEY_CONTACT_NAME: &str = "Contact-Name";
+pub const KEY_CONTACT_PHONE: &str = "Contact-Phone";
+pub const KEY_CONTACT_EMAIL: &str = "Contact-Email";
+pub const KEY_EXTERNAL_DESCRIPTION: &str = "External-Description";
+pub const KEY_EXTERNAL_IDENTIFIER: &str = "External-Identifier";
+pub const KEY_BAG_SIZE: &str = "Bag-Size";
+pub const KEY_BAG_GROUP_IDENTIFIER: &str = "Bag-Group-Identifier";
+pub const KEY_BAG_COUNT: &str = "Bag-Count";
+pub const KEY_INTERNAL_SENDER_IDENTIFIER: &str = "Internal-Sender-Identifier";
+pub const KEY_INTERNAL_SENDER_DESCRIPTION: &str = "Internal-Sender-Description";
 
 #[derive(Debug, PartialEq, Clone)]
+/// Tag of `bagit.txt` or `bag-info.txt`: a label and its value
+///
+/// Labels reserved by RFC 8493 section 2.2.2 have their own variant, with their value parsed when it has a structure.
+/// Any other label is kept as [`Metadata::Custom`].
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::Metadata;
+/// let tag = Metadata::contact_email("archives@totebag.example")?;
+/// assert_eq!(tag.to_string(), "Contact-Email: archives@totebag.example");
+///
+/// let tag: Metadata = "Bag-Count: 2 of 3".parse()?;
+/// assert_eq!(tag, Metadata::BagCount { number: 2, total: Some(3) });
+/// # Ok::<(), async_bagit::error::MetadataError>(())
+/// ```
 pub enum Metadata {
+    /// Tag with a label not reserved by the spec
     Custom {
+        /// Label of tag
         key: String,
+        /// Value of tag
         value: String,
     },
+    /// `BagIt-Version` of `bagit.txt`
     BagitVersion {
+        /// Major number of version
         major: u8,
+        /// Minor number of version
         minor: u8,
     },
-    Encoding,
+    /// `Tag-File-Character-Encoding` of `bagit.txt`
+    Encoding(TagEncoding),
+    /// `Bagging-Date`, when the bag was created
     #[cfg(feature = "date")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "date")))]
     BaggingDate(Date),
-    /// OctetStream sum (Oxum)
+    /// `Payload-Oxum`, OctetStream sum (Oxum)
     PayloadOctetStreamSummary {
         /// Count of bytes in all streams
         octet_count: u64,
         /// Number of streams (aka files)
         stream_count: usize,
     },
+    /// `Source-Organization`, organization transferring the bag
+    SourceOrganization(String),
+    /// `Organization-Address`, mailing address of the source organization
+    OrganizationAddress(String),
+    /// `Contact-Name`, person at the source organization responsible for the bag
+    ContactName(String),
+    /// `Contact-Phone`, international format telephone number of the contact
+    ContactPhone(String),
+    /// `Contact-Email`, email address of the contact
+    ContactEmail(String),
+    /// `External-Description`, summary of the contents of the bag
+    ExternalDescription(String),
+    /// `External-Identifier`, identifier of the bag outside of the transfer
+    ExternalIdentifier(String),
+    /// `Bag-Size`, human-readable size of the bag, such as "260 GB"
+    BagSize(String),
+    /// `Bag-Group-Identifier`, identifier of the group of bags the bag belongs to
+    BagGroupIdentifier(String),
+    /// `Bag-Count`, position of the bag in its group
+    BagCount {
+        /// Number of the bag in its group, starting at 1
+        number: u32,
+        /// Number of bags in the group, if it is known: `?` otherwise
+        total: Option<u32>,
+    },
+    /// `Internal-Sender-Identifier`, identifier of the bag for the sender
+    InternalSenderIdentifier(String),
+    /// `Internal-Sender-Description`, description of the contents of the bag for the sender
+    InternalSenderDescription(String),
 }
 
 impl Metadata {
+    /// Label of tag
     pub fn key(&self) -> &str {
         match self {
             Metadata::Custom { key, .. } => key,
             Metadata::BagitVersion { .. } => KEY_VERSION,
-            Metadata::Encoding => KEY_ENCODING,
+            Metadata::Encoding(_) => KEY_ENCODING,
             #[cfg(feature = "date")]
             Metadata::BaggingDate(_) => KEY_DATE,
             Metadata::PayloadOctetStreamSummary { .. } => KEY_OXUM,
+            Metadata::SourceOrganization(_) => KEY_SOURCE_ORGANIZATION,
+            Metadata::OrganizationAddress(_) => KEY_ORGANIZATION_ADDRESS,
+            Metadata::ContactName(_) => KEY_CONTACT_NAME,
+            Metadata::ContactPhone(_) => KEY_CONTACT_PHONE,
+            Metadata::ContactEmail(_) => KEY_CONTACT_EMAIL,
+            Metadata::ExternalDescription(_) => KEY_EXTERNAL_DESCRIPTION,
+            Metadata::ExternalIdentifier(_) => KEY_EXTERNAL_IDENTIFIER,
+            Metadata::BagSize(_) => KEY_BAG_SIZE,
+            Metadata::BagGroupIdentifier(_) => KEY_BAG_GROUP_IDENTIFIER,
+            Metadata::BagCount { .. } => KEY_BAG_COUNT,
+            Metadata::InternalSenderIdentifier(_) => KEY_INTERNAL_SENDER_IDENTIFIER,
+            Metadata::InternalSenderDescription(_) => KEY_INTERNAL_SENDER_DESCRIPTION,
         }
     }
 
+    /// Value of tag, formatted like in tag files
     pub fn value(&self) -> String {
         match self {
             Metadata::Custom { value, .. } => value.to_string(),
             Metadata::BagitVersion { major, minor } => format!("{major}.{minor}"),
-            Metadata::Encoding => "UTF-8".to_string(),
+            Metadata::Encoding(encoding) => encoding.label().to_string(),
             #[cfg(feature = "date")]
             Metadata::BaggingDate(date) => date.to_string(),
             Metadata::PayloadOctetStreamSummary {
                 octet_count,
                 stream_count,
             } => format!("{octet_count}.{stream_count}"),
+            Metadata::BagCount {
+                number,
+                total: Some(total),
+            } => format!("{number} of {total}"),
+            Metadata::BagCount {
+                number,
+                total: None,
+            } => format!("{number} of ?"),
+            Metadata::SourceOrganization(value)
+            | Metadata::OrganizationAddress(value)
+            | Metadata::ContactName(value)
+            | Metadata::ContactPhone(value)
+            | Metadata::ContactEmail(value)
+            | Metadata::ExternalDescription(value)
+            | Metadata::ExternalIdentifier(value)
+            | Metadata::BagSize(value)
+            | Metadata::BagGroupIdentifier(value)
+            | Metadata::InternalSenderIdentifier(value)
+            | Metadata::InternalSenderDescription(value) => value.to_string(),
         }
     }
 }
@@ -68,6 +172,7 @@ impl Display for Metadata {
 }
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+/// Possible errors when parsing or creating tags
 pub enum MetadataError {
     /// Metadata format must be: "<key>: <value>"
     #[error("Invalid format")]
@@ -81,8 +186,8 @@ pub enum MetadataError {
     /// Some characters are forbidden for values
     #[error("Failed to parse metadata value for key `{0}`")]
     ValueParsing(&'static str),
-    /// Got other encoding value, accepting only utf-8
-    #[error("Only UTF-8 is supported")]
+    /// Got other encoding value, accepting only utf-8 and legacy encodings that can be transcoded
+    #[error("Only UTF-8 and ISO-8859-1 are supported")]
     Encoding,
 }
 
@@ -110,11 +215,9 @@ impl FromStr for Metadata {
                 Metadata::BagitVersion { major, minor }
             }
             (KEY_ENCODING, encoding) => {
-                if encoding != "UTF-8" {
-                    return Err(MetadataError::Encoding);
-                }
+                let encoding = TagEncoding::from_label(encoding).ok_or(MetadataError::Encoding)?;
 
-                Metadata::Encoding
+                Metadata::Encoding(encoding)
             }
             #[cfg(feature = "date")]
             (KEY_DATE, date) => {
@@ -140,6 +243,40 @@ impl FromStr for Metadata {
                     stream_count,
                 }
             }
+            (KEY_BAG_COUNT, count) => {
+                let (number, total) = count
+                    .split_once(" of ")
+                    .ok_or(MetadataError::ValueParsing(KEY_BAG_COUNT))?;
+
+                let number = number
+                    .parse()
+                    .map_err(|_| MetadataError::ValueParsing(KEY_BAG_COUNT))?;
+                let total = match total {
+                    "?" => None,
+                    total => Some(
+                        total
+                            .parse()
+                            .map_err(|_| MetadataError::ValueParsing(KEY_BAG_COUNT))?,
+                    ),
+                };
+
+                Self::bag_count(number, total)?
+            }
+            (KEY_SOURCE_ORGANIZATION, value) => Metadata::SourceOrganization(value.to_string()),
+            (KEY_ORGANIZATION_ADDRESS, value) => Metadata::OrganizationAddress(value.to_string()),
+            (KEY_CONTACT_NAME, value) => Metadata::ContactName(value.to_string()),
+            (KEY_CONTACT_PHONE, value) => Metadata::ContactPhone(value.to_string()),
+            (KEY_CONTACT_EMAIL, value) => Metadata::ContactEmail(value.to_string()),
+            (KEY_EXTERNAL_DESCRIPTION, value) => Metadata::ExternalDescription(value.to_string()),
+            (KEY_EXTERNAL_IDENTIFIER, value) => Metadata::ExternalIdentifier(value.to_string()),
+            (KEY_BAG_SIZE, value) => Metadata::BagSize(value.to_string()),
+            (KEY_BAG_GROUP_IDENTIFIER, value) => Metadata::BagGroupIdentifier(value.to_string()),
+            (KEY_INTERNAL_SENDER_IDENTIFIER, value) => {
+                Metadata::InternalSenderIdentifier(value.to_string())
+            }
+            (KEY_INTERNAL_SENDER_DESCRIPTION, value) => {
+                Metadata::InternalSenderDescription(value.to_string())
+            }
             (_, _) => Metadata::Custom {
                 key: key.to_string(),
                 value: value.to_string(),
@@ -167,6 +304,7 @@ impl Metadata {
 }
 
 impl Metadata {
+    /// Tag with any label, use other constructors for labels reserved by the spec
     pub fn custom(key: impl Into<String>, value: impl Into<String>) -> Result<Self, MetadataError> {
         let key = key.into();
         let value = value.into();
@@ -174,11 +312,96 @@ impl Metadata {
 
         Ok(Self::Custom { key, value })
     }
+
+    /// Tag with reserved label `key`, its value is text
+    fn text(
+        key: &str,
+        value: impl Into<String>,
+        variant: fn(String) -> Self,
+    ) -> Result<Self, MetadataError> {
+        let value = value.into();
+        Self::validate_format(key, &value)?;
+
+        Ok(variant(value))
+    }
+
+    /// Tag `Source-Organization`
+    pub fn source_organization(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_SOURCE_ORGANIZATION, value, Self::SourceOrganization)
+    }
+
+    /// Tag `Organization-Address`
+    pub fn organization_address(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_ORGANIZATION_ADDRESS, value, Self::OrganizationAddress)
+    }
+
+    /// Tag `Contact-Name`
+    pub fn contact_name(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_CONTACT_NAME, value, Self::ContactName)
+    }
+
+    /// Tag `Contact-Phone`
+    pub fn contact_phone(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_CONTACT_PHONE, value, Self::ContactPhone)
+    }
+
+    /// Tag `Contact-Email`
+    pub fn contact_email(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_CONTACT_EMAIL, value, Self::ContactEmail)
+    }
+
+    /// Tag `External-Description`
+    pub fn external_description(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_EXTERNAL_DESCRIPTION, value, Self::ExternalDescription)
+    }
+
+    /// Tag `External-Identifier`
+    pub fn external_identifier(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_EXTERNAL_IDENTIFIER, value, Self::ExternalIdentifier)
+    }
+
+    /// Tag `Bag-Size`
+    pub fn bag_size(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_BAG_SIZE, value, Self::BagSize)
+    }
+
+    /// Tag `Bag-Group-Identifier`
+    pub fn bag_group_identifier(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(KEY_BAG_GROUP_IDENTIFIER, value, Self::BagGroupIdentifier)
+    }
+
+    /// Tag `Internal-Sender-Identifier`
+    pub fn internal_sender_identifier(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(
+            KEY_INTERNAL_SENDER_IDENTIFIER,
+            value,
+            Self::InternalSenderIdentifier,
+        )
+    }
+
+    /// Tag `Internal-Sender-Description`
+    pub fn internal_sender_description(value: impl Into<String>) -> Result<Self, MetadataError> {
+        Self::text(
+            KEY_INTERNAL_SENDER_DESCRIPTION,
+            value,
+            Self::InternalSenderDescription,
+        )
+    }
+
+    /// Tag `Bag-Count`, bag `number` starts at 1 and can't be greater than `total` when it is known
+    pub fn bag_count(number: u32, total: Option<u32>) -> Result<Self, MetadataError> {
+        if number == 0 || total.is_some_and(|total| number > total) {
+            return Err(MetadataError::ValueParsing(KEY_BAG_COUNT));
+        }
+
+        Ok(Self::BagCount { number, total })
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::{Metadata, MetadataError};
+    use crate::encoding::TagEncoding;
     #[cfg(feature = "date")]
     use jiff::civil::Date;
     use std::str::FromStr;
@@ -200,7 +423,10 @@ mod test {
                     minor: 69,
                 }),
             ),
-            ("Tag-File-Character-Encoding: UTF-8", Ok(Metadata::Encoding)),
+            (
+                "Tag-File-Character-Encoding: UTF-8",
+                Ok(Metadata::Encoding(TagEncoding::Utf8)),
+            ),
             #[cfg(feature = "date")]
             (
                 "Bagging-Date: 2024-07-28 17:48",
@@ -213,6 +439,30 @@ mod test {
                     stream_count: 69,
                 }),
             ),
+            (
+                "Source-Organization: Totebag Inc.",
+                Ok(Metadata::SourceOrganization("Totebag Inc.".into())),
+            ),
+            (
+                "Contact-Email: archives@totebag.example",
+                Ok(Metadata::ContactEmail("archives@totebag.example".into())),
+            ),
+            ("Bag-Size: 260 GB", Ok(Metadata::BagSize("260 GB".into()))),
+            (
+                "Bag-Count: 1 of ?",
+                Ok(Metadata::BagCount {
+                    number: 1,
+                    total: None,
+                }),
+            ),
+            (
+                "Bag-Count: 4 of 3",
+                Err(MetadataError::ValueParsing("Bag-Count")),
+            ),
+            (
+                "Bag-Count: many",
+                Err(MetadataError::ValueParsing("Bag-Count")),
+            ),
         ] {
             assert_eq!(
                 Metadata::from_str(input),
@@ -318,4 +568,26 @@ mod test {
             "Unusual-But-Correct-Tag: Unexpected but good value"
         );
     }
+
+    #[test]
+    fn reserved_tags() {
+        let tag = Metadata::external_identifier("ark:/13030/tb42").unwrap();
+        assert_eq!(tag, Metadata::ExternalIdentifier("ark:/13030/tb42".into()));
+        assert_eq!(tag.key(), "External-Identifier");
+        assert_eq!(tag.to_string(), "External-Identifier: ark:/13030/tb42");
+        assert_eq!(Metadata::from_str(&tag.to_string()), Ok(tag));
+
+        assert_eq!(
+            Metadata::contact_name(" Philippe"),
+            Err(MetadataError::ValueForbiddenCharacter)
+        );
+        assert_eq!(Metadata::bag_size(""), Err(MetadataError::Format));
+
+        let count = Metadata::bag_count(2, Some(3)).unwrap();
+        assert_eq!(count.to_string(), "Bag-Count: 2 of 3");
+        assert_eq!(
+            Metadata::bag_count(0, None),
+            Err(MetadataError::ValueParsing("Bag-Count"))
+        );
+    }
 }
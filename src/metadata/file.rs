@@ -1,69 +1,172 @@
-use super::{Metadata, MetadataError};
-use std::path::Path;
+use super::Metadata;
+#[cfg(feature = "read")]
+use super::MetadataError;
+#[cfg(feature = "read")]
+use crate::encoding::{TagEncoding, TagFileReader, BYTE_ORDER_MARK};
+#[cfg(feature = "read")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "read")]
 use std::str::FromStr;
+#[cfg(feature = "read")]
 use tokio::fs;
+#[cfg(feature = "read")]
 use tokio::io::{AsyncBufReadExt, BufReader};
 
 #[derive(Debug, PartialEq, Default)]
 pub struct MetadataFile(Vec<Metadata>);
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+#[cfg(feature = "read")]
 pub enum MetadataFileError {
     /// Metadata errors
     #[error(transparent)]
     Metadata(#[from] MetadataError),
     /// Read file error
-    #[error("Failed to read file: `{0}`")]
-    ReadFile(std::io::ErrorKind),
+    #[error("Failed to read file `{}`: {1}", .0.display())]
+    ReadFile(PathBuf, std::io::ErrorKind),
 }
 
 impl MetadataFile {
-    pub async fn read(path: impl AsRef<Path>) -> Result<Self, MetadataFileError> {
-        let file = fs::File::open(path.as_ref())
+    /// Read tag file encoded with `encoding`
+    ///
+    /// Lines starting with spaces or tabs continue the value of the previous tag, they are unfolded with a single space.
+    /// A byte order mark at the start of the file is skipped.
+    #[cfg(feature = "read")]
+    pub async fn read(
+        path: impl AsRef<Path>,
+        encoding: TagEncoding,
+    ) -> Result<Self, MetadataFileError> {
+        let path = path.as_ref();
+        let file = fs::File::open(path)
             .await
-            .map_err(|e| MetadataFileError::ReadFile(e.kind()))?;
-        let file = BufReader::new(file);
+            .map_err(|e| MetadataFileError::ReadFile(path.to_path_buf(), e.kind()))?;
+        let file = BufReader::new(TagFileReader::new(file, encoding));
         let mut lines = file.lines();
 
-        let mut tags = Vec::new();
+        let mut tag_lines: Vec<String> = Vec::new();
 
-        while let Some(line) = lines
+        while let Some(mut line) = lines
             .next_line()
             .await
-            .map_err(|e| MetadataFileError::ReadFile(e.kind()))?
+            .map_err(|e| MetadataFileError::ReadFile(path.to_path_buf(), e.kind()))?
         {
-            tags.push(Metadata::from_str(&line)?);
+            if tag_lines.is_empty() && line.starts_with(BYTE_ORDER_MARK) {
+                line.remove(0);
+            }
+
+            if line.starts_with(FOLD_WHITESPACE) {
+                let tag_line = tag_lines.last_mut().ok_or(MetadataError::Format)?;
+                tag_line.push(' ');
+                tag_line.push_str(line.trim_start_matches(FOLD_WHITESPACE));
+            } else {
+                tag_lines.push(line);
+            }
         }
 
+        let tags = tag_lines
+            .iter()
+            .map(|line| Metadata::from_str(line))
+            .collect::<Result<_, _>>()?;
+
         Ok(Self(tags))
     }
 
-    pub async fn write(&self, path: impl AsRef<Path>) -> Result<(), std::io::Error> {
-        let contents = self
-            .0
+    /// Contents of tag file, folding values of tags longer than `fold_width` characters onto continuation lines
+    #[cfg(feature = "generate")]
+    pub fn render(&self, fold_width: Option<usize>) -> String {
+        self.0
             .iter()
-            .map(|tag| tag.to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        fs::write(path.as_ref(), contents).await
+            .map(|tag| match fold_width {
+                Some(width) => fold(tag, width),
+                None => tag.to_string(),
+            })
+            .map(|line| line + "\n")
+            .collect()
     }
 
+    #[cfg(feature = "generate")]
     pub fn add(&mut self, tag: Metadata) {
         self.0.push(tag);
     }
 
+    #[cfg(feature = "read")]
     pub fn tags(&self) -> impl Iterator<Item = &Metadata> {
         self.0.iter()
     }
 
+    #[cfg(feature = "read")]
     pub fn consume_tags(self) -> impl IntoIterator<Item = Metadata> {
         self.0.into_iter()
     }
 }
 
+/// Characters starting continuation lines of folded values
+#[cfg(feature = "read")]
+const FOLD_WHITESPACE: [char; 2] = [' ', '\t'];
+
+/// Indentation of continuation lines, when folding values
+#[cfg(feature = "generate")]
+const FOLD_INDENT: &str = "  ";
+
+/// Line of `tag`, broken on spaces so that lines are at most `width` characters long when possible
+///
+/// Only single spaces between words are broken, so that unfolding gives the same value back.
+/// Words longer than `width` are kept whole.
+#[cfg(feature = "generate")]
+fn fold(tag: &Metadata, width: usize) -> String {
+    let mut folded = format!("{}:", tag.key());
+    let mut line_length = folded.chars().count();
+
+    let value = tag.value();
+    let mut previous_word = "";
+    for (index, word) in value.split(' ').enumerate() {
+        let word_length = word.chars().count();
+        if index > 0
+            && !word.is_empty()
+            && !previous_word.is_empty()
+            && line_length + 1 + word_length > width
+        {
+            folded.push('\n');
+            folded.push_str(FOLD_INDENT);
+            line_length = FOLD_INDENT.len();
+        } else {
+            folded.push(' ');
+            line_length += 1;
+        }
+
+        folded.push_str(word);
+        line_length += word_length;
+        previous_word = word;
+    }
+
+    folded
+}
+
 impl From<Vec<Metadata>> for MetadataFile {
     fn from(value: Vec<Metadata>) -> Self {
         Self(value)
     }
 }
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use super::{fold, Metadata};
+
+    #[test]
+    fn fold_values() {
+        let tag = Metadata::external_description(
+            "Photographs of tote bags  and paper bags, from the 2024 collection",
+        )
+        .unwrap();
+
+        assert_eq!(
+            fold(&tag, 40),
+            "External-Description: Photographs of\n  tote bags  and paper bags, from the\n  2024 collection"
+        );
+        assert_eq!(fold(&tag, 1000), tag.to_string());
+
+        // Words are never split
+        let tag = Metadata::custom("Checksum", "0fe3bd6e7c36aa2c979f3330037b220c").unwrap();
+        assert_eq!(fold(&tag, 10), "Checksum: 0fe3bd6e7c36aa2c979f3330037b220c");
+    }
+}
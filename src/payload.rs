@@ -1,22 +1,37 @@
+#[cfg(feature = "zstd")]
+use crate::compression::{self, Original};
+#[cfg(feature = "read")]
+use crate::manifest::PREFIX_TAG_MANIFEST;
 use crate::{
-    checksum::{compute_checksum_file, ChecksumComputeError},
-    BagIt, Checksum,
+    checksum::{hash_file, ChecksumComputeError},
+    long_path, BagVersion, Checksum,
 };
 use digest::Digest;
+#[cfg(feature = "read")]
+use std::future::Future;
 use std::{
+    borrow::Cow,
     fmt::Display,
     path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::{
+    fs,
+    io::{AsyncRead, ReadBuf},
 };
 
 #[derive(thiserror::Error, Debug, PartialEq)]
 /// Possible errors when manipulating bagit payloads
 pub enum PayloadError {
-    /// Each line of manifest must be: "\<payload checksum\> \<relative path of payload\>"
+    /// Each line of manifest must be: "\<payload checksum\> \<relative path of payload\>", with checksum encoded as hex
     #[error("Invalid line format")]
     InvalidLine,
     /// This might happen when manifest contains wrongly formatted paths
-    #[error("Failed to get absolute path")]
-    Absolute(std::io::ErrorKind),
+    #[error("Failed to get absolute path of `{}`: {1}", .0.display())]
+    Absolute(PathBuf, std::io::ErrorKind),
     /// Path of payload must be relative to container's path
     #[error("Payload is not inside bag")]
     NotInsideBag,
@@ -24,11 +39,336 @@ pub enum PayloadError {
     #[error("Failed to compute checksum: {0}")]
     ComputeChecksum(#[from] ChecksumComputeError),
     /// Checksum is not the same after computing it and comparing with the one provided in the bag
-    #[error("Provided checksum differs from file on disk")]
-    ChecksumDiffers,
+    #[error("Checksum of `{}` differs from file on disk: expected {expected}, computed {actual}", .path.display())]
+    ChecksumDiffers {
+        /// Path of the file, relative to the bag
+        path: PathBuf,
+        /// Checksum provided in the bag
+        expected: Checksum<'static>,
+        /// Checksum computed from the file on disk
+        actual: Checksum<'static>,
+    },
+    /// Size of file is not the same after reading it and comparing with the one provided in the bag,
+    /// like the size of the original file of a compressed payload
+    #[error("Size of `{}` differs from file on disk: expected {expected} bytes, read {actual}", .path.display())]
+    SizeDiffers {
+        /// Path of the file, relative to the bag
+        path: PathBuf,
+        /// Size in bytes provided in the bag
+        expected: u64,
+        /// Size in bytes read from the file on disk
+        actual: u64,
+    },
     /// Used for metadata tag `Oxum`
-    #[error("Failed to get file size: {0}")]
-    FileSize(std::io::ErrorKind),
+    #[error("Failed to get size of file `{}`: {1}", .0.display())]
+    FileSize(PathBuf, std::io::ErrorKind),
+    /// Failed to open file of payload
+    #[error("Failed to open file `{}`: {1}", .0.display())]
+    Open(PathBuf, std::io::ErrorKind),
+    /// Failed to read sidecar tag file of payload
+    #[error("Failed to read sidecar `{}`: {1}", .0.display())]
+    Sidecar(PathBuf, std::io::ErrorKind),
+    /// Path of manifest must be relative, it can't start with `/`, `\` or a drive letter like `C:`
+    #[error("Path of manifest is absolute")]
+    AbsolutePath,
+    /// Path of manifest can't have `..` components, it could lead outside of the bag
+    #[error("Path of manifest has a parent directory component")]
+    ParentDirectory,
+    /// Path of payload manifest must be inside payload directory `data`
+    #[error("Payload is not inside payload directory")]
+    OutsidePayloadDirectory,
+    /// Tag manifest can't list tag manifests, including itself
+    #[error("Tag manifest lists a tag manifest")]
+    ListsTagManifest,
+    /// Checksum of manifest line is not hex, or does not have the size of digests of the algorithm of the manifest
+    #[error("Checksum `{checksum}` of `{}` is not a valid digest", .path.display())]
+    MalformedChecksum {
+        /// Path of the file, relative to the bag
+        path: PathBuf,
+        /// Checksum as written in the manifest
+        checksum: String,
+    },
+    /// Path goes through a symbolic link, which is refused by [`SymlinkPolicy`]
+    #[error("Symbolic link `{}` is not allowed", .0.display())]
+    SymlinkRejected(PathBuf),
+}
+
+/// Where paths of a manifest must lead to
+#[cfg(feature = "read")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ManifestScope {
+    /// Manifest of payloads: inside payload directory `data`
+    Payloads,
+    /// Tag manifest: anywhere in the bag
+    Tags,
+}
+
+/// Make sure `path` of a manifest stays inside the bag, without looking at the filesystem
+///
+/// Both `/` and `\` are taken as separators, so paths written on Windows are checked as well.
+#[cfg(feature = "read")]
+pub(crate) fn check_manifest_path(path: &str, scope: ManifestScope) -> Result<(), PayloadError> {
+    let drive_letter =
+        matches!(path.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic());
+    if path.starts_with(['/', '\\']) || drive_letter {
+        return Err(PayloadError::AbsolutePath);
+    }
+
+    let components = path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>();
+    if components.contains(&"..") {
+        return Err(PayloadError::ParentDirectory);
+    }
+
+    match (scope, components.as_slice()) {
+        (ManifestScope::Payloads, ["data", _, ..]) => Ok(()),
+        (ManifestScope::Payloads, _) => Err(PayloadError::OutsidePayloadDirectory),
+        (ManifestScope::Tags, [name])
+            if name.to_ascii_lowercase().starts_with(PREFIX_TAG_MANIFEST) =>
+        {
+            Err(PayloadError::ListsTagManifest)
+        }
+        (ManifestScope::Tags, _) => Ok(()),
+    }
+}
+
+/// Path with `/` as separator, as required by RFC 8493 section 2.1.3, even on platforms using another one like Windows
+fn portable_path(path: &Path) -> Cow<'_, str> {
+    use std::path::MAIN_SEPARATOR;
+
+    let path = path.to_string_lossy();
+    match MAIN_SEPARATOR != '/' && path.contains(MAIN_SEPARATOR) {
+        true => path.replace(MAIN_SEPARATOR, "/").into(),
+        false => path,
+    }
+}
+
+/// Path as written in manifests and `fetch.txt` since version 1.0
+///
+/// Characters `%`, CR and LF are percent-encoded, as required by RFC 8493 section 2.1.3.
+fn encode_path(path: &Path) -> Cow<'_, str> {
+    let path = portable_path(path);
+    if !path.contains(['%', '\r', '\n']) {
+        return path;
+    }
+
+    let mut encoded = String::with_capacity(path.len() + 4);
+    for character in path.chars() {
+        match character {
+            '%' => encoded.push_str("%25"),
+            '\r' => encoded.push_str("%0D"),
+            '\n' => encoded.push_str("%0A"),
+            character => encoded.push(character),
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+/// Path as written in manifests and `fetch.txt` of a bag with `version`, percent-encoded only if the version requires it
+pub(crate) fn manifest_path(path: &Path, version: BagVersion) -> Cow<'_, str> {
+    match version.percent_encodes_paths() {
+        true => encode_path(path),
+        false => portable_path(path),
+    }
+}
+
+/// Path from a manifest or `fetch.txt`, see [`encode_path()`]
+///
+/// Only `%25`, `%0D` and `%0A` are decoded, ignoring case of hex digits; other `%` are kept as-is.
+#[cfg(any(feature = "read", feature = "generate", feature = "codec"))]
+pub(crate) fn decode_path(path: &str) -> Cow<'_, str> {
+    if !path.contains('%') {
+        return Cow::Borrowed(path);
+    }
+
+    let mut decoded = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(position) = rest.find('%') {
+        decoded.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        let character = match rest.get(1..3).map(str::to_ascii_uppercase).as_deref() {
+            Some("25") => '%',
+            Some("0D") => '\r',
+            Some("0A") => '\n',
+            _ => {
+                decoded.push('%');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        decoded.push(character);
+        rest = &rest[3..];
+    }
+    decoded.push_str(rest);
+
+    Cow::Owned(decoded)
+}
+
+/// Split first field of a line of manifest or `fetch.txt` from the rest of the line
+///
+/// Fields are separated by runs of spaces or tabs, leading ones are ignored. The rest of the line is kept as is after the
+/// separator, since paths may contain whitespace. Lines without a second field give `None`.
+#[cfg(any(feature = "read", feature = "generate", feature = "codec"))]
+pub(crate) fn split_field(line: &str) -> Option<(&str, &str)> {
+    let (field, rest) = line
+        .trim_start_matches([' ', '\t'])
+        .split_once([' ', '\t'])?;
+    let rest = rest.trim_start_matches([' ', '\t']);
+
+    match rest.is_empty() {
+        true => None,
+        false => Some((field, rest)),
+    }
+}
+
+/// Split a manifest line in checksum and path of payload, relative to the bag directory
+///
+/// Checksum and path are separated by the first run of spaces or tabs, the rest of the line is the path:
+/// it may contain whitespace itself. Path is percent-decoded if bag `version` requires it, see [`decode_path()`].
+#[cfg(any(feature = "read", feature = "generate", feature = "codec"))]
+pub(crate) fn parse_manifest_line(
+    line: &str,
+    version: BagVersion,
+) -> Result<(Checksum<'static>, Cow<'_, str>), PayloadError> {
+    use std::str::FromStr;
+
+    let (checksum, relative_path) = split_field(line).ok_or(PayloadError::InvalidLine)?;
+
+    let relative_path = match version.percent_encodes_paths() {
+        true => decode_path(relative_path),
+        false => Cow::Borrowed(relative_path),
+    };
+
+    let checksum = Checksum::from_str(checksum).map_err(|_| PayloadError::MalformedChecksum {
+        path: relative_path.as_ref().into(),
+        checksum: checksum.to_string(),
+    })?;
+
+    Ok((checksum, relative_path))
+}
+
+/// Make sure `checksum` of file at `relative_path` has the size of digests of the algorithm, `digest_size` bytes
+#[cfg(feature = "read")]
+fn check_digest_size(
+    checksum: &Checksum,
+    relative_path: &str,
+    digest_size: usize,
+) -> Result<(), PayloadError> {
+    match checksum.as_bytes().len() == digest_size {
+        true => Ok(()),
+        false => Err(PayloadError::MalformedChecksum {
+            path: relative_path.into(),
+            checksum: checksum.to_string(),
+        }),
+    }
+}
+
+/// Directory of the bag with sidecar tag files of payloads
+const SIDECAR_DIRECTORY: &str = "metadata";
+
+/// Extension of sidecar tag files of payloads
+const SIDECAR_EXTENSION: &str = "json";
+
+/// Path of sidecar tag file of payload at `relative_path`, see [`Payload::sidecar_path()`]
+pub(crate) fn sidecar_path(relative_path: &Path) -> PathBuf {
+    let path_in_payload_directory = relative_path.strip_prefix("data").unwrap_or(relative_path);
+
+    let mut sidecar = Path::new(SIDECAR_DIRECTORY)
+        .join(path_in_payload_directory)
+        .into_os_string();
+    sidecar.push(".");
+    sidecar.push(SIDECAR_EXTENSION);
+    sidecar.into()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Unicode normalization of paths of manifests, applied before their files are looked up on disk
+///
+/// Filesystems of macOS store names of files decomposed, while manifests made elsewhere usually have them composed:
+/// the same name is then written with different characters, and payloads are not found.
+/// See [`ReadOptions::path_normalization()`](crate::ReadOptions::path_normalization).
+#[cfg(feature = "read")]
+pub enum PathNormalization {
+    /// Paths are used as written in manifests
+    #[default]
+    AsIs,
+    /// Paths are composed, with Normalization Form C
+    Nfc,
+    /// Paths are decomposed, with Normalization Form D
+    Nfd,
+}
+
+#[cfg(feature = "read")]
+impl PathNormalization {
+    /// Normalize `path`, it is kept as is when it is already in the expected form
+    pub(crate) fn apply<'p>(&self, path: Cow<'p, str>) -> Cow<'p, str> {
+        use unicode_normalization::{is_nfc, is_nfd, UnicodeNormalization};
+
+        match self {
+            Self::Nfc if !is_nfc(&path) => path.nfc().collect::<String>().into(),
+            Self::Nfd if !is_nfd(&path) => path.nfd().collect::<String>().into(),
+            _ => path,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// What to do with symbolic links met when reading a bag or adding files to it
+///
+/// Symbolic links can lead outside of the bag, and they are lost when a bag is archived or copied to another filesystem.
+/// See [`ReadOptions::symlinks()`](crate::ReadOptions::symlinks) and [`BagIt::set_symlink_policy()`](crate::BagIt::set_symlink_policy).
+pub enum SymlinkPolicy {
+    /// Symbolic links are followed, their targets are used like regular files
+    #[default]
+    Follow,
+    /// Symbolic links are refused with [`PayloadError::SymlinkRejected`]
+    Reject,
+    /// Symbolic links are left out: reported with [`ReadWarning::SkippedSymlink`](crate::ReadWarning::SkippedSymlink)
+    /// when reading, and listed by [`BagIt::skipped_symlinks()`](crate::BagIt::skipped_symlinks) when adding files
+    SkipWithWarning,
+}
+
+/// First path of `relative_path` inside `base_directory` that is a symbolic link, relative to `base_directory`
+#[cfg(feature = "read")]
+pub(crate) async fn find_symlink(base_directory: &Path, relative_path: &Path) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in relative_path.components() {
+        path.push(component);
+        if fs::symlink_metadata(long_path::extended(&base_directory.join(&path)))
+            .await
+            .is_ok_and(|metadata| metadata.is_symlink())
+        {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// How paths of manifests are resolved to files of the bag, see [`ReadOptions`](crate::ReadOptions)
+#[cfg(feature = "read")]
+pub(crate) struct PathResolution {
+    pub normalization: PathNormalization,
+    /// Whether `\` is taken as a separator, like `/`
+    pub windows_separators: bool,
+    pub symlinks: SymlinkPolicy,
+}
+
+#[cfg(feature = "read")]
+impl PathResolution {
+    /// Path of manifest as it is looked up on disk
+    pub(crate) fn apply<'p>(&self, path: Cow<'p, str>) -> Cow<'p, str> {
+        let path = match self.windows_separators && path.contains('\\') {
+            true => path.replace('\\', "/").into(),
+            false => path,
+        };
+
+        self.normalization.apply(path)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,100 +377,288 @@ pub struct Payload<'a> {
     checksum: Checksum<'a>,
 
     /// Path relative to the bag directory
-    relative_path: std::path::PathBuf,
+    ///
+    /// Boxed to avoid keeping spare capacity around, bags can have lots of payloads
+    relative_path: Box<Path>,
 
     /// File size in bytes
     bytes: u64,
+
+    /// Directory of the bag containing the payload, shared between all payloads of the bag
+    bag_directory: Arc<Path>,
+
+    /// Original file, when payload is stored compressed
+    #[cfg(feature = "zstd")]
+    original: Option<Original>,
 }
 
 impl Display for Payload<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.checksum, self.relative_path.display())
+        write!(
+            f,
+            "{} {}",
+            self.checksum,
+            manifest_path(&self.relative_path, BagVersion::V1_0)
+        )
     }
 }
 
 impl<'a> Payload<'a> {
-    #[cfg(test)]
+    #[cfg(all(test, feature = "read"))]
     pub(crate) fn test_payload(
         relative_path_file: impl AsRef<Path>,
-        checksum: &'a str,
+        checksum: &str,
         bytes: u64,
     ) -> Self {
+        use std::str::FromStr;
+
         Self {
-            checksum: Checksum::from(checksum),
-            relative_path: PathBuf::from(relative_path_file.as_ref()),
+            checksum: Checksum::from_str(checksum).unwrap(),
+            relative_path: relative_path_file.as_ref().into(),
             bytes,
+            bag_directory: Path::new("").into(),
+            #[cfg(feature = "zstd")]
+            original: None,
+        }
+    }
+
+    #[cfg(all(test, feature = "read"))]
+    pub(crate) fn with_bag_directory(self, bag_directory: Arc<Path>) -> Self {
+        Self {
+            bag_directory,
+            ..self
         }
     }
 
-    pub(crate) fn new(
-        absolute_base_path: impl AsRef<Path>,
+    #[cfg(feature = "generate")]
+    pub(crate) async fn new(
+        bag_directory: Arc<Path>,
         relative_path_file: impl AsRef<Path>,
         checksum: Checksum<'a>,
     ) -> Result<Self, PayloadError> {
-        let relative_path = relative_path_file.as_ref().to_path_buf();
-
-        // Get absolute path
-        let bytes = absolute_base_path
-            .as_ref()
-            .join(relative_path_file.as_ref())
-            // Get file metadata
-            .metadata()
+        let relative_path = relative_path_file.as_ref().into();
+
+        // Get file size from its metadata
+        let bytes = fs::metadata(long_path::extended(
+            &bag_directory.join(relative_path_file.as_ref()),
+        ))
+        .await
+        .map(|metadata| metadata.len())
+        .map_err(|e| {
+            PayloadError::FileSize(bag_directory.join(relative_path_file.as_ref()), e.kind())
+        })?;
+
+        Ok(Self {
+            checksum,
+            relative_path,
+            bytes,
+            bag_directory,
+            #[cfg(feature = "zstd")]
+            original: None,
+        })
+    }
+
+    #[cfg(feature = "read")]
+    pub(crate) async fn from_manifest<ChecksumAlgo: Digest + Send + 'static>(
+        manifest_line: &str,
+        bag_directory: Arc<Path>,
+        version: BagVersion,
+        resolution: PathResolution,
+        scope: ManifestScope,
+    ) -> Result<Self, PayloadError> {
+        Self::from_manifest_with(
+            manifest_line,
+            bag_directory,
+            version,
+            resolution,
+            scope,
+            <ChecksumAlgo as Digest>::output_size(),
+            |path| hash_file::<ChecksumAlgo>(path),
+        )
+        .await
+    }
+
+    /// Parse and validate payload of manifest line like [`Self::from_manifest()`], computing its checksum with `hash`
+    ///
+    /// Checksums of the manifest must be digests of `digest_size` bytes, the size of those computed by `hash`.
+    #[cfg(feature = "read")]
+    pub(crate) async fn from_manifest_with<Hash, HashFuture>(
+        manifest_line: &str,
+        bag_directory: Arc<Path>,
+        version: BagVersion,
+        resolution: PathResolution,
+        scope: ManifestScope,
+        digest_size: usize,
+        hash: Hash,
+    ) -> Result<Self, PayloadError>
+    where
+        Hash: FnOnce(PathBuf) -> HashFuture,
+        HashFuture: Future<Output = Result<Checksum<'static>, ChecksumComputeError>>,
+    {
+        let (checksum_from_manifest, relative_path, file_path) = Self::resolve_manifest_line(
+            manifest_line,
+            &bag_directory,
+            version,
+            resolution,
+            scope,
+            digest_size,
+        )
+        .await?;
+
+        let checksum = hash(file_path.clone()).await?;
+
+        if checksum != checksum_from_manifest {
+            return Err(PayloadError::ChecksumDiffers {
+                path: relative_path.into(),
+                expected: checksum_from_manifest,
+                actual: checksum,
+            });
+        }
+
+        // File size
+        let bytes = fs::metadata(&file_path)
+            .await
             .map(|metadata| metadata.len())
-            .map_err(|e| PayloadError::FileSize(e.kind()))?;
+            .map_err(|e| PayloadError::FileSize(file_path.clone(), e.kind()))?;
 
         Ok(Self {
             checksum,
             relative_path,
             bytes,
+            bag_directory,
+            #[cfg(feature = "zstd")]
+            original: None,
         })
     }
 
-    pub(crate) async fn from_manifest<'manifest, 'item, ChecksumAlgo: Digest>(
-        manifest_line: &'manifest str,
-        base_directory: impl AsRef<Path>,
+    /// Parse payload of manifest line without computing its checksum, the one of the manifest is trusted
+    ///
+    /// Its path and the size of its checksum are checked like [`Self::from_manifest()`], and its file must exist for its size.
+    #[cfg(feature = "read")]
+    pub(crate) async fn from_manifest_unvalidated(
+        manifest_line: &str,
+        bag_directory: Arc<Path>,
+        version: BagVersion,
+        resolution: PathResolution,
+        scope: ManifestScope,
+        digest_size: usize,
     ) -> Result<Self, PayloadError> {
-        let base_directory = base_directory.as_ref();
+        let (checksum, relative_path, file_path) = Self::resolve_manifest_line(
+            manifest_line,
+            &bag_directory,
+            version,
+            resolution,
+            scope,
+            digest_size,
+        )
+        .await?;
 
-        // TODO: wait for https://github.com/rust-lang/rust/issues/98326 to stabilize
-        let [checksum_from_manifest, relative_file_path] = manifest_line
-            .split_whitespace()
-            .next_chunk()
-            .map_err(|_| PayloadError::InvalidLine)?;
+        // File size
+        let bytes = fs::metadata(&file_path)
+            .await
+            .map(|metadata| metadata.len())
+            .map_err(|e| PayloadError::FileSize(file_path.clone(), e.kind()))?;
+
+        Ok(Self {
+            checksum,
+            relative_path,
+            bytes,
+            bag_directory,
+            #[cfg(feature = "zstd")]
+            original: None,
+        })
+    }
+
+    /// Checksum and path of manifest line, along with the absolute path of its file, making sure it is inside the bag
+    ///
+    /// Checksums that are not digests of `digest_size` bytes are refused with [`PayloadError::MalformedChecksum`].
+    /// Unless symbolic links are followed, paths going through a symbolic link are refused with [`PayloadError::SymlinkRejected`].
+    #[cfg(feature = "read")]
+    async fn resolve_manifest_line(
+        manifest_line: &str,
+        bag_directory: &Path,
+        version: BagVersion,
+        resolution: PathResolution,
+        scope: ManifestScope,
+        digest_size: usize,
+    ) -> Result<(Checksum<'static>, Box<Path>, PathBuf), PayloadError> {
+        let (checksum, relative_file_path) = parse_manifest_line(manifest_line, version)?;
+        check_digest_size(&checksum, &relative_file_path, digest_size)?;
+        check_manifest_path(&relative_file_path, scope)?;
+        let relative_file_path = resolution.apply(relative_file_path);
+
+        if resolution.symlinks != SymlinkPolicy::Follow {
+            if let Some(symlink) =
+                find_symlink(bag_directory, Path::new(relative_file_path.as_ref())).await
+            {
+                return Err(PayloadError::SymlinkRejected(symlink));
+            }
+        }
 
         // Absolute path of payload
-        let file_path = base_directory
-            .join(relative_file_path)
-            .canonicalize()
-            .map_err(|e| PayloadError::Absolute(e.kind()))?;
+        let file_path = bag_directory.join(relative_file_path.as_ref());
+        let file_path = fs::canonicalize(long_path::extended(&file_path))
+            .await
+            .map_err(|e| PayloadError::Absolute(file_path.clone(), e.kind()))?;
 
         // Get absolute path of base directory, in case there are some unresolved symlinks
-        let base_directory = base_directory
-            .canonicalize()
-            .map_err(|e| PayloadError::Absolute(e.kind()))?;
+        let base_directory = fs::canonicalize(long_path::extended(bag_directory))
+            .await
+            .map_err(|e| PayloadError::Absolute(bag_directory.to_path_buf(), e.kind()))?;
 
         // Make sure payload is inside bag, prevent path traversal attacks
         if !file_path.starts_with(base_directory) {
             return Err(PayloadError::NotInsideBag);
         }
 
-        let checksum = compute_checksum_file::<ChecksumAlgo>(&file_path).await?;
+        Ok((
+            checksum,
+            Path::new(relative_file_path.as_ref()).into(),
+            file_path,
+        ))
+    }
 
-        if checksum != checksum_from_manifest.into() {
-            return Err(PayloadError::ChecksumDiffers);
-        }
+    #[cfg(all(feature = "zstd", any(feature = "read", feature = "generate")))]
+    pub(crate) fn set_original(&mut self, original: Original) {
+        self.original = Some(original);
+    }
 
-        // File size
-        let bytes = file_path
-            .metadata()
-            .map(|metadata| metadata.len())
-            .map_err(|e| PayloadError::FileSize(e.kind()))?;
+    #[cfg(all(feature = "zstd", feature = "generate"))]
+    pub(crate) fn original(&self) -> Option<&Original> {
+        self.original.as_ref()
+    }
 
-        Ok(Self {
-            checksum,
-            relative_path: PathBuf::from(relative_file_path),
-            bytes,
-        })
+    /// Whether payload is stored compressed, see [`BagIt::add_file_compressed()`](crate::BagIt::add_file_compressed)
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub fn is_compressed(&self) -> bool {
+        self.original.is_some()
+    }
+
+    /// Checksum of original file of compressed payload, with the same algorithm as [`Payload::checksum()`]
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub fn original_checksum(&self) -> Option<&Checksum<'static>> {
+        self.original.as_ref().map(|original| &original.checksum)
+    }
+
+    /// Size in bytes of original file of compressed payload
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub fn original_bytes(&self) -> Option<u64> {
+        self.original.as_ref().map(|original| original.bytes)
+    }
+
+    /// Get an owned payload, copying its checksum if it was borrowed
+    pub fn into_owned(self) -> Payload<'static> {
+        Payload {
+            checksum: self.checksum.into_owned(),
+            relative_path: self.relative_path,
+            bytes: self.bytes,
+            bag_directory: self.bag_directory,
+            #[cfg(feature = "zstd")]
+            original: self.original,
+        }
     }
 
     /// A checksum of the payload.
@@ -138,7 +666,7 @@ impl<'a> Payload<'a> {
     /// The algorithm used is not specified, refer to either:
     /// - the moment when the payload was added
     /// - when the bag was opened.
-    pub fn checksum(&self) -> &Checksum {
+    pub fn checksum(&self) -> &Checksum<'_> {
         &self.checksum
     }
 
@@ -147,13 +675,305 @@ impl<'a> Payload<'a> {
         &self.relative_path
     }
 
+    /// Name of file of payload, if it is valid UTF-8
+    pub fn file_name(&self) -> Option<&str> {
+        self.relative_path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+    }
+
+    /// Extension of file of payload, if it has one and it is valid UTF-8
+    pub fn extension(&self) -> Option<&str> {
+        self.relative_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+    }
+
+    /// Whether payload is inside `directory` or one of its subdirectories, a path relative to the bag directory
+    ///
+    /// Only whole path components are compared: `data/images` is not under `data/ima`.
+    pub fn is_under(&self, directory: impl AsRef<Path>) -> bool {
+        self.relative_path.starts_with(directory)
+    }
+
     /// Absolute path of payload
-    pub fn absolute_path(&self, bag: &BagIt) -> PathBuf {
-        bag.path().join(&self.relative_path)
+    pub fn absolute_path(&self) -> PathBuf {
+        self.bag_directory.join(&self.relative_path)
     }
 
     /// Size of payload in bytes
     pub fn bytes(&self) -> u64 {
         self.bytes
     }
+
+    /// Open file of payload in read-only mode
+    ///
+    /// Compressed payloads are decompressed while they are read.
+    pub async fn open(&self) -> Result<PayloadReader, PayloadError> {
+        let file = fs::File::open(long_path::extended(&self.absolute_path()))
+            .await
+            .map_err(|e| PayloadError::Open(self.absolute_path(), e.kind()))?;
+
+        #[cfg(feature = "zstd")]
+        if self.is_compressed() {
+            return Ok(PayloadReader(Box::pin(compression::decompress(file))));
+        }
+
+        Ok(PayloadReader(Box::pin(file)))
+    }
+
+    /// Path of sidecar tag file of payload, relative to the bag directory
+    ///
+    /// Path inside payload directory is kept, under directory `metadata` and with an extra extension `.json`:
+    /// `data/images/totebag.jpg` has sidecar `metadata/images/totebag.jpg.json`.
+    pub fn sidecar_path(&self) -> PathBuf {
+        sidecar_path(&self.relative_path)
+    }
+
+    /// Contents of sidecar tag file of payload, if it has one
+    ///
+    /// See [`BagIt::add_file_with_sidecar()`](crate::BagIt::add_file_with_sidecar) to create one.
+    /// Sidecars are listed in the tag manifest, they are validated when reading the bag.
+    pub async fn sidecar(&self) -> Result<Option<Vec<u8>>, PayloadError> {
+        match fs::read(long_path::extended(
+            &self.bag_directory.join(self.sidecar_path()),
+        ))
+        .await
+        {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(PayloadError::Sidecar(
+                self.bag_directory.join(self.sidecar_path()),
+                e.kind(),
+            )),
+        }
+    }
+
+    /// Compute checksum of file of payload, and compare it with the one of the payload
+    ///
+    /// The algorithm must be the one used when the payload was added or read.
+    /// Compressed payloads are also decompressed, to compare checksum and size of their original file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let bag = BagIt::read_existing(bagit_directory, &algorithm).await?;
+    ///
+    /// // Payloads can be handed over without the bag
+    /// let payload = bag.payload_items().next().unwrap();
+    /// payload.verify::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify<ChecksumAlgo: Digest + Send + 'static>(&self) -> Result<(), PayloadError> {
+        let checksum = hash_file::<ChecksumAlgo>(self.absolute_path()).await?;
+
+        if checksum != self.checksum {
+            return Err(PayloadError::ChecksumDiffers {
+                path: self.relative_path.to_path_buf(),
+                expected: self.checksum.clone().into_owned(),
+                actual: checksum,
+            });
+        }
+
+        #[cfg(feature = "zstd")]
+        if let Some(original) = &self.original {
+            let (checksum, bytes) = crate::checksum::hash_reader::<ChecksumAlgo>(
+                self.open().await?,
+                &self.absolute_path(),
+            )
+            .await?;
+
+            if checksum != original.checksum {
+                return Err(PayloadError::ChecksumDiffers {
+                    path: self.relative_path.to_path_buf(),
+                    expected: original.checksum.clone(),
+                    actual: checksum,
+                });
+            }
+            if bytes != original.bytes {
+                return Err(PayloadError::SizeDiffers {
+                    path: self.relative_path.to_path_buf(),
+                    expected: original.bytes,
+                    actual: bytes,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reader of contents of a payload, see [`Payload::open()`]
+pub struct PayloadReader(Pin<Box<dyn AsyncRead + Send + Sync>>);
+
+impl AsyncRead for PayloadReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.0.as_mut().poll_read(cx, buf)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// Payload of a bag, along with metadata of its file on disk
+///
+/// Metadata is gathered at the moment the item is yielded by [`BagIt::payload_stream()`],
+/// it may differ from what was recorded when the payload was added or validated.
+pub struct PayloadStatus<'a> {
+    payload: &'a Payload<'a>,
+
+    /// File is present on disk
+    exists: bool,
+
+    /// Current file size in bytes
+    bytes: Option<u64>,
+
+    /// Last modification time of file
+    modified: Option<SystemTime>,
+}
+
+impl<'a> PayloadStatus<'a> {
+    pub(crate) async fn gather(payload: &'a Payload<'a>) -> Result<Self, PayloadError> {
+        let path = payload.absolute_path();
+        match fs::metadata(long_path::extended(&path)).await {
+            Ok(metadata) => Ok(Self {
+                payload,
+                exists: true,
+                bytes: Some(metadata.len()),
+                modified: metadata.modified().ok(),
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self {
+                payload,
+                exists: false,
+                bytes: None,
+                modified: None,
+            }),
+            Err(e) => Err(PayloadError::FileSize(path, e.kind())),
+        }
+    }
+
+    /// Payload as listed in the bag
+    pub fn payload(&self) -> &Payload<'a> {
+        self.payload
+    }
+
+    /// Whether the file of the payload is present on disk
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+
+    /// Current size of file in bytes, if it exists
+    pub fn bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    /// Last modification time of file, if it exists and the platform supports it
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+#[cfg(all(test, feature = "read"))]
+mod test {
+    use super::{check_manifest_path, manifest_path, ManifestScope, PathResolution, PayloadError};
+    use crate::BagVersion;
+    use std::{borrow::Cow, path::Path};
+
+    #[test]
+    fn is_under() {
+        use super::Payload;
+
+        let payload = Payload::test_payload("data/images/2024/scan.tiff", "00", 1);
+        for directory in ["data", "data/images", "data/images/2024", "data/images/"] {
+            assert!(payload.is_under(directory), "`{directory}`");
+        }
+        for directory in [
+            "data/ima",
+            "data/images/2023",
+            "images",
+            "data/images/2024/scan.tiff/x",
+        ] {
+            assert!(!payload.is_under(directory), "`{directory}`");
+        }
+    }
+
+    #[test]
+    fn manifest_paths() {
+        for path in [
+            "data/totebag.jpg",
+            "data/images/2024/scan.tiff",
+            "./data/a.txt",
+        ] {
+            assert_eq!(check_manifest_path(path, ManifestScope::Payloads), Ok(()));
+        }
+        for path in ["bag-info.txt", "metadata/marc.xml"] {
+            assert_eq!(check_manifest_path(path, ManifestScope::Tags), Ok(()));
+        }
+
+        for (path, error) in [
+            ("/etc/passwd", PayloadError::AbsolutePath),
+            ("\\\\server\\share\\data.txt", PayloadError::AbsolutePath),
+            ("C:\\Windows\\win.ini", PayloadError::AbsolutePath),
+            ("c:data/totebag.jpg", PayloadError::AbsolutePath),
+            ("data/../../etc/passwd", PayloadError::ParentDirectory),
+            ("data\\..\\..\\secret", PayloadError::ParentDirectory),
+            ("bagit.txt", PayloadError::OutsidePayloadDirectory),
+            ("data", PayloadError::OutsidePayloadDirectory),
+            ("data/", PayloadError::OutsidePayloadDirectory),
+            ("metadata/data/a.txt", PayloadError::OutsidePayloadDirectory),
+        ] {
+            assert_eq!(
+                check_manifest_path(path, ManifestScope::Payloads),
+                Err(error),
+                "failing on path `{path}`"
+            );
+        }
+        assert_eq!(
+            check_manifest_path("../bagit.txt", ManifestScope::Tags),
+            Err(PayloadError::ParentDirectory)
+        );
+        for path in ["tagmanifest-sha256.txt", "./TagManifest-MD5.txt"] {
+            assert_eq!(
+                check_manifest_path(path, ManifestScope::Tags),
+                Err(PayloadError::ListsTagManifest)
+            );
+        }
+        assert_eq!(
+            check_manifest_path("docs/tagmanifest-sha256.txt", ManifestScope::Tags),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn windows_separators() {
+        // Joined with `\` on Windows, always written with `/`
+        let path = Path::new("data").join("images").join("photo 100%.jpg");
+        assert_eq!(
+            manifest_path(&path, BagVersion::V1_0),
+            "data/images/photo 100%25.jpg"
+        );
+        assert_eq!(
+            manifest_path(&path, BagVersion::V0_97),
+            "data/images/photo 100%.jpg"
+        );
+
+        let resolve = |windows_separators| {
+            PathResolution {
+                windows_separators,
+                ..Default::default()
+            }
+            .apply(Cow::Borrowed("data\\images\\photo.jpg"))
+        };
+        assert_eq!(resolve(false), "data\\images\\photo.jpg");
+        assert_eq!(resolve(true), "data/images/photo.jpg");
+    }
 }
@@ -0,0 +1,78 @@
+//! Progress of validation of payloads and tag files, for bags taking long to read
+
+use std::path::Path;
+use std::sync::Arc;
+
+/// Observer of files being hashed while a bag is read, see [`ReadOptions::progress()`](crate::ReadOptions::progress)
+///
+/// Methods are called from the task reading the bag, they should return quickly.
+/// Every method does nothing by default, implement only the ones needed.
+///
+/// # Examples
+///
+/// ```
+/// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions, ReadProgress};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// #[derive(Default)]
+/// struct HashedBytes(AtomicU64);
+///
+/// impl ReadProgress for HashedBytes {
+///     fn bytes_hashed(&self, bytes: u64) {
+///         self.0.fetch_add(bytes, Ordering::Relaxed);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+/// # bagit_directory.push("tests/sample-bag");
+/// let hashed = Arc::new(HashedBytes::default());
+///
+/// // A progress bar would poll `hashed` from another task
+/// let options = ReadOptions::default().progress(hashed.clone());
+/// let bag = BagIt::read_existing_with_options(bagit_directory, &algorithm, options).await?;
+///
+/// assert_eq!(hashed.0.load(Ordering::Relaxed), 85766);
+/// # Ok(())
+/// # }
+/// ```
+pub trait ReadProgress: Send + Sync {
+    /// File at `path` on disk starts being hashed
+    fn file_started(&self, _path: &Path) {}
+
+    /// Some more `bytes` of a file were hashed
+    ///
+    /// Several files may be hashed at the same time, see [`ReadOptions::concurrency()`](crate::ReadOptions::concurrency).
+    fn bytes_hashed(&self, _bytes: u64) {}
+
+    /// File at `path` on disk was hashed, its checksum is then compared with the one of the manifest
+    fn file_completed(&self, _path: &Path) {}
+}
+
+/// Observers shared with the application, to read what they gathered while the bag is being read
+impl<T: ReadProgress + ?Sized> ReadProgress for Arc<T> {
+    fn file_started(&self, path: &Path) {
+        self.as_ref().file_started(path)
+    }
+
+    fn bytes_hashed(&self, bytes: u64) {
+        self.as_ref().bytes_hashed(bytes)
+    }
+
+    fn file_completed(&self, path: &Path) {
+        self.as_ref().file_completed(path)
+    }
+}
+
+/// Observer kept in options, shared with streams validating manifests
+#[derive(Clone)]
+pub(crate) struct Observer(pub(crate) Arc<dyn ReadProgress>);
+
+impl std::fmt::Debug for Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Observer")
+    }
+}
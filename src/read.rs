@@ -1,10 +1,25 @@
+use crate::cache::SharedCache;
+use crate::encoding::TagEncoding;
+#[cfg(feature = "generate")]
+use crate::error::GenerateError;
 use crate::error::PayloadError;
-use crate::manifest::Manifest;
+use crate::fetch::{read_fetch_file, FETCH_FILE_NAME};
+use crate::manifest::{Manifest, ReadContext};
 use crate::metadata::{Metadata, MetadataFile, MetadataFileError, KEY_ENCODING, KEY_VERSION};
-use crate::{BagIt, ChecksumAlgorithm};
-use digest::Digest;
-use std::path::Path;
-use tokio::fs;
+use crate::payload::{ManifestScope, PathNormalization, PathResolution, SymlinkPolicy};
+use crate::progress::{Observer, ReadProgress};
+use crate::walk;
+use crate::{
+    algorithm::NewHasher, Algorithm, AlgorithmRegistry, BagIt, BagVersion, ChecksumAlgorithm,
+    ChecksumCache, DynChecksumAlgorithm, Payload, ValidationOutcome, ValidationStatus,
+};
+use digest::{Digest, DynDigest};
+use futures::{future::BoxFuture, Stream, TryStreamExt};
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(thiserror::Error, Debug, PartialEq)]
 /// Possible errors when reading bag declaration file `bagit.txt`
@@ -21,6 +36,9 @@ pub enum BagDeclarationError {
     /// Wrongly formatted `bagit.txt`
     #[error("Wrong number of tags for `bagit.txt` file")]
     NumberTags,
+    /// Tag files have a legacy encoding, see [`ReadOptions::legacy_encodings()`]
+    #[error("Tag files are encoded in {0} instead of UTF-8")]
+    LegacyEncoding(&'static str),
 }
 
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -38,26 +56,463 @@ pub enum ReadError {
     /// Error related to `bag-info.txt`
     #[error("Bag info incorrect Oxum: {0}")]
     BagInfoOxum(&'static str),
+    /// Bag has no Payload-Oxum in `bag-info.txt` to validate, see [`BagIt::validate_oxum()`]
+    #[error("Bag info has no Payload-Oxum")]
+    MissingOxum,
     /// Failed to gather list of potential checksum files
-    #[error("Listing checksum files")]
-    ListChecksumFiles(std::io::ErrorKind),
+    #[error("Listing checksum files of `{}`: {1}", .0.display())]
+    ListChecksumFiles(PathBuf, std::io::ErrorKind),
     /// The algorithm asked is not present in the bag
     #[error("Requested algorithm is missing")]
     NotRequestedAlgorithm,
+    /// None of the manifests of the bag has an algorithm that is known, see [`BagIt::read_existing_auto()`]
+    #[error("No manifest with a known algorithm")]
+    NoKnownAlgorithm,
     /// Failed to open file
-    #[error("Failed to open file")]
-    OpenFile(std::io::ErrorKind),
+    #[error("Failed to open file `{}`: {1}", .0.display())]
+    OpenFile(PathBuf, std::io::ErrorKind),
     /// Failed to read one line
-    #[error("Failed to read a line in file")]
-    ReadLine(std::io::ErrorKind),
+    #[error("Failed to read a line in file `{}`: {1}", .0.display())]
+    ReadLine(PathBuf, std::io::ErrorKind),
     /// See [`PayloadError`]
     #[error("Failed to process a line in checksum file: {0}")]
     ProcessManifestLine(#[from] PayloadError),
+    /// Bag was read in strict mode, and something unusual was found, see [`ReadOptions::strict()`]
+    #[error("Strict mode: {0}")]
+    Strict(#[from] ReadWarning),
+    /// Failed to write repaired files of the bag, see [`BagIt::fix_oxum()`]
+    #[cfg(feature = "generate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+    #[error("Failed to repair bag: {0}")]
+    Repair(GenerateError),
+    /// Version declared in `bagit.txt` is not accepted, see [`ReadOptions::accepted_versions()`]
+    #[error("BagIt version {0} is not supported")]
+    UnsupportedVersion(BagVersion),
+    /// Line of `fetch.txt` must be: "\<url\> \<length\> \<relative path of payload\>", with payload inside the payload directory
+    #[error("Invalid line in `fetch.txt`")]
+    InvalidFetchLine,
+    /// Failed to list tag files in subdirectories of the bag
+    #[error("Listing tag files of `{}`: {1}", .0.display())]
+    ListTagFiles(PathBuf, std::io::ErrorKind),
+    /// Failed to look for symbolic links in the bag, see [`ReadOptions::symlinks()`]
+    #[error("Listing symbolic links of `{}`: {1}", .0.display())]
+    ListSymlinks(PathBuf, std::io::ErrorKind),
+    /// Payload directory `data` is missing, or is not a directory
+    #[error("Payload directory `data` is missing")]
+    MissingPayloadDirectory,
+    /// Failed to list files of payload directory `data`
+    #[error("Listing payload files of `{}`: {1}", .0.display())]
+    ListPayloadFiles(PathBuf, std::io::ErrorKind),
+    /// Path is listed more than once in a manifest or tag manifest, even with the same checksum
+    #[error("Path `{}` is listed more than once in manifest", .0.display())]
+    DuplicateManifestEntry(PathBuf),
+    /// Files of payload directory `data` are not listed in the manifest, the bag is invalid
+    #[error("Payloads not listed in manifest: {}", .0.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", "))]
+    UnlistedPayloads(Vec<PathBuf>),
+}
+
+/// What to do when Payload-Oxum of `bag-info.txt` does not match payloads
+#[derive(Clone, Copy, PartialEq)]
+enum OxumPolicy {
+    Validate,
+    #[cfg(feature = "generate")]
+    Repair,
+}
+
+#[derive(thiserror::Error, Clone, Debug, PartialEq)]
+/// Unusual things found in a bag that can still be read
+///
+/// See [`BagIt::warnings()`].
+pub enum ReadWarning {
+    /// Manifest or tag manifest was found with a name that differs by case from the one of the spec,
+    /// like `Manifest-SHA256.txt`: it usually comes from a case-insensitive filesystem
+    #[error("Name of manifest `{}` does not have the expected case", .0.display())]
+    ManifestNameCase(PathBuf),
+    /// Manifest or tag manifest could not be validated, its algorithm was not registered with [`ReadOptions::algorithm()`]
+    ///
+    /// Only reported when [`ReadOptions::all_manifests()`] is enabled.
+    #[error("Manifest `{}` was not validated, its algorithm is unknown", .0.display())]
+    UncheckedManifest(PathBuf),
+    /// Tag file of the bag is not listed in the tag manifest, its checksum was not verified
+    ///
+    /// Only reported for bags with a tag manifest, which should cover `bagit.txt`, `bag-info.txt`, manifests of payloads,
+    /// `fetch.txt` and other tag files of [`BagIt::tag_files()`].
+    #[error("Tag file `{}` is not listed in the tag manifest", .0.display())]
+    UnlistedTagFile(PathBuf),
+    /// Symbolic link of the bag was left out, files listed in manifests through it were not validated
+    ///
+    /// Only reported when reading with [`SymlinkPolicy::SkipWithWarning`], see [`ReadOptions::symlinks()`].
+    #[error("Symbolic link `{}` was skipped", .0.display())]
+    SkippedSymlink(PathBuf),
+    /// Tag manifest was ignored, tag files were not validated
+    ///
+    /// Only reported when [`ReadOptions::skip_tag_manifests()`] is enabled, it is not rejected in strict mode.
+    #[error("Tag manifest `{}` was skipped", .0.display())]
+    SkippedTagManifest(PathBuf),
+}
+
+/// Read and validate a bag with an algorithm chosen at runtime, see [`BagIt::read_existing_auto()`]
+pub(crate) type ReadWith =
+    for<'algo> fn(
+        Arc<Path>,
+        &'algo Algorithm,
+        ReadOptions,
+    ) -> BoxFuture<'algo, Result<BagIt<'static, 'algo>, ReadError>>;
+
+/// Monomorphized [`BagIt::read_existing_with_options()`] for `ChecksumAlgo`, to keep along with its algorithm
+pub(crate) fn read_with<ChecksumAlgo: Digest + Send + 'static>(
+    bag_it_directory: Arc<Path>,
+    algorithm: &Algorithm,
+    options: ReadOptions,
+) -> BoxFuture<'_, Result<BagIt<'static, '_>, ReadError>> {
+    Box::pin(BagIt::read::<ChecksumAlgo>(
+        bag_it_directory,
+        algorithm,
+        options,
+        OxumPolicy::Validate,
+    ))
+}
+
+#[derive(Clone, Debug)]
+/// Algorithm registered with [`ReadOptions::algorithm()`]
+struct RegisteredAlgorithm {
+    algorithm: Algorithm,
+    new_hasher: NewHasher,
+    read_with: ReadWith,
+}
+
+/// Algorithms always known by [`BagIt::read_existing_auto()`], after the ones of [`ReadOptions::algorithm()`]
+#[cfg(feature = "sha2")]
+static BUILTIN_ALGORITHMS: [(Algorithm, ReadWith); 2] = [
+    (Algorithm::Sha512, read_with::<sha2::Sha512>),
+    (Algorithm::Sha256, read_with::<sha2::Sha256>),
+];
+
+#[derive(Clone, Debug)]
+/// Options when reading a bagit container
+///
+/// See [`BagIt::read_existing_with_options()`].
+pub struct ReadOptions {
+    strict: bool,
+    accepted_versions: (Bound<BagVersion>, Bound<BagVersion>),
+    legacy_encodings: bool,
+    all_manifests: bool,
+    skip_tag_manifests: bool,
+    algorithms: Vec<RegisteredAlgorithm>,
+    path_resolution: PathResolution,
+    progress: Option<Observer>,
+    checksum_cache: Option<SharedCache>,
+    concurrency: usize,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            accepted_versions: (Bound::Unbounded, Bound::Unbounded),
+            legacy_encodings: false,
+            all_manifests: false,
+            skip_tag_manifests: false,
+            algorithms: vec![],
+            path_resolution: PathResolution::default(),
+            progress: None,
+            checksum_cache: None,
+            concurrency: std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+impl ReadOptions {
+    /// Reject bags instead of reading them with warnings, disabled by default
+    ///
+    /// The first warning is returned as [`ReadError::Strict`]. Tag manifests skipped on request
+    /// with [`ReadOptions::skip_tag_manifests()`] are not rejected.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Range of versions declared in `bagit.txt` to accept, all of them by default
+    ///
+    /// Bags with other versions are rejected with [`ReadError::UnsupportedVersion`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{BagVersion, ReadOptions};
+    /// // Only bags following RFC 8493, or later revisions
+    /// let options = ReadOptions::default().accepted_versions(BagVersion::V1_0..);
+    /// ```
+    pub fn accepted_versions(mut self, versions: impl RangeBounds<BagVersion>) -> Self {
+        self.accepted_versions = (
+            versions.start_bound().cloned(),
+            versions.end_bound().cloned(),
+        );
+        self
+    }
+
+    /// Read bags whose tag files have a legacy encoding declared in `bagit.txt`, disabled by default
+    ///
+    /// Tag files are transcoded to UTF-8 while they are read, nothing is written to the bag.
+    /// Only ISO-8859-1, and its subset US-ASCII, are supported: bags with other encodings are always rejected.
+    /// Paths of payloads in manifests are transcoded as well, files are expected under their UTF-8 names.
+    ///
+    /// Bags with legacy encodings are rejected with [`ReadError::BagDeclaration`] when disabled.
+    pub fn legacy_encodings(mut self, legacy_encodings: bool) -> Self {
+        self.legacy_encodings = legacy_encodings;
+        self
+    }
+
+    /// Validate every manifest and tag manifest of the bag, not only those of the requested algorithm, disabled by default
+    ///
+    /// RFC 8493 requires a valid bag to satisfy all of its manifests. Manifests are validated with algorithms registered with [`ReadOptions::algorithm()`],
+    /// those of other algorithms are reported with [`ReadWarning::UncheckedManifest`].
+    /// Files are read once for all other manifests, their checksums are computed with every algorithm listing them at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let sha512 = ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// // Bag must satisfy its SHA-512 manifest too, if it has one
+    /// let options = ReadOptions::default().all_manifests(true).algorithm(&sha512);
+    /// let bag = BagIt::read_existing_with_options(bagit_directory, &sha256, options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_manifests(mut self, all_manifests: bool) -> Self {
+        self.all_manifests = all_manifests;
+        self
+    }
+
+    /// Ignore tag manifests instead of validating them, disabled by default
+    ///
+    /// Payloads are still validated with the manifest, but tag files are not: useful when tag files such as `bag-info.txt`
+    /// are rewritten once a bag is received. Skipped tag manifests are reported with [`ReadWarning::SkippedTagManifest`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::ReadOptions;
+    /// // `bag-info.txt` is rewritten by the ingest pipeline
+    /// let options = ReadOptions::default().skip_tag_manifests(true);
+    /// ```
+    pub fn skip_tag_manifests(mut self, skip_tag_manifests: bool) -> Self {
+        self.skip_tag_manifests = skip_tag_manifests;
+        self
+    }
+
+    /// Register an algorithm to validate manifests other than the one of the requested algorithm, see [`ReadOptions::all_manifests()`]
+    pub fn algorithm<ChecksumAlgo: Digest + DynDigest + Send + 'static>(
+        mut self,
+        algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        self.algorithms.push(RegisteredAlgorithm {
+            algorithm: algorithm.algorithm().clone(),
+            new_hasher: algorithm.new_hasher(),
+            read_with: read_with::<ChecksumAlgo>,
+        });
+        self
+    }
+
+    /// Register every algorithm of `registry`, in its order, see [`ReadOptions::algorithm()`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, AlgorithmRegistry, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let registry = AlgorithmRegistry::empty()
+    ///     .register(&ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::custom("blake3")))
+    ///     .register(&ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256));
+    /// let options = ReadOptions::default().algorithms(&registry);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// let bag = BagIt::read_existing_auto(bagit_directory, &options).await?;
+    /// assert_eq!(bag.algorithm(), &Algorithm::Sha256);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn algorithms(mut self, registry: &AlgorithmRegistry) -> Self {
+        self.algorithms
+            .extend(registry.iter().map(|algorithm| RegisteredAlgorithm {
+                algorithm: algorithm.algorithm().clone(),
+                new_hasher: algorithm.new_hasher(),
+                read_with: algorithm.read_with(),
+            }));
+        self
+    }
+
+    /// Unicode normalization of paths of manifests before looking up their files, paths are used as is by default
+    ///
+    /// Payloads keep their normalized paths, which are the names of their files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{PathNormalization, ReadOptions};
+    /// // Bag stored on a filesystem of macOS, with decomposed names of files
+    /// let options = ReadOptions::default().path_normalization(PathNormalization::Nfd);
+    /// ```
+    pub fn path_normalization(mut self, path_normalization: PathNormalization) -> Self {
+        self.path_resolution.normalization = path_normalization;
+        self
+    }
+
+    /// Accept `\` as separator of paths of manifests, like `/`, disabled by default
+    ///
+    /// RFC 8493 requires `/`, but some tools running on Windows write `\` instead: such paths are only found on Windows.
+    /// When enabled, payloads of those manifests are looked up and kept with `/` on every platform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::ReadOptions;
+    /// // Manifest lists `data\images\photo.jpg`, found as `data/images/photo.jpg`
+    /// let options = ReadOptions::default().windows_separators(true);
+    /// ```
+    pub fn windows_separators(mut self, windows_separators: bool) -> Self {
+        self.path_resolution.windows_separators = windows_separators;
+        self
+    }
+
+    /// What to do with files of manifests reached through a symbolic link, they are followed by default
+    ///
+    /// With [`SymlinkPolicy::Reject`], the bag is rejected with [`PayloadError::SymlinkRejected`].
+    /// With [`SymlinkPolicy::SkipWithWarning`], those files are not validated and every symbolic link of the bag
+    /// is reported with [`ReadWarning::SkippedSymlink`]: Payload-Oxum is then not checked, as some payloads are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{ReadOptions, SymlinkPolicy};
+    /// // Bag received from an untrusted source
+    /// let options = ReadOptions::default().symlinks(SymlinkPolicy::Reject);
+    /// ```
+    pub fn symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.path_resolution.symlinks = symlinks;
+        self
+    }
+
+    /// Observe files being hashed with the manifest and the tag manifest of the requested algorithm, see [`ReadProgress`]
+    pub fn progress(mut self, progress: impl ReadProgress + 'static) -> Self {
+        self.progress = Some(Observer(Arc::new(progress)));
+        self
+    }
+
+    /// Look up checksums of files in `cache` before hashing them, and record those computed, see [`ChecksumCache`]
+    ///
+    /// Applies to the manifest and the tag manifest of the requested algorithm. Files found in the cache are not hashed,
+    /// they are not reported to [`ReadOptions::progress()`], unless manifests of other algorithms list them, see
+    /// [`ReadOptions::all_manifests()`]. A file modified without changing its size nor its modification time is not noticed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{FileChecksumCache, ReadOptions};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let cache = Arc::new(FileChecksumCache::load("/var/cache/fixity.txt").await);
+    /// let options = ReadOptions::default().checksum_cache(cache);
+    /// # }
+    /// ```
+    pub fn checksum_cache(mut self, cache: Arc<dyn ChecksumCache>) -> Self {
+        self.checksum_cache = Some(SharedCache(cache));
+        self
+    }
+
+    /// Maximum number of files hashed at the same time, the number of CPUs by default
+    ///
+    /// Files are hashed on blocking threads of the runtime, hashing several of them at once uses as many CPUs.
+    /// Payloads are still in order of the manifest. Reading several files at once is much faster on SSD and NVMe storage,
+    /// use `1` for spinning disks or network filesystems, where seeking between files is slow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::ReadOptions;
+    /// # use std::num::NonZeroUsize;
+    /// let options = ReadOptions::default().concurrency(NonZeroUsize::MIN);
+    /// ```
+    pub fn concurrency(mut self, concurrency: NonZeroUsize) -> Self {
+        self.concurrency = concurrency.get();
+        self
+    }
+
+    /// Algorithms registered with [`ReadOptions::algorithm()`], in order of registration
+    pub(crate) fn registered_algorithms(&self) -> impl Iterator<Item = (&Algorithm, NewHasher)> {
+        self.algorithms
+            .iter()
+            .map(|registered| (&registered.algorithm, registered.new_hasher))
+    }
+
+    /// Cache of [`ReadOptions::checksum_cache()`], for checksums computed with `algorithm`
+    fn cache_for(&self, algorithm: &Algorithm) -> Option<(SharedCache, Algorithm)> {
+        self.checksum_cache
+            .clone()
+            .map(|cache| (cache, algorithm.clone()))
+    }
+
+    /// Settings to read manifests of bag at `bag_it_directory` with, checksums are not looked up in a cache
+    pub(crate) fn read_context(
+        &self,
+        bag_it_directory: Arc<Path>,
+        encoding: TagEncoding,
+        version: BagVersion,
+        remote_paths: Arc<[PathBuf]>,
+    ) -> ReadContext {
+        ReadContext {
+            bag_it_directory,
+            encoding,
+            version,
+            resolution: self.path_resolution,
+            remote_paths,
+            progress: self.progress.clone(),
+            cache: None,
+            concurrency: self.concurrency,
+        }
+    }
+
+    /// How paths of manifests are resolved to files of the bag
+    pub(crate) fn path_resolution(&self) -> PathResolution {
+        self.path_resolution
+    }
+
+    /// Observer of files being hashed, see [`ReadOptions::progress()`]
+    pub(crate) fn observer(&self) -> Option<Observer> {
+        self.progress.clone()
+    }
+
+    /// Maximum number of files hashed at the same time, see [`ReadOptions::concurrency()`]
+    pub(crate) fn concurrency_limit(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Make sure tag files with `encoding` can be read
+    pub(crate) fn check_encoding(&self, encoding: TagEncoding) -> Result<(), ReadError> {
+        if encoding != TagEncoding::Utf8 && !self.legacy_encodings {
+            return Err(BagDeclarationError::LegacyEncoding(encoding.label()).into());
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, 'algo> BagIt<'a, 'algo> {
     /// Read and validate a bagit container
     ///
+    /// Every file of payload directory `data` must be listed in the manifest, the bag is rejected with
+    /// [`ReadError::UnlistedPayloads`] otherwise.
+    ///
     /// # Examples
     ///
     /// ```
@@ -75,198 +530,2713 @@ impl<'a, 'algo> BagIt<'a, 'algo> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn read_existing<ChecksumAlgo: Digest + 'algo>(
+    pub async fn read_existing<ChecksumAlgo: Digest + Send + 'static>(
         bag_it_directory: impl AsRef<Path>,
         checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
     ) -> Result<BagIt<'a, 'algo>, ReadError> {
-        if !bag_it_directory.as_ref().is_dir() {
-            return Err(ReadError::NotDirectory);
-        }
+        Self::read_existing_with_options(
+            bag_it_directory,
+            checksum_algorithm,
+            ReadOptions::default(),
+        )
+        .await
+    }
+
+    /// Read and validate a bagit container, like [`BagIt::read_existing()`] with non-default options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// // Refuse bags that are readable, but not quite right
+    /// let options = ReadOptions::default().strict(true);
+    /// let bag_it = BagIt::read_existing_with_options(bagit_directory, &algorithm, options).await?;
+    /// assert!(bag_it.warnings().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_existing_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+        options: ReadOptions,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        Self::read::<ChecksumAlgo>(
+            bag_it_directory,
+            checksum_algorithm.algorithm(),
+            options,
+            OxumPolicy::Validate,
+        )
+        .await
+    }
+
+    /// Read and validate a bagit container, with an algorithm picked from the manifests it has
+    ///
+    /// Algorithms registered with [`ReadOptions::algorithm()`] are tried first, in order of registration.
+    /// With the `sha2` feature, SHA-512 and SHA-256 are always known, after registered algorithms.
+    /// The first one having a payload manifest in the bag is used, [`BagIt::algorithm()`] tells which one it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let blake3 = ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::custom("blake3"));
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let options = ReadOptions::default().algorithm(&blake3).algorithm(&sha256);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// // Bag has SHA-256 and SHA-512 manifests, but no BLAKE3 one
+    /// let bag = BagIt::read_existing_auto(bagit_directory, &options).await?;
+    /// assert_eq!(bag.algorithm(), &Algorithm::Sha256);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_existing_auto(
+        bag_it_directory: impl AsRef<Path>,
+        options: &'algo ReadOptions,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+
+        let available = Self::available_algorithms(&bag_it_directory).await?;
+
+        let registered = options
+            .algorithms
+            .iter()
+            .map(|registered| (&registered.algorithm, registered.read_with));
+        #[cfg(feature = "sha2")]
+        let registered = registered.chain(
+            BUILTIN_ALGORITHMS
+                .iter()
+                .map(|(algorithm, read_with)| (algorithm, *read_with)),
+        );
+
+        let (algorithm, read_with) = registered
+            .into_iter()
+            .find(|(algorithm, _)| available.has_payload_manifest(algorithm))
+            .ok_or(ReadError::NoKnownAlgorithm)?;
+
+        read_with(bag_it_directory, algorithm, options.clone()).await
+    }
+
+    /// Read and validate a bagit container, with an algorithm chosen at runtime
+    ///
+    /// Same as [`Self::read_existing()`], without naming the digest as a type parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, AlgorithmRegistry, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let registry = AlgorithmRegistry::empty()
+    ///     .register(&ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256))
+    ///     .register(&ChecksumAlgorithm::<sha2::Sha512>::new(Algorithm::Sha512));
+    ///
+    /// // Name of the algorithm from a configuration file
+    /// let algorithm = registry.get("sha512").ok_or("unknown algorithm")?;
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// let bag = BagIt::read_existing_dyn(bagit_directory, algorithm).await?;
+    /// assert_eq!(bag.algorithm(), &Algorithm::Sha512);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_existing_dyn(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo DynChecksumAlgorithm,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        Self::read_existing_dyn_with_options(
+            bag_it_directory,
+            checksum_algorithm,
+            ReadOptions::default(),
+        )
+        .await
+    }
+
+    /// Read and validate a bagit container with an algorithm chosen at runtime and `options`,
+    /// see [`Self::read_existing_dyn()`] and [`Self::read_existing_with_options()`]
+    pub async fn read_existing_dyn_with_options(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo DynChecksumAlgorithm,
+        options: ReadOptions,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        let read_with = checksum_algorithm.read_with();
+        read_with(
+            bag_it_directory.as_ref().into(),
+            checksum_algorithm.algorithm(),
+            options,
+        )
+        .await
+    }
+
+    /// Read and validate a bagit container whose Payload-Oxum is wrong, and repair it
+    ///
+    /// Some generators are known to write incorrect Payload-Oxum tags. When checksums of all payloads and tag files
+    /// are valid, the Oxum is computed again, `bag-info.txt` is rewritten and every tag manifest is updated with its own algorithm.
+    /// Nothing is written if the Oxum is correct, or if `bag-info.txt` does not have one.
+    ///
+    /// Tag manifests of other algorithms can only be updated when their algorithm is known by default, see [`AlgorithmRegistry`]:
+    /// the bag is left untouched with [`GenerateError::UnknownTagManifestAlgorithm`] otherwise, use [`Self::fix_oxum_with_options()`]
+    /// to register them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{error::ReadError, Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    ///
+    /// let bag = match BagIt::read_existing("/path/to/bag", &algorithm).await {
+    ///     Err(ReadError::BagInfoOxum(_)) => BagIt::fix_oxum("/path/to/bag", &algorithm).await?,
+    ///     bag => bag?,
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "generate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+    pub async fn fix_oxum<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        Self::fix_oxum_with_options(bag_it_directory, checksum_algorithm, ReadOptions::default())
+            .await
+    }
+
+    /// Read and validate a bagit container whose Payload-Oxum is wrong, and repair it like [`Self::fix_oxum()`] with non-default options
+    ///
+    /// Algorithms registered with [`ReadOptions::algorithm()`] are used to update tag manifests of other algorithms.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sha256 = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let blake3 = ChecksumAlgorithm::<blake3::Hasher>::new(Algorithm::custom("blake3"));
+    ///
+    /// // Bag has `tagmanifest-sha256.txt` and `tagmanifest-blake3.txt`
+    /// let options = ReadOptions::default().algorithm(&blake3);
+    /// let bag = BagIt::fix_oxum_with_options("/path/to/bag", &sha256, options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "generate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+    pub async fn fix_oxum_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+        options: ReadOptions,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        Self::read::<ChecksumAlgo>(
+            bag_it_directory,
+            checksum_algorithm.algorithm(),
+            options,
+            OxumPolicy::Repair,
+        )
+        .await
+    }
+
+    async fn read<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        algorithm: &'algo Algorithm,
+        options: ReadOptions,
+        oxum_policy: OxumPolicy,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+        let (version, encoding) =
+            Self::read_accepted_declaration(&bag_it_directory, &options).await?;
+        Self::check_payload_directory(&bag_it_directory).await?;
+        let bag_info = Self::read_bag_info(&bag_it_directory, encoding).await?;
+
+        // Optional if present: payloads to fetch, they may be missing from the bag
+        let fetch_items = read_fetch_file(&bag_it_directory, encoding, version).await?;
+        let remote_paths: Arc<[PathBuf]> = fetch_items
+            .iter()
+            .map(|item| item.relative_path().to_path_buf())
+            .collect();
+
+        // Find manifests in directory
+        let mut warnings = vec![];
+        let (manifest, tag_manifest) =
+            Manifest::find_manifests(&bag_it_directory, algorithm, &mut warnings).await?;
+
+        // Tag manifests are ignored, when asked to
+        let mut skipped_tag_manifests = vec![];
+        let tag_manifest = match tag_manifest {
+            Some(tag_manifest) if options.skip_tag_manifests => {
+                skipped_tag_manifests.push(PathBuf::from(tag_manifest));
+                None
+            }
+            tag_manifest => tag_manifest,
+        };
+
+        // Symbolic links are left out of validation, when asked to
+        let skipped_symlinks = match options.path_resolution.symlinks {
+            SymlinkPolicy::SkipWithWarning => walk::list_symlinks(&bag_it_directory)
+                .await
+                .map_err(|e| ReadError::ListSymlinks(bag_it_directory.to_path_buf(), e.kind()))?,
+            _ => vec![],
+        };
+        warnings.extend(
+            skipped_symlinks
+                .iter()
+                .map(|symlink| ReadWarning::SkippedSymlink(symlink.clone())),
+        );
+
+        // Manifests of other algorithms are validated along with the one of requested algorithm, when asked to
+        let mut other_manifests = vec![];
+        if options.all_manifests {
+            for (other_manifest, other_algorithm) in Manifest::find_all(&bag_it_directory).await? {
+                if other_algorithm.eq_ignore_ascii_case(algorithm.name()) {
+                    continue;
+                }
+                if options.skip_tag_manifests && other_manifest.scope() == ManifestScope::Tags {
+                    skipped_tag_manifests.push(other_manifest.into());
+                    continue;
+                }
+
+                match options.algorithms.iter().find(|registered| {
+                    registered
+                        .algorithm
+                        .name()
+                        .eq_ignore_ascii_case(&other_algorithm)
+                }) {
+                    Some(RegisteredAlgorithm { new_hasher, .. }) => {
+                        other_manifests.push((other_manifest, *new_hasher))
+                    }
+                    None => warnings.push(ReadWarning::UncheckedManifest(other_manifest.into())),
+                }
+            }
+        }
+        if let Some(warning) = warnings.first().filter(|_| options.strict) {
+            return Err(ReadError::Strict(warning.clone()));
+        }
+
+        // Get and validate payloads from manifest of requested checksum algorithm,
+        // along with checksums from tag manifest: they cover different files, so both are verified at the same time
+        let manifest = manifest.ok_or(ReadError::NotRequestedAlgorithm)?;
+        let tag_manifest_found = tag_manifest.is_some();
+        let context = ReadContext {
+            cache: options.cache_for(algorithm),
+            ..options.read_context(bag_it_directory.clone(), encoding, version, remote_paths)
+        };
+        let (payloads, tag_items) = if other_manifests.is_empty() {
+            futures::try_join!(
+                manifest.get_validate_payloads::<ChecksumAlgo>(&context),
+                async {
+                    // Optional if present
+                    match tag_manifest {
+                        Some(tag_manifest) => {
+                            tag_manifest
+                                .get_validate_payloads::<ChecksumAlgo>(&context)
+                                .await
+                        }
+                        None => Ok(vec![]),
+                    }
+                }
+            )?
+        } else {
+            // Every file is read once for all algorithms
+            Manifest::validate_payloads_with::<ChecksumAlgo>(
+                manifest,
+                tag_manifest,
+                other_manifests,
+                &context,
+                &skipped_symlinks,
+            )
+            .await?
+        };
+
+        // Every file of payload directory must be in the manifest, except skipped symbolic links
+        let listed = payloads.iter().map(|payload| payload.relative_path());
+        let mut unlisted = Self::unlisted_payloads(&bag_it_directory, listed).await?;
+        unlisted.retain(|file| {
+            !skipped_symlinks
+                .iter()
+                .any(|symlink| file.starts_with(symlink))
+        });
+        if !unlisted.is_empty() {
+            return Err(ReadError::UnlistedPayloads(unlisted));
+        }
+
+        // Supplementary tag files, like `metadata/marc.xml`: those listed in the tag manifest were verified with it
+        let tag_files = walk::list_tag_files(&bag_it_directory)
+            .await
+            .map_err(|e| ReadError::ListTagFiles(bag_it_directory.to_path_buf(), e.kind()))?;
+        if tag_manifest_found {
+            // Tag files required or reserved by the spec, when the bag has them
+            let mut covered = vec![PathBuf::from("bagit.txt")];
+            if bag_info.is_some() {
+                covered.push("bag-info.txt".into());
+            }
+            let mut payload_manifests = Manifest::find_all(&bag_it_directory)
+                .await?
+                .into_iter()
+                .filter(|(manifest, _)| manifest.scope() == ManifestScope::Payloads)
+                .filter_map(|(manifest, _)| manifest.as_ref().file_name().map(PathBuf::from))
+                .collect::<Vec<_>>();
+            payload_manifests.sort();
+            covered.extend(payload_manifests);
+            if !fetch_items.is_empty() {
+                covered.push(FETCH_FILE_NAME.into());
+            }
+
+            warnings.extend(
+                covered
+                    .iter()
+                    .chain(tag_files.iter())
+                    .filter(|file| !tag_items.iter().any(|item| item.relative_path() == *file))
+                    .map(|file| ReadWarning::UnlistedTagFile(file.clone())),
+            );
+            if let Some(warning) = warnings.first().filter(|_| options.strict) {
+                return Err(ReadError::Strict(warning.clone()));
+            }
+        }
+        warnings.extend(
+            skipped_tag_manifests
+                .into_iter()
+                .map(ReadWarning::SkippedTagManifest),
+        );
+
+        // Optional if present: original files of compressed payloads
+        #[cfg(feature = "zstd")]
+        let payloads = {
+            let mut payloads = payloads;
+            crate::compression::read_compressed_payloads(&bag_it_directory, &mut payloads).await?;
+            payloads
+        };
+
+        // Optional if present: validate number of payload files and total file size,
+        // it can only match once every payload of `fetch.txt` is in the bag, and no symbolic link was skipped
+        let octet_count = payloads.iter().map(|payload| payload.bytes()).sum();
+        let fetched = fetch_items.iter().all(|item| {
+            payloads
+                .iter()
+                .any(|payload| payload.relative_path() == item.relative_path())
+        });
+        let repair = match Self::check_oxum(bag_info.as_ref(), payloads.len(), octet_count) {
+            _ if !fetched || !skipped_symlinks.is_empty() => false,
+            Err(e) if oxum_policy == OxumPolicy::Validate => return Err(e),
+            oxum => oxum.is_err(),
+        };
+
+        // Get tags from bag info
+        let mut tags: Vec<Metadata> = bag_info
+            .map(|file| file.consume_tags().into_iter().collect())
+            .unwrap_or_default();
+
+        // Everything else is valid, replace wrong Oxum
+        if repair {
+            for tag in tags.iter_mut() {
+                if let Metadata::PayloadOctetStreamSummary { .. } = tag {
+                    *tag = Metadata::PayloadOctetStreamSummary {
+                        stream_count: payloads.len(),
+                        octet_count,
+                    };
+                }
+            }
+        }
+
+        let bag = BagIt {
+            path: bag_it_directory,
+            version,
+            items: payloads,
+            octet_count,
+            checksum_algorithm: algorithm,
+            tags,
+            tag_files: tag_files
+                .into_iter()
+                .map(PathBuf::into_boxed_path)
+                .collect(),
+            tag_items,
+            fetch_items,
+            validation: Some(ValidationStatus::now(algorithm, ValidationOutcome::Valid)),
+            warnings,
+            #[cfg(feature = "generate")]
+            additional_manifests: vec![],
+            #[cfg(feature = "generate")]
+            known_algorithms: options
+                .registered_algorithms()
+                .map(|(algorithm, new_hasher)| (algorithm.clone(), new_hasher))
+                .collect(),
+            #[cfg(feature = "generate")]
+            symlinks: SymlinkPolicy::Follow,
+            #[cfg(feature = "generate")]
+            add_mode: crate::generate::AddMode::Copy,
+            #[cfg(feature = "generate")]
+            collisions: crate::generate::CollisionPolicy::Overwrite,
+            #[cfg(feature = "generate")]
+            skipped_symlinks: vec![],
+            #[cfg(feature = "generate")]
+            stale_manifests: vec![],
+            #[cfg(feature = "generate")]
+            written_manifests: None,
+            #[cfg(feature = "generate")]
+            created_files: vec![],
+        };
+
+        #[cfg(feature = "generate")]
+        if repair {
+            let mut bag = bag;
+            bag.rewrite_bag_info::<ChecksumAlgo>()
+                .await
+                .map_err(ReadError::Repair)?;
+            return Ok(bag);
+        }
+
+        Ok(bag)
+    }
+
+    /// Unusual things found when the bag was read, that did not prevent reading it
+    ///
+    /// Always empty for bags that were not read with [`BagIt::read_existing()`] or [`BagIt::read_existing_with_options()`].
+    pub fn warnings(&self) -> &[ReadWarning] {
+        &self.warnings
+    }
+
+    /// Read a bagit container, validating its payloads lazily
+    ///
+    /// Tag files are validated right away, then payloads are streamed from the manifest and validated one by one as the stream is polled.
+    /// Nothing is kept in memory once a payload is yielded, making it suitable for bags with a huge number of payloads.
+    ///
+    /// If `bag-info.txt` has a Payload-Oxum, it is checked after the last payload: the stream then ends with an error if it does not match.
+    /// Payloads listed in `fetch.txt` that are not in the bag yet are skipped, the Payload-Oxum is not checked for such bags.
+    /// Files of payload directory `data` missing from the manifest are not looked for, see [`BagIt::check_completeness()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// use futures::TryStreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// let payloads = BagIt::read_existing_lazy(bagit_directory, &algorithm).await?;
+    ///
+    /// // Sum sizes of payloads without holding them all in memory
+    /// let total_bytes = payloads
+    ///     .try_fold(0, |sum, payload| async move { Ok(sum + payload.bytes()) })
+    ///     .await?;
+    /// assert_eq!(total_bytes, 85766);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_existing_lazy<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<impl Stream<Item = Result<Payload<'static>, ReadError>>, ReadError> {
+        Self::read_existing_lazy_with_options(
+            bag_it_directory,
+            checksum_algorithm,
+            ReadOptions::default(),
+        )
+        .await
+    }
+
+    /// Read a bagit container, validating its payloads lazily like [`BagIt::read_existing_lazy()`] with non-default options
+    ///
+    /// Options apply like with [`BagIt::read_existing_with_options()`], but [`ReadOptions::all_manifests()`]: only the manifest
+    /// of requested algorithm is validated. Payloads are validated one at a time, [`ReadOptions::concurrency()`] only applies to tag files.
+    /// With [`SymlinkPolicy::SkipWithWarning`], payloads going through a symbolic link are left out of the stream
+    /// and the Payload-Oxum is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{error::ReadError, Algorithm, BagIt, BagVersion, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    ///
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag/");
+    /// // Sample bag follows version 1.0 of the spec
+    /// let options = ReadOptions::default().accepted_versions(..BagVersion::V1_0);
+    /// let payloads = BagIt::read_existing_lazy_with_options(bagit_directory, &algorithm, options).await;
+    /// assert!(matches!(payloads, Err(ReadError::UnsupportedVersion(_))));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_existing_lazy_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+        options: ReadOptions,
+    ) -> Result<impl Stream<Item = Result<Payload<'static>, ReadError>>, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+        let (version, encoding) =
+            Self::read_accepted_declaration(&bag_it_directory, &options).await?;
+        Self::check_payload_directory(&bag_it_directory).await?;
+        let bag_info = Self::read_bag_info(&bag_it_directory, encoding).await?;
+        let remote_paths: Arc<[PathBuf]> = read_fetch_file(&bag_it_directory, encoding, version)
+            .await?
+            .iter()
+            .map(|item| item.relative_path().to_path_buf())
+            .collect();
+
+        // Find manifests in directory, there is no bag to report warnings with but they are refused when strict
+        let mut warnings = vec![];
+        let algorithm = checksum_algorithm.algorithm();
+        let (manifest, tag_manifest) =
+            Manifest::find_manifests(&bag_it_directory, algorithm, &mut warnings).await?;
+        if let Some(warning) = warnings.first().filter(|_| options.strict) {
+            return Err(ReadError::Strict(warning.clone()));
+        }
+        let manifest = manifest.ok_or(ReadError::NotRequestedAlgorithm)?;
+        let tag_manifest = tag_manifest.filter(|_| !options.skip_tag_manifests);
+
+        // Payloads of `fetch.txt` may be missing, or symbolic links skipped: the Oxum can not be validated then
+        let bag_info = bag_info.filter(|_| {
+            remote_paths.is_empty()
+                && options.path_resolution.symlinks != SymlinkPolicy::SkipWithWarning
+        });
+        let context = ReadContext {
+            cache: options.cache_for(algorithm),
+            ..options.read_context(bag_it_directory, encoding, version, remote_paths)
+        };
+
+        // Optional if present: validate checksums from tag manifest
+        if let Some(tag_manifest) = tag_manifest {
+            tag_manifest
+                .get_validate_payloads::<ChecksumAlgo>(&context)
+                .await?;
+        }
+
+        let payloads = manifest
+            .stream_validate_payloads::<ChecksumAlgo>(context)
+            .await?;
+
+        // Keep track of payloads going through the stream, to validate Oxum at the end
+        Ok(futures::stream::try_unfold(
+            (Box::pin(payloads), bag_info, 0, 0),
+            |(mut payloads, bag_info, stream_count, octet_count)| async move {
+                match payloads.try_next().await? {
+                    Some(payload) => {
+                        let bytes = payload.bytes();
+                        Ok(Some((
+                            payload,
+                            (payloads, bag_info, stream_count + 1, octet_count + bytes),
+                        )))
+                    }
+                    None => {
+                        Self::check_oxum(bag_info.as_ref(), stream_count, octet_count)?;
+                        Ok(None)
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Read a bagit container without validating it, checksums of manifests are trusted
+    ///
+    /// `bagit.txt`, `bag-info.txt`, `fetch.txt` and manifests are parsed, but no file is hashed: payloads and tag files
+    /// only have to exist for their sizes. It is quick enough to list and browse bags, verification can be deferred to when
+    /// payloads are used, with [`Payload::verify()`] or [`BagIt::validate()`].
+    ///
+    /// The bag was never validated, see [`BagIt::last_validation()`]. Payload-Oxum and files of payload directory `data`
+    /// missing from the manifest are not checked either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let bag = BagIt::read_unvalidated(bagit_directory, &algorithm).await?;
+    /// assert!(!bag.is_verified());
+    ///
+    /// // Verify payloads when they are needed
+    /// for payload in bag.payload_items().filter(|payload| payload.extension() == Some("csv")) {
+    ///     payload.verify::<sha2::Sha256>().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_unvalidated<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        Self::read_unvalidated_with_options(
+            bag_it_directory,
+            checksum_algorithm,
+            ReadOptions::default(),
+        )
+        .await
+    }
+
+    /// Read a bagit container without validating it like [`BagIt::read_unvalidated()`], with non-default options
+    ///
+    /// Options apply like with [`BagIt::read_existing_with_options()`], but those about hashing files:
+    /// [`ReadOptions::all_manifests()`], [`ReadOptions::concurrency()`], [`ReadOptions::progress()`] and the checksum cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, BagVersion, ChecksumAlgorithm, ReadOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// // Only open bags following RFC 8493
+    /// let options = ReadOptions::default().accepted_versions(BagVersion::V1_0..);
+    /// let bag = BagIt::read_unvalidated_with_options(bagit_directory, &algorithm, options).await?;
+    /// assert_eq!(bag.version(), BagVersion::V1_0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_unvalidated_with_options<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+        options: ReadOptions,
+    ) -> Result<BagIt<'a, 'algo>, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+        let (version, encoding) =
+            Self::read_accepted_declaration(&bag_it_directory, &options).await?;
+        Self::check_payload_directory(&bag_it_directory).await?;
+        let bag_info = Self::read_bag_info(&bag_it_directory, encoding).await?;
+        let fetch_items = read_fetch_file(&bag_it_directory, encoding, version).await?;
+        let remote_paths = fetch_items
+            .iter()
+            .map(|item| item.relative_path().to_path_buf())
+            .collect();
+        let context =
+            options.read_context(bag_it_directory.clone(), encoding, version, remote_paths);
+
+        let mut warnings = vec![];
+        let (manifest, tag_manifest) = Manifest::find_manifests(
+            &bag_it_directory,
+            checksum_algorithm.algorithm(),
+            &mut warnings,
+        )
+        .await?;
+        if let Some(warning) = warnings.first().filter(|_| options.strict) {
+            return Err(ReadError::Strict(warning.clone()));
+        }
+        let manifest = manifest.ok_or(ReadError::NotRequestedAlgorithm)?;
+        let tag_manifest = match tag_manifest {
+            Some(tag_manifest) if options.skip_tag_manifests => {
+                warnings.push(ReadWarning::SkippedTagManifest(tag_manifest.into()));
+                None
+            }
+            tag_manifest => tag_manifest,
+        };
+
+        let payloads = manifest
+            .read_payloads_unvalidated(&context, <ChecksumAlgo as Digest>::output_size())
+            .await?;
+        let tag_items = match tag_manifest {
+            Some(tag_manifest) => {
+                tag_manifest
+                    .read_payloads_unvalidated(&context, <ChecksumAlgo as Digest>::output_size())
+                    .await?
+            }
+            None => vec![],
+        };
+
+        let tag_files = walk::list_tag_files(&bag_it_directory)
+            .await
+            .map_err(|e| ReadError::ListTagFiles(bag_it_directory.to_path_buf(), e.kind()))?;
+
+        // Optional if present: original files of compressed payloads
+        #[cfg(feature = "zstd")]
+        let payloads = {
+            let mut payloads = payloads;
+            crate::compression::read_compressed_payloads(&bag_it_directory, &mut payloads).await?;
+            payloads
+        };
+
+        Ok(BagIt {
+            path: bag_it_directory,
+            version,
+            octet_count: payloads.iter().map(|payload| payload.bytes()).sum(),
+            items: payloads,
+            checksum_algorithm: checksum_algorithm.algorithm(),
+            tags: bag_info
+                .map(|file| file.consume_tags().into_iter().collect())
+                .unwrap_or_default(),
+            tag_files: tag_files
+                .into_iter()
+                .map(PathBuf::into_boxed_path)
+                .collect(),
+            tag_items,
+            fetch_items,
+            validation: None,
+            warnings,
+            #[cfg(feature = "generate")]
+            additional_manifests: vec![],
+            #[cfg(feature = "generate")]
+            known_algorithms: options
+                .registered_algorithms()
+                .map(|(algorithm, new_hasher)| (algorithm.clone(), new_hasher))
+                .collect(),
+            #[cfg(feature = "generate")]
+            symlinks: SymlinkPolicy::Follow,
+            #[cfg(feature = "generate")]
+            add_mode: crate::generate::AddMode::Copy,
+            #[cfg(feature = "generate")]
+            collisions: crate::generate::CollisionPolicy::Overwrite,
+            #[cfg(feature = "generate")]
+            skipped_symlinks: vec![],
+            #[cfg(feature = "generate")]
+            stale_manifests: vec![],
+            #[cfg(feature = "generate")]
+            written_manifests: None,
+            #[cfg(feature = "generate")]
+            created_files: vec![],
+        })
+    }
+
+    /// Files of payload directory `data` that are not in `listed`, relative to the bag and sorted
+    pub(crate) async fn unlisted_payloads(
+        bag_it_directory: &Path,
+        listed: impl IntoIterator<Item = &Path>,
+    ) -> Result<Vec<PathBuf>, ReadError> {
+        let files = match walk::list_files(&bag_it_directory.join("data")).await {
+            Ok(files) => files,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => {
+                return Err(ReadError::ListPayloadFiles(
+                    bag_it_directory.join("data"),
+                    e.kind(),
+                ))
+            }
+        };
+
+        let listed = listed.into_iter().collect::<HashSet<_>>();
+        Ok(files
+            .into_iter()
+            .map(|file| Path::new("data").join(file))
+            .filter(|file| !listed.contains(file.as_path()))
+            .collect())
+    }
+
+    /// Read bag declaration `bagit.txt` like [`BagIt::read_declaration()`], making sure `options` accept
+    /// its version and the encoding of tag files
+    pub(crate) async fn read_accepted_declaration(
+        bag_it_directory: &Path,
+        options: &ReadOptions,
+    ) -> Result<(BagVersion, TagEncoding), ReadError> {
+        let (version, encoding) = Self::read_declaration(bag_it_directory).await?;
+        if !options.accepted_versions.contains(&version) {
+            return Err(ReadError::UnsupportedVersion(version));
+        }
+        options.check_encoding(encoding)?;
+
+        Ok((version, encoding))
+    }
+
+    /// Read and check bag declaration `bagit.txt`, returning declared version and encoding of tag files
+    pub(crate) async fn read_declaration(
+        bag_it_directory: &Path,
+    ) -> Result<(BagVersion, TagEncoding), ReadError> {
+        if !bag_it_directory.is_dir() {
+            return Err(ReadError::NotDirectory);
+        }
+
+        // Read `bagit.txt`
+        let path_bagit = bag_it_directory.join("bagit.txt");
+        if !path_bagit.exists() {
+            return Err(ReadError::BagDeclaration(BagDeclarationError::Missing));
+        }
+        let bagit_file = MetadataFile::read(path_bagit, TagEncoding::Utf8)
+            .await
+            .map_err(|e| ReadError::BagDeclaration(e.into()))?;
+        let mut bagit_file = bagit_file.tags();
+
+        // Expecting first tag to be BagIt version
+        let version = match bagit_file.next() {
+            Some(Metadata::BagitVersion { major, minor }) => BagVersion::new(*major, *minor),
+            _ => return Err(BagDeclarationError::Tag(KEY_VERSION).into()),
+        };
+
+        // Expecting second tag to be Encoding
+        let encoding = match bagit_file.next() {
+            Some(Metadata::Encoding(encoding)) => *encoding,
+            _ => return Err(BagDeclarationError::Tag(KEY_ENCODING).into()),
+        };
+
+        // Expecting no more tags
+        if bagit_file.next().is_some() {
+            return Err(BagDeclarationError::NumberTags.into());
+        }
+
+        Ok((version, encoding))
+    }
+
+    /// Make sure the bag has a payload directory `data`, even when it is empty
+    pub(crate) async fn check_payload_directory(bag_it_directory: &Path) -> Result<(), ReadError> {
+        let payload_directory = bag_it_directory.join("data");
+        match tokio::fs::metadata(&payload_directory).await {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(ReadError::MissingPayloadDirectory),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ReadError::MissingPayloadDirectory)
+            }
+            // Payload directory may be there, but can't be accessed
+            Err(e) => Err(ReadError::ListPayloadFiles(payload_directory, e.kind())),
+        }
+    }
+
+    /// Get optional `bag-info.txt`
+    pub(crate) async fn read_bag_info(
+        bag_it_directory: &Path,
+        encoding: TagEncoding,
+    ) -> Result<Option<MetadataFile>, ReadError> {
+        let path_baginfo = bag_it_directory.join("bag-info.txt");
+        if !path_baginfo.exists() {
+            return Ok(None);
+        }
+
+        MetadataFile::read(path_baginfo, encoding)
+            .await
+            .map(Some)
+            .map_err(ReadError::BagInfo)
+    }
+
+    /// Compare number of payload files and total file size with Oxum of `bag-info.txt`, if present
+    pub(crate) fn check_oxum(
+        bag_info: Option<&MetadataFile>,
+        payloads_count: usize,
+        payloads_bytes: u64,
+    ) -> Result<(), ReadError> {
+        let Some(bag_info) = bag_info else {
+            return Ok(());
+        };
+
+        for tag in bag_info.tags() {
+            if let Metadata::PayloadOctetStreamSummary {
+                octet_count,
+                stream_count,
+            } = tag
+            {
+                if *stream_count != payloads_count {
+                    // Expected number of payloads does not match
+                    return Err(ReadError::BagInfoOxum("stream_count"));
+                }
+
+                if *octet_count != payloads_bytes {
+                    // Expected total bytes does not match
+                    return Err(ReadError::BagInfoOxum("octet_count"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    #[cfg(feature = "generate")]
+    use crate::{error::GenerateError, Checksum, ReadWarning};
+    use crate::{
+        error::{PayloadError, ReadError},
+        metadata::Metadata,
+        Algorithm, BagIt, BagVersion, ChecksumAlgorithm, PathNormalization, Payload, ReadOptions,
+    };
+    use futures::TryStreamExt;
+    #[cfg(feature = "date")]
+    use jiff::civil::Date;
+    use md5::Md5;
+    use sha2::Sha256;
+
+    #[tokio::test]
+    async fn bag_with_date_sha256() {
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let bag = BagIt::read_existing(&bagit_directory, &algo).await.unwrap();
+
+        let expected = BagIt::from_existing_items(
+            bagit_directory,
+            vec![
+                Payload::test_payload(
+                    "data/bagit.md",
+                    "eccdbbade12ba878af8f2140cb00c914f427405a987de2670e5c3014faf59f8e",
+                    6302,
+                ),
+                Payload::test_payload(
+                    "data/paper_bag.jpg",
+                    "2b22a8fd0dc46cbdc7a67b6cf588a03a8dd6f8ea23ce0b02e921ca5d79930bb2",
+                    19895,
+                ),
+                Payload::test_payload(
+                    "data/rfc8493.txt",
+                    "4964147d2e6e16442d4a6dbfbe68178a8f33c3e791c06d68a8b33f51ad821537",
+                    48783,
+                ),
+                Payload::test_payload(
+                    "data/sources.csv",
+                    "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72",
+                    369,
+                ),
+                Payload::test_payload(
+                    "data/totebag.jpg",
+                    "38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31",
+                    10417,
+                ),
+            ],
+            algo.algorithm(),
+            vec![
+                #[cfg(feature = "date")]
+                Metadata::BaggingDate(Date::new(2024, 7, 11).unwrap()),
+                #[cfg(not(feature = "date"))]
+                Metadata::Custom {
+                    key: "Bagging-Date".into(),
+                    value: "2024-07-11".into(),
+                },
+                Metadata::PayloadOctetStreamSummary {
+                    octet_count: 85766,
+                    stream_count: 5,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(bag, expected);
+    }
+
+    #[tokio::test]
+    async fn basic_bag_wrong_algorithm_md5() {
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag/");
+
+        let algo = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        assert_eq!(
+            BagIt::read_existing(&bagit_directory, &algo).await,
+            Err(ReadError::NotRequestedAlgorithm)
+        );
+    }
+
+    #[tokio::test]
+    async fn lazy_bag_sha256() {
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let bag = BagIt::read_existing(&bagit_directory, &algo).await.unwrap();
+
+        let payloads = BagIt::read_existing_lazy(&bagit_directory, &algo)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            payloads.iter().collect::<Vec<_>>(),
+            bag.payload_items().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn lazy_bag_wrong_oxum() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Claim there is one more payload than there is, without a tag manifest to catch it
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "Payload-Oxum: 10417.2")
+            .await
+            .unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+
+        let mut payloads = Box::pin(
+            BagIt::read_existing_lazy(&temp_directory, &algo)
+                .await
+                .unwrap(),
+        );
+
+        // Payload is valid, but stream ends with wrong Oxum
+        assert!(matches!(payloads.try_next().await, Ok(Some(_))));
+        assert_eq!(
+            payloads.try_next().await,
+            Err(ReadError::BagInfoOxum("stream_count"))
+        );
+        assert_eq!(payloads.try_next().await, Ok(None));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn manifest_name_case() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        let expected = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert!(expected.warnings().is_empty());
+
+        // Like a bag coming from a case-insensitive filesystem
+        let renamed = temp_directory.join("TagManifest-SHA256.TXT");
+        tokio::fs::rename(temp_directory.join("tagmanifest-sha256.txt"), &renamed)
+            .await
+            .unwrap();
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag, expected);
+        assert_eq!(
+            bag.warnings(),
+            [ReadWarning::ManifestNameCase(renamed.clone())]
+        );
+
+        assert_eq!(
+            BagIt::read_existing_with_options(
+                &temp_directory,
+                &algo,
+                ReadOptions::default().strict(true)
+            )
+            .await,
+            Err(ReadError::Strict(ReadWarning::ManifestNameCase(renamed)))
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn uppercase_checksums() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+        let expected = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+
+        // Digests of tag manifest in uppercase, like some tools write them
+        let tag_manifest = temp_directory.join("tagmanifest-sha256.txt");
+        let contents = tokio::fs::read_to_string(&tag_manifest).await.unwrap();
+        let uppercase = contents
+            .lines()
+            .map(|line| {
+                let (checksum, path) = line.split_once(' ').unwrap();
+                format!("{} {path}\n", checksum.to_ascii_uppercase())
+            })
+            .collect::<String>();
+        assert_ne!(uppercase, contents);
+        tokio::fs::write(&tag_manifest, uppercase).await.unwrap();
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag, expected);
+
+        // Digests of manifest in mixed case, without a tag manifest covering it
+        tokio::fs::remove_file(&tag_manifest).await.unwrap();
+        let manifest = temp_directory.join("manifest-sha256.txt");
+        let contents = tokio::fs::read_to_string(&manifest).await.unwrap();
+        let mixed_case = contents
+            .lines()
+            .map(|line| {
+                let (checksum, path) = line.split_once(' ').unwrap();
+                let checksum = checksum
+                    .chars()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        if i % 2 == 0 {
+                            c.to_ascii_uppercase()
+                        } else {
+                            c
+                        }
+                    })
+                    .collect::<String>();
+                format!("{checksum} {path}\n")
+            })
+            .collect::<String>();
+        assert_ne!(mixed_case, contents);
+        tokio::fs::write(&manifest, mixed_case).await.unwrap();
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            bag.payload_items().collect::<Vec<_>>(),
+            expected.payload_items().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn fix_oxum() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Wrong Oxum with a matching tag manifest, like a buggy generator would write
+        let bag_info = temp_directory.join("bag-info.txt");
+        tokio::fs::write(&bag_info, "Payload-Oxum: 10416.1")
+            .await
+            .unwrap();
+        let tag_manifests = bag.tag_manifest_files().await.unwrap();
+        bag.update_tagmanifest_entry::<Sha256>("bag-info.txt", &tag_manifests)
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::BagInfoOxum("octet_count"))
+        );
+
+        let fixed = BagIt::fix_oxum(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(&bag_info).await.unwrap(),
+            "Payload-Oxum: 10417.1\n"
+        );
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Ok(fixed)
+        );
+
+        // Payloads must still be valid
+        tokio::fs::write(temp_directory.join("data/totebag.jpg"), "not a bag")
+            .await
+            .unwrap();
+        assert!(matches!(
+            BagIt::fix_oxum(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(_))
+        ));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn fix_oxum_all_tag_manifests() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Wrong Oxum with matching tag manifests of both algorithms
+        let bag_info = temp_directory.join("bag-info.txt");
+        tokio::fs::write(&bag_info, "Payload-Oxum: 10416.1")
+            .await
+            .unwrap();
+        let tag_manifests = bag.tag_manifest_files().await.unwrap();
+        assert_eq!(tag_manifests.len(), 2);
+        bag.update_tagmanifest_entry::<Sha256>("bag-info.txt", &tag_manifests)
+            .await
+            .unwrap();
+        let options = || ReadOptions::default().all_manifests(true).algorithm(&md5);
+        assert_eq!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options()).await,
+            Err(ReadError::BagInfoOxum("octet_count"))
+        );
+
+        // Algorithm of the other tag manifest is unknown, nothing is written
+        assert_eq!(
+            BagIt::fix_oxum(&temp_directory, &sha256).await,
+            Err(ReadError::Repair(
+                GenerateError::UnknownTagManifestAlgorithm(
+                    temp_directory.join("tagmanifest-md5.txt")
+                )
+            ))
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(&bag_info).await.unwrap(),
+            "Payload-Oxum: 10416.1"
+        );
+
+        // Every tag manifest is updated with its own algorithm
+        BagIt::fix_oxum_with_options(&temp_directory, &sha256, options())
+            .await
+            .unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(&bag_info).await.unwrap(),
+            "Payload-Oxum: 10417.1\n"
+        );
+        for tag_manifest in ["tagmanifest-sha256.txt", "tagmanifest-md5.txt"] {
+            let contents = tokio::fs::read_to_string(temp_directory.join(tag_manifest))
+                .await
+                .unwrap();
+            assert_eq!(
+                contents
+                    .lines()
+                    .filter(|line| line.ends_with(" bag-info.txt"))
+                    .count(),
+                1,
+                "{tag_manifest}"
+            );
+        }
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options())
+                .await
+                .is_ok()
+        );
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .algorithm(&sha256);
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &md5, options)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn tag_manifest_items() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("totebag.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(bag.tag_manifest_items().count(), 0);
+        bag.finalize::<Sha256>().await.unwrap();
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag
+                .tag_manifest_items()
+                .map(|item| item.relative_path().to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["bagit.txt", "bag-info.txt", "manifest-sha256.txt"]
+        );
+        assert!(read_bag.tag_manifest_items().eq(bag.tag_manifest_items()));
+
+        // Sample bag has no tag manifest
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag");
+        let sample_bag = BagIt::read_existing(&bagit_directory, &algo).await.unwrap();
+        assert_eq!(sample_bag.tag_manifest_items().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn accepted_versions() {
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let bag = BagIt::read_existing_with_options(
+            &bagit_directory,
+            &algo,
+            ReadOptions::default().accepted_versions(BagVersion::new(0, 97)..=BagVersion::V1_0),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bag.version(), BagVersion::V1_0);
+
+        assert_eq!(
+            BagIt::read_existing_with_options(
+                &bagit_directory,
+                &algo,
+                ReadOptions::default().accepted_versions(..BagVersion::V1_0),
+            )
+            .await,
+            Err(ReadError::UnsupportedVersion(BagVersion::V1_0))
+        );
+
+        // Every way of reading a bag goes through the same checks
+        assert_eq!(
+            BagIt::read_unvalidated_with_options(
+                &bagit_directory,
+                &algo,
+                ReadOptions::default().accepted_versions(..BagVersion::V1_0),
+            )
+            .await,
+            Err(ReadError::UnsupportedVersion(BagVersion::V1_0))
+        );
+        assert!(matches!(
+            BagIt::read_existing_lazy_with_options(
+                &bagit_directory,
+                &algo,
+                ReadOptions::default().accepted_versions(..BagVersion::V1_0),
+            )
+            .await,
+            Err(ReadError::UnsupportedVersion(BagVersion::V1_0))
+        ));
+        assert!(matches!(
+            BagIt::cross_validate(
+                &bagit_directory,
+                &ReadOptions::default()
+                    .algorithm(&algo)
+                    .accepted_versions(..BagVersion::V1_0),
+            )
+            .await,
+            Err(ReadError::UnsupportedVersion(BagVersion::V1_0))
+        ));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn legacy_encoding() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize_with_options::<Sha256>(
+            crate::FinalizeOptions::default().tag_files([crate::TagFile::BagInfo]),
+        )
+        .await
+        .unwrap();
+
+        // Bag made before version 1.0 of the spec, with a tag in ISO-8859-1
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 0.97\nTag-File-Character-Encoding: ISO-8859-1\n",
+        )
+        .await
+        .unwrap();
+        let mut bag_info = tokio::fs::read(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        bag_info.extend_from_slice(b"Source-Organization: Biblioth\xe8que nationale\n");
+        tokio::fs::write(temp_directory.join("bag-info.txt"), bag_info)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::BagDeclaration(
+                super::BagDeclarationError::LegacyEncoding("ISO-8859-1")
+            ))
+        );
+
+        let bag = BagIt::read_existing_with_options(
+            &temp_directory,
+            &algo,
+            ReadOptions::default().legacy_encodings(true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bag.version(), BagVersion::new(0, 97));
+        assert!(bag.tags.contains(&Metadata::SourceOrganization(
+            "Bibliothèque nationale".into()
+        )));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn version_0_97() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file_as::<Sha256>(source_directory.join("sources.csv"), "data/100%25.csv")
+            .await
+            .unwrap();
+        bag.finalize_with_options::<Sha256>(
+            crate::FinalizeOptions::default().tag_files([crate::TagFile::BagInfo]),
+        )
+        .await
+        .unwrap();
+
+        // Paths in manifests of bags before version 1.0 are not percent-encoded
+        let manifest =
+            "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72  data/100%25.csv\n";
+        tokio::fs::write(temp_directory.join("manifest-sha256.txt"), manifest)
+            .await
+            .unwrap();
+        assert!(matches!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(_))
+        ));
+
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 0.97\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.version(), BagVersion::V0_97);
+        assert_eq!(
+            bag.payload_items().next().unwrap().relative_path(),
+            std::path::Path::new("data/100%25.csv")
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn fetch_file() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Payload is listed in manifest, but left on a remote server
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_err());
+
+        tokio::fs::write(
+            temp_directory.join("fetch.txt"),
+            "https://example.com/totebag.jpg 10417 data/totebag.jpg\n",
+        )
+        .await
+        .unwrap();
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert_eq!(bag.fetch_items().count(), 1);
+        assert!(!bag.is_complete());
+        let pending = bag.pending_fetch_items().next().unwrap();
+        assert_eq!(pending.url(), "https://example.com/totebag.jpg");
+        assert_eq!(pending.length(), Some(10417));
+
+        let payloads: Vec<_> = BagIt::read_existing_lazy(&temp_directory, &algo)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(payloads.len(), 1);
+
+        // Once fetched, the bag is complete
+        tokio::fs::copy(
+            source_directory.join("totebag.jpg"),
+            temp_directory.join("data/totebag.jpg"),
+        )
+        .await
+        .unwrap();
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.payload_items().count(), 2);
+        assert!(bag.is_complete());
+
+        tokio::fs::write(
+            temp_directory.join("fetch.txt"),
+            "https://example.com/bagit.txt - bagit.txt\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::InvalidFetchLine)
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn all_manifests() {
+        use crate::error::PayloadError;
+        use sha2::Sha512;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let sha512 = ChecksumAlgorithm::<Sha512>::new(Algorithm::Sha512);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        // Same payloads, with manifests of both algorithms
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        let mut bag = BagIt::new_empty(&temp_directory, &sha512);
+        bag.add_file::<Sha512>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha512>().await.unwrap();
+
+        // Tag manifest of SHA-256 was written before the manifest of SHA-512 existed
+        let uncovered = ReadWarning::UnlistedTagFile("manifest-sha512.txt".into());
+        let bag = BagIt::read_existing(&temp_directory, &sha256)
+            .await
+            .unwrap();
+        assert_eq!(bag.warnings(), std::slice::from_ref(&uncovered));
+
+        let options = ReadOptions::default().all_manifests(true);
+        let bag = BagIt::read_existing_with_options(&temp_directory, &sha256, options.clone())
+            .await
+            .unwrap();
+        let mut unchecked = bag.warnings().to_vec();
+        unchecked.sort_by_key(|warning| warning.to_string());
+        assert_eq!(
+            unchecked,
+            [
+                ReadWarning::UncheckedManifest(temp_directory.join("manifest-sha512.txt")),
+                ReadWarning::UncheckedManifest(temp_directory.join("tagmanifest-sha512.txt")),
+                uncovered.clone(),
+            ]
+        );
+        assert!(matches!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options.strict(true)).await,
+            Err(ReadError::Strict(ReadWarning::UncheckedManifest(_)))
+        ));
+
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .algorithm(&sha512);
+        let bag = BagIt::read_existing_with_options(&temp_directory, &sha256, options.clone())
+            .await
+            .unwrap();
+        assert_eq!(bag.warnings(), [uncovered]);
+
+        // Other manifest is not satisfied by the payload
+        let manifest = temp_directory.join("manifest-sha512.txt");
+        let contents = tokio::fs::read_to_string(&manifest).await.unwrap();
+        tokio::fs::write(&manifest, contents.replacen('0', "1", 1))
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing(&temp_directory, &sha256).await.is_ok());
+        // Tag manifest of SHA-512 is validated first, it notices the change of the manifest
+        assert!(matches!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ChecksumDiffers { path, .. }
+            )) if path == std::path::Path::new("manifest-sha512.txt")
+        ));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn all_manifests_incomplete() {
+        use sha2::Sha512;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let sha512 = ChecksumAlgorithm::<Sha512>::new(Algorithm::Sha512);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&sha512).unwrap();
+        for file in ["sources.csv", "rfc8493.txt"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Tag manifests would notice the change of the manifest first
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .skip_tag_manifests(true)
+            .algorithm(&sha512);
+        let manifest = temp_directory.join("manifest-sha512.txt");
+        let contents = tokio::fs::read_to_string(&manifest).await.unwrap();
+
+        // Other manifest misses a payload of the bag
+        let incomplete = contents
+            .lines()
+            .filter(|line| !line.ends_with(" data/rfc8493.txt"))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        tokio::fs::write(&manifest, incomplete).await.unwrap();
+        assert_eq!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options.clone()).await,
+            Err(ReadError::UnlistedPayloads(vec!["data/rfc8493.txt".into()]))
+        );
+
+        // Other manifest lists a payload twice
+        let duplicate = format!("{contents}{}\n", contents.lines().next().unwrap());
+        tokio::fs::write(&manifest, duplicate).await.unwrap();
+        assert!(matches!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options.clone()).await,
+            Err(ReadError::DuplicateManifestEntry(_))
+        ));
+
+        tokio::fs::write(&manifest, contents).await.unwrap();
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn malformed_checksums() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+
+        let manifest = temp_directory.join("manifest-sha256.txt");
+        let checksum = Checksum::digest::<Sha256>(b"hello".to_vec()).to_string();
+        for malformed in [
+            // Truncated
+            &checksum[..40],
+            // Too long, like a digest of SHA-512
+            &checksum.repeat(2),
+            // Not hex
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b98zz",
+            "12345",
+        ] {
+            tokio::fs::write(&manifest, format!("{malformed}  data/hello.txt\n"))
+                .await
+                .unwrap();
+            assert_eq!(
+                BagIt::read_existing(&temp_directory, &algo).await,
+                Err(ReadError::ProcessManifestLine(
+                    PayloadError::MalformedChecksum {
+                        path: "data/hello.txt".into(),
+                        checksum: malformed.to_string(),
+                    }
+                )),
+                "failing on checksum `{malformed}`"
+            );
+            assert!(matches!(
+                BagIt::read_unvalidated(&temp_directory, &algo).await,
+                Err(ReadError::ProcessManifestLine(
+                    PayloadError::MalformedChecksum { .. }
+                ))
+            ));
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn checksum_cache() {
+        use crate::{ChecksumCache, FileChecksumCache};
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let bag_directory = temp_directory.join("bag");
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Payload and tag files are recorded in the cache
+        let cache =
+            std::sync::Arc::new(FileChecksumCache::load(temp_directory.join("cache.txt")).await);
+        let options = ReadOptions::default().checksum_cache(cache.clone());
+        BagIt::read_existing_with_options(&bag_directory, &algo, options.clone())
+            .await
+            .unwrap();
+        let tag_files = tokio::fs::read_to_string(bag_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap()
+            .lines()
+            .count();
+        assert_eq!(cache.len(), 1 + tag_files);
+
+        // Checksum found in the cache is trusted, payload is not hashed again
+        let payload = bag_directory.join("data/hello.txt");
+        let key = crate::CacheKey::from_file(&payload).await.unwrap();
+        let bogus = Checksum::digest::<Sha256>(b"world".to_vec());
+        cache.insert(key, &Algorithm::Sha256, bogus);
+        assert!(matches!(
+            BagIt::read_existing_with_options(&bag_directory, &algo, options).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ChecksumDiffers { path, .. }
+            )) if path == std::path::Path::new("data/hello.txt")
+        ));
+        assert!(BagIt::read_existing(&bag_directory, &algo).await.is_ok());
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn all_manifests_single_pass() {
+        use crate::error::PayloadError;
+        use sha2::Sha512;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let sha512 = ChecksumAlgorithm::<Sha512>::new(Algorithm::Sha512);
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&sha512).unwrap();
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_bytes::<Sha256>("hello", "data/hello.txt")
+            .await
+            .unwrap();
+        bag.add_bytes::<Sha256>("world", "data/world.txt")
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Files are read once for all algorithms, the requested one included
+        #[derive(Default)]
+        struct ReadFiles(std::sync::Mutex<(Vec<std::path::PathBuf>, u64)>);
+        impl crate::ReadProgress for ReadFiles {
+            fn file_started(&self, path: &std::path::Path) {
+                self.0.lock().unwrap().0.push(path.to_path_buf());
+            }
+
+            fn bytes_hashed(&self, bytes: u64) {
+                self.0.lock().unwrap().1 += bytes;
+            }
+        }
+        let read_files = std::sync::Arc::new(ReadFiles::default());
+
+        // Payloads are listed in manifests of both other algorithms
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .algorithm(&sha512)
+            .algorithm(&md5);
+        let bag = BagIt::read_existing_with_options(
+            &temp_directory,
+            &sha256,
+            options.clone().progress(read_files.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bag.warnings(), []);
+
+        let (mut started, bytes_hashed) = read_files.0.lock().unwrap().clone();
+        started.sort();
+        let mut bytes = 0;
+        for path in started.iter() {
+            bytes += tokio::fs::metadata(path).await.unwrap().len();
+        }
+        let files = started.len();
+        started.dedup();
+        assert_eq!(started.len(), files);
+        assert!(started.contains(&temp_directory.join("data/hello.txt")));
+        assert!(started.contains(&temp_directory.join("manifest-sha256.txt")));
+        assert_eq!(bytes_hashed, bytes);
+
+        // Payload no longer satisfies one of them, tag manifests are left out to reach it
+        tokio::fs::write(temp_directory.join("manifest-md5.txt"), {
+            let contents = tokio::fs::read_to_string(temp_directory.join("manifest-md5.txt"))
+                .await
+                .unwrap();
+            contents.replace(
+                &Checksum::digest::<Md5>(b"world".to_vec()).to_string(),
+                &Checksum::digest::<Md5>(b"earth".to_vec()).to_string(),
+            )
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            BagIt::read_existing_with_options(
+                &temp_directory,
+                &sha256,
+                options.skip_tag_manifests(true)
+            )
+            .await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ChecksumDiffers { path, .. }
+            )) if path == std::path::Path::new("data/world.txt")
+        ));
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn tag_files() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+        let bag_directory = temp_directory.join("bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let metadata = temp_directory.join("metadata");
+        tokio::fs::create_dir_all(&metadata).await.unwrap();
+        tokio::fs::write(metadata.join("marc.xml"), "<record/>")
+            .await
+            .unwrap();
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.add_tag_directory(&metadata, "metadata").await.unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        let mut read_bag = BagIt::read_existing(&bag_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag.tag_files().collect::<Vec<_>>(),
+            [std::path::Path::new("metadata/marc.xml")]
+        );
+        assert!(read_bag.warnings().is_empty());
+
+        // Tag files found when reading are kept in the tag manifest when finalizing again
+        read_bag.finalize::<Sha256>().await.unwrap();
+        let tag_manifest = tokio::fs::read_to_string(bag_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tag_manifest.ends_with(" metadata/marc.xml\n"));
+
+        // Tag file that is not in the tag manifest is surfaced, without a checksum
+        tokio::fs::create_dir_all(bag_directory.join("docs/schemas"))
+            .await
+            .unwrap();
+        tokio::fs::write(bag_directory.join("docs/schemas/sources.json"), "{}")
+            .await
+            .unwrap();
+        let read_bag = BagIt::read_existing(&bag_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag.tag_files().collect::<Vec<_>>(),
+            [
+                std::path::Path::new("docs/schemas/sources.json"),
+                std::path::Path::new("metadata/marc.xml")
+            ]
+        );
+        assert_eq!(
+            read_bag.warnings(),
+            [ReadWarning::UnlistedTagFile(
+                "docs/schemas/sources.json".into()
+            )]
+        );
+        assert_eq!(
+            BagIt::read_existing_with_options(
+                &bag_directory,
+                &algo,
+                ReadOptions::default().strict(true)
+            )
+            .await
+            .unwrap_err(),
+            ReadError::Strict(ReadWarning::UnlistedTagFile(
+                "docs/schemas/sources.json".into()
+            ))
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn unlisted_payloads() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Files slipped in the payload directory after the bag was made
+        tokio::fs::create_dir(temp_directory.join("data/extra"))
+            .await
+            .unwrap();
+        for file in ["data/extra/notes.txt", "data/.DS_Store"] {
+            tokio::fs::write(temp_directory.join(file), "")
+                .await
+                .unwrap();
+        }
+
+        let error = BagIt::read_existing(&temp_directory, &algo)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ReadError::UnlistedPayloads(vec![
+                "data/.DS_Store".into(),
+                "data/extra/notes.txt".into()
+            ])
+        );
+        assert_eq!(
+            error.to_string(),
+            "Payloads not listed in manifest: data/.DS_Store, data/extra/notes.txt"
+        );
+    }
+
+    #[cfg(all(unix, feature = "generate"))]
+    #[tokio::test]
+    async fn symlinks() {
+        use crate::SymlinkPolicy;
+
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let bag_directory = temp_directory.join("bag");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&bag_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Payload moved out of the bag, leaving a symbolic link behind
+        let outside = temp_directory.join("sources.csv");
+        tokio::fs::rename(bag_directory.join("data/sources.csv"), &outside)
+            .await
+            .unwrap();
+        tokio::fs::symlink(&outside, bag_directory.join("data/sources.csv"))
+            .await
+            .unwrap();
+
+        let read = |symlinks| {
+            BagIt::read_existing_with_options(
+                &bag_directory,
+                &algo,
+                ReadOptions::default().symlinks(symlinks),
+            )
+        };
+
+        // Followed by default, its target must still be inside the bag
+        assert_eq!(
+            read(SymlinkPolicy::Follow).await,
+            Err(ReadError::ProcessManifestLine(PayloadError::NotInsideBag))
+        );
+
+        assert_eq!(
+            read(SymlinkPolicy::Reject).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::SymlinkRejected("data/sources.csv".into())
+            ))
+        );
+
+        let bag = read(SymlinkPolicy::SkipWithWarning).await.unwrap();
+        assert_eq!(
+            bag.payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new("data/totebag.jpg")]
+        );
+        assert_eq!(
+            bag.warnings(),
+            [ReadWarning::SkippedSymlink("data/sources.csv".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_payload_directory() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Bag made by another tool, without payloads
+        tokio::fs::create_dir(temp_directory.join("data"))
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "Payload-Oxum: 0.0\n")
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("manifest-sha256.txt"), "")
+            .await
+            .unwrap();
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.payload_items().count(), 0);
+        assert!(bag.warnings().is_empty());
+
+        let payloads = BagIt::read_existing_lazy(&temp_directory, &algo)
+            .await
+            .unwrap();
+        assert_eq!(payloads.try_collect::<Vec<_>>().await, Ok(vec![]));
+
+        let health = BagIt::health_check(&temp_directory).await.unwrap();
+        assert_eq!(health.payload_count(), 0);
+        assert_eq!(health.oxum_matches(), Some(true));
+        assert_eq!(BagIt::check_completeness(&temp_directory).await, Ok(()));
+
+        // A payload is expected
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "Payload-Oxum: 1.1\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::BagInfoOxum("stream_count"))
+        );
+
+        // Payload directory is required, even without payloads
+        tokio::fs::remove_dir(temp_directory.join("data"))
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::MissingPayloadDirectory)
+        );
+        // Payload directory is a file
+        tokio::fs::write(temp_directory.join("data"), "")
+            .await
+            .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::MissingPayloadDirectory)
+        );
+        assert_eq!(
+            BagIt::read_unvalidated(&temp_directory, &algo)
+                .await
+                .map(|_| ()),
+            Err(ReadError::MissingPayloadDirectory)
+        );
+    }
 
-        // Read `bagit.txt`
-        let path_bagit = bag_it_directory.as_ref().join("bagit.txt");
-        if !path_bagit.exists() {
-            return Err(ReadError::BagDeclaration(BagDeclarationError::Missing));
+    #[tokio::test]
+    async fn byte_order_mark() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Tag files written by a Windows tool
+        tokio::fs::create_dir(temp_directory.join("data"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/hello.txt"), "hello")
+            .await
+            .unwrap();
+        for (file, contents) in [
+            (
+                "bagit.txt",
+                "BagIt-Version: 1.0\r\nTag-File-Character-Encoding: UTF-8\r\n",
+            ),
+            ("bag-info.txt", "Payload-Oxum: 5.1\r\n"),
+            (
+                "manifest-sha256.txt",
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data/hello.txt\r\n",
+            ),
+        ] {
+            tokio::fs::write(temp_directory.join(file), format!("\u{feff}{contents}"))
+                .await
+                .unwrap();
         }
-        let bagit_file = MetadataFile::read(path_bagit)
+
+        let bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.version(), BagVersion::V1_0);
+        assert_eq!(
+            bag.tags().collect::<Vec<_>>(),
+            [&Metadata::PayloadOctetStreamSummary {
+                octet_count: 5,
+                stream_count: 1
+            }]
+        );
+        assert_eq!(
+            bag.payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new("data/hello.txt")]
+        );
+
+        // Further in the file, a byte order mark is part of the line
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "\u{feff}2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data/hello.txt\r\n\u{feff}2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data/hello.txt\r\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo)
+                .await
+                .map(|_| ()),
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::MalformedChecksum {
+                    path: "data/hello.txt".into(),
+                    checksum: format!(
+                        "\u{feff}{}",
+                        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                    ),
+                }
+            ))
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn skip_tag_manifests() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
             .await
-            .map_err(|e| ReadError::BagDeclaration(e.into()))?;
-        let mut bagit_file = bagit_file.tags();
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
 
-        // Expecting first tag to be BagIt version
-        match bagit_file.next() {
-            Some(Metadata::BagitVersion { .. }) => (),
-            _ => return Err(BagDeclarationError::Tag(KEY_VERSION).into()),
-        }
+        // Tag file rewritten once the bag was received
+        let bag_info = tokio::fs::read_to_string(temp_directory.join("bag-info.txt"))
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bag-info.txt"),
+            format!("{bag_info}Internal-Sender-Identifier: ingest-42\n"),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ChecksumDiffers { .. }
+            ))
+        ));
 
-        // Expecting second tag to be Encoding (utf-8)
-        match bagit_file.next() {
-            Some(Metadata::Encoding) => (),
-            _ => return Err(BagDeclarationError::Tag(KEY_ENCODING).into()),
-        }
+        let options = ReadOptions::default()
+            .skip_tag_manifests(true)
+            .all_manifests(true)
+            .algorithm(&md5)
+            .strict(true);
+        let bag = BagIt::read_existing_with_options(&temp_directory, &algo, options)
+            .await
+            .unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert_eq!(
+            bag.warnings(),
+            [
+                ReadWarning::SkippedTagManifest(temp_directory.join("tagmanifest-sha256.txt")),
+                ReadWarning::SkippedTagManifest(temp_directory.join("tagmanifest-md5.txt")),
+            ]
+        );
 
-        // Expecting no more tags
-        if bagit_file.next().is_some() {
-            return Err(BagDeclarationError::NumberTags.into());
-        }
+        // Payloads are still validated
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "")
+            .await
+            .unwrap();
+        assert!(BagIt::read_existing_with_options(
+            &temp_directory,
+            &algo,
+            ReadOptions::default().skip_tag_manifests(true)
+        )
+        .await
+        .is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn duplicate_manifest_entries() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-        // Get optional `bag-info.txt`
-        let path_baginfo = bag_it_directory.as_ref().join("bag-info.txt");
-        let bag_info = if path_baginfo.exists() {
-            Some(
-                MetadataFile::read(path_baginfo)
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        tokio::fs::remove_file(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+
+        let manifest = tokio::fs::read_to_string(temp_directory.join("manifest-sha256.txt"))
+            .await
+            .unwrap();
+        let line = manifest.lines().next().unwrap();
+        for duplicate in [
+            line.to_string(),
+            format!("{}{}", "0".repeat(64), &line[64..]),
+        ] {
+            tokio::fs::write(
+                temp_directory.join("manifest-sha256.txt"),
+                format!("{line}\n{duplicate}\n"),
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                BagIt::read_existing(&temp_directory, &algo).await,
+                Err(ReadError::DuplicateManifestEntry("data/sources.csv".into()))
+            );
+            assert_eq!(
+                BagIt::read_unvalidated(&temp_directory, &algo).await,
+                Err(ReadError::DuplicateManifestEntry("data/sources.csv".into()))
+            );
+            assert_eq!(
+                BagIt::read_existing_lazy(&temp_directory, &algo)
                     .await
-                    .map_err(ReadError::BagInfo)?,
+                    .unwrap()
+                    .try_collect::<Vec<_>>()
+                    .await,
+                Err(ReadError::DuplicateManifestEntry("data/sources.csv".into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn windows_separators() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Manifest written by a tool running on Windows
+        tokio::fs::create_dir_all(temp_directory.join("data/notes"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/notes/hello.txt"), "hello")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  data\\notes\\hello.txt\n",
+        )
+        .await
+        .unwrap();
+
+        let bag = BagIt::read_existing_with_options(
+            &temp_directory,
+            &algo,
+            ReadOptions::default().windows_separators(true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            bag.payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new("data/notes/hello.txt")]
+        );
+    }
+
+    #[tokio::test]
+    async fn path_normalization() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        // Name of file is decomposed like on macOS, manifest has it composed
+        let decomposed = "data/cafe\u{301}.txt";
+        let composed = "data/caf\u{e9}.txt";
+        tokio::fs::create_dir(temp_directory.join("data"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join(decomposed), "hello")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            temp_directory.join("manifest-sha256.txt"),
+            format!(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  {composed}\n"
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(
+                crate::error::PayloadError::Absolute(
+                    temp_directory.join(composed),
+                    std::io::ErrorKind::NotFound
+                )
+            ))
+        );
+        let read = |normalization| {
+            BagIt::read_existing_with_options(
+                &temp_directory,
+                &algo,
+                ReadOptions::default().path_normalization(normalization),
             )
-        } else {
-            None
         };
+        assert!(read(PathNormalization::Nfc).await.is_err());
+
+        let bag = read(PathNormalization::Nfd).await.unwrap();
+        assert_eq!(
+            bag.payload_items()
+                .map(|payload| payload.relative_path())
+                .collect::<Vec<_>>(),
+            [std::path::Path::new(decomposed)]
+        );
+    }
+
+    #[tokio::test]
+    async fn unsafe_manifest_paths() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-        // Get all files in directory
-        let mut dir = fs::read_dir(bag_it_directory.as_ref())
+        tokio::fs::create_dir(temp_directory.join("data"))
             .await
-            .map_err(|e| ReadError::ListChecksumFiles(e.kind()))?;
-        let mut files_in_dir = Vec::new();
-        while let Some(entry) = dir
-            .next_entry()
+            .unwrap();
+        tokio::fs::write(
+            temp_directory.join("bagit.txt"),
+            "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n",
+        )
+        .await
+        .unwrap();
+
+        // Rejected before looking for files, they don't need to exist
+        for (path, error) in [
+            ("data/../../secret.txt", PayloadError::ParentDirectory),
+            ("/etc/passwd", PayloadError::AbsolutePath),
+            ("C:\\secret.txt", PayloadError::AbsolutePath),
+            ("bagit.txt", PayloadError::OutsidePayloadDirectory),
+        ] {
+            tokio::fs::write(
+                temp_directory.join("manifest-sha256.txt"),
+                format!(
+                    "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  {path}\n"
+                ),
+            )
             .await
-            .map_err(|e| ReadError::ListChecksumFiles(e.kind()))?
-        {
-            let path = entry.path();
-            files_in_dir.push(path);
+            .unwrap();
+            assert_eq!(
+                BagIt::read_existing(&temp_directory, &algo).await,
+                Err(ReadError::ProcessManifestLine(error)),
+                "failing on path `{path}`"
+            );
         }
+    }
 
-        // Get and validate payloads from manifest of requested checksum algorithm
-        let payloads = Manifest::find_manifest(files_in_dir.as_ref(), checksum_algorithm)
-            .await?
-            .ok_or(ReadError::NotRequestedAlgorithm)?
-            .get_validate_payloads::<ChecksumAlgo>(bag_it_directory.as_ref())
-            .await?;
+    #[cfg(feature = "generate")]
+    #[tokio::test]
+    async fn tag_manifest_coverage() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
 
-        // Optional if present: validate number of payload files and total file size
-        if let Some(ref bag_info) = bag_info {
-            for tag in bag_info.tags() {
-                if let Metadata::PayloadOctetStreamSummary {
-                    octet_count,
-                    stream_count,
-                } = tag
-                {
-                    if *stream_count != payloads.len() {
-                        // Expected number of payloads does not match
-                        return Err(ReadError::BagInfoOxum("stream_count"));
-                    }
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-                    let payload_bytes_sum = payloads.iter().map(|payload| payload.bytes()).sum();
-                    if *octet_count != payload_bytes_sum {
-                        // Expected total bytes does not match
-                        return Err(ReadError::BagInfoOxum("octet_count"));
-                    }
-                }
-            }
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Tag manifest only covers `bagit.txt`
+        let tag_manifest_path = temp_directory.join("tagmanifest-sha256.txt");
+        let tag_manifest = tokio::fs::read_to_string(&tag_manifest_path).await.unwrap();
+        let bagit_line = tag_manifest
+            .lines()
+            .find(|line| line.ends_with(" bagit.txt"))
+            .unwrap();
+        tokio::fs::write(&tag_manifest_path, bagit_line)
+            .await
+            .unwrap();
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(
+            read_bag.warnings(),
+            [
+                ReadWarning::UnlistedTagFile("bag-info.txt".into()),
+                ReadWarning::UnlistedTagFile("manifest-sha256.txt".into())
+            ]
+        );
+
+        // Tag manifest lists itself
+        tokio::fs::write(
+            &tag_manifest_path,
+            format!(
+                "{tag_manifest}2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824 tagmanifest-sha256.txt"
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            BagIt::read_existing(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(
+                PayloadError::ListsTagManifest
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrency() {
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag/");
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let sequential = BagIt::read_existing_with_options(
+            &bagit_directory,
+            &algo,
+            ReadOptions::default().concurrency(std::num::NonZeroUsize::MIN),
+        )
+        .await
+        .unwrap();
+        let concurrent = BagIt::read_existing_with_options(
+            &bagit_directory,
+            &algo,
+            ReadOptions::default().concurrency(std::num::NonZeroUsize::new(4).unwrap()),
+        )
+        .await
+        .unwrap();
+
+        // Payloads stay in order of the manifest
+        assert_eq!(sequential, concurrent);
+        assert_eq!(
+            sequential.payload_items().collect::<Vec<_>>(),
+            concurrent.payload_items().collect::<Vec<_>>()
+        );
+    }
+
+    /// SHA-256 taking its time, keeping track of how many files are hashed at the same time
+    #[derive(Clone, Default)]
+    struct SlowSha256(Sha256);
+
+    static HASHING: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static MAX_HASHING: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    impl digest::HashMarker for SlowSha256 {}
+
+    impl digest::OutputSizeUser for SlowSha256 {
+        type OutputSize = <Sha256 as digest::OutputSizeUser>::OutputSize;
+    }
+
+    impl digest::Update for SlowSha256 {
+        fn update(&mut self, data: &[u8]) {
+            use std::sync::atomic::Ordering;
+            let hashing = HASHING.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_HASHING.fetch_max(hashing, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            HASHING.fetch_sub(1, Ordering::SeqCst);
+            digest::Update::update(&mut self.0, data);
         }
+    }
 
-        // Optional if present: validate checksums from tag manifest
-        if let Some(tag_manifest) =
-            Manifest::find_tag_manifest(files_in_dir.as_ref(), checksum_algorithm).await?
-        {
-            tag_manifest
-                .get_validate_payloads::<ChecksumAlgo>(bag_it_directory.as_ref())
-                .await?;
+    impl digest::FixedOutput for SlowSha256 {
+        fn finalize_into(self, out: &mut digest::Output<Self>) {
+            digest::FixedOutput::finalize_into(self.0, out);
         }
+    }
 
-        // Get tags from bag info
-        let tags = bag_info
-            .map(|file| file.consume_tags().into_iter().collect())
-            .unwrap_or_default();
+    impl digest::Reset for SlowSha256 {
+        fn reset(&mut self) {
+            digest::Reset::reset(&mut self.0);
+        }
+    }
 
-        Ok(BagIt {
-            path: bag_it_directory.as_ref().to_path_buf(),
-            items: payloads,
-            checksum_algorithm: checksum_algorithm.algorithm(),
-            tags,
-        })
+    impl digest::FixedOutputReset for SlowSha256 {
+        fn finalize_into_reset(&mut self, out: &mut digest::Output<Self>) {
+            digest::FixedOutputReset::finalize_into_reset(&mut self.0, out);
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
+    #[tokio::test]
+    async fn concurrency_parallel_hashing() {
+        use std::sync::atomic::Ordering;
 
-    use crate::{
-        error::ReadError, metadata::Metadata, Algorithm, BagIt, ChecksumAlgorithm, Payload,
-    };
-    #[cfg(feature = "date")]
-    use jiff::civil::Date;
-    use md5::Md5;
-    use sha2::Sha256;
+        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        bagit_directory.push("tests/sample-bag/");
+
+        let algo = ChecksumAlgorithm::<SlowSha256>::new(Algorithm::Sha256);
+        let options = ReadOptions::default()
+            .concurrency(std::num::NonZeroUsize::new(4).unwrap())
+            .algorithm(&algo);
+
+        // Runtime of the test has a single thread, files are still hashed at the same time
+        BagIt::read_existing_with_options(&bagit_directory, &algo, options.clone())
+            .await
+            .unwrap();
+        assert!(MAX_HASHING.swap(0, Ordering::SeqCst) > 1);
+
+        let results = BagIt::cross_validate(&bagit_directory, &options)
+            .await
+            .unwrap();
+        assert!(results.is_valid());
+        assert!(MAX_HASHING.swap(0, Ordering::SeqCst) > 1);
+    }
 
+    #[cfg(feature = "generate")]
     #[tokio::test]
-    async fn bag_with_date_sha256() {
-        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        bagit_directory.push("tests/sample-bag");
+    async fn read_unvalidated() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
 
         let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
 
-        let bag = BagIt::read_existing(&bagit_directory, &algo).await.unwrap();
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
 
-        let expected = BagIt::from_existing_items(
-            bagit_directory,
-            vec![
-                Payload::test_payload(
-                    "data/bagit.md",
-                    "eccdbbade12ba878af8f2140cb00c914f427405a987de2670e5c3014faf59f8e",
-                    6302,
-                ),
-                Payload::test_payload(
-                    "data/paper_bag.jpg",
-                    "2b22a8fd0dc46cbdc7a67b6cf588a03a8dd6f8ea23ce0b02e921ca5d79930bb2",
-                    19895,
-                ),
-                Payload::test_payload(
-                    "data/rfc8493.txt",
-                    "4964147d2e6e16442d4a6dbfbe68178a8f33c3e791c06d68a8b33f51ad821537",
-                    48783,
-                ),
-                Payload::test_payload(
-                    "data/sources.csv",
-                    "0fe3bd6e7c36aa2c979f3330037b220c5ca88ed0eabf16622202dc0b33c44e72",
-                    369,
-                ),
-                Payload::test_payload(
-                    "data/totebag.jpg",
-                    "38ff57167d746859f6383e80eb84ec0dd84de2ab1ed126ad317e73fbf502fb31",
-                    10417,
-                ),
-            ],
-            algo.algorithm(),
-            vec![
-                #[cfg(feature = "date")]
-                Metadata::BaggingDate(Date::new(2024, 7, 11).unwrap()),
-                #[cfg(not(feature = "date"))]
-                Metadata::Custom {
-                    key: "Bagging-Date".into(),
-                    value: "2024-07-11".into(),
-                },
-                Metadata::PayloadOctetStreamSummary {
-                    octet_count: 85766,
-                    stream_count: 5,
-                },
-            ],
-        )
-        .unwrap();
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+        let expected = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
 
+        let bag = BagIt::read_unvalidated(&temp_directory, &algo)
+            .await
+            .unwrap();
         assert_eq!(bag, expected);
+        assert_eq!(bag.last_validation(), None);
+
+        // Damaged payload is only noticed when validating
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        let mut bag = BagIt::read_unvalidated(&temp_directory, &algo)
+            .await
+            .unwrap();
+        assert_eq!(
+            bag.payload_items().next().unwrap().checksum(),
+            expected.payload_items().next().unwrap().checksum()
+        );
+        assert!(matches!(
+            bag.validate::<Sha256>().await,
+            Err(PayloadError::ChecksumDiffers { actual, .. })
+                if actual == Checksum::digest::<Sha256>(b"oops".to_vec())
+        ));
+        assert!(!bag.is_verified());
+
+        // Files must still exist
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            BagIt::read_unvalidated(&temp_directory, &algo).await,
+            Err(ReadError::ProcessManifestLine(PayloadError::Absolute(_, _)))
+        ));
     }
 
+    #[cfg(feature = "generate")]
     #[tokio::test]
-    async fn basic_bag_wrong_algorithm_md5() {
-        let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        bagit_directory.push("tests/sample-bag/");
+    async fn read_existing_auto() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let md5 = ChecksumAlgorithm::<Md5>::new(Algorithm::custom("md5"));
 
-        let algo = ChecksumAlgorithm::<Md5>::new(Algorithm::Custom("md5"));
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
 
+        let mut bag = BagIt::new_empty(&temp_directory, &md5);
+        bag.add_file::<Md5>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Md5>().await.unwrap();
+
+        let options = ReadOptions::default();
         assert_eq!(
-            BagIt::read_existing(&bagit_directory, &algo).await,
-            Err(ReadError::NotRequestedAlgorithm)
+            BagIt::read_existing_auto(&temp_directory, &options).await,
+            Err(ReadError::NoKnownAlgorithm)
+        );
+
+        let options = ReadOptions::default().algorithm(&md5);
+        let bag = BagIt::read_existing_auto(&temp_directory, &options)
+            .await
+            .unwrap();
+        assert_eq!(bag.algorithm(), &Algorithm::custom("md5"));
+        assert_eq!(
+            bag,
+            BagIt::read_existing(&temp_directory, &md5).await.unwrap()
         );
     }
 }
@@ -0,0 +1,436 @@
+//! Validation of damaged bags, reporting every problem found instead of stopping at the first one
+
+use crate::checksum::ChecksumComputeError;
+use crate::encoding::{TagEncoding, TagFileReader};
+use crate::fetch::read_fetch_file;
+use crate::manifest::{is_unfetched, next_line, Manifest};
+use crate::metadata::Metadata;
+use crate::payload::{parse_manifest_line, PathResolution};
+use crate::{
+    error::{PayloadError, ReadError},
+    BagIt, BagVersion, ChecksumAlgorithm, Payload, ReadOptions, ValidationOutcome,
+};
+use digest::Digest;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::{fs, io::BufReader};
+
+#[derive(Debug, Default, PartialEq)]
+/// Every problem found when validating a bag on disk
+///
+/// See [`BagIt::validate_report()`].
+pub struct ValidationReport {
+    failed_payloads: Vec<(PathBuf, PayloadError)>,
+    missing_files: Vec<PathBuf>,
+    unlisted_payloads: Vec<PathBuf>,
+    oxum_matches: Option<bool>,
+    tag_errors: Vec<(PathBuf, PayloadError)>,
+}
+
+impl ValidationReport {
+    /// Whether no problem was found, the bag would be read by [`BagIt::read_existing()`]
+    pub fn is_valid(&self) -> bool {
+        self.failed_payloads.is_empty()
+            && self.missing_files.is_empty()
+            && self.unlisted_payloads.is_empty()
+            && self.oxum_matches != Some(false)
+            && self.tag_errors.is_empty()
+    }
+
+    /// Payloads of the manifest that failed validation, like [`PayloadError::ChecksumDiffers`], in order of the manifest
+    pub fn failed_payloads(&self) -> &[(PathBuf, PayloadError)] {
+        &self.failed_payloads
+    }
+
+    /// Payloads and tag files listed in the manifest or tag manifest, that are not in the bag
+    ///
+    /// Payloads of `fetch.txt` that were not fetched yet are not missing.
+    pub fn missing_files(&self) -> &[PathBuf] {
+        &self.missing_files
+    }
+
+    /// Files of payload directory `data` that are not listed in the manifest, sorted
+    pub fn unlisted_payloads(&self) -> &[PathBuf] {
+        &self.unlisted_payloads
+    }
+
+    /// Whether Payload-Oxum of `bag-info.txt` matches payloads on disk, if the bag has one and every payload was fetched
+    pub fn oxum_matches(&self) -> Option<bool> {
+        self.oxum_matches
+    }
+
+    /// Tag files of the tag manifest that failed validation, in order of the tag manifest
+    pub fn tag_errors(&self) -> &[(PathBuf, PayloadError)] {
+        &self.tag_errors
+    }
+}
+
+/// Outcome of validating every line of a manifest or tag manifest
+#[derive(Default)]
+struct ManifestCheck {
+    /// Paths listed in the manifest, except payloads of `fetch.txt` not fetched yet
+    listed: Vec<PathBuf>,
+    /// Total size of files found on disk
+    bytes: u64,
+    /// Number of payloads of `fetch.txt` not fetched yet
+    unfetched: usize,
+}
+
+/// Validate every line of `manifest`, pushing failures and missing files instead of stopping at the first one
+async fn check_manifest<ChecksumAlgo: Digest + Send + 'static>(
+    manifest: Manifest,
+    bag_it_directory: &Arc<Path>,
+    encoding: TagEncoding,
+    version: BagVersion,
+    remote_paths: &[PathBuf],
+    failures: &mut Vec<(PathBuf, PayloadError)>,
+    missing: &mut Vec<PathBuf>,
+) -> Result<ManifestCheck, ReadError> {
+    let scope = manifest.scope();
+    let manifest_path = manifest.as_ref().to_path_buf();
+    let manifest = fs::File::open(manifest)
+        .await
+        .map_err(|e| ReadError::OpenFile(manifest_path.clone(), e.kind()))?;
+    let mut manifest = BufReader::new(TagFileReader::new(manifest, encoding));
+    let mut line = String::new();
+
+    let mut check = ManifestCheck::default();
+    while let Some(line) = next_line(&mut manifest, &mut line)
+        .await
+        .map_err(|e| ReadError::ReadLine(manifest_path.clone(), e.kind()))?
+    {
+        // Lines that can't be parsed don't have a path to report, the manifest itself is broken
+        let (_, relative_path) = parse_manifest_line(line, version)?;
+        let relative_path = PathBuf::from(relative_path.as_ref());
+        if is_unfetched(
+            line,
+            version,
+            PathResolution::default(),
+            bag_it_directory,
+            remote_paths,
+        )
+        .await
+        {
+            check.unfetched += 1;
+            continue;
+        }
+
+        match Payload::from_manifest::<ChecksumAlgo>(
+            line,
+            bag_it_directory.clone(),
+            version,
+            PathResolution::default(),
+            scope,
+        )
+        .await
+        {
+            Ok(payload) => check.bytes += payload.bytes(),
+            Err(PayloadError::Absolute(_, std::io::ErrorKind::NotFound)) => {
+                missing.push(relative_path.clone())
+            }
+            Err(e) => {
+                // Damaged files still count in Payload-Oxum
+                if matches!(e, PayloadError::ChecksumDiffers { .. }) {
+                    if let Ok(metadata) = fs::metadata(bag_it_directory.join(&relative_path)).await
+                    {
+                        check.bytes += metadata.len();
+                    }
+                }
+                failures.push((relative_path.clone(), e));
+            }
+        }
+        check.listed.push(relative_path);
+    }
+
+    Ok(check)
+}
+
+/// Verify every payload of `payloads`, pushing failures and missing files instead of stopping at the first one
+async fn verify_payloads<ChecksumAlgo: Digest + Send + 'static>(
+    payloads: &[Payload<'_>],
+    failures: &mut Vec<(PathBuf, PayloadError)>,
+    missing: &mut Vec<PathBuf>,
+) {
+    for payload in payloads {
+        match payload.verify::<ChecksumAlgo>().await {
+            Ok(()) => {}
+            Err(PayloadError::ComputeChecksum(ChecksumComputeError::FileNotFound(_))) => {
+                missing.push(payload.relative_path().to_path_buf())
+            }
+            Err(e) => failures.push((payload.relative_path().to_path_buf(), e)),
+        }
+    }
+}
+
+impl BagIt<'_, '_> {
+    /// Verify an opened bag again, comparing files with checksums it holds, and gather every problem found in a [`ValidationReport`]
+    ///
+    /// Unlike [`BagIt::validate()`], every payload is verified even after a failure. With `tag_files`, tag files of the
+    /// tag manifest are verified as well, see [`BagIt::tag_manifest_items()`]. Nothing is read again from manifests:
+    /// files added to payload directory `data` since the bag was opened are not looked for, and Payload-Oxum is not checked.
+    ///
+    /// The outcome is recorded, see [`BagIt::last_validation()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let mut bag = BagIt::read_existing(bagit_directory, &algorithm).await?;
+    ///
+    /// // Periodic fixity check of a long-lived bag
+    /// let report = bag.verify::<sha2::Sha256>(true).await;
+    /// assert!(report.is_valid());
+    /// assert!(bag.is_verified());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        tag_files: bool,
+    ) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        verify_payloads::<ChecksumAlgo>(
+            &self.items,
+            &mut report.failed_payloads,
+            &mut report.missing_files,
+        )
+        .await;
+        if tag_files {
+            verify_payloads::<ChecksumAlgo>(
+                &self.tag_items,
+                &mut report.tag_errors,
+                &mut report.missing_files,
+            )
+            .await;
+        }
+
+        self.record_outcome(match report.is_valid() {
+            true => ValidationOutcome::Valid,
+            false => ValidationOutcome::Invalid,
+        });
+        report
+    }
+
+    /// Validate a bag on disk, gathering every problem found in a [`ValidationReport`]
+    ///
+    /// Unlike [`BagIt::read_existing()`] stopping at the first error, every payload of the manifest and every tag file
+    /// of the tag manifest is validated, to see the full damage of a bag in one pass.
+    /// An error is only returned when the bag can't be validated at all: `bagit.txt` or `bag-info.txt` can't be read,
+    /// there is no manifest for `checksum_algorithm`, or a line of a manifest can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let report = BagIt::validate_report(bagit_directory, &algorithm).await?;
+    ///
+    /// for (path, error) in report.failed_payloads() {
+    ///     eprintln!("{}: {error}", path.display());
+    /// }
+    /// assert!(report.is_valid());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_report<ChecksumAlgo: Digest + Send + 'static>(
+        bag_it_directory: impl AsRef<Path>,
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<ValidationReport, ReadError> {
+        let bag_it_directory: Arc<Path> = bag_it_directory.as_ref().into();
+        let (version, encoding) = Self::read_declaration(&bag_it_directory).await?;
+        ReadOptions::default().check_encoding(encoding)?;
+        let bag_info = Self::read_bag_info(&bag_it_directory, encoding).await?;
+        let remote_paths = read_fetch_file(&bag_it_directory, encoding, version)
+            .await?
+            .iter()
+            .map(|item| item.relative_path().to_path_buf())
+            .collect::<Vec<_>>();
+
+        let (manifest, tag_manifest) = Manifest::find_manifests(
+            &bag_it_directory,
+            checksum_algorithm.algorithm(),
+            &mut vec![],
+        )
+        .await?;
+        let manifest = manifest.ok_or(ReadError::NotRequestedAlgorithm)?;
+
+        let mut report = ValidationReport::default();
+        let payloads = check_manifest::<ChecksumAlgo>(
+            manifest,
+            &bag_it_directory,
+            encoding,
+            version,
+            &remote_paths,
+            &mut report.failed_payloads,
+            &mut report.missing_files,
+        )
+        .await?;
+        if let Some(tag_manifest) = tag_manifest {
+            check_manifest::<ChecksumAlgo>(
+                tag_manifest,
+                &bag_it_directory,
+                encoding,
+                version,
+                &[],
+                &mut report.tag_errors,
+                &mut report.missing_files,
+            )
+            .await?;
+        }
+
+        report.unlisted_payloads = Self::unlisted_payloads(
+            &bag_it_directory,
+            payloads.listed.iter().map(PathBuf::as_path),
+        )
+        .await?;
+
+        // Payload-Oxum can only match once every payload of `fetch.txt` is in the bag
+        report.oxum_matches = bag_info
+            .as_ref()
+            .filter(|_| payloads.unfetched == 0)
+            .and_then(|bag_info| {
+                bag_info
+                    .tags()
+                    .any(|tag| matches!(tag, Metadata::PayloadOctetStreamSummary { .. }))
+                    .then(|| {
+                        Self::check_oxum(Some(bag_info), payloads.listed.len(), payloads.bytes)
+                            .is_ok()
+                    })
+            });
+
+        Ok(report)
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use crate::{error::PayloadError, Algorithm, BagIt, ChecksumAlgorithm};
+    use sha2::Sha256;
+    use std::path::{Path, PathBuf};
+
+    #[tokio::test]
+    async fn validate_report() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["bagit.md", "sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        let report = BagIt::validate_report(&temp_directory, &algo)
+            .await
+            .unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.oxum_matches(), Some(true));
+
+        // Damage the bag in every possible way
+        tokio::fs::write(temp_directory.join("data/bagit.md"), "oops")
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("data/notes.txt"), "")
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "Payload-Oxum: 8.3\n")
+            .await
+            .unwrap();
+
+        let report = BagIt::validate_report(&temp_directory, &algo)
+            .await
+            .unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(
+            report
+                .failed_payloads()
+                .iter()
+                .map(|(path, _)| path.as_path())
+                .collect::<Vec<_>>(),
+            ["data/bagit.md", "data/sources.csv"]
+        );
+        assert!(report.failed_payloads().iter().all(|(path, e)| {
+            matches!(e, PayloadError::ChecksumDiffers { path: differs, .. } if differs == path)
+        }));
+        assert_eq!(report.missing_files(), [PathBuf::from("data/totebag.jpg")]);
+        assert_eq!(
+            report.unlisted_payloads(),
+            [PathBuf::from("data/notes.txt")]
+        );
+        // Every payload of the manifest is counted, sizes are the ones of files on disk
+        assert_eq!(report.oxum_matches(), Some(true));
+        assert!(matches!(
+            report.tag_errors(),
+            [(path, PayloadError::ChecksumDiffers { .. })] if path == Path::new("bag-info.txt")
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["bagit.md", "sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert!(bag.verify::<Sha256>(true).await.is_valid());
+
+        tokio::fs::write(temp_directory.join("data/bagit.md"), "oops")
+            .await
+            .unwrap();
+        tokio::fs::remove_file(temp_directory.join("data/totebag.jpg"))
+            .await
+            .unwrap();
+        tokio::fs::write(temp_directory.join("bag-info.txt"), "")
+            .await
+            .unwrap();
+
+        // Tag files are only verified when asked
+        let report = bag.verify::<Sha256>(false).await;
+        assert!(matches!(
+            report.failed_payloads(),
+            [(path, PayloadError::ChecksumDiffers { .. })] if path == Path::new("data/bagit.md")
+        ));
+        assert_eq!(report.missing_files(), [PathBuf::from("data/totebag.jpg")]);
+        assert!(report.tag_errors().is_empty());
+        assert!(!bag.is_verified());
+
+        let report = bag.verify::<Sha256>(true).await;
+        assert!(matches!(
+            report.tag_errors(),
+            [(path, PayloadError::ChecksumDiffers { .. })] if path == Path::new("bag-info.txt")
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+//! Bag bound to the digest of its algorithm, so that payloads can't be hashed with another one
+
+use crate::{error::GenerateError, BagIt, ChecksumAlgorithm, FinalizeOptions, FinalizePreview};
+use digest::Digest;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncRead;
+
+#[derive(Debug)]
+/// Bag being created, bound to the digest `ChecksumAlgo` of its algorithm
+///
+/// Methods of [`BagIt`] adding payloads and finalizing the bag take the digest as a type parameter, which has to be repeated
+/// on every call and can differ from the algorithm of the bag, writing wrong manifests. Here, it is chosen once when the bag is
+/// created and checked by the compiler afterwards. Other methods of [`BagIt`] are available through [`Deref`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_bagit::{Algorithm, ChecksumAlgorithm, TypedBagIt};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+/// let mut bag = TypedBagIt::new_empty("/path/to/bag", &algorithm);
+///
+/// bag.add_file("/path/to/dataset.csv").await?;
+/// bag.finalize().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TypedBagIt<'a, 'algo, ChecksumAlgo> {
+    bag: BagIt<'a, 'algo>,
+    digest: PhantomData<fn() -> ChecksumAlgo>,
+}
+
+impl<'a, 'algo, ChecksumAlgo: Digest + Send + 'static> TypedBagIt<'a, 'algo, ChecksumAlgo> {
+    /// Create an empty bag, like [`BagIt::new_empty()`]
+    pub fn new_empty(
+        directory: impl AsRef<Path>,
+        checksum_algorithm: &'algo ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Self {
+        Self {
+            bag: BagIt::new_empty(directory, checksum_algorithm),
+            digest: PhantomData,
+        }
+    }
+
+    /// Bind `bag` to the digest of `checksum_algorithm`, like a bag from [`BagItBuilder`](crate::BagItBuilder)
+    /// or [`BagIt::open_for_update()`]
+    ///
+    /// Fails with [`GenerateError::AlgorithmMismatch`] when `checksum_algorithm` is not the algorithm of the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagItBuilder, BagVersion, ChecksumAlgorithm, TypedBagIt};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let bag = BagItBuilder::new("/path/to/bag", &algorithm)
+    ///     .version(BagVersion::V0_97)
+    ///     .build()?;
+    ///
+    /// let mut bag = TypedBagIt::from_bag(bag, &algorithm)?;
+    /// bag.add_file("/path/to/dataset.csv").await?;
+    /// bag.finalize().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bag(
+        bag: BagIt<'a, 'algo>,
+        checksum_algorithm: &ChecksumAlgorithm<ChecksumAlgo>,
+    ) -> Result<Self, GenerateError> {
+        if bag.algorithm() != checksum_algorithm.algorithm() {
+            return Err(GenerateError::AlgorithmMismatch(
+                checksum_algorithm.algorithm().clone(),
+            ));
+        }
+
+        Ok(Self {
+            bag,
+            digest: PhantomData,
+        })
+    }
+
+    /// Bag, no longer bound to the digest of its algorithm
+    pub fn into_inner(self) -> BagIt<'a, 'algo> {
+        self.bag
+    }
+
+    /// See [`BagIt::add_file()`]
+    pub async fn add_file(&mut self, file: impl AsRef<Path>) -> Result<(), GenerateError> {
+        self.bag.add_file::<ChecksumAlgo>(file).await
+    }
+
+    /// See [`BagIt::add_file_move()`]
+    pub async fn add_file_move(&mut self, file: impl AsRef<Path>) -> Result<(), GenerateError> {
+        self.bag.add_file_move::<ChecksumAlgo>(file).await
+    }
+
+    /// See [`BagIt::add_file_relative()`]
+    pub async fn add_file_relative(
+        &mut self,
+        base_directory: impl AsRef<Path>,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.bag
+            .add_file_relative::<ChecksumAlgo>(base_directory, file)
+            .await
+    }
+
+    /// See [`BagIt::add_file_as()`]
+    pub async fn add_file_as(
+        &mut self,
+        file: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.bag
+            .add_file_as::<ChecksumAlgo>(file, destination)
+            .await
+    }
+
+    /// See [`BagIt::add_directory()`]
+    pub async fn add_directory(
+        &mut self,
+        directory: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, Result<(), GenerateError>)>, GenerateError> {
+        self.bag.add_directory::<ChecksumAlgo>(directory).await
+    }
+
+    /// See [`BagIt::bag_in_place()`]
+    pub async fn bag_in_place(&mut self) -> Result<(), GenerateError> {
+        self.bag.bag_in_place::<ChecksumAlgo>().await
+    }
+
+    /// See [`BagIt::add_from_reader()`]
+    pub async fn add_from_reader(
+        &mut self,
+        reader: impl AsyncRead + Unpin,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.bag
+            .add_from_reader::<ChecksumAlgo>(reader, destination)
+            .await
+    }
+
+    /// See [`BagIt::add_bytes()`]
+    pub async fn add_bytes(
+        &mut self,
+        bytes: impl AsRef<[u8]>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.bag.add_bytes::<ChecksumAlgo>(bytes, destination).await
+    }
+
+    /// See [`BagIt::add_file_compressed()`]
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub async fn add_file_compressed(
+        &mut self,
+        file: impl AsRef<Path>,
+    ) -> Result<(), GenerateError> {
+        self.bag.add_file_compressed::<ChecksumAlgo>(file).await
+    }
+
+    /// See [`BagIt::add_file_with_sidecar()`]
+    pub async fn add_file_with_sidecar(
+        &mut self,
+        file: impl AsRef<Path>,
+        sidecar: impl AsRef<[u8]>,
+    ) -> Result<(), GenerateError> {
+        self.bag
+            .add_file_with_sidecar::<ChecksumAlgo>(file, sidecar)
+            .await
+    }
+
+    /// See [`BagIt::preview_finalize()`]
+    pub async fn preview_finalize(&self) -> Result<FinalizePreview, GenerateError> {
+        self.bag.preview_finalize::<ChecksumAlgo>().await
+    }
+
+    /// See [`BagIt::preview_finalize_with_options()`]
+    pub async fn preview_finalize_with_options(
+        &self,
+        options: FinalizeOptions,
+    ) -> Result<FinalizePreview, GenerateError> {
+        self.bag
+            .preview_finalize_with_options::<ChecksumAlgo>(options)
+            .await
+    }
+
+    /// See [`BagIt::finalize()`]
+    pub async fn finalize(&mut self) -> Result<(), GenerateError> {
+        self.bag.finalize::<ChecksumAlgo>().await
+    }
+
+    /// See [`BagIt::finalize_with_options()`]
+    pub async fn finalize_with_options(
+        &mut self,
+        options: FinalizeOptions,
+    ) -> Result<(), GenerateError> {
+        self.bag
+            .finalize_with_options::<ChecksumAlgo>(options)
+            .await
+    }
+}
+
+impl<'a, 'algo, ChecksumAlgo> Deref for TypedBagIt<'a, 'algo, ChecksumAlgo> {
+    type Target = BagIt<'a, 'algo>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bag
+    }
+}
+
+impl<ChecksumAlgo> DerefMut for TypedBagIt<'_, '_, ChecksumAlgo> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bag
+    }
+}
+
+#[cfg(all(test, feature = "read"))]
+mod test {
+    use super::TypedBagIt;
+    use crate::error::GenerateError;
+    use crate::{Algorithm, BagIt, ChecksumAlgorithm};
+    use sha2::{Sha256, Sha512};
+
+    #[tokio::test]
+    async fn typed_bag() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let mut bag = TypedBagIt::new_empty(&temp_directory, &algo);
+        bag.add_bytes("hello", "data/hello.txt").await.unwrap();
+        assert_eq!(bag.payload_items().count(), 1);
+        assert_eq!(bag.finalize().await, Ok(()));
+
+        let read_bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert!(read_bag
+            .payload_items()
+            .eq(bag.into_inner().payload_items()));
+
+        // Bag of another algorithm
+        let sha512 = ChecksumAlgorithm::<Sha512>::new(Algorithm::Sha512);
+        assert_eq!(
+            TypedBagIt::from_bag(read_bag, &sha512).err(),
+            Some(GenerateError::AlgorithmMismatch(Algorithm::Sha512))
+        );
+    }
+}
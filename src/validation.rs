@@ -0,0 +1,563 @@
+use crate::cache::{CacheMode, SidecarCache};
+#[cfg(feature = "generate")]
+use crate::error::GenerateError;
+use crate::{error::PayloadError, Algorithm, BagIt};
+use digest::Digest;
+use std::time::SystemTime;
+use tokio::fs;
+
+/// Name of tag file recording history of validations, see [`BagIt::append_validation_log()`]
+#[cfg(any(feature = "read", feature = "generate"))]
+pub(crate) const VALIDATION_LOG_FILE_NAME: &str = "validation-log.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Result of a validation of a bag
+pub enum ValidationOutcome {
+    /// All payloads matched their checksums
+    Valid,
+    /// At least one payload did not match its checksum, or could not be read
+    Invalid,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// When and how a bag was last validated
+///
+/// See [`BagIt::last_validation()`].
+pub struct ValidationStatus {
+    validated_at: SystemTime,
+    algorithm: Algorithm,
+    outcome: ValidationOutcome,
+}
+
+impl ValidationStatus {
+    pub(crate) fn now(algorithm: &Algorithm, outcome: ValidationOutcome) -> Self {
+        Self {
+            validated_at: SystemTime::now(),
+            algorithm: algorithm.clone(),
+            outcome,
+        }
+    }
+
+    /// Moment when validation finished
+    pub fn validated_at(&self) -> SystemTime {
+        self.validated_at
+    }
+
+    /// Algorithm used to compute checksums during validation
+    pub fn algorithm(&self) -> &Algorithm {
+        &self.algorithm
+    }
+
+    /// Whether the bag was valid
+    pub fn outcome(&self) -> ValidationOutcome {
+        self.outcome
+    }
+
+    /// Line of validation log: "\<timestamp\> \<tool/version\> \<algorithm\> \<outcome\>"
+    #[cfg(feature = "generate")]
+    fn log_line(&self) -> String {
+        let outcome = match self.outcome {
+            ValidationOutcome::Valid => "valid",
+            ValidationOutcome::Invalid => "invalid",
+        };
+
+        format!(
+            "{} {}/{} {} {}",
+            format_timestamp(self.validated_at),
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            self.algorithm,
+            outcome
+        )
+    }
+}
+
+/// Format time as RFC 3339 in UTC, with a precision of one second
+#[cfg(feature = "generate")]
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+impl BagIt<'_, '_> {
+    /// Compute checksums of all payloads again, and compare them with the ones of the bag
+    ///
+    /// The outcome is recorded, see [`BagIt::last_validation()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// # let mut bagit_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # bagit_directory.push("tests/sample-bag");
+    /// let mut bag = BagIt::read_existing(bagit_directory, &algorithm).await?;
+    ///
+    /// // Some time later, in a long-lived service
+    /// let validated_long_ago = bag
+    ///     .last_validation()
+    ///     .and_then(|status| status.validated_at().elapsed().ok())
+    ///     .is_none_or(|elapsed| elapsed > Duration::from_secs(24 * 60 * 60));
+    /// if validated_long_ago {
+    ///     bag.validate::<sha2::Sha256>().await?;
+    /// }
+    /// assert!(bag.is_verified());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), PayloadError> {
+        let mut result = Ok(());
+        for payload in self.payload_items() {
+            result = payload.verify::<ChecksumAlgo>().await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.record_validation(result)
+    }
+
+    /// Validate the bag like [`BagIt::validate()`], with a sidecar cache of previous verifications
+    ///
+    /// The cache is a file named `.bagit-cache.txt` in the bag directory, it records checksum, size and
+    /// modification time of every payload at its last successful verification. It is not a tag file and
+    /// is never listed in manifests.
+    ///
+    /// With [`CacheMode::SkipUnchanged`], checksums of payloads are not computed again when their file did
+    /// not change since the last verification. Use [`CacheMode::FullRehash`] to compute all of them regardless.
+    /// The cache is rewritten after each validation, keeping entries of payloads that were not verified again.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, CacheMode, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::read_existing("/path/to/bag", &algorithm).await?;
+    ///
+    /// // Nightly fixity check, only new or modified files are hashed
+    /// bag.validate_with_cache::<sha2::Sha256>(CacheMode::SkipUnchanged)
+    ///     .await?;
+    ///
+    /// // Monthly full check
+    /// bag.validate_with_cache::<sha2::Sha256>(CacheMode::FullRehash)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_with_cache<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+        mode: CacheMode,
+    ) -> Result<(), PayloadError> {
+        // Results are merged into the cache: payloads not verified this time keep their entries
+        let mut cache = SidecarCache::load(self.path()).await;
+
+        let mut result = Ok(());
+        for payload in self.payload_items() {
+            let metadata = match fs::metadata(payload.absolute_path()).await {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    cache.remove(payload);
+                    result = Err(PayloadError::FileSize(payload.absolute_path(), e.kind()));
+                    break;
+                }
+            };
+
+            if mode == CacheMode::FullRehash || !cache.is_unchanged(payload, &metadata) {
+                result = payload.verify::<ChecksumAlgo>().await;
+                if result.is_err() {
+                    cache.remove(payload);
+                    break;
+                }
+            }
+
+            cache.insert(payload, &metadata);
+        }
+
+        // The cache is only an optimization: failing to write it does not change the outcome,
+        // checksums will be computed again next time
+        let _ = cache.write(self.path()).await;
+
+        self.record_validation(result)
+    }
+
+    fn record_validation(&mut self, result: Result<(), PayloadError>) -> Result<(), PayloadError> {
+        self.record_outcome(match result {
+            Ok(()) => ValidationOutcome::Valid,
+            Err(_) => ValidationOutcome::Invalid,
+        });
+
+        result
+    }
+
+    pub(crate) fn record_outcome(&mut self, outcome: ValidationOutcome) {
+        self.validation = Some(ValidationStatus::now(self.checksum_algorithm, outcome));
+    }
+
+    /// Status of the last validation of the bag, if it was ever validated
+    ///
+    /// Bags opened with [`BagIt::read_existing()`] are validated when read.
+    pub fn last_validation(&self) -> Option<&ValidationStatus> {
+        self.validation.as_ref()
+    }
+
+    /// Whether the last validation of the bag was successful
+    pub fn is_verified(&self) -> bool {
+        self.last_validation()
+            .is_some_and(|status| status.outcome() == ValidationOutcome::Valid)
+    }
+
+    /// Record the last validation in tag file `validation-log.txt`, giving the bag its own fixity history
+    ///
+    /// A line with timestamp, version of this library, algorithm and outcome is appended to the file,
+    /// it is created if needed. Tag manifests of the bag are updated with the new checksum of the log, each with its own algorithm:
+    /// algorithms other than the one of the bag must be known, see [`GenerateError::UnknownTagManifestAlgorithm`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use async_bagit::{Algorithm, BagIt, ChecksumAlgorithm};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let algorithm = ChecksumAlgorithm::<sha2::Sha256>::new(Algorithm::Sha256);
+    /// let mut bag = BagIt::read_existing("/path/to/bag", &algorithm).await?;
+    ///
+    /// bag.validate::<sha2::Sha256>().await?;
+    /// bag.append_validation_log::<sha2::Sha256>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "generate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "generate")))]
+    pub async fn append_validation_log<ChecksumAlgo: Digest + Send + 'static>(
+        &mut self,
+    ) -> Result<(), GenerateError> {
+        use tokio::io::AsyncWriteExt;
+
+        let status = self.last_validation().ok_or(GenerateError::NotValidated)?;
+        let log_line = format!("{}\n", status.log_line());
+
+        // Nothing is written unless every tag manifest can be updated
+        let tag_manifests = self.tag_manifest_files().await?;
+
+        let log_path = self.path().join(VALIDATION_LOG_FILE_NAME);
+        let mut log_file = fs::File::options()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .await
+            .map_err(|e| GenerateError::Finalize(log_path.clone(), e.kind()))?;
+        log_file
+            .write_all(log_line.as_bytes())
+            .await
+            .map_err(|e| GenerateError::Finalize(log_path.clone(), e.kind()))?;
+        log_file
+            .flush()
+            .await
+            .map_err(|e| GenerateError::Finalize(log_path.clone(), e.kind()))?;
+
+        self.update_tagmanifest_entry::<ChecksumAlgo>(VALIDATION_LOG_FILE_NAME, &tag_manifests)
+            .await
+    }
+}
+
+#[cfg(all(test, feature = "read", feature = "generate"))]
+mod test {
+    use super::{format_timestamp, ValidationOutcome};
+    use crate::{
+        error::{GenerateError, PayloadError},
+        Algorithm, BagIt, CacheMode, ChecksumAlgorithm, ReadOptions,
+    };
+    use sha2::Sha256;
+    use std::path::Path;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn timestamp_rfc3339() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_722_470_400 + 3_661)),
+            "2024-08-01T01:01:01Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[tokio::test]
+    async fn validation_log() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        assert_eq!(
+            bag.append_validation_log::<Sha256>().await,
+            Err(GenerateError::NotValidated)
+        );
+
+        // Two validations, two lines
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        bag.append_validation_log::<Sha256>().await.unwrap();
+        bag.validate::<Sha256>().await.unwrap();
+        bag.append_validation_log::<Sha256>().await.unwrap();
+
+        let log = tokio::fs::read_to_string(temp_directory.join("validation-log.txt"))
+            .await
+            .unwrap();
+        let lines = log.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let [_timestamp, tool, algorithm, outcome] =
+            lines[1].split(' ').collect::<Vec<_>>().try_into().unwrap();
+        assert_eq!(tool, concat!("async_bagit/", env!("CARGO_PKG_VERSION")));
+        assert_eq!(algorithm, "sha256");
+        assert_eq!(outcome, "valid");
+
+        // Log is covered by tag manifest, exactly once
+        let tagmanifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert_eq!(
+            tagmanifest
+                .lines()
+                .filter(|line| line.ends_with(" validation-log.txt"))
+                .count(),
+            1
+        );
+        assert_eq!(tagmanifest.lines().count(), 4);
+        assert!(BagIt::read_existing(&temp_directory, &algo).await.is_ok());
+
+        // Finalizing again keeps the log in tag manifest
+        bag.finalize::<Sha256>().await.unwrap();
+        let tagmanifest = tokio::fs::read_to_string(temp_directory.join("tagmanifest-sha256.txt"))
+            .await
+            .unwrap();
+        assert!(tagmanifest.contains(" validation-log.txt"));
+    }
+
+    #[tokio::test]
+    async fn validation_log_all_tag_manifests() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let sha256 = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+        let md5 = ChecksumAlgorithm::<md5::Md5>::new(Algorithm::custom("md5"));
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &sha256);
+        bag.add_algorithm(&md5).unwrap();
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+
+        // Algorithm of the other tag manifest must be known, nothing is written otherwise
+        let mut bag = BagIt::read_existing(&temp_directory, &sha256)
+            .await
+            .unwrap();
+        assert_eq!(
+            bag.append_validation_log::<Sha256>().await,
+            Err(GenerateError::UnknownTagManifestAlgorithm(
+                temp_directory.join("tagmanifest-md5.txt")
+            ))
+        );
+        assert!(!temp_directory.join("validation-log.txt").exists());
+
+        // Log is covered by every tag manifest, each with its own algorithm
+        let options = || ReadOptions::default().all_manifests(true).algorithm(&md5);
+        let mut bag = BagIt::read_existing_with_options(&temp_directory, &sha256, options())
+            .await
+            .unwrap();
+        bag.append_validation_log::<Sha256>().await.unwrap();
+        bag.append_validation_log::<Sha256>().await.unwrap();
+        for tag_manifest in ["tagmanifest-sha256.txt", "tagmanifest-md5.txt"] {
+            let contents = tokio::fs::read_to_string(temp_directory.join(tag_manifest))
+                .await
+                .unwrap();
+            assert_eq!(
+                contents
+                    .lines()
+                    .filter(|line| line.ends_with(" validation-log.txt"))
+                    .count(),
+                1,
+                "{tag_manifest}"
+            );
+        }
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &sha256, options())
+                .await
+                .is_ok()
+        );
+        let options = ReadOptions::default()
+            .all_manifests(true)
+            .algorithm(&sha256);
+        assert!(
+            BagIt::read_existing_with_options(&temp_directory, &md5, options)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_records_outcome() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        // New bags were never validated
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        bag.add_file::<Sha256>(source_directory.join("sources.csv"))
+            .await
+            .unwrap();
+        bag.finalize::<Sha256>().await.unwrap();
+        assert_eq!(bag.last_validation(), None);
+        assert!(!bag.is_verified());
+
+        // Reading a bag validates it
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        let status = bag.last_validation().unwrap();
+        assert_eq!(status.algorithm(), &Algorithm::Sha256);
+        assert_eq!(status.outcome(), ValidationOutcome::Valid);
+        assert!(bag.is_verified());
+        let first_validation = status.validated_at();
+
+        // Tamper with payload, validate again
+        tokio::fs::write(temp_directory.join("data/sources.csv"), "oops")
+            .await
+            .unwrap();
+        assert!(matches!(
+            bag.validate::<Sha256>().await,
+            Err(PayloadError::ChecksumDiffers { path, .. }) if path == Path::new("data/sources.csv")
+        ));
+        let status = bag.last_validation().unwrap();
+        assert_eq!(status.outcome(), ValidationOutcome::Invalid);
+        assert!(status.validated_at() >= first_validation);
+        assert!(!bag.is_verified());
+    }
+
+    #[tokio::test]
+    async fn validate_with_cache() {
+        let temp_directory = async_tempfile::TempDir::new().await.unwrap();
+        let temp_directory = temp_directory.to_path_buf();
+
+        let algo = ChecksumAlgorithm::<Sha256>::new(Algorithm::Sha256);
+
+        let mut source_directory = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        source_directory.push("tests/sample-bag/data");
+
+        let mut bag = BagIt::new_empty(&temp_directory, &algo);
+        for file in ["sources.csv", "totebag.jpg"] {
+            bag.add_file::<Sha256>(source_directory.join(file))
+                .await
+                .unwrap();
+        }
+        bag.finalize::<Sha256>().await.unwrap();
+
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+
+        // First run populates the cache
+        let cache_file = temp_directory.join(".bagit-cache.txt");
+        assert!(!cache_file.exists());
+        assert_eq!(
+            bag.validate_with_cache::<Sha256>(CacheMode::SkipUnchanged)
+                .await,
+            Ok(())
+        );
+        let cache = tokio::fs::read_to_string(&cache_file).await.unwrap();
+        assert_eq!(cache.lines().count(), 2);
+
+        // Cache is not part of the bag
+        let mut bag = BagIt::read_existing(&temp_directory, &algo).await.unwrap();
+        assert_eq!(bag.payload_items().count(), 2);
+
+        // Pretend checksum of a payload was verified while its file is still unchanged
+        let sources = temp_directory.join("data/sources.csv");
+        let original = tokio::fs::read(&sources).await.unwrap();
+        let modified = std::fs::metadata(&sources).unwrap().modified().unwrap();
+        tokio::fs::write(&sources, vec![b'x'; original.len()])
+            .await
+            .unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&sources)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        // Cache skips unchanged files, full rehash does not
+        assert_eq!(
+            bag.validate_with_cache::<Sha256>(CacheMode::SkipUnchanged)
+                .await,
+            Ok(())
+        );
+        assert!(matches!(
+            bag.validate_with_cache::<Sha256>(CacheMode::FullRehash)
+                .await,
+            Err(PayloadError::ChecksumDiffers { path, .. }) if path == Path::new("data/sources.csv")
+        ));
+        assert_eq!(
+            bag.last_validation().unwrap().outcome(),
+            ValidationOutcome::Invalid
+        );
+
+        // Payloads left unverified after the failure keep their entries
+        let cache = tokio::fs::read_to_string(&cache_file).await.unwrap();
+        assert_eq!(
+            cache
+                .lines()
+                .map(|line| line.rsplit(' ').next())
+                .collect::<Vec<_>>(),
+            vec![Some("data/totebag.jpg")]
+        );
+
+        // Failed payload is not in the cache anymore
+        assert!(matches!(
+            bag.validate_with_cache::<Sha256>(CacheMode::SkipUnchanged)
+                .await,
+            Err(PayloadError::ChecksumDiffers { path, .. }) if path == Path::new("data/sources.csv")
+        ));
+    }
+}
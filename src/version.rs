@@ -0,0 +1,60 @@
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Version of the BagIt specification a bag conforms to, declared in `bagit.txt`
+///
+/// Versions are ordered, ranges of them can be used to choose which bags are accepted,
+/// see [`ReadOptions::accepted_versions()`](crate::ReadOptions::accepted_versions).
+pub struct BagVersion {
+    major: u8,
+    minor: u8,
+}
+
+impl BagVersion {
+    /// Version 0.97, last draft before RFC 8493, still widely found in the wild
+    pub const V0_97: Self = Self::new(0, 97);
+
+    /// Version 1.0, defined by RFC 8493
+    pub const V1_0: Self = Self::new(1, 0);
+
+    /// Create a version from its major and minor numbers
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Major number of version
+    pub fn major(&self) -> u8 {
+        self.major
+    }
+
+    /// Minor number of version
+    pub fn minor(&self) -> u8 {
+        self.minor
+    }
+
+    /// Whether `%`, CR and LF in paths of manifests and `fetch.txt` are percent-encoded
+    ///
+    /// Encoding was introduced with version 1.0, paths of older bags are written as-is.
+    pub(crate) fn percent_encodes_paths(&self) -> bool {
+        *self >= Self::V1_0
+    }
+}
+
+impl Display for BagVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BagVersion;
+
+    #[test]
+    fn ordering() {
+        assert!(BagVersion::new(0, 97) < BagVersion::V1_0);
+        assert!(BagVersion::new(0, 97) > BagVersion::new(0, 96));
+        assert_eq!(BagVersion::new(0, 97).to_string(), "0.97");
+        assert!((BagVersion::new(0, 96)..=BagVersion::V1_0).contains(&BagVersion::new(0, 97)));
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(feature = "zstd")]
+use crate::compression::COMPRESSED_PAYLOADS_FILE_NAME;
+use crate::{
+    cache::CACHE_FILE_NAME,
+    fetch::FETCH_FILE_NAME,
+    long_path,
+    manifest::{PREFIX_MANIFEST, PREFIX_TAG_MANIFEST},
+    validation::VALIDATION_LOG_FILE_NAME,
+};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// List files inside `directory` and its subdirectories, as paths relative to `directory`
+///
+/// Symbolic links to files are listed, symbolic links to directories are not followed and broken ones are skipped.
+/// Paths are sorted, to get the same order on all platforms.
+pub(crate) async fn list_files(directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = vec![];
+    let mut directories = vec![PathBuf::new()];
+
+    while let Some(relative_directory) = directories.pop() {
+        let mut entries =
+            fs::read_dir(long_path::extended(&directory.join(&relative_directory))).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let relative_path = relative_directory.join(entry.file_name());
+
+            if entry.file_type().await?.is_dir() {
+                directories.push(relative_path);
+            } else if fs::metadata(long_path::extended(&entry.path()))
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+            {
+                files.push(relative_path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// List symbolic links inside `directory` and its subdirectories, as paths relative to `directory`
+///
+/// Symbolic links to directories are listed without looking inside them. Paths are sorted like with [`list_files()`].
+#[cfg(feature = "read")]
+pub(crate) async fn list_symlinks(directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut symlinks = vec![];
+    let mut directories = vec![PathBuf::new()];
+
+    while let Some(relative_directory) = directories.pop() {
+        let mut entries =
+            fs::read_dir(long_path::extended(&directory.join(&relative_directory))).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let relative_path = relative_directory.join(entry.file_name());
+            let file_type = entry.file_type().await?;
+
+            if file_type.is_symlink() {
+                symlinks.push(relative_path);
+            } else if file_type.is_dir() {
+                directories.push(relative_path);
+            }
+        }
+    }
+
+    symlinks.sort();
+    Ok(symlinks)
+}
+
+/// Whether `path`, relative to the bag, is a tag file reserved by the spec or written by the crate itself
+///
+/// Names of manifests are matched ignoring case, like when they are read.
+pub(crate) fn is_reserved_tag_file(path: &Path) -> bool {
+    let Some(path) = path.to_str() else {
+        return false;
+    };
+
+    #[cfg(feature = "zstd")]
+    if path == COMPRESSED_PAYLOADS_FILE_NAME {
+        return true;
+    }
+
+    let lowercase = path.to_ascii_lowercase();
+    path == "bagit.txt"
+        || path == "bag-info.txt"
+        || path == FETCH_FILE_NAME
+        || path == VALIDATION_LOG_FILE_NAME
+        || path == CACHE_FILE_NAME
+        || lowercase.starts_with(PREFIX_MANIFEST)
+        || lowercase.starts_with(PREFIX_TAG_MANIFEST)
+}
+
+/// List files of the bag at `bag_directory` outside of payload directory `data`, reserved tag files included
+///
+/// Paths are relative to `bag_directory`, and sorted like with [`list_files()`].
+pub(crate) async fn list_bag_files(bag_directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = vec![];
+
+    let mut entries = fs::read_dir(long_path::extended(bag_directory)).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let relative_path = PathBuf::from(entry.file_name());
+
+        if entry.file_type().await?.is_dir() {
+            if relative_path == Path::new("data") {
+                continue;
+            }
+            files.extend(
+                list_files(&bag_directory.join(&relative_path))
+                    .await?
+                    .into_iter()
+                    .map(|file| relative_path.join(file)),
+            );
+        } else if fs::metadata(long_path::extended(&entry.path()))
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+        {
+            files.push(relative_path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// List tag files of the bag at `bag_directory`, outside of payload directory `data`
+///
+/// Files at the root of the bag are listed unless they are reserved, see [`is_reserved_tag_file()`],
+/// files in subdirectories are always listed.
+/// Paths are relative to `bag_directory`, and sorted like with [`list_files()`].
+#[cfg(feature = "read")]
+pub(crate) async fn list_tag_files(bag_directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = list_bag_files(bag_directory).await?;
+    files.retain(|file| file.components().count() > 1 || !is_reserved_tag_file(file));
+    Ok(files)
+}